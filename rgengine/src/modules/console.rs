@@ -0,0 +1,856 @@
+//the developer console's commands and console variables (cvars), usable from anywhere on the
+//main thread. layers and engine modules register them, usually from `on_attach`, and the
+//console overlay (`imgui::DevConsole`) runs what's typed with `execute`.
+//
+//a line is a command or cvar name followed by its arguments separated by spaces. quotes keep
+//spaces in an argument, `;` separates commands and `//` starts a comment. a cvar on its own
+//prints its value, with an argument it's set
+
+use std::{cell::RefCell, collections::BTreeMap, fmt, path::Path, rc::Rc};
+
+const MAX_OUTPUT: usize = 1024;
+const MAX_HISTORY: usize = 256;
+//scripts running scripts, so one running itself stops
+const MAX_EXEC_DEPTH: u32 = 16;
+
+///the value of a cvar or a command argument
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Float(f32),
+    String(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueKind {
+    Bool,
+    Int,
+    Float,
+    String,
+}
+
+impl ValueKind {
+    ///`1`, `on` and `true` are true, `0`, `off` and `false` are false
+    pub fn parse(self, text: &str) -> Result<Value, String> {
+        let invalid = || format!("`{text}` isn't a valid {self}");
+        match self {
+            ValueKind::Bool => match text.to_ascii_lowercase().as_str() {
+                "1" | "on" | "true" => Ok(Value::Bool(true)),
+                "0" | "off" | "false" => Ok(Value::Bool(false)),
+                _ => Err(invalid()),
+            },
+            ValueKind::Int => text.parse().map(Value::Int).map_err(|_| invalid()),
+            ValueKind::Float => text.parse().map(Value::Float).map_err(|_| invalid()),
+            ValueKind::String => Ok(Value::String(text.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ValueKind::Bool => "bool",
+            ValueKind::Int => "int",
+            ValueKind::Float => "float",
+            ValueKind::String => "string",
+        })
+    }
+}
+
+impl Value {
+    pub fn kind(&self) -> ValueKind {
+        match self {
+            Value::Bool(_) => ValueKind::Bool,
+            Value::Int(_) => ValueKind::Int,
+            Value::Float(_) => ValueKind::Float,
+            Value::String(_) => ValueKind::String,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    ///ints too
+    pub fn as_float(&self) -> Option<f32> {
+        match self {
+            Value::Float(f) => Some(*f),
+            Value::Int(i) => Some(*i as f32),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    //the same value as `kind`, parsed from its text when the kinds differ
+    fn convert(self, kind: ValueKind) -> Result<Value, String> {
+        if self.kind() == kind {
+            Ok(self)
+        } else {
+            kind.parse(&self.to_string())
+        }
+    }
+}
+
+///parses back with `ValueKind::parse`, bools are `1` and `0`
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bool(b) => write!(f, "{}", u8::from(*b)),
+            Value::Int(i) => write!(f, "{i}"),
+            Value::Float(x) => write!(f, "{x}"),
+            Value::String(s) => f.write_str(s),
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Int(value)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::Int(value as i64)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(value: u32) -> Self {
+        Value::Int(value as i64)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+///a named setting read and written from the console. archived ones are saved with the ui
+///settings and set again the next time they're registered
+#[derive(Clone, Debug)]
+pub struct CVar {
+    name: String,
+    help: String,
+    value: Value,
+    default: Value,
+    archived: bool,
+    choices: Vec<String>,
+}
+
+impl CVar {
+    ///its kind is the kind of `default`, values set later are converted to it
+    pub fn new(name: &str, default: impl Into<Value>, help: &str) -> Self {
+        let default = default.into();
+        Self {
+            name: name.to_string(),
+            help: help.to_string(),
+            value: default.clone(),
+            default,
+            archived: false,
+            choices: Vec::new(),
+        }
+    }
+
+    pub fn archived(mut self) -> Self {
+        self.archived = true;
+        self
+    }
+
+    ///the only values it accepts, they're also what tab completes after its name
+    pub fn choices(mut self, choices: &[&str]) -> Self {
+        self.choices = choices.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn help(&self) -> &str {
+        &self.help
+    }
+
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    pub fn default_value(&self) -> &Value {
+        &self.default
+    }
+
+    pub fn kind(&self) -> ValueKind {
+        self.default.kind()
+    }
+
+    pub fn is_archived(&self) -> bool {
+        self.archived
+    }
+
+    fn validate(&self, value: Value) -> Result<Value, String> {
+        let value = value.convert(self.kind())?;
+        let text = value.to_string();
+        if !self.choices.is_empty() && !self.choices.contains(&text) {
+            return Err(format!(
+                "`{text}` isn't one of {} for {}",
+                self.choices.join(", "),
+                self.name
+            ));
+        }
+        Ok(value)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Arg {
+    name: String,
+    kind: ValueKind,
+    default: Option<Value>,
+}
+
+type Handler = Rc<dyn Fn(&Args) -> Result<(), String>>;
+
+///a console command with typed arguments, parsed before the handler is called. a string as
+///the last argument takes the rest of the line
+#[derive(Clone)]
+pub struct Command {
+    name: String,
+    help: String,
+    args: Vec<Arg>,
+    handler: Handler,
+}
+
+impl Command {
+    ///the handler prints with `print` and returns errors to show in the console. it can run
+    ///other commands, so state it changes is shared through a `Cell` or `RefCell`
+    pub fn new<F>(name: &str, help: &str, handler: F) -> Self
+    where
+        F: Fn(&Args) -> Result<(), String> + 'static,
+    {
+        Self {
+            name: name.to_string(),
+            help: help.to_string(),
+            args: Vec::new(),
+            handler: Rc::new(handler),
+        }
+    }
+
+    ///required arguments come before optional ones
+    pub fn arg(mut self, name: &str, kind: ValueKind) -> Self {
+        self.args.push(Arg {
+            name: name.to_string(),
+            kind,
+            default: None,
+        });
+        self
+    }
+
+    ///an argument that is `default` when it's left out, of the same kind
+    pub fn optional_arg(mut self, name: &str, default: impl Into<Value>) -> Self {
+        let default = default.into();
+        self.args.push(Arg {
+            name: name.to_string(),
+            kind: default.kind(),
+            default: Some(default),
+        });
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn help(&self) -> &str {
+        &self.help
+    }
+
+    ///e.g. `spawn <x:float> <y:float> [name:string=quad]`
+    pub fn usage(&self) -> String {
+        let mut usage = self.name.clone();
+        for arg in &self.args {
+            match &arg.default {
+                Some(default) => usage += &format!(" [{}:{}={}]", arg.name, arg.kind, default),
+                None => usage += &format!(" <{}:{}>", arg.name, arg.kind),
+            }
+        }
+        usage
+    }
+
+    fn parse_args(&self, tokens: &[String]) -> Result<Args, String> {
+        let usage = || format!("usage: {}", self.usage());
+        let takes_rest = self
+            .args
+            .last()
+            .is_some_and(|a| a.kind == ValueKind::String);
+        if tokens.len() > self.args.len() && !takes_rest {
+            return Err(usage());
+        }
+        let mut values = Vec::with_capacity(self.args.len());
+        for (i, arg) in self.args.iter().enumerate() {
+            let value = match tokens.get(i) {
+                Some(_) if takes_rest && i + 1 == self.args.len() => {
+                    Value::String(tokens[i..].join(" "))
+                }
+                Some(token) => arg
+                    .kind
+                    .parse(token)
+                    .map_err(|e| format!("{}: {e}\n{}", arg.name, usage()))?,
+                None => arg.default.clone().ok_or_else(usage)?,
+            };
+            values.push((arg.name.clone(), value));
+        }
+        Ok(Args { values })
+    }
+}
+
+///the parsed arguments of a command, by name. the typed getters panic on a name the command
+///doesn't have or of another kind, that's a mistake in the command itself
+#[derive(Clone, Debug)]
+pub struct Args {
+    values: Vec<(String, Value)>,
+}
+
+impl Args {
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    fn expect<'a, T>(
+        &'a self,
+        name: &str,
+        kind: ValueKind,
+        get: impl Fn(&'a Value) -> Option<T>,
+    ) -> T {
+        self.get(name)
+            .and_then(get)
+            .unwrap_or_else(|| panic!("the command has no {kind} argument `{name}`"))
+    }
+
+    pub fn bool(&self, name: &str) -> bool {
+        self.expect(name, ValueKind::Bool, Value::as_bool)
+    }
+
+    pub fn int(&self, name: &str) -> i64 {
+        self.expect(name, ValueKind::Int, Value::as_int)
+    }
+
+    pub fn float(&self, name: &str) -> f32 {
+        self.expect(name, ValueKind::Float, Value::as_float)
+    }
+
+    pub fn string(&self, name: &str) -> &str {
+        self.expect(name, ValueKind::String, Value::as_str)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputKind {
+    ///an executed line
+    Input,
+    Info,
+    Error,
+}
+
+#[derive(Clone, Debug)]
+pub struct OutputLine {
+    pub kind: OutputKind,
+    pub text: String,
+}
+
+///what tab completion made of a line
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Completion {
+    ///the line completed as far as every candidate agrees
+    pub line: String,
+    ///every name or value that could follow, empty when there's only one
+    pub candidates: Vec<String>,
+}
+
+struct Registry {
+    commands: BTreeMap<String, Command>,
+    cvars: BTreeMap<String, CVar>,
+    //saved values of archived cvars that haven't been registered yet
+    pending: BTreeMap<String, String>,
+    output: Vec<OutputLine>,
+    history: Vec<String>,
+    //bumped whenever an archived cvar changes
+    archive_generation: u64,
+    exec_depth: u32,
+}
+
+thread_local! {
+    static REGISTRY: RefCell<Registry> = RefCell::new(Registry::new());
+}
+
+impl Registry {
+    fn new() -> Self {
+        let mut registry = Self {
+            commands: BTreeMap::new(),
+            cvars: BTreeMap::new(),
+            pending: BTreeMap::new(),
+            output: Vec::new(),
+            history: Vec::new(),
+            archive_generation: 0,
+            exec_depth: 0,
+        };
+        for command in builtin_commands() {
+            registry.commands.insert(command.name.clone(), command);
+        }
+        registry
+    }
+
+    fn print(&mut self, kind: OutputKind, text: &str) {
+        for line in text.lines() {
+            self.output.push(OutputLine {
+                kind,
+                text: line.to_string(),
+            });
+        }
+        if self.output.len() > MAX_OUTPUT {
+            let extra = self.output.len() - MAX_OUTPUT;
+            self.output.drain(..extra);
+        }
+    }
+
+    fn set_cvar(&mut self, name: &str, value: Value) -> Result<(), String> {
+        let cvar = self
+            .cvars
+            .get_mut(name)
+            .ok_or_else(|| format!("unknown cvar `{name}`"))?;
+        let value = cvar.validate(value)?;
+        if cvar.value != value {
+            cvar.value = value;
+            if cvar.archived {
+                self.archive_generation += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+///adds a command, replacing one with the same name
+pub fn register_command(command: Command) {
+    REGISTRY.with_borrow_mut(|r| r.commands.insert(command.name.clone(), command));
+}
+
+pub fn unregister_command(name: &str) {
+    REGISTRY.with_borrow_mut(|r| r.commands.remove(name));
+}
+
+///adds a cvar. registering one that already exists keeps its value when it's still valid, so
+///layers can register theirs every time they're attached. an archived one starts with its saved
+///value
+pub fn register_cvar(mut cvar: CVar) {
+    REGISTRY.with_borrow_mut(|r| {
+        if let Some(old) = r.cvars.get(&cvar.name) {
+            if let Ok(value) = cvar.validate(old.value.clone()) {
+                cvar.value = value;
+            }
+        } else if let Some(saved) = r.pending.remove(&cvar.name).filter(|_| cvar.archived) {
+            match cvar.validate(Value::String(saved)) {
+                Ok(value) => cvar.value = value,
+                Err(e) => r.print(OutputKind::Error, &e),
+            }
+        }
+        r.cvars.insert(cvar.name.clone(), cvar);
+    });
+}
+
+pub fn unregister_cvar(name: &str) {
+    REGISTRY.with_borrow_mut(|r| r.cvars.remove(name));
+}
+
+pub fn cvar(name: &str) -> Option<Value> {
+    REGISTRY.with_borrow(|r| r.cvars.get(name).map(|c| c.value.clone()))
+}
+
+pub fn cvar_bool(name: &str) -> Option<bool> {
+    cvar(name)?.as_bool()
+}
+
+pub fn cvar_int(name: &str) -> Option<i64> {
+    cvar(name)?.as_int()
+}
+
+pub fn cvar_float(name: &str) -> Option<f32> {
+    cvar(name)?.as_float()
+}
+
+pub fn cvar_string(name: &str) -> Option<String> {
+    match cvar(name)? {
+        Value::String(s) => Some(s),
+        _ => None,
+    }
+}
+
+///converted to the cvar's kind, fails for unknown cvars and values it doesn't accept
+pub fn set_cvar(name: &str, value: impl Into<Value>) -> Result<(), String> {
+    REGISTRY.with_borrow_mut(|r| r.set_cvar(name, value.into()))
+}
+
+///every cvar, by name
+pub fn cvars() -> Vec<CVar> {
+    REGISTRY.with_borrow(|r| r.cvars.values().cloned().collect())
+}
+
+///the names of every command and cvar, sorted
+pub fn names() -> Vec<String> {
+    REGISTRY.with_borrow(|r| {
+        let mut names: Vec<String> = r.commands.keys().chain(r.cvars.keys()).cloned().collect();
+        names.sort();
+        names
+    })
+}
+
+pub fn print(text: &str) {
+    REGISTRY.with_borrow_mut(|r| r.print(OutputKind::Info, text));
+}
+
+pub fn print_error(text: &str) {
+    REGISTRY.with_borrow_mut(|r| r.print(OutputKind::Error, text));
+}
+
+///the console's output, oldest first. nothing can be printed from `f`
+pub fn with_output<R>(f: impl FnOnce(&[OutputLine]) -> R) -> R {
+    REGISTRY.with_borrow(|r| f(&r.output))
+}
+
+pub fn clear_output() {
+    REGISTRY.with_borrow_mut(|r| r.output.clear());
+}
+
+///executed lines, oldest first
+pub fn history() -> Vec<String> {
+    REGISTRY.with_borrow(|r| r.history.clone())
+}
+
+///runs a typed line, it's echoed to the output and added to the history. errors are printed
+pub fn execute(line: &str) {
+    let line = line.trim();
+    if line.is_empty() {
+        return;
+    }
+    REGISTRY.with_borrow_mut(|r| {
+        r.print(OutputKind::Input, &format!("> {line}"));
+        if r.history.last().map(String::as_str) != Some(line) {
+            r.history.push(line.to_string());
+        }
+        if r.history.len() > MAX_HISTORY {
+            r.history.remove(0);
+        }
+    });
+    for statement in split_statements(line) {
+        if let Err(e) = run_statement(&statement) {
+            print_error(&e);
+        }
+    }
+}
+
+///runs a line without echoing it or adding it to the history, stopping at the first error
+pub fn run(line: &str) -> Result<(), String> {
+    for statement in split_statements(line) {
+        run_statement(&statement)?;
+    }
+    Ok(())
+}
+
+///runs every line of a file, e.g. an autoexec. errors are printed with their line number and
+///don't stop the rest
+pub fn exec_file(path: &Path) -> Result<(), String> {
+    let script = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+    let depth = REGISTRY.with_borrow_mut(|r| {
+        r.exec_depth += 1;
+        r.exec_depth
+    });
+    if depth <= MAX_EXEC_DEPTH {
+        for (number, line) in script.lines().enumerate() {
+            if let Err(e) = run(line) {
+                print_error(&format!("{}:{}: {e}", path.display(), number + 1));
+            }
+        }
+    }
+    REGISTRY.with_borrow_mut(|r| r.exec_depth -= 1);
+    if depth > MAX_EXEC_DEPTH {
+        return Err(format!("{} scripts deep, stopped", MAX_EXEC_DEPTH));
+    }
+    Ok(())
+}
+
+fn run_statement(tokens: &[String]) -> Result<(), String> {
+    let Some((name, args)) = tokens.split_first() else {
+        return Ok(());
+    };
+    //looked up first so the handler runs without the registry borrowed, it may use the console
+    let command = REGISTRY.with_borrow_mut(|r| {
+        if let Some(command) = r.commands.get(name) {
+            return command
+                .parse_args(args)
+                .map(|args| Some((command.handler.clone(), args)));
+        }
+        let cvar = r
+            .cvars
+            .get(name)
+            .ok_or_else(|| format!("unknown command or cvar `{name}`"))?;
+        if args.is_empty() {
+            let text = format!("{} = {} ({})", cvar.name, cvar.value, cvar.help);
+            r.print(OutputKind::Info, &text);
+        } else {
+            r.set_cvar(name, Value::String(args.join(" ")))?;
+        }
+        Ok(None)
+    })?;
+    if let Some((handler, args)) = command {
+        handler(&args)?;
+    }
+    Ok(())
+}
+
+//statements split on `;`, each split into tokens, with comments dropped
+fn split_statements(line: &str) -> Vec<Vec<String>> {
+    let mut statements = vec![Vec::new()];
+    let mut token: Option<String> = None;
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                token.get_or_insert_with(String::new);
+            }
+            '/' if !quoted && chars.peek() == Some(&'/') => break,
+            ';' if !quoted => {
+                statements.last_mut().unwrap().extend(token.take());
+                statements.push(Vec::new());
+            }
+            c if c.is_whitespace() && !quoted => {
+                statements.last_mut().unwrap().extend(token.take());
+            }
+            c => token.get_or_insert_with(String::new).push(c),
+        }
+    }
+    statements.last_mut().unwrap().extend(token);
+    statements.retain(|s| !s.is_empty());
+    statements
+}
+
+///completes the name or cvar value at the end of `line`
+pub fn complete(line: &str) -> Completion {
+    //only the last statement is completed
+    let start = line.rfind(';').map_or(0, |i| i + 1);
+    let (before, statement) = line.split_at(start);
+    let statement = statement.trim_start();
+    let words: Vec<&str> = statement.split(' ').collect();
+    let (options, prefix) = REGISTRY.with_borrow(|r| match words.as_slice() {
+        [prefix] => {
+            let names = r.commands.keys().chain(r.cvars.keys()).cloned();
+            (names.collect::<Vec<_>>(), *prefix)
+        }
+        [name, prefix] => {
+            let options = match *name {
+                "help" => r.commands.keys().chain(r.cvars.keys()).cloned().collect(),
+                "reset" | "toggle" => r.cvars.keys().cloned().collect(),
+                name => match r.cvars.get(name) {
+                    Some(cvar) if cvar.kind() == ValueKind::Bool => vec!["0".into(), "1".into()],
+                    Some(cvar) => cvar.choices.clone(),
+                    None => Vec::new(),
+                },
+            };
+            (options, *prefix)
+        }
+        _ => (Vec::new(), ""),
+    });
+    let mut candidates: Vec<String> = options
+        .into_iter()
+        .filter(|o| o.starts_with(prefix))
+        .collect();
+    candidates.sort();
+    candidates.dedup();
+
+    let head = &statement[..statement.len() - prefix.len()];
+    let separator = if before.is_empty() { "" } else { " " };
+    let completed = match candidates.as_slice() {
+        [] => {
+            return Completion {
+                line: line.to_string(),
+                candidates,
+            };
+        }
+        [only] => {
+            let line = format!("{}{separator}{head}{only} ", before.trim_end());
+            return Completion {
+                line,
+                candidates: Vec::new(),
+            };
+        }
+        [first, rest @ ..] => rest.iter().fold(first.as_str(), |common, c| {
+            let len = common
+                .char_indices()
+                .zip(c.chars())
+                .find(|((_, a), b)| a != b)
+                .map_or(common.len().min(c.len()), |((i, _), _)| i);
+            &common[..len]
+        }),
+    };
+    Completion {
+        line: format!("{}{separator}{head}{completed}", before.trim_end()),
+        candidates,
+    }
+}
+
+///archived cvars that aren't at their default, as saved text
+pub fn archived_cvars() -> Vec<(String, String)> {
+    REGISTRY.with_borrow(|r| {
+        let saved = r
+            .cvars
+            .values()
+            .filter(|c| c.archived && c.value != c.default)
+            .map(|c| (c.name.clone(), c.value.to_string()));
+        //values of cvars that weren't registered this time are kept too
+        let pending = r.pending.iter().map(|(n, v)| (n.clone(), v.clone()));
+        saved.chain(pending).collect()
+    })
+}
+
+///sets archived cvars to saved values, the ones not registered yet get theirs when they are
+pub fn load_archived<I: IntoIterator<Item = (String, String)>>(values: I) {
+    REGISTRY.with_borrow_mut(|r| {
+        for (name, value) in values {
+            match r.cvars.get(&name) {
+                Some(cvar) if cvar.archived => {
+                    if let Err(e) = r.set_cvar(&name, Value::String(value)) {
+                        r.print(OutputKind::Error, &e);
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    r.pending.insert(name, value);
+                }
+            }
+        }
+    });
+}
+
+///changes whenever an archived cvar does, to know when to save them
+pub fn archive_generation() -> u64 {
+    REGISTRY.with_borrow(|r| r.archive_generation)
+}
+
+fn builtin_commands() -> Vec<Command> {
+    vec![
+        Command::new("help", "lists the commands, or describes one", |args| {
+            let name = args.string("name");
+            let text = REGISTRY.with_borrow(|r| {
+                if let Some(command) = r.commands.get(name) {
+                    return Ok(format!("{}\n  {}", command.usage(), command.help));
+                }
+                if let Some(cvar) = r.cvars.get(name) {
+                    return Ok(format!(
+                        "{} <{}> = {} (default {})\n  {}",
+                        cvar.name,
+                        cvar.kind(),
+                        cvar.value,
+                        cvar.default,
+                        cvar.help
+                    ));
+                }
+                if !name.is_empty() {
+                    return Err(format!("unknown command or cvar `{name}`"));
+                }
+                let mut text = String::new();
+                for command in r.commands.values() {
+                    text += &format!("{} - {}\n", command.usage(), command.help);
+                }
+                text += &format!("{} cvars, list them with `cvars`", r.cvars.len());
+                Ok(text)
+            })?;
+            print(&text);
+            Ok(())
+        })
+        .optional_arg("name", ""),
+        Command::new(
+            "cvars",
+            "lists the cvars whose names contain `filter`",
+            |args| {
+                let filter = args.string("filter");
+                let text = REGISTRY.with_borrow(|r| {
+                    r.cvars
+                        .values()
+                        .filter(|c| c.name.contains(filter))
+                        .map(|c| format!("{} = {} - {}", c.name, c.value, c.help))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                });
+                print(&text);
+                Ok(())
+            },
+        )
+        .optional_arg("filter", ""),
+        Command::new("reset", "sets a cvar back to its default", |args| {
+            let name = args.string("cvar");
+            let default = REGISTRY.with_borrow(|r| r.cvars.get(name).map(|c| c.default.clone()));
+            let default = default.ok_or_else(|| format!("unknown cvar `{name}`"))?;
+            set_cvar(name, default)
+        })
+        .arg("cvar", ValueKind::String),
+        Command::new("toggle", "flips a bool cvar", |args| {
+            let name = args.string("cvar");
+            let value = cvar_bool(name).ok_or_else(|| format!("`{name}` isn't a bool cvar"))?;
+            set_cvar(name, !value)
+        })
+        .arg("cvar", ValueKind::String),
+        Command::new("echo", "prints its arguments", |args| {
+            print(args.string("text"));
+            Ok(())
+        })
+        .optional_arg("text", ""),
+        Command::new("exec", "runs every line of a file", |args| {
+            exec_file(Path::new(args.string("path")))
+        })
+        .arg("path", ValueKind::String),
+        Command::new("history", "prints the executed lines", |_| {
+            print(&history().join("\n"));
+            Ok(())
+        }),
+        Command::new("clear", "clears the console output", |_| {
+            clear_output();
+            Ok(())
+        }),
+    ]
+}
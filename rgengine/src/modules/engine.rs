@@ -1,49 +1,361 @@
+use std::{
+    cell::{Cell, RefCell},
+    path::PathBuf,
+    rc::Rc,
+};
+
 use crate::events::*;
 
-use super::{imgui::ImGuiLayer, layers::LayerStack, renderer::Renderer, window::WindowManager};
+use super::{
+    console::{self, CVar, Command},
+    imgui::{ImGuiLayer, UiSettings},
+    layers::{Layer, LayerStack},
+    log::{self, LogConfig, rge_engine_error},
+    renderer::{
+        RenderTarget, Renderer, capture::FrameCapture, framebuffer::Framebuffer,
+        post_process::PostProcessStack,
+    },
+    window::WindowManager,
+};
+
+//the choices of the `log_level` cvar
+const LOG_LEVELS: [&str; 7] = ["off", "critical", "error", "warn", "info", "debug", "trace"];
+
+///what `GameEngine::with_config` sets the engine up with
+#[derive(Clone, Debug)]
+pub struct EngineConfig {
+    ///the window title, and the folder the ui layout and archived cvars are saved in
+    pub project: String,
+    ///`Some` sets up logging before the window opens, see `GameEngine::with_log_config`
+    pub log: Option<LogConfig>,
+}
+
+impl EngineConfig {
+    pub fn new(project: &str) -> Self {
+        Self {
+            project: project.to_string(),
+            log: None,
+        }
+    }
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self::new("Game Engine")
+    }
+}
 
 // engine/mod.rs
 pub struct GameEngine {
-    window: WindowManager,
     renderer: Renderer,
     layer_stack: LayerStack,
+    imgui: ImGuiLayer,
+    render_target: Option<Rc<RefCell<Framebuffer>>>,
+    post_process: Option<Rc<RefCell<PostProcessStack>>>,
+    capture: FrameCapture,
+    startup_script: Option<PathBuf>,
+    //set by the console's quit, screenshot and perf_overlay commands
+    quit_requested: Rc<Cell<bool>>,
+    screenshots_requested: Rc<Cell<u32>>,
+    perf_overlay_toggled: Rc<Cell<bool>>,
+    //`log_level` as it was last applied, the levels from `RGE_LOG` stay until it's changed.
+    //shared with the log_levels command, which sets the cvar to what it applied
+    log_level: Rc<RefCell<Option<String>>>,
     is_running: bool,
+    //dropped last, everything above may still use its gl context or close windows sharing it
+    window: WindowManager,
 }
 
 impl GameEngine {
     pub fn new() -> Self {
-        let mut window = WindowManager::new("Game Engine", 1280, 720);
-        let renderer = Renderer::new(&mut window);
-        let mut layer_stack = LayerStack::new();
+        Self::with_config(EngineConfig::default())
+    }
 
-        layer_stack.push_overlay(Box::new(ImGuiLayer::new(&mut window, 1)));
+    ///sets up logging from `config` before the window opens, e.g. to log to a file. when
+    ///logging is already set up `config` is ignored
+    pub fn with_log_config(config: LogConfig) -> Self {
+        Self::with_config(EngineConfig {
+            log: Some(config),
+            ..EngineConfig::default()
+        })
+    }
 
-        Self {
+    pub fn with_config(config: EngineConfig) -> Self {
+        if let Some(log_config) = config.log
+            && let Err(e) = log::init_with(log_config)
+        {
+            eprintln!("logging not set up from the engine's config: {e}");
+        }
+        let mut window = WindowManager::new(&config.project, 1280, 720);
+        let renderer = Renderer::new(&mut window);
+        let layer_stack = LayerStack::new();
+        let settings = UiSettings::for_project(&config.project);
+        let imgui = ImGuiLayer::new(&mut window, 1, settings);
+
+        let mut engine = Self {
             window,
             renderer,
             layer_stack,
+            imgui,
+            render_target: None,
+            post_process: None,
+            capture: FrameCapture::new("screenshots"),
+            startup_script: None,
+            quit_requested: Rc::new(Cell::new(false)),
+            screenshots_requested: Rc::new(Cell::new(0)),
+            perf_overlay_toggled: Rc::new(Cell::new(false)),
+            log_level: Rc::new(RefCell::new(None)),
             is_running: true,
+        };
+        engine.register_console();
+        //a saved level differs from the default and is applied on the first frame, unless
+        //`RGE_LOG` picked the levels for this run
+        *engine.log_level.borrow_mut() = console::cvar_string("log_level");
+        engine.imgui.load_settings();
+        if std::env::var_os("RGE_LOG").is_some() {
+            *engine.log_level.borrow_mut() = console::cvar_string("log_level");
         }
+        engine
+    }
+
+    ///layers are created after the gl context so they can make renderer resources
+    pub fn push_layer(&mut self, mut layer: Box<dyn Layer>) {
+        layer.on_attach();
+        self.layer_stack.push_layer(layer);
+    }
+
+    pub fn push_overlay(&mut self, mut overlay: Box<dyn Layer>) {
+        overlay.on_attach();
+        self.layer_stack.push_overlay(overlay);
+    }
+
+    ///`Some` renders the layers into the framebuffer instead of the window, e.g. so an
+    ///editor panel can show it. overlays still draw on the window.
+    ///it stays borrowed while rendering so resize it from on_update or on_event
+    pub fn set_render_target(&mut self, target: Option<Rc<RefCell<Framebuffer>>>) {
+        self.render_target = target;
+    }
+
+    pub fn render_target(&self) -> Option<&Rc<RefCell<Framebuffer>>> {
+        self.render_target.as_ref()
+    }
+
+    ///`Some` draws the layers into the stack's hdr target and runs its effects on the way to
+    ///the render target. keep a clone to toggle and tune effects at runtime. its msaa follows
+    ///the `r_msaa` cvar
+    pub fn set_post_process(&mut self, post_process: Option<Rc<RefCell<PostProcessStack>>>) {
+        self.post_process = post_process;
+    }
+
+    pub fn post_process(&self) -> Option<&Rc<RefCell<PostProcessStack>>> {
+        self.post_process.as_ref()
+    }
+
+    ///the imgui context, e.g. to turn on the demo window. layers add their own windows
+    ///through `Layer::on_imgui`
+    pub fn imgui(&mut self) -> &mut ImGuiLayer {
+        &mut self.imgui
+    }
+
+    ///a console script run once before the first frame, once every layer is attached so their
+    ///commands and cvars exist. a missing file is skipped
+    pub fn set_startup_script(&mut self, path: Option<PathBuf>) {
+        self.startup_script = path;
+    }
+
+    ///screenshots (F12 by default) and frame sequences
+    pub fn capture(&mut self) -> &mut FrameCapture {
+        &mut self.capture
     }
 
     pub fn run(&mut self) {
+        if let Some(path) = self.startup_script.as_ref().filter(|p| p.exists())
+            && let Err(e) = console::exec_file(path)
+        {
+            rge_engine_error!("{}", e);
+        }
         while self.is_running {
+            self.layer_stack.reset_timings();
             //events
-            let event = self.window.handle_events();
-
-            if let Some(e) = event {
-                if e.get_type() == EventType::WindowClose {
-                    self.is_running = false;
+            self.window.pump_events();
+            while let Some(mut e) = self.window.handle_events() {
+                match &e {
+                    RGEvent::WindowClose(_) => self.is_running = false,
+                    RGEvent::WindowResize(r) => self
+                        .renderer
+                        .on_window_resize(r.width as u32, r.height as u32),
+                    _ => {}
+                }
+                if let Some(post_process) = &self.post_process {
+                    post_process.borrow_mut().on_event(&e);
                 }
+                self.capture.on_event(&e);
+                self.imgui.on_event(&mut e);
                 self.layer_stack.on_event(&e);
             }
+            self.apply_console();
 
             //rendering
             self.layer_stack.update();
-            self.renderer.render_frame(&mut self.window);
-            self.layer_stack.render(&mut self.window);
+
+            let render_target = self.render_target.clone();
+            let framebuffer = render_target.as_ref().map(|f| f.borrow());
+            let target = match &framebuffer {
+                Some(f) => RenderTarget::Framebuffer(f),
+                None => RenderTarget::Window,
+            };
+            self.renderer.render_frame(target);
+            let post_process = self.post_process.clone();
+            let mut post_process = post_process.as_ref().map(|p| p.borrow_mut());
+            if let Some(post_process) = &mut post_process {
+                post_process.begin(self.renderer.clear_color());
+            }
+            self.layer_stack.render_layers(&mut self.window);
+            if let Some(post_process) = &mut post_process {
+                post_process.end();
+            }
+            drop(post_process);
+            self.renderer.end_frame(target);
+            self.layer_stack.render_overlays(&mut self.window);
+            self.imgui.render(&mut self.window, &mut self.layer_stack);
+            //the back buffer is in framebuffer pixels, which aren't the window size on hidpi
+            let (width, height) = self.window.window.get_framebuffer_size();
+            self.capture
+                .end_frame(width as u32, height as u32, framebuffer.as_deref());
 
             self.window.swap_buffers();
         }
     }
+
+    fn register_console(&self) {
+        console::register_cvar(
+            CVar::new(
+                "r_vsync",
+                true,
+                "waits for the monitor before showing a frame",
+            )
+            .archived(),
+        );
+        console::register_cvar(
+            CVar::new(
+                "r_msaa",
+                4,
+                "msaa samples of the post process scene target, 1 is off",
+            )
+            .archived()
+            .choices(&["1", "2", "4", "8"]),
+        );
+        console::register_cvar(
+            CVar::new(
+                "log_level",
+                "trace",
+                "the most verbose level both loggers write, RGE_LOG sets them at startup",
+            )
+            .archived()
+            .choices(&LOG_LEVELS),
+        );
+
+        let quit_requested = self.quit_requested.clone();
+        console::register_command(Command::new("quit", "closes the engine", move |_| {
+            quit_requested.set(true);
+            Ok(())
+        }));
+        let screenshots_requested = self.screenshots_requested.clone();
+        console::register_command(
+            Command::new(
+                "screenshot",
+                "saves the next frame, or a sequence of frames",
+                move |args| {
+                    let frames = args.int("frames");
+                    if frames < 1 {
+                        return Err("frames has to be at least 1".to_string());
+                    }
+                    screenshots_requested.set(frames as u32);
+                    Ok(())
+                },
+            )
+            .optional_arg("frames", 1),
+        );
+        let log_level = self.log_level.clone();
+        console::register_command(
+            Command::new(
+                "log_levels",
+                "prints the loggers' levels or sets them like RGE_LOG, e.g. engine=warn",
+                move |args| {
+                    let spec = args.string("spec");
+                    if !spec.is_empty() {
+                        log::set_levels(spec)?;
+                        //`log_level` follows without applying itself over the new levels
+                        let level = log::most_verbose_level().map_or("off", |l| l.as_str());
+                        *log_level.borrow_mut() = Some(level.to_string());
+                        return console::set_cvar("log_level", level);
+                    }
+                    for name in ["client", "engine"] {
+                        if let Some(logger) = log::get_logger(name) {
+                            let level = log::level_filter_name(logger.level_filter());
+                            console::print(&format!("{name}={level}"));
+                        }
+                    }
+                    Ok(())
+                },
+            )
+            .optional_arg("spec", ""),
+        );
+        let perf_overlay_toggled = self.perf_overlay_toggled.clone();
+        console::register_command(Command::new(
+            "perf_overlay",
+            "shows or hides fps, layer timings and renderer stats",
+            move |_| {
+                perf_overlay_toggled.set(true);
+                Ok(())
+            },
+        ));
+    }
+
+    //the cvars are checked every frame so they can be changed from anywhere
+    fn apply_console(&mut self) {
+        if self.quit_requested.take() {
+            self.is_running = false;
+        }
+        if self.perf_overlay_toggled.take() {
+            self.imgui.perf_overlay_mut().toggle();
+        }
+        match self.screenshots_requested.take() {
+            0 => {}
+            1 => self.capture.request_screenshot(),
+            frames => self.capture.capture_frames(frames),
+        }
+
+        if let Some(vsync) = console::cvar_bool("r_vsync")
+            && vsync != self.window.is_vsync()
+        {
+            self.window.set_vsync(vsync);
+        }
+        if let Some(samples) = console::cvar_int("r_msaa")
+            && let Some(post_process) = &self.post_process
+            && post_process.borrow().samples() != samples as u32
+        {
+            post_process.borrow_mut().set_samples(samples as u32);
+        }
+        let level = console::cvar_string("log_level");
+        if level != *self.log_level.borrow() {
+            if let Some(filter) = level.as_deref().and_then(log::parse_level_filter) {
+                log::set_level_filter(filter);
+            }
+            *self.log_level.borrow_mut() = level;
+        }
+    }
+}
+
+impl Default for GameEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//the loggers live in statics, which are never dropped
+impl Drop for GameEngine {
+    fn drop(&mut self) {
+        log::flush();
+    }
 }
@@ -1,10 +1,8 @@
-use std::any::Any;
-
-use glfw::{Action, Key, Modifiers, MouseButton, Scancode, WindowEvent};
-
 use paste::paste;
+
+use crate::math::{IVec2, Vec2};
 //might need to change how all this works
-////need a much better way of comparing event types
+//need a much better way of comparing event types
 
 #[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Debug)]
 pub enum EventType {
@@ -15,10 +13,14 @@ pub enum EventType {
     WindowLostFocus,
     KeyPressed,
     KeyReleased,
+    KeyTyped,
     MouseButtonPressed,
     MouseButtonReleased,
     MouseMoved,
     MouseScrolled,
+    GamepadButtonPressed,
+    GamepadButtonReleased,
+    GamepadAxisMoved,
     Unknown,
 }
 
@@ -31,6 +33,7 @@ bitflags::bitflags! {
     const Keyboard = 1 << 2;
     const Mouse = 1 << 3;
     const MouseButton = 1 << 4;
+    const Gamepad = 1 << 5;
     }
 }
 #[macro_export]
@@ -50,28 +53,47 @@ pub enum RGEvent {
     WindowResize(WindowResize),
     KeyPressed(KeyPressed),
     KeyReleased(KeyReleased),
+    KeyTyped(KeyTyped),
     MouseButtonPressed(MouseButtonPressed),
     MouseButtonReleased(MouseButtonReleased),
     MouseMoved(MouseMoved),
     MouseScrolled(MouseScrolled),
+    GamepadButtonPressed(GamepadButtonPressed),
+    GamepadButtonReleased(GamepadButtonReleased),
+    GamepadAxisMoved(GamepadAxisMoved),
+}
+
+macro_rules! forEvent {
+    ($event:expr, $name:ident($($arg:ident),*)) => {
+        match $event {
+            RGEvent::WindowClose(event) => event.$name($($arg),*),
+            RGEvent::WindowFocus(event) => event.$name($($arg),*),
+            RGEvent::WindowLostFocus(event) => event.$name($($arg),*),
+            RGEvent::WindowMoved(event) => event.$name($($arg),*),
+            RGEvent::WindowResize(event) => event.$name($($arg),*),
+            RGEvent::KeyPressed(event) => event.$name($($arg),*),
+            RGEvent::KeyReleased(event) => event.$name($($arg),*),
+            RGEvent::KeyTyped(event) => event.$name($($arg),*),
+            RGEvent::MouseButtonPressed(event) => event.$name($($arg),*),
+            RGEvent::MouseButtonReleased(event) => event.$name($($arg),*),
+            RGEvent::MouseMoved(event) => event.$name($($arg),*),
+            RGEvent::MouseScrolled(event) => event.$name($($arg),*),
+            RGEvent::GamepadButtonPressed(event) => event.$name($($arg),*),
+            RGEvent::GamepadButtonReleased(event) => event.$name($($arg),*),
+            RGEvent::GamepadAxisMoved(event) => event.$name($($arg),*),
+        }
+    };
 }
 
 macro_rules! makeFn {
     ($name:ident, $type:ty) => {
         pub fn $name(&self) -> $type {
-            match self {
-                RGEvent::WindowClose(event) => event.$name(),
-                RGEvent::WindowFocus(event) => event.$name(),
-                RGEvent::WindowLostFocus(event) => event.$name(),
-                RGEvent::WindowMoved(event) => event.$name(),
-                RGEvent::WindowResize(event) => event.$name(),
-                RGEvent::KeyPressed(event) => event.$name(),
-                RGEvent::KeyReleased(event) => event.$name(),
-                RGEvent::MouseButtonPressed(event) => event.$name(),
-                RGEvent::MouseButtonReleased(event) => event.$name(),
-                RGEvent::MouseMoved(event) => event.$name(),
-                RGEvent::MouseScrolled(event) => event.$name(),
-            }
+            forEvent!(self, $name())
+        }
+    };
+    (mut $name:ident $(, $arg:ident: $arg_type:ty)*) => {
+        pub fn $name(&mut self $(, $arg: $arg_type)*) {
+            forEvent!(self, $name($($arg),*))
         }
     };
 }
@@ -80,6 +102,8 @@ impl RGEvent {
     makeFn!(get_type, EventType);
     makeFn!(get_category, EventCategory);
     makeFn!(is_handled, bool);
+    //handled events aren't passed on to the layers below
+    makeFn!(mut set_handled, b: bool);
 
     pub fn is_in_category(&self, category: EventCategory) -> bool {
         self.get_category().contains(category)
@@ -96,6 +120,7 @@ macro_rules! create_event_struct {
             }
 
 
+            #[allow(clippy::new_without_default)]
             impl $event_type {
                 /// Constructor for the event struct
                 pub fn new($($field_name: $field_type),*) -> Self {
@@ -135,12 +160,45 @@ create_event_struct!(WindowClose, EventCategory::Engine);
 create_event_struct!(WindowFocus, EventCategory::Engine);
 create_event_struct!(WindowLostFocus, EventCategory::Engine);
 create_event_struct!(WindowMoved, EventCategory::Engine, x:i32, y:i32);
+//the new framebuffer size in pixels, which is bigger than the window's size on hidpi screens
 create_event_struct!(WindowResize, EventCategory::Engine, width:i32, height:i32);
 
 create_event_struct!(KeyPressed, EventCategory::Keyboard | EventCategory::Input, key:glfw::Key, repeat:bool);
 create_event_struct!(KeyReleased,EventCategory::Keyboard | EventCategory::Input, key:glfw::Key);
+//text input, with the keyboard layout and dead keys already applied
+create_event_struct!(KeyTyped, EventCategory::Keyboard | EventCategory::Input, ch:char);
 create_event_struct!(MouseButtonPressed,EventCategory::MouseButton | EventCategory::Input | EventCategory::Mouse, button:glfw::MouseButton);
 create_event_struct!(MouseButtonReleased,EventCategory::MouseButton | EventCategory::Input | EventCategory::Mouse, button:glfw::MouseButton);
 
 create_event_struct!(MouseMoved, EventCategory::Input | EventCategory::Mouse, x:f64, y:f64);
 create_event_struct!(MouseScrolled, EventCategory::Input | EventCategory::Mouse,x_offset:f64, y_offset:f64);
+
+//from the first connected joystick with a gamepad mapping
+create_event_struct!(GamepadButtonPressed, EventCategory::Gamepad | EventCategory::Input, button:glfw::GamepadButton);
+create_event_struct!(GamepadButtonReleased, EventCategory::Gamepad | EventCategory::Input, button:glfw::GamepadButton);
+create_event_struct!(GamepadAxisMoved, EventCategory::Gamepad | EventCategory::Input, axis:glfw::GamepadAxis, value:f32);
+
+//vector versions of the raw fields
+impl WindowMoved {
+    pub fn position(&self) -> IVec2 {
+        IVec2::new(self.x, self.y)
+    }
+}
+
+impl WindowResize {
+    pub fn size(&self) -> IVec2 {
+        IVec2::new(self.width, self.height)
+    }
+}
+
+impl MouseMoved {
+    pub fn position(&self) -> Vec2 {
+        Vec2::new(self.x as f32, self.y as f32)
+    }
+}
+
+impl MouseScrolled {
+    pub fn offset(&self) -> Vec2 {
+        Vec2::new(self.x_offset as f32, self.y_offset as f32)
+    }
+}
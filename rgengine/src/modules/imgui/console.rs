@@ -0,0 +1,264 @@
+use std::{process::Command, sync::Arc};
+
+use chrono::{DateTime, Local};
+use imgui::{ListClipper, StyleColor, Ui};
+use spdlog::Level;
+
+use super::UiSettings;
+use crate::log::{LogEntry, RingBufferSink, get_log_buffer, rge_engine_error};
+
+const CONSOLE_SECTION: &str = "console";
+const LEVELS: usize = Level::Trace as usize + 1;
+
+///the color a level has in the terminal
+pub fn level_color(level: Level) -> [f32; 4] {
+    match level {
+        Level::Trace => [0.55, 0.55, 0.55, 1.0],
+        Level::Debug => [0.3, 0.8, 0.85, 1.0],
+        Level::Info => [0.4, 0.8, 0.4, 1.0],
+        Level::Warn => [0.95, 0.8, 0.3, 1.0],
+        Level::Error => [0.95, 0.35, 0.3, 1.0],
+        Level::Critical => [0.85, 0.4, 0.85, 1.0],
+    }
+}
+
+///the log panel's contents, the records of a `RingBufferSink` in their level's color with
+///level and text filters. clicking a record opens its source location with the command set by
+///`set_open_command`, or copies it when there is none
+pub struct LogConsole {
+    buffer: Option<Arc<RingBufferSink>>,
+    shown: [bool; LEVELS],
+    search: String,
+    //lowercased once when the search changes instead of for every record
+    search_lowercase: String,
+    //copies of the records that pass the filters, so the buffer isn't locked while they're
+    //drawn. refiltered when the buffer's total and length or the filters change
+    visible: Vec<LogEntry>,
+    filtered: Option<(u64, usize)>,
+    auto_scroll: bool,
+    open_command: Option<String>,
+    changed: bool,
+}
+
+impl Default for LogConsole {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogConsole {
+    ///shows the loggers' buffer from `log::init`, the open command comes from `RGE_EDITOR`
+    pub fn new() -> Self {
+        Self {
+            buffer: get_log_buffer().cloned(),
+            shown: [true; LEVELS],
+            search: String::new(),
+            search_lowercase: String::new(),
+            visible: Vec::new(),
+            filtered: None,
+            auto_scroll: true,
+            open_command: std::env::var("RGE_EDITOR").ok(),
+            changed: false,
+        }
+    }
+
+    pub fn buffer(&self) -> Option<&Arc<RingBufferSink>> {
+        self.buffer.as_ref()
+    }
+
+    pub fn set_buffer(&mut self, buffer: Option<Arc<RingBufferSink>>) {
+        self.buffer = buffer;
+        self.filtered = None;
+    }
+
+    pub fn is_level_shown(&self, level: Level) -> bool {
+        self.shown[level as usize]
+    }
+
+    pub fn set_level_shown(&mut self, level: Level, shown: bool) {
+        if self.shown[level as usize] != shown {
+            self.changed = true;
+            self.filtered = None;
+        }
+        self.shown[level as usize] = shown;
+    }
+
+    ///records containing this in their payload or logger name, ignoring case
+    pub fn set_search(&mut self, search: &str) {
+        self.search = search.to_string();
+        self.search_changed();
+    }
+
+    fn search_changed(&mut self) {
+        self.search_lowercase = self.search.to_lowercase();
+        self.filtered = None;
+    }
+
+    ///keeps the newest record in view while the list is scrolled to the bottom
+    pub fn set_auto_scroll(&mut self, auto_scroll: bool) {
+        self.changed |= self.auto_scroll != auto_scroll;
+        self.auto_scroll = auto_scroll;
+    }
+
+    ///e.g. `code --goto {file}:{line}`, `{file}` and `{line}` are replaced before it's split
+    ///on spaces
+    pub fn set_open_command(&mut self, command: Option<String>) {
+        self.open_command = command;
+    }
+
+    pub fn is_visible(&self, entry: &LogEntry) -> bool {
+        is_visible(entry, &self.shown, &self.search_lowercase)
+    }
+
+    //copies what's visible out of the buffer if anything changed since the last frame
+    fn filter(&mut self, buffer: &RingBufferSink) {
+        let records = buffer.records();
+        let key = (buffer.total(), records.len());
+        if self.filtered == Some(key) {
+            return;
+        }
+        let (shown, search) = (&self.shown, &self.search_lowercase);
+        self.visible.clear();
+        self.visible.extend(
+            records
+                .iter()
+                .filter(|entry| is_visible(entry, shown, search))
+                .cloned(),
+        );
+        self.filtered = Some(key);
+    }
+
+    ///the toolbar and the records, meant for the inside of a panel
+    pub fn draw(&mut self, ui: &Ui) {
+        let Some(buffer) = self.buffer.clone() else {
+            ui.text_disabled("logging isn't initialized, see log::init");
+            return;
+        };
+        if ui.button("Clear") {
+            buffer.clear();
+        }
+        ui.same_line();
+        let mut auto_scroll = self.auto_scroll;
+        ui.checkbox("Auto-scroll", &mut auto_scroll);
+        self.set_auto_scroll(auto_scroll);
+        for level in Level::iter() {
+            ui.same_line();
+            let mut shown = self.is_level_shown(level);
+            let color = ui.push_style_color(StyleColor::Text, level_color(level));
+            ui.checkbox(level.as_str(), &mut shown);
+            color.pop();
+            self.set_level_shown(level, shown);
+        }
+        ui.same_line();
+        ui.set_next_item_width(-f32::MIN_POSITIVE);
+        if ui
+            .input_text("##search", &mut self.search)
+            .hint("search")
+            .build()
+        {
+            self.search_changed();
+        }
+        ui.separator();
+
+        self.filter(&buffer);
+        let mut clicked = None;
+        ui.child_window("records")
+            .horizontal_scrollbar(true)
+            .build(|| {
+                for i in ListClipper::new(self.visible.len() as i32).begin(ui).iter() {
+                    let entry = &self.visible[i as usize];
+                    if self.entry(ui, entry) {
+                        clicked = entry.source;
+                    }
+                }
+                if self.auto_scroll && ui.scroll_y() >= ui.scroll_max_y() {
+                    ui.set_scroll_here_y_with_ratio(1.0);
+                }
+            });
+        if let Some((file, line)) = clicked {
+            self.open_source(ui, file, line);
+        }
+    }
+
+    //one row per record, returns whether it was clicked
+    fn entry(&self, ui: &Ui, entry: &LogEntry) -> bool {
+        let time = DateTime::<Local>::from(entry.time).format("%H:%M:%S%.3f");
+        let mut lines = entry.payload.lines();
+        let first = lines.next().unwrap_or_default();
+        let more = if lines.next().is_some() { " ..." } else { "" };
+        ui.text_colored(
+            level_color(entry.level),
+            format!("[{time}] [{}] {}: {first}{more}", entry.level, entry.logger),
+        );
+        if ui.is_item_hovered() {
+            ui.tooltip(|| {
+                if !more.is_empty() {
+                    ui.text(&entry.payload);
+                }
+                if let Some((file, line)) = entry.source {
+                    let action = if self.open_command.is_some() {
+                        "open"
+                    } else {
+                        "copy"
+                    };
+                    ui.text_disabled(format!("{file}:{line}, click to {action}"));
+                }
+            });
+        }
+        entry.source.is_some() && ui.is_item_clicked()
+    }
+
+    fn open_source(&self, ui: &Ui, file: &str, line: u32) {
+        let Some(command) = &self.open_command else {
+            ui.set_clipboard_text(format!("{file}:{line}"));
+            return;
+        };
+        let command = command
+            .replace("{file}", file)
+            .replace("{line}", &line.to_string());
+        let mut args = command.split_whitespace();
+        let Some(program) = args.next() else {
+            return;
+        };
+        if let Err(e) = Command::new(program).args(args).spawn() {
+            rge_engine_error!("failed to run `{}`: {}", command, e);
+        }
+    }
+
+    pub(super) fn load(&mut self, settings: &UiSettings) {
+        for level in Level::iter() {
+            if let Some(shown) = settings.get(CONSOLE_SECTION, level.as_str()) {
+                self.shown[level as usize] = shown != "0";
+            }
+        }
+        self.filtered = None;
+        if let Some(auto_scroll) = settings.get(CONSOLE_SECTION, "auto_scroll") {
+            self.auto_scroll = auto_scroll != "0";
+        }
+    }
+
+    pub(super) fn store(&mut self, settings: &mut UiSettings) {
+        if !std::mem::take(&mut self.changed) {
+            return;
+        }
+        for level in Level::iter() {
+            settings.set(
+                CONSOLE_SECTION,
+                level.as_str(),
+                u8::from(self.is_level_shown(level)),
+            );
+        }
+        settings.set(CONSOLE_SECTION, "auto_scroll", u8::from(self.auto_scroll));
+    }
+}
+
+//`search` is already lowercase
+fn is_visible(entry: &LogEntry, shown: &[bool; LEVELS], search: &str) -> bool {
+    if !shown[entry.level as usize] {
+        return false;
+    }
+    if search.is_empty() {
+        return true;
+    }
+    entry.payload.to_lowercase().contains(search) || entry.logger.to_lowercase().contains(search)
+}
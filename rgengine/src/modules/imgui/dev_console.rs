@@ -0,0 +1,234 @@
+use glfw::Key;
+use imgui::{
+    Condition, HistoryDirection, InputTextCallback, InputTextCallbackHandler, StyleColor,
+    TextCallbackData, Ui, WindowFlags, sys,
+};
+
+use super::UiSettings;
+use crate::{
+    console::{self, OutputKind},
+    events::RGEvent,
+};
+
+const CVARS_SECTION: &str = "cvars";
+
+///a quake style console dropping down over the top of the main window, it runs what's typed
+///with `console::execute`. up and down go through the history, tab completes names and cvar
+///values
+pub struct DevConsole {
+    open: bool,
+    toggle_key: Option<Key>,
+    //fraction of the window it covers
+    height: f32,
+    input: String,
+    //the history entry shown in the input, `None` while typing a new line
+    history_position: Option<usize>,
+    focus_input: bool,
+    //the key that opens it also types a character, which shouldn't end up in the input
+    swallow_char: bool,
+    output_len: usize,
+    archive_generation: u64,
+}
+
+impl Default for DevConsole {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DevConsole {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            toggle_key: Some(Key::GraveAccent),
+            height: 0.4,
+            input: String::new(),
+            history_position: None,
+            focus_input: false,
+            swallow_char: false,
+            output_len: 0,
+            archive_generation: console::archive_generation(),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        self.focus_input |= open && !self.open;
+        self.open = open;
+    }
+
+    pub fn toggle(&mut self) {
+        self.set_open(!self.open);
+    }
+
+    ///`None` turns the key binding off, the key left of 1 (grave accent) by default
+    pub fn set_toggle_key(&mut self, key: Option<Key>) {
+        self.toggle_key = key;
+    }
+
+    ///how much of the window's height it covers, 0.4 by default
+    pub fn set_height(&mut self, height: f32) {
+        self.height = height.clamp(0.1, 1.0);
+    }
+
+    ///true for events it used, the toggle key and the character it types
+    pub fn on_event(&mut self, event: &RGEvent) -> bool {
+        match event {
+            RGEvent::KeyPressed(e) if Some(e.key) == self.toggle_key => {
+                if !e.repeat {
+                    self.toggle();
+                    self.swallow_char = true;
+                }
+                true
+            }
+            RGEvent::KeyReleased(e) if Some(e.key) == self.toggle_key => true,
+            RGEvent::KeyTyped(_) if self.swallow_char => {
+                self.swallow_char = false;
+                true
+            }
+            _ => {
+                self.swallow_char = false;
+                false
+            }
+        }
+    }
+
+    pub fn draw(&mut self, ui: &Ui) {
+        if !self.open {
+            return;
+        }
+        let viewport = unsafe { &*sys::igGetMainViewport() };
+        let (position, size) = (viewport.WorkPos, viewport.WorkSize);
+        unsafe { sys::igSetNextWindowViewport(viewport.ID) };
+        let flags = WindowFlags::NO_TITLE_BAR
+            | WindowFlags::NO_RESIZE
+            | WindowFlags::NO_MOVE
+            | WindowFlags::NO_COLLAPSE
+            | WindowFlags::NO_SAVED_SETTINGS
+            | WindowFlags::NO_DOCKING;
+        let focus = std::mem::take(&mut self.focus_input);
+        ui.window("Console")
+            .position([position.x, position.y], Condition::Always)
+            .size([size.x, size.y * self.height], Condition::Always)
+            .bg_alpha(0.9)
+            .focused(focus)
+            .flags(flags)
+            .build(|| {
+                self.output(ui);
+                self.input(ui, focus);
+            });
+    }
+
+    fn output(&mut self, ui: &Ui) {
+        let footer = ui.frame_height_with_spacing();
+        ui.child_window("output")
+            .size([0.0, -footer])
+            .horizontal_scrollbar(true)
+            .build(|| {
+                let (len, follow) = console::with_output(|lines| {
+                    let follow = lines.len() != self.output_len;
+                    for line in lines {
+                        let color = match line.kind {
+                            OutputKind::Input => ui.style_color(StyleColor::TextDisabled),
+                            OutputKind::Info => ui.style_color(StyleColor::Text),
+                            OutputKind::Error => [0.95, 0.35, 0.3, 1.0],
+                        };
+                        ui.text_colored(color, &line.text);
+                    }
+                    (lines.len(), follow)
+                });
+                self.output_len = len;
+                if follow {
+                    ui.set_scroll_here_y_with_ratio(1.0);
+                }
+            });
+    }
+
+    fn input(&mut self, ui: &Ui, focus: bool) {
+        let history = console::history();
+        ui.set_next_item_width(-f32::MIN_POSITIVE);
+        if focus {
+            ui.set_keyboard_focus_here();
+        }
+        let entered = ui
+            .input_text("##input", &mut self.input)
+            .hint("help lists the commands")
+            .enter_returns_true(true)
+            .callback(
+                InputTextCallback::HISTORY | InputTextCallback::COMPLETION,
+                Callbacks {
+                    history: &history,
+                    position: &mut self.history_position,
+                },
+            )
+            .build();
+        if entered {
+            console::execute(&std::mem::take(&mut self.input));
+            self.history_position = None;
+            //enter takes the focus away from the input
+            ui.set_keyboard_focus_here_with_offset(imgui::FocusedWidget::Previous);
+        }
+    }
+
+    pub(super) fn load(&mut self, settings: &UiSettings) {
+        if let Some(section) = settings.section(CVARS_SECTION) {
+            console::load_archived(section.clone());
+        }
+        self.archive_generation = console::archive_generation();
+    }
+
+    //rewrites the cvars section whenever an archived cvar changed
+    pub(super) fn store(&mut self, settings: &mut UiSettings) {
+        let generation = console::archive_generation();
+        if generation == self.archive_generation {
+            return;
+        }
+        self.archive_generation = generation;
+        settings.remove_section(CVARS_SECTION);
+        for (name, value) in console::archived_cvars() {
+            settings.set(CVARS_SECTION, &name, value);
+        }
+    }
+}
+
+struct Callbacks<'a> {
+    history: &'a [String],
+    position: &'a mut Option<usize>,
+}
+
+impl InputTextCallbackHandler for Callbacks<'_> {
+    fn on_history(&mut self, direction: HistoryDirection, mut data: TextCallbackData) {
+        //down while typing a new line keeps it
+        if self.history.is_empty() || direction == HistoryDirection::Down && self.position.is_none()
+        {
+            return;
+        }
+        let last = self.history.len().checked_sub(1);
+        *self.position = match (direction, *self.position) {
+            (HistoryDirection::Up, None) => last,
+            (HistoryDirection::Up, Some(position)) => Some(position.saturating_sub(1)),
+            (HistoryDirection::Down, Some(position)) if Some(position) != last => {
+                Some(position + 1)
+            }
+            (HistoryDirection::Down, _) => None,
+        };
+        data.clear();
+        if let Some(line) = self.position.and_then(|p| self.history.get(p)) {
+            data.push_str(line);
+        }
+    }
+
+    fn on_completion(&mut self, mut data: TextCallbackData) {
+        let completion = console::complete(data.str());
+        if completion.line != data.str() {
+            data.clear();
+            data.push_str(&completion.line);
+        }
+        if !completion.candidates.is_empty() {
+            console::print(&completion.candidates.join("  "));
+        }
+    }
+}
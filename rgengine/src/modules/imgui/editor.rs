@@ -0,0 +1,415 @@
+use std::{
+    collections::BTreeMap,
+    ffi::CString,
+    ptr::{null, null_mut},
+};
+
+use imgui::{Condition, Image, StyleVar, TreeNodeFlags, Ui, WindowFlags, sys};
+
+use super::{LogConsole, UiSettings, framebuffer_texture_id};
+use crate::{layers::LayerStack, renderer::framebuffer::Framebuffer};
+
+const PANELS_SECTION: &str = "editor.panels";
+const LAYOUT_SECTION: &str = "editor.layout";
+
+///where a panel starts out in the default layout
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DockArea {
+    Center,
+    Left,
+    Right,
+    Bottom,
+}
+
+impl DockArea {
+    const ALL: [DockArea; 4] = [
+        DockArea::Center,
+        DockArea::Left,
+        DockArea::Right,
+        DockArea::Bottom,
+    ];
+
+    fn key(self) -> &'static str {
+        match self {
+            DockArea::Center => "center",
+            DockArea::Left => "left",
+            DockArea::Right => "right",
+            DockArea::Bottom => "bottom",
+        }
+    }
+}
+
+///the panels the default layout has a place for. the engine fills the layer stack, stats and log,
+///layers fill the rest from `Layer::on_imgui`, several of them can add to the same panel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StandardPanel {
+    SceneViewport,
+    Log,
+    Stats,
+    LayerStack,
+    Properties,
+}
+
+impl StandardPanel {
+    pub const ALL: [StandardPanel; 5] = [
+        StandardPanel::SceneViewport,
+        StandardPanel::Log,
+        StandardPanel::Stats,
+        StandardPanel::LayerStack,
+        StandardPanel::Properties,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            StandardPanel::SceneViewport => "Scene Viewport",
+            StandardPanel::Log => "Log",
+            StandardPanel::Stats => "Stats",
+            StandardPanel::LayerStack => "Layer Stack",
+            StandardPanel::Properties => "Properties",
+        }
+    }
+
+    pub fn area(self) -> DockArea {
+        match self {
+            StandardPanel::SceneViewport => DockArea::Center,
+            StandardPanel::Log | StandardPanel::Stats => DockArea::Bottom,
+            StandardPanel::LayerStack => DockArea::Left,
+            StandardPanel::Properties => DockArea::Right,
+        }
+    }
+}
+
+///the scene viewport panel as it was drawn this frame
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SceneViewport {
+    ///in framebuffer pixels, resize the framebuffer to this from `on_update`
+    pub size: [u32; 2],
+    ///top left of the image in imgui's coordinates, the desktop's while viewports are on
+    pub position: [f32; 2],
+    pub hovered: bool,
+    pub focused: bool,
+}
+
+//what the menu bar changes outside the editor
+pub(super) struct MenuState<'a> {
+    pub show_demo_window: &'a mut bool,
+    pub viewports: &'a mut bool,
+    pub reset_layout: bool,
+}
+
+///a menu bar and a dockspace over the main window with a default layout for the standard
+///panels. panels are imgui windows added with `panel`, they can be docked anywhere, closed
+///from the view menu and dragged out of the window while viewports are on. layers can add
+///their own menus with `ui.main_menu_bar`
+pub struct Editor {
+    enabled: bool,
+    //every panel seen so far and whether it's open
+    panels: BTreeMap<String, bool>,
+    //dock nodes of the default layout, so panels seen for the first time start in their area
+    //of a saved layout too
+    areas: BTreeMap<&'static str, u32>,
+    scene: Option<SceneViewport>,
+    console: LogConsole,
+    changed: bool,
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Editor {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            panels: StandardPanel::ALL
+                .iter()
+                .map(|panel| (panel.title().to_string(), true))
+                .collect(),
+            areas: BTreeMap::new(),
+            scene: None,
+            console: LogConsole::new(),
+            changed: false,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    ///off, panels are plain windows and the engine's own panels aren't shown
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    ///the scene viewport of the last frame, `None` while it's closed or hidden behind a tab
+    pub fn scene_viewport_state(&self) -> Option<SceneViewport> {
+        self.scene
+    }
+
+    pub fn is_panel_open(&self, title: &str) -> bool {
+        self.panels.get(title).copied().unwrap_or(true)
+    }
+
+    pub fn set_panel_open(&mut self, title: &str, open: bool) {
+        if self.panels.insert(title.to_string(), open) != Some(open) {
+            self.changed = true;
+        }
+    }
+
+    ///a dockable window starting in `area`, `build` isn't called while it's closed or collapsed.
+    ///returns what `build` did
+    pub fn panel<R, F: FnOnce() -> R>(
+        &mut self,
+        ui: &Ui,
+        title: &str,
+        area: DockArea,
+        build: F,
+    ) -> Option<R> {
+        let mut open = *self.panels.entry(title.to_string()).or_insert_with(|| {
+            self.changed = true;
+            true
+        });
+        if !open {
+            return None;
+        }
+        if let Some(&node) = self.areas.get(area.key()).filter(|_| self.enabled) {
+            unsafe { sys::igSetNextWindowDockID(node, Condition::FirstUseEver as i32) };
+        }
+        let result = ui
+            .window(title)
+            .size([400.0, 300.0], Condition::FirstUseEver)
+            .opened(&mut open)
+            .build(build);
+        if !open {
+            self.set_panel_open(title, false);
+        }
+        result
+    }
+
+    pub fn standard_panel<R, F: FnOnce() -> R>(
+        &mut self,
+        ui: &Ui,
+        panel: StandardPanel,
+        build: F,
+    ) -> Option<R> {
+        self.panel(ui, panel.title(), panel.area(), build)
+    }
+
+    ///shows a color attachment of `framebuffer` filling the scene viewport panel. render the
+    ///scene into it with `GameEngine::set_render_target` and resize it to the returned size
+    pub fn scene_viewport(&mut self, ui: &Ui, framebuffer: &Framebuffer) -> Option<SceneViewport> {
+        let scale = ui.io().display_framebuffer_scale;
+        let padding = ui.push_style_var(StyleVar::WindowPadding([0.0, 0.0]));
+        let scene = self.standard_panel(ui, StandardPanel::SceneViewport, || {
+            padding.end();
+            let size = ui.content_region_avail();
+            //gl textures start at the bottom
+            Image::new(framebuffer_texture_id(framebuffer, 0), size)
+                .uv0([0.0, 1.0])
+                .uv1([1.0, 0.0])
+                .build(ui);
+            SceneViewport {
+                size: [
+                    (size[0] * scale[0]).max(1.0) as u32,
+                    (size[1] * scale[1]).max(1.0) as u32,
+                ],
+                position: ui.item_rect_min(),
+                hovered: ui.is_item_hovered(),
+                focused: ui.is_window_focused(),
+            }
+        });
+        self.scene = self.scene.or(scene);
+        scene
+    }
+
+    ///what the log panel shows and how it opens source locations
+    pub fn console(&self) -> &LogConsole {
+        &self.console
+    }
+
+    pub fn console_mut(&mut self) -> &mut LogConsole {
+        &mut self.console
+    }
+
+    ///the records of both loggers, see `LogConsole`
+    pub fn log_panel(&mut self, ui: &Ui) {
+        //the console is moved out while the panel borrows the editor
+        let mut console = std::mem::take(&mut self.console);
+        self.standard_panel(ui, StandardPanel::Log, || console.draw(ui));
+        self.console = console;
+    }
+
+    ///the layers and overlays in the order they update
+    pub fn layer_stack_panel(&mut self, ui: &Ui, layer_stack: &LayerStack) {
+        self.standard_panel(ui, StandardPanel::LayerStack, || {
+            for (label, layers) in [
+                ("Layers", layer_stack.layers()),
+                ("Overlays", layer_stack.overlays()),
+            ] {
+                if !ui.collapsing_header(
+                    format!("{label} ({})", layers.len()),
+                    TreeNodeFlags::DEFAULT_OPEN,
+                ) {
+                    continue;
+                }
+                for (i, layer) in layers.iter().enumerate() {
+                    ui.text(format!("{i}: {} (id {})", layer.get_name(), layer.get_id()));
+                }
+            }
+        });
+    }
+
+    ///frame rate and imgui's own counts
+    pub fn stats_panel(&mut self, ui: &Ui) {
+        self.standard_panel(ui, StandardPanel::Stats, || {
+            let io = ui.io();
+            ui.text(format!(
+                "{:.1} fps, {:.2} ms",
+                io.framerate,
+                1000.0 / io.framerate.max(f32::EPSILON)
+            ));
+            ui.text(format!(
+                "ui: {} windows, {} vertices",
+                io.metrics_render_windows, io.metrics_render_vertices
+            ));
+        });
+    }
+
+    //menu bar and dockspace, before any panel
+    pub(super) fn begin(&mut self, ui: &Ui, menu: &mut MenuState) {
+        self.scene = None;
+        if !self.enabled {
+            return;
+        }
+        ui.main_menu_bar(|| {
+            ui.menu("File", || {
+                if ui.menu_item("Reset Layout") {
+                    menu.reset_layout = true;
+                }
+            });
+            ui.menu("View", || {
+                let mut toggled = None;
+                for (title, &open) in &self.panels {
+                    if ui.menu_item_config(title).selected(open).build() {
+                        toggled = Some((title.clone(), !open));
+                    }
+                }
+                if let Some((title, open)) = toggled {
+                    self.set_panel_open(&title, open);
+                }
+                ui.separator();
+                ui.menu_item_config("Detachable Windows")
+                    .build_with_ref(menu.viewports);
+                ui.menu_item_config("ImGui Demo")
+                    .build_with_ref(menu.show_demo_window);
+            });
+        });
+        self.dockspace(ui);
+    }
+
+    //a window covering the main viewport below the menu bar that hosts the dockspace, the
+    //scene shows through its empty middle
+    fn dockspace(&mut self, ui: &Ui) {
+        let viewport = unsafe { &*sys::igGetMainViewport() };
+        let (position, size) = (viewport.WorkPos, viewport.WorkSize);
+        unsafe { sys::igSetNextWindowViewport(viewport.ID) };
+        let rounding = ui.push_style_var(StyleVar::WindowRounding(0.0));
+        let border = ui.push_style_var(StyleVar::WindowBorderSize(0.0));
+        let padding = ui.push_style_var(StyleVar::WindowPadding([0.0, 0.0]));
+        ui.window("Editor")
+            .position([position.x, position.y], Condition::Always)
+            .size([size.x, size.y], Condition::Always)
+            .flags(
+                WindowFlags::NO_DECORATION
+                    | WindowFlags::NO_MOVE
+                    | WindowFlags::NO_DOCKING
+                    | WindowFlags::NO_BRING_TO_FRONT_ON_FOCUS
+                    | WindowFlags::NO_NAV_FOCUS
+                    | WindowFlags::NO_BACKGROUND
+                    | WindowFlags::NO_SAVED_SETTINGS,
+            )
+            .build(|| {
+                padding.end();
+                border.end();
+                rounding.end();
+                let id = unsafe { sys::igGetID_Str(c"Dockspace".as_ptr()) };
+                //no node means nothing was saved, or the layout was just reset
+                if unsafe { sys::igDockBuilderGetNode(id) }.is_null() {
+                    self.build_default_layout(id, [size.x, size.y]);
+                }
+                unsafe {
+                    sys::igDockSpace(
+                        id,
+                        sys::ImVec2 { x: 0.0, y: 0.0 },
+                        sys::ImGuiDockNodeFlags_PassthruCentralNode as i32,
+                        null(),
+                    )
+                };
+            });
+    }
+
+    fn build_default_layout(&mut self, dockspace: u32, size: [f32; 2]) {
+        let split = |node: &mut u32, dir: sys::ImGuiDir, ratio: f32| unsafe {
+            sys::igDockBuilderSplitNode(*node, dir, ratio, null_mut(), node)
+        };
+        unsafe {
+            sys::igDockBuilderAddNode(dockspace, sys::ImGuiDockNodeFlags_DockSpace);
+            sys::igDockBuilderSetNodeSize(
+                dockspace,
+                sys::ImVec2 {
+                    x: size[0],
+                    y: size[1],
+                },
+            );
+        }
+        let mut center = dockspace;
+        let left = split(&mut center, sys::ImGuiDir_Left, 0.2);
+        let right = split(&mut center, sys::ImGuiDir_Right, 0.25);
+        let bottom = split(&mut center, sys::ImGuiDir_Down, 0.3);
+        for (area, node) in DockArea::ALL.into_iter().zip([center, left, right, bottom]) {
+            self.areas.insert(area.key(), node);
+        }
+        for panel in StandardPanel::ALL {
+            let title = CString::new(panel.title()).unwrap_or_default();
+            unsafe { sys::igDockBuilderDockWindow(title.as_ptr(), self.areas[panel.area().key()]) };
+        }
+        unsafe { sys::igDockBuilderFinish(dockspace) };
+        self.changed = true;
+    }
+
+    ///opens every panel again, the layout itself is reset by the `ImGuiLayer`
+    pub(super) fn reset(&mut self) {
+        for open in self.panels.values_mut() {
+            *open = true;
+        }
+        self.changed = true;
+    }
+
+    pub(super) fn load(&mut self, settings: &UiSettings) {
+        self.console.load(settings);
+        for (title, open) in settings.section(PANELS_SECTION).into_iter().flatten() {
+            self.panels.insert(title.clone(), open.as_str() != "0");
+        }
+        for area in DockArea::ALL {
+            if let Some(node) = settings.get_parsed(LAYOUT_SECTION, area.key()) {
+                self.areas.insert(area.key(), node);
+            }
+        }
+    }
+
+    //only touches the settings when something changed, so they aren't saved every frame
+    pub(super) fn store(&mut self, settings: &mut UiSettings) {
+        self.console.store(settings);
+        if !std::mem::take(&mut self.changed) {
+            return;
+        }
+        for (title, &open) in &self.panels {
+            settings.set(PANELS_SECTION, title, u8::from(open));
+        }
+        for (area, node) in &self.areas {
+            settings.set(LAYOUT_SECTION, area, node);
+        }
+    }
+}
@@ -0,0 +1,345 @@
+mod console;
+mod dev_console;
+mod editor;
+mod perf_overlay;
+mod platform;
+mod renderer;
+mod settings;
+mod viewports;
+
+pub use console::{LogConsole, level_color};
+pub use dev_console::DevConsole;
+pub use editor::{DockArea, Editor, SceneViewport, StandardPanel};
+pub use perf_overlay::{PerfOverlay, resident_memory};
+pub use platform::ImGuiPlatform;
+pub use renderer::{
+    GlUiBackend, ImGuiRenderer, NullUiBackend, UiBackend, UiDrawCommand, UiTexture,
+    framebuffer_texture_id, texture_id,
+};
+pub use settings::{UiSettings, config_dir};
+
+use std::{
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use imgui::{ConfigFlags, Context, Ui};
+
+use editor::MenuState;
+use viewports::ViewportWindows;
+
+use crate::{
+    events::{EventCategory, RGEvent},
+    layers::LayerStack,
+    log::{rge_engine_error, rge_engine_warn},
+    window::WindowManager,
+};
+
+///owns the imgui context, the engine runs one frame of it after the overlays and every layer
+///adds its windows from `Layer::on_imgui`. the layout is saved with the `UiSettings` it's
+///made with, the engine's are the ones of its project
+pub struct ImGuiLayer {
+    imgui: Context,
+    platform: ImGuiPlatform,
+    renderer: ImGuiRenderer<GlUiBackend>,
+    viewports: ViewportWindows,
+    editor: Editor,
+    dev_console: DevConsole,
+    perf_overlay: PerfOverlay,
+    settings: UiSettings,
+    last_save: Instant,
+    reset_layout: bool,
+    id: usize,
+    show_demo_window: bool,
+}
+
+//window manager may be better as a shared referance
+impl ImGuiLayer {
+    ///`settings` are read by `load_settings`
+    pub fn new(window: &mut WindowManager, id: usize, settings: UiSettings) -> Self {
+        let viewports = ViewportWindows::new(window);
+        let (mut imgui, platform) = create_context(window, &viewports);
+        let renderer = ImGuiRenderer::new(&mut imgui, GlUiBackend::new());
+
+        Self {
+            imgui,
+            platform,
+            renderer,
+            viewports,
+            editor: Editor::new(),
+            dev_console: DevConsole::new(),
+            perf_overlay: PerfOverlay::new(),
+            settings,
+            last_save: Instant::now(),
+            reset_layout: false,
+            id,
+            show_demo_window: false,
+        }
+    }
+
+    ///reads the layout and app settings, including the console's archived cvars. the engine
+    ///calls this once its cvars are registered so the saved values replace the defaults
+    pub fn load_settings(&mut self) {
+        match self.settings.load() {
+            Ok(ini) => self.imgui.load_ini_settings(&ini),
+            Err(e) => rge_engine_warn!("failed to load the ui settings: {}", e),
+        }
+        self.editor.load(&self.settings);
+        self.dev_console.load(&self.settings);
+        self.perf_overlay.load(&self.settings);
+    }
+
+    ///the layout and the app sections saved with it
+    pub fn settings(&self) -> &UiSettings {
+        &self.settings
+    }
+
+    ///for the app's own sections, they are saved with the layout
+    pub fn settings_mut(&mut self) -> &mut UiSettings {
+        &mut self.settings
+    }
+
+    ///saves to the old file and moves to `path`, `None` stops saving. the layout and app
+    ///settings in a file that's already there are loaded, otherwise the current ones are
+    ///written there on the next save
+    pub fn set_settings_path(&mut self, path: Option<PathBuf>) {
+        self.save_settings();
+        let exists = path.as_deref().is_some_and(Path::exists);
+        self.settings.set_path(path);
+        if exists {
+            self.load_settings();
+        }
+    }
+
+    ///writes the layout and app settings now, this also happens every few seconds while they
+    ///change and when the layer is dropped
+    pub fn save_settings(&mut self) {
+        let mut ini = String::new();
+        self.imgui.save_ini_settings(&mut ini);
+        self.imgui.io_mut().want_save_ini_settings = false;
+        self.last_save = Instant::now();
+        if let Err(e) = self.settings.save(&ini) {
+            rge_engine_error!("failed to save the ui settings: {}", e);
+        }
+    }
+
+    ///puts every window back where the code places it on first use and the editor panels in
+    ///the default layout, at the start of the next frame. app settings are kept
+    pub fn reset_layout(&mut self) {
+        self.reset_layout = true;
+    }
+
+    ///the dockspace, menu bar and panels
+    pub fn editor(&self) -> &Editor {
+        &self.editor
+    }
+
+    pub fn editor_mut(&mut self) -> &mut Editor {
+        &mut self.editor
+    }
+
+    ///the command console, toggled with the grave accent key by default
+    pub fn dev_console(&self) -> &DevConsole {
+        &self.dev_console
+    }
+
+    pub fn dev_console_mut(&mut self) -> &mut DevConsole {
+        &mut self.dev_console
+    }
+
+    ///fps, layer timings and renderer stats, toggled with F3 by default
+    pub fn perf_overlay(&self) -> &PerfOverlay {
+        &self.perf_overlay
+    }
+
+    pub fn perf_overlay_mut(&mut self) -> &mut PerfOverlay {
+        &mut self.perf_overlay
+    }
+
+    ///the editor shell instead of loose windows, off by default
+    pub fn set_editor(&mut self, enabled: bool) {
+        self.editor.set_enabled(enabled);
+    }
+
+    ///lets imgui windows be dragged out of the main window into windows of their own, off by
+    ///default
+    pub fn set_viewports(&mut self, enabled: bool) {
+        //imgui stops managing the windows once this is off, they would stay open
+        if !enabled && self.viewports_enabled() {
+            unsafe { imgui::sys::igDestroyPlatformWindows() };
+        }
+        self.imgui
+            .io_mut()
+            .config_flags
+            .set(ConfigFlags::VIEWPORTS_ENABLE, enabled);
+    }
+
+    pub fn viewports_enabled(&self) -> bool {
+        self.imgui
+            .io()
+            .config_flags
+            .contains(ConfigFlags::VIEWPORTS_ENABLE)
+    }
+
+    ///the imgui demo, off by default
+    pub fn set_show_demo_window(&mut self, show: bool) {
+        self.show_demo_window = show;
+    }
+
+    pub fn show_demo_window(&self) -> bool {
+        self.show_demo_window
+    }
+
+    ///moving between widgets with a gamepad, off by default
+    pub fn set_gamepad_navigation(&mut self, enabled: bool) {
+        self.imgui
+            .io_mut()
+            .config_flags
+            .set(ConfigFlags::NAV_ENABLE_GAMEPAD, enabled);
+    }
+
+    ///the mouse is over an imgui window or dragging something in one, mouse events are marked
+    ///handled while this is set
+    pub fn want_capture_mouse(&self) -> bool {
+        self.imgui.io().want_capture_mouse
+    }
+
+    ///an imgui text field has focus, key events are marked handled while this is set
+    pub fn want_capture_keyboard(&self) -> bool {
+        self.imgui.io().want_capture_keyboard
+    }
+
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+
+    ///one imgui frame with the windows of every layer in the stack, and the engine's panels
+    ///when the editor is on
+    pub fn render(&mut self, window: &mut WindowManager, layer_stack: &mut LayerStack) {
+        self.perf_overlay.record_layers(layer_stack);
+        self.frame(window, |ui, editor| {
+            if editor.is_enabled() {
+                editor.stats_panel(ui);
+                editor.log_panel(ui);
+                editor.layer_stack_panel(ui, layer_stack);
+            }
+            layer_stack.on_imgui(ui, editor);
+        });
+    }
+
+    ///starts the frame, lets `build` add windows and draws it to the window, and to the
+    ///windows of viewports dragged out of it
+    pub fn frame<F: FnOnce(&Ui, &mut Editor)>(&mut self, window: &mut WindowManager, build: F) {
+        if std::mem::take(&mut self.reset_layout) {
+            self.recreate_context(window);
+            self.editor.reset();
+        }
+        self.platform.prepare_frame(self.imgui.io_mut(), window);
+        let viewports_enabled = self.viewports_enabled();
+        if viewports_enabled {
+            self.viewports.prepare_frame(&mut self.imgui, window);
+        }
+        let ui = self.imgui.new_frame();
+
+        let mut viewports = viewports_enabled;
+        let mut menu = MenuState {
+            show_demo_window: &mut self.show_demo_window,
+            viewports: &mut viewports,
+            reset_layout: false,
+        };
+        self.editor.begin(ui, &mut menu);
+        self.reset_layout |= menu.reset_layout;
+        build(ui, &mut self.editor);
+        if self.show_demo_window {
+            ui.show_demo_window(&mut self.show_demo_window);
+        }
+        self.perf_overlay.draw(ui);
+        self.dev_console.draw(ui);
+
+        self.platform.prepare_render(&self.imgui, window);
+        let draw_data = self.imgui.render();
+        self.renderer.render_draw_data(draw_data);
+        if viewports_enabled {
+            self.viewports.update(&mut self.imgui, window);
+            self.viewports.render(&self.imgui, &mut self.renderer);
+        }
+        self.renderer.end_frame();
+        if viewports != viewports_enabled {
+            self.set_viewports(viewports);
+        }
+
+        self.editor.store(&mut self.settings);
+        self.dev_console.store(&mut self.settings);
+        self.perf_overlay.store(&mut self.settings);
+
+        //imgui asks once its layout has been changed for `ini_saving_rate` seconds
+        let app_settings_due = self.settings.is_dirty()
+            && self.last_save.elapsed().as_secs_f32() >= self.imgui.io().ini_saving_rate;
+        if self.imgui.io().want_save_ini_settings || app_settings_due {
+            self.save_settings();
+        }
+    }
+
+    //imgui keeps the settings of windows it has seen for as long as the context lives, so
+    //forgetting the layout takes a new context
+    fn recreate_context(&mut self, window: &mut WindowManager) {
+        let config_flags = self.imgui.io().config_flags;
+        //only one context can be active, the old one is made inactive so the new one can be
+        //created before the old one is dropped
+        unsafe { imgui::sys::igSetCurrentContext(std::ptr::null_mut()) };
+        let (imgui, platform) = create_context(window, &self.viewports);
+        drop(std::mem::replace(&mut self.imgui, imgui));
+        self.platform = platform;
+        self.imgui.io_mut().config_flags = config_flags;
+        self.renderer.reload_fonts(&mut self.imgui);
+        self.save_settings();
+    }
+
+    ///should see events before the layers so it can mark the ones it uses as handled.
+    ///presses and moves imgui wants are marked so the layers don't react to clicks and typing
+    ///meant for a window, unless they're over the scene viewport. releases always go through
+    ///so layers never miss the end of a press they saw. the console's and the performance
+    ///overlay's toggle keys are always marked
+    pub fn on_event(&mut self, event: &mut RGEvent) {
+        if self.dev_console.on_event(event) || self.perf_overlay.on_event(event) {
+            event.set_handled(true);
+            return;
+        }
+        self.platform.on_event(self.imgui.io_mut(), event);
+
+        let io = self.imgui.io();
+        let scene = self.editor.scene_viewport_state().unwrap_or_default();
+        let released = matches!(
+            event,
+            RGEvent::MouseButtonReleased(_) | RGEvent::KeyReleased(_)
+        );
+        let captured =
+            (event.is_in_category(EventCategory::Mouse) && io.want_capture_mouse && !scene.hovered)
+                || (event.is_in_category(EventCategory::Keyboard)
+                    && io.want_capture_keyboard
+                    && !scene.focused);
+        if captured && !released {
+            event.set_handled(true);
+        }
+    }
+}
+
+impl Drop for ImGuiLayer {
+    fn drop(&mut self) {
+        self.save_settings();
+    }
+}
+
+//saving is managed by `UiSettings`, imgui only tells when it's time
+fn create_context(
+    window: &mut WindowManager,
+    viewports: &ViewportWindows,
+) -> (Context, ImGuiPlatform) {
+    let mut imgui = Context::create();
+    imgui.set_ini_filename(None);
+    imgui.style_mut().use_dark_colors();
+    imgui.io_mut().config_flags |= ConfigFlags::DOCKING_ENABLE;
+    let platform = ImGuiPlatform::new(&mut imgui, window);
+    viewports.install(&mut imgui);
+    (imgui, platform)
+}
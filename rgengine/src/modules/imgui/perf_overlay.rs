@@ -0,0 +1,226 @@
+use std::{collections::VecDeque, time::Duration};
+
+use glfw::Key;
+use imgui::{Condition, TableFlags, Ui, WindowFlags, sys};
+
+use super::UiSettings;
+use crate::{
+    events::RGEvent,
+    layers::{LayerStack, LayerTiming},
+    renderer::stats::{self, RenderStats},
+};
+
+const PERF_SECTION: &str = "perf_overlay";
+//frames in the graph, about two seconds at 60 fps
+const HISTORY: usize = 120;
+//from the corner of the main window
+const MARGIN: f32 = 10.0;
+
+///the memory the process has in ram, `None` where it can't be read
+pub fn resident_memory() -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+        let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kilobytes * 1024)
+    }
+    #[cfg(not(target_os = "linux"))]
+    None
+}
+
+///fps with a graph of the last frame times, the time each layer spent in its callbacks, the
+///renderer's `stats` and the memory in use, in a small window in the top right corner.
+///toggled with F3 by default
+pub struct PerfOverlay {
+    open: bool,
+    toggle_key: Option<Key>,
+    //in milliseconds, oldest first
+    frame_times: VecDeque<f32>,
+    layers: Vec<(String, LayerTiming)>,
+    render_stats: RenderStats,
+    changed: bool,
+}
+
+impl Default for PerfOverlay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PerfOverlay {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            toggle_key: Some(Key::F3),
+            frame_times: VecDeque::with_capacity(HISTORY),
+            layers: Vec::new(),
+            render_stats: RenderStats::default(),
+            changed: false,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn set_open(&mut self, open: bool) {
+        self.changed |= self.open != open;
+        self.open = open;
+    }
+
+    pub fn toggle(&mut self) {
+        self.set_open(!self.open);
+    }
+
+    ///`None` turns the key binding off
+    pub fn set_toggle_key(&mut self, key: Option<Key>) {
+        self.toggle_key = key;
+    }
+
+    ///true for the toggle key
+    pub fn on_event(&mut self, event: &RGEvent) -> bool {
+        match event {
+            RGEvent::KeyPressed(e) if Some(e.key) == self.toggle_key => {
+                if !e.repeat {
+                    self.toggle();
+                }
+                true
+            }
+            RGEvent::KeyReleased(e) if Some(e.key) == self.toggle_key => true,
+            _ => false,
+        }
+    }
+
+    ///adds a frame to the graph, `draw` adds one for every imgui frame
+    pub fn record_frame(&mut self, frame_time: Duration) {
+        if self.frame_times.len() == HISTORY {
+            self.frame_times.pop_front();
+        }
+        self.frame_times
+            .push_back(frame_time.as_secs_f32() * 1000.0);
+    }
+
+    ///the average over the graph
+    pub fn fps(&self) -> f32 {
+        let total: f32 = self.frame_times.iter().sum();
+        if total > 0.0 {
+            self.frame_times.len() as f32 * 1000.0 / total
+        } else {
+            0.0
+        }
+    }
+
+    ///takes the stack's timings for the next `draw`, they're reset every frame so this has to
+    ///happen after the layers rendered
+    pub fn record_layers(&mut self, layer_stack: &LayerStack) {
+        if !self.open {
+            return;
+        }
+        self.layers.clear();
+        self.layers.extend(
+            layer_stack
+                .timings()
+                .map(|(layer, timing)| (layer.get_name().to_string(), timing)),
+        );
+    }
+
+    pub fn draw(&mut self, ui: &Ui) {
+        self.record_frame(Duration::from_secs_f32(ui.io().delta_time));
+        if !self.open {
+            return;
+        }
+        self.render_stats = stats::current();
+
+        let viewport = unsafe { &*sys::igGetMainViewport() };
+        let (position, size) = (viewport.WorkPos, viewport.WorkSize);
+        unsafe { sys::igSetNextWindowViewport(viewport.ID) };
+        let flags = WindowFlags::NO_DECORATION
+            | WindowFlags::ALWAYS_AUTO_RESIZE
+            | WindowFlags::NO_MOVE
+            | WindowFlags::NO_SAVED_SETTINGS
+            | WindowFlags::NO_FOCUS_ON_APPEARING
+            | WindowFlags::NO_NAV
+            | WindowFlags::NO_DOCKING;
+        ui.window("Performance")
+            .position(
+                [position.x + size.x - MARGIN, position.y + MARGIN],
+                Condition::Always,
+            )
+            .position_pivot([1.0, 0.0])
+            .bg_alpha(0.6)
+            .flags(flags)
+            .build(|| {
+                self.frames(ui);
+                ui.separator();
+                self.renderer(ui);
+                ui.separator();
+                self.layer_table(ui);
+            });
+    }
+
+    fn frames(&mut self, ui: &Ui) {
+        let fps = self.fps();
+        let times = self.frame_times.make_contiguous();
+        let last = times.last().copied().unwrap_or_default();
+        let max = times.iter().copied().fold(0.0, f32::max);
+        ui.text(format!("{fps:.0} fps  {last:.2} ms"));
+        //16.7 ms stays in view so a steady 60 fps isn't a wall of noise
+        ui.plot_lines("##frame_times", times)
+            .scale_min(0.0)
+            .scale_max(max.max(1000.0 / 60.0))
+            .graph_size([240.0, 48.0])
+            .overlay_text(format!("max {max:.2} ms"))
+            .build();
+    }
+
+    fn renderer(&self, ui: &Ui) {
+        let stats = self.render_stats;
+        ui.text(format!("draw calls     {}", stats.draw_calls));
+        ui.text(format!("triangles      {}", stats.triangles));
+        ui.text(format!("state changes  {}", stats.state_changes));
+        match resident_memory() {
+            Some(bytes) => ui.text(format!(
+                "memory         {:.1} MiB",
+                bytes as f64 / (1024.0 * 1024.0)
+            )),
+            None => ui.text_disabled("memory         n/a"),
+        }
+    }
+
+    fn layer_table(&self, ui: &Ui) {
+        if self.layers.is_empty() {
+            ui.text_disabled("no layers");
+            return;
+        }
+        let flags = TableFlags::SIZING_FIXED_FIT | TableFlags::ROW_BG;
+        let Some(_table) = ui.begin_table_with_flags("layers", 4, flags) else {
+            return;
+        };
+        for column in ["layer", "update", "event", "render"] {
+            ui.table_setup_column(column);
+        }
+        ui.table_headers_row();
+        for (name, timing) in &self.layers {
+            ui.table_next_column();
+            //type names are long, the last path segment is enough to tell layers apart
+            ui.text(name.rsplit("::").next().unwrap_or(name));
+            for time in [timing.update, timing.event, timing.render] {
+                ui.table_next_column();
+                ui.text(format!("{:.3}", time.as_secs_f64() * 1000.0));
+            }
+        }
+    }
+
+    pub(super) fn load(&mut self, settings: &UiSettings) {
+        if let Some(open) = settings.get(PERF_SECTION, "open") {
+            self.open = open != "0";
+        }
+    }
+
+    pub(super) fn store(&mut self, settings: &mut UiSettings) {
+        if std::mem::take(&mut self.changed) {
+            settings.set(PERF_SECTION, "open", u8::from(self.open));
+        }
+    }
+}
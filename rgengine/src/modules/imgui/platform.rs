@@ -0,0 +1,471 @@
+use std::{
+    ffi::{CStr, CString},
+    time::Instant,
+};
+
+use glfw::{Context as _, GamepadAxis, GamepadButton, Key, MouseButton, StandardCursor};
+use imgui::{
+    BackendFlags, ClipboardBackend, ConfigFlags, Context, FontConfig, FontSource, Io,
+    Key as ImGuiKey, MouseButton as ImGuiMouseButton, MouseCursor,
+};
+
+use crate::{events::RGEvent, window::WindowManager};
+
+//stick direction, axis value where it starts and where it's fully pressed
+const NAV_STICK: [(ImGuiKey, GamepadAxis, f32, f32); 8] = [
+    (
+        ImGuiKey::GamepadLStickLeft,
+        GamepadAxis::AxisLeftX,
+        -0.25,
+        -1.0,
+    ),
+    (
+        ImGuiKey::GamepadLStickRight,
+        GamepadAxis::AxisLeftX,
+        0.25,
+        1.0,
+    ),
+    (
+        ImGuiKey::GamepadLStickUp,
+        GamepadAxis::AxisLeftY,
+        -0.25,
+        -1.0,
+    ),
+    (
+        ImGuiKey::GamepadLStickDown,
+        GamepadAxis::AxisLeftY,
+        0.25,
+        1.0,
+    ),
+    (
+        ImGuiKey::GamepadRStickLeft,
+        GamepadAxis::AxisRightX,
+        -0.25,
+        -1.0,
+    ),
+    (
+        ImGuiKey::GamepadRStickRight,
+        GamepadAxis::AxisRightX,
+        0.25,
+        1.0,
+    ),
+    (
+        ImGuiKey::GamepadRStickUp,
+        GamepadAxis::AxisRightY,
+        -0.25,
+        -1.0,
+    ),
+    (
+        ImGuiKey::GamepadRStickDown,
+        GamepadAxis::AxisRightY,
+        0.25,
+        1.0,
+    ),
+];
+
+//imgui has one key per modifier, whichever side of the keyboard it's on
+const MODIFIERS: [(Key, Key, ImGuiKey); 4] = [
+    (Key::LeftControl, Key::RightControl, ImGuiKey::ModCtrl),
+    (Key::LeftShift, Key::RightShift, ImGuiKey::ModShift),
+    (Key::LeftAlt, Key::RightAlt, ImGuiKey::ModAlt),
+    (Key::LeftSuper, Key::RightSuper, ImGuiKey::ModSuper),
+];
+
+const DEFAULT_FONT_SIZE: f32 = 13.0;
+
+///both sides of each modifier, so letting go of one shift keeps shift down while the other
+///is still held
+#[derive(Default)]
+pub(super) struct HeldModifiers([[bool; 2]; 4]);
+
+impl HeldModifiers {
+    //the modifier the key belongs to and whether either side is still down
+    fn set(&mut self, key: Key, down: bool) -> Option<(ImGuiKey, bool)> {
+        MODIFIERS
+            .iter()
+            .enumerate()
+            .find_map(|(i, &(left, right, modifier))| {
+                let side = [left, right].iter().position(|&k| k == key)?;
+                self.0[i][side] = down;
+                Some((modifier, self.0[i][0] || self.0[i][1]))
+            })
+    }
+}
+
+struct GlfwClipboard(*mut glfw::ffi::GLFWwindow);
+
+impl ClipboardBackend for GlfwClipboard {
+    fn get(&mut self) -> Option<String> {
+        //null when the clipboard is empty or isn't text
+        let text = unsafe { glfw::ffi::glfwGetClipboardString(self.0) };
+        if text.is_null() {
+            return None;
+        }
+        let text = unsafe { CStr::from_ptr(text) };
+        Some(text.to_string_lossy().into_owned())
+    }
+
+    fn set(&mut self, value: &str) {
+        //imgui strings can't hold a nul, but keep what comes before one
+        let value = value.split('\0').next().unwrap_or_default();
+        if let Ok(value) = CString::new(value) {
+            unsafe { glfw::ffi::glfwSetClipboardString(self.0, value.as_ptr()) };
+        }
+    }
+}
+
+///feeds imgui from the engine's events instead of reading the window itself. the window is
+///only touched for the clipboard, the cursor shape and its size
+pub struct ImGuiPlatform {
+    last_frame: Instant,
+    cursor: Option<MouseCursor>,
+    //where the window's top left is on the desktop, mouse positions are relative to the
+    //desktop while imgui windows can leave the main one
+    window_pos: [f32; 2],
+    scale: f32,
+    modifiers: HeldModifiers,
+}
+
+impl ImGuiPlatform {
+    ///sets up the clipboard and fonts, so it has to come before the renderer builds the font
+    ///texture
+    pub fn new(imgui: &mut Context, window: &mut WindowManager) -> Self {
+        let native = window.native_window();
+        imgui.set_clipboard_backend(GlfwClipboard(native.window_ptr()));
+        imgui.set_platform_name(Some(String::from("rgengine-glfw")));
+
+        //the content scale is the dpi scale the os asks for, on screens where the framebuffer
+        //is already bigger than the window (macos) that part is handled by the framebuffer scale
+        let (window_width, _) = native.get_size();
+        let (framebuffer_width, _) = native.get_framebuffer_size();
+        let framebuffer_scale = framebuffer_width.max(1) as f32 / window_width.max(1) as f32;
+        let scale = (native.get_content_scale().0 / framebuffer_scale).max(1.0);
+        imgui.fonts().add_font(&[FontSource::DefaultFontData {
+            config: Some(FontConfig {
+                size_pixels: (DEFAULT_FONT_SIZE * scale).round(),
+                ..FontConfig::default()
+            }),
+        }]);
+        imgui.style_mut().scale_all_sizes(scale);
+
+        let io = imgui.io_mut();
+        io.backend_flags |= BackendFlags::HAS_MOUSE_CURSORS;
+        io.backend_flags |= BackendFlags::HAS_SET_MOUSE_POS;
+
+        let (x, y) = native.get_pos();
+        Self {
+            last_frame: Instant::now(),
+            cursor: None,
+            window_pos: [x as f32, y as f32],
+            scale,
+            modifiers: HeldModifiers::default(),
+        }
+    }
+
+    ///feeds a context that has no window, like in tests. the context is left as it is, so it
+    ///gets no clipboard, fonts or cursors
+    pub fn detached() -> Self {
+        Self {
+            last_frame: Instant::now(),
+            cursor: None,
+            window_pos: [0.0, 0.0],
+            scale: 1.0,
+            modifiers: HeldModifiers::default(),
+        }
+    }
+
+    ///the ui scale picked from the monitor's dpi when imgui was created
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    ///passes an event from the main window on to imgui, whether the layers still get it is up
+    ///to the `ImGuiLayer`
+    pub fn on_event(&mut self, io: &mut Io, event: &RGEvent) {
+        match event {
+            RGEvent::MouseButtonPressed(e) => add_mouse_button(io, e.button, true),
+            RGEvent::MouseButtonReleased(e) => add_mouse_button(io, e.button, false),
+            RGEvent::MouseMoved(e) => {
+                let [x, y] = self.desktop_offset(io);
+                io.add_mouse_pos_event([e.x as f32 + x, e.y as f32 + y]);
+            }
+            RGEvent::MouseScrolled(e) => {
+                io.add_mouse_wheel_event([e.x_offset as f32, e.y_offset as f32])
+            }
+            RGEvent::KeyPressed(e) => add_key(io, &mut self.modifiers, e.key, true),
+            RGEvent::KeyReleased(e) => add_key(io, &mut self.modifiers, e.key, false),
+            RGEvent::KeyTyped(e) => io.add_input_character(e.ch),
+            RGEvent::GamepadButtonPressed(e) => add_gamepad_button(io, e.button, true),
+            RGEvent::GamepadButtonReleased(e) => add_gamepad_button(io, e.button, false),
+            RGEvent::GamepadAxisMoved(e) => add_gamepad_axis(io, e.axis, e.value),
+            RGEvent::WindowMoved(e) => self.window_pos = [e.x as f32, e.y as f32],
+            //imgui lets go of every key and button itself
+            RGEvent::WindowFocus(_) => add_focus(io, &mut self.modifiers, true),
+            RGEvent::WindowLostFocus(_) => add_focus(io, &mut self.modifiers, false),
+            _ => {}
+        }
+    }
+
+    //zero unless imgui windows can be dragged out of the main one
+    fn desktop_offset(&self, io: &Io) -> [f32; 2] {
+        if io.config_flags.contains(ConfigFlags::VIEWPORTS_ENABLE) {
+            self.window_pos
+        } else {
+            [0.0, 0.0]
+        }
+    }
+
+    ///time and display size for the next frame
+    pub fn prepare_frame(&mut self, io: &mut Io, window: &mut WindowManager) {
+        let now = Instant::now();
+        io.update_delta_time(now.duration_since(self.last_frame));
+        self.last_frame = now;
+
+        let native = window.native_window();
+        let (width, height) = native.get_size();
+        let (framebuffer_width, framebuffer_height) = native.get_framebuffer_size();
+        io.display_size = [width as f32, height as f32];
+        if width > 0 && height > 0 {
+            io.display_framebuffer_scale = [
+                framebuffer_width as f32 / width as f32,
+                framebuffer_height as f32 / height as f32,
+            ];
+        }
+        let (x, y) = native.get_pos();
+        self.window_pos = [x as f32, y as f32];
+
+        //asked for by keyboard and gamepad navigation last frame
+        if io.want_set_mouse_pos {
+            let [x, y] = self.desktop_offset(io);
+            native.set_cursor_pos((io.mouse_pos[0] - x) as f64, (io.mouse_pos[1] - y) as f64);
+        }
+    }
+
+    ///matches the window's cursor to the one imgui wants, after the frame is built
+    pub fn prepare_render(&mut self, imgui: &Context, window: &mut WindowManager) {
+        let io = imgui.io();
+        if io
+            .config_flags
+            .contains(ConfigFlags::NO_MOUSE_CURSOR_CHANGE)
+        {
+            return;
+        }
+        //imgui draws its own cursor or hides it
+        let cursor = if io.mouse_draw_cursor {
+            None
+        } else {
+            imgui.mouse_cursor()
+        };
+        if cursor == self.cursor {
+            return;
+        }
+        self.cursor = cursor;
+        let native = window.native_window();
+        match cursor {
+            Some(cursor) => {
+                native.set_cursor_mode(glfw::CursorMode::Normal);
+                native.set_cursor(Some(glfw::Cursor::standard(standard_cursor(cursor))));
+            }
+            None => native.set_cursor_mode(glfw::CursorMode::Hidden),
+        }
+    }
+}
+
+pub(super) fn standard_cursor(cursor: MouseCursor) -> StandardCursor {
+    match cursor {
+        MouseCursor::TextInput => StandardCursor::IBeam,
+        MouseCursor::ResizeNS => StandardCursor::VResize,
+        MouseCursor::ResizeEW => StandardCursor::HResize,
+        MouseCursor::Hand => StandardCursor::Hand,
+        //glfw 3.3 has no diagonal or all direction resize cursors
+        MouseCursor::ResizeAll => StandardCursor::Crosshair,
+        _ => StandardCursor::Arrow,
+    }
+}
+
+pub(super) fn add_mouse_button(io: &mut Io, button: MouseButton, down: bool) {
+    let button = match button {
+        MouseButton::Button1 => ImGuiMouseButton::Left,
+        MouseButton::Button2 => ImGuiMouseButton::Right,
+        MouseButton::Button3 => ImGuiMouseButton::Middle,
+        MouseButton::Button4 => ImGuiMouseButton::Extra1,
+        MouseButton::Button5 => ImGuiMouseButton::Extra2,
+        _ => return,
+    };
+    io.add_mouse_button_event(button, down);
+}
+
+pub(super) fn add_key(io: &mut Io, modifiers: &mut HeldModifiers, key: Key, down: bool) {
+    if let Some((modifier, down)) = modifiers.set(key, down) {
+        io.add_key_event(modifier, down);
+    }
+    if let Some(key) = imgui_key(key) {
+        io.add_key_event(key, down);
+    }
+}
+
+pub(super) fn add_focus(io: &mut Io, modifiers: &mut HeldModifiers, focused: bool) {
+    //imgui lets go of the keys on its side, the releases won't come while unfocused
+    if !focused {
+        *modifiers = HeldModifiers::default();
+    }
+    unsafe { imgui::sys::ImGuiIO_AddFocusEvent(raw_io(io), focused) };
+}
+
+fn add_gamepad_button(io: &mut Io, button: GamepadButton, down: bool) {
+    io.backend_flags |= BackendFlags::HAS_GAMEPAD;
+    //same layout as the glfw imgui backend
+    let key = match button {
+        GamepadButton::ButtonA => ImGuiKey::GamepadFaceDown,
+        GamepadButton::ButtonB => ImGuiKey::GamepadFaceRight,
+        GamepadButton::ButtonX => ImGuiKey::GamepadFaceLeft,
+        GamepadButton::ButtonY => ImGuiKey::GamepadFaceUp,
+        GamepadButton::ButtonLeftBumper => ImGuiKey::GamepadL1,
+        GamepadButton::ButtonRightBumper => ImGuiKey::GamepadR1,
+        GamepadButton::ButtonBack => ImGuiKey::GamepadBack,
+        GamepadButton::ButtonStart => ImGuiKey::GamepadStart,
+        GamepadButton::ButtonLeftThumb => ImGuiKey::GamepadL3,
+        GamepadButton::ButtonRightThumb => ImGuiKey::GamepadR3,
+        GamepadButton::ButtonDpadUp => ImGuiKey::GamepadDpadUp,
+        GamepadButton::ButtonDpadRight => ImGuiKey::GamepadDpadRight,
+        GamepadButton::ButtonDpadDown => ImGuiKey::GamepadDpadDown,
+        GamepadButton::ButtonDpadLeft => ImGuiKey::GamepadDpadLeft,
+        GamepadButton::ButtonGuide => return,
+    };
+    io.add_key_event(key, down);
+}
+
+fn add_gamepad_axis(io: &mut Io, axis: GamepadAxis, value: f32) {
+    io.backend_flags |= BackendFlags::HAS_GAMEPAD;
+    let mut add = |key: ImGuiKey, amount: f32| unsafe {
+        imgui::sys::ImGuiIO_AddKeyAnalogEvent(raw_io(io), key as u32, amount > 0.1, amount);
+    };
+    match axis {
+        //triggers rest at -1
+        GamepadAxis::AxisLeftTrigger => add(ImGuiKey::GamepadL2, (value + 1.0) / 2.0),
+        GamepadAxis::AxisRightTrigger => add(ImGuiKey::GamepadR2, (value + 1.0) / 2.0),
+        _ => {
+            for (key, stick_axis, start, end) in NAV_STICK {
+                if stick_axis == axis {
+                    add(key, ((value - start) / (end - start)).clamp(0.0, 1.0));
+                }
+            }
+        }
+    }
+}
+
+//imgui-rs only wraps some of the input functions
+fn raw_io(io: &mut Io) -> *mut imgui::sys::ImGuiIO {
+    (io as *mut Io).cast()
+}
+
+fn imgui_key(key: Key) -> Option<ImGuiKey> {
+    Some(match key {
+        Key::Tab => ImGuiKey::Tab,
+        Key::Left => ImGuiKey::LeftArrow,
+        Key::Right => ImGuiKey::RightArrow,
+        Key::Up => ImGuiKey::UpArrow,
+        Key::Down => ImGuiKey::DownArrow,
+        Key::PageUp => ImGuiKey::PageUp,
+        Key::PageDown => ImGuiKey::PageDown,
+        Key::Home => ImGuiKey::Home,
+        Key::End => ImGuiKey::End,
+        Key::Insert => ImGuiKey::Insert,
+        Key::Delete => ImGuiKey::Delete,
+        Key::Backspace => ImGuiKey::Backspace,
+        Key::Space => ImGuiKey::Space,
+        Key::Enter => ImGuiKey::Enter,
+        Key::Escape => ImGuiKey::Escape,
+        Key::Apostrophe => ImGuiKey::Apostrophe,
+        Key::Comma => ImGuiKey::Comma,
+        Key::Minus => ImGuiKey::Minus,
+        Key::Period => ImGuiKey::Period,
+        Key::Slash => ImGuiKey::Slash,
+        Key::Semicolon => ImGuiKey::Semicolon,
+        Key::Equal => ImGuiKey::Equal,
+        Key::LeftBracket => ImGuiKey::LeftBracket,
+        Key::Backslash => ImGuiKey::Backslash,
+        Key::RightBracket => ImGuiKey::RightBracket,
+        Key::GraveAccent => ImGuiKey::GraveAccent,
+        Key::CapsLock => ImGuiKey::CapsLock,
+        Key::ScrollLock => ImGuiKey::ScrollLock,
+        Key::NumLock => ImGuiKey::NumLock,
+        Key::PrintScreen => ImGuiKey::PrintScreen,
+        Key::Pause => ImGuiKey::Pause,
+        Key::Kp0 => ImGuiKey::Keypad0,
+        Key::Kp1 => ImGuiKey::Keypad1,
+        Key::Kp2 => ImGuiKey::Keypad2,
+        Key::Kp3 => ImGuiKey::Keypad3,
+        Key::Kp4 => ImGuiKey::Keypad4,
+        Key::Kp5 => ImGuiKey::Keypad5,
+        Key::Kp6 => ImGuiKey::Keypad6,
+        Key::Kp7 => ImGuiKey::Keypad7,
+        Key::Kp8 => ImGuiKey::Keypad8,
+        Key::Kp9 => ImGuiKey::Keypad9,
+        Key::KpDecimal => ImGuiKey::KeypadDecimal,
+        Key::KpDivide => ImGuiKey::KeypadDivide,
+        Key::KpMultiply => ImGuiKey::KeypadMultiply,
+        Key::KpSubtract => ImGuiKey::KeypadSubtract,
+        Key::KpAdd => ImGuiKey::KeypadAdd,
+        Key::KpEnter => ImGuiKey::KeypadEnter,
+        Key::KpEqual => ImGuiKey::KeypadEqual,
+        Key::LeftShift => ImGuiKey::LeftShift,
+        Key::LeftControl => ImGuiKey::LeftCtrl,
+        Key::LeftAlt => ImGuiKey::LeftAlt,
+        Key::LeftSuper => ImGuiKey::LeftSuper,
+        Key::RightShift => ImGuiKey::RightShift,
+        Key::RightControl => ImGuiKey::RightCtrl,
+        Key::RightAlt => ImGuiKey::RightAlt,
+        Key::RightSuper => ImGuiKey::RightSuper,
+        Key::Menu => ImGuiKey::Menu,
+        Key::Num0 => ImGuiKey::Alpha0,
+        Key::Num1 => ImGuiKey::Alpha1,
+        Key::Num2 => ImGuiKey::Alpha2,
+        Key::Num3 => ImGuiKey::Alpha3,
+        Key::Num4 => ImGuiKey::Alpha4,
+        Key::Num5 => ImGuiKey::Alpha5,
+        Key::Num6 => ImGuiKey::Alpha6,
+        Key::Num7 => ImGuiKey::Alpha7,
+        Key::Num8 => ImGuiKey::Alpha8,
+        Key::Num9 => ImGuiKey::Alpha9,
+        Key::A => ImGuiKey::A,
+        Key::B => ImGuiKey::B,
+        Key::C => ImGuiKey::C,
+        Key::D => ImGuiKey::D,
+        Key::E => ImGuiKey::E,
+        Key::F => ImGuiKey::F,
+        Key::G => ImGuiKey::G,
+        Key::H => ImGuiKey::H,
+        Key::I => ImGuiKey::I,
+        Key::J => ImGuiKey::J,
+        Key::K => ImGuiKey::K,
+        Key::L => ImGuiKey::L,
+        Key::M => ImGuiKey::M,
+        Key::N => ImGuiKey::N,
+        Key::O => ImGuiKey::O,
+        Key::P => ImGuiKey::P,
+        Key::Q => ImGuiKey::Q,
+        Key::R => ImGuiKey::R,
+        Key::S => ImGuiKey::S,
+        Key::T => ImGuiKey::T,
+        Key::U => ImGuiKey::U,
+        Key::V => ImGuiKey::V,
+        Key::W => ImGuiKey::W,
+        Key::X => ImGuiKey::X,
+        Key::Y => ImGuiKey::Y,
+        Key::Z => ImGuiKey::Z,
+        Key::F1 => ImGuiKey::F1,
+        Key::F2 => ImGuiKey::F2,
+        Key::F3 => ImGuiKey::F3,
+        Key::F4 => ImGuiKey::F4,
+        Key::F5 => ImGuiKey::F5,
+        Key::F6 => ImGuiKey::F6,
+        Key::F7 => ImGuiKey::F7,
+        Key::F8 => ImGuiKey::F8,
+        Key::F9 => ImGuiKey::F9,
+        Key::F10 => ImGuiKey::F10,
+        Key::F11 => ImGuiKey::F11,
+        Key::F12 => ImGuiKey::F12,
+        _ => return None,
+    })
+}
@@ -0,0 +1,190 @@
+use std::rc::Rc;
+
+use imgui::{DrawIdx, DrawVert};
+
+use super::{UiBackend, UiDrawCommand, UiTexture};
+use crate::{
+    math::Mat4,
+    renderer::{
+        Renderer,
+        buffer::{
+            BufferElement, BufferLayout, IndexBuffer, ShaderDataType, VertexArray, VertexBuffer,
+        },
+        material::{BlendMode, CullMode, RenderState},
+        shader::Shader,
+        texture::{Texture2D, TextureFilter, TextureWrap},
+    },
+};
+
+const UI_VERTEX_SHADER: &str = r#"
+#version 450 core
+layout(location = 0) in vec2 a_Position;
+layout(location = 1) in vec2 a_TexCoord;
+layout(location = 2) in vec4 a_Color;
+
+uniform mat4 u_Projection;
+
+out vec2 v_TexCoord;
+out vec4 v_Color;
+
+void main() {
+    v_TexCoord = a_TexCoord;
+    v_Color = a_Color;
+    gl_Position = u_Projection * vec4(a_Position, 0.0, 1.0);
+}
+"#;
+
+const UI_FRAGMENT_SHADER: &str = r#"
+#version 450 core
+layout(location = 0) out vec4 o_Color;
+
+in vec2 v_TexCoord;
+in vec4 v_Color;
+
+uniform sampler2D u_Texture;
+
+void main() {
+    o_Color = v_Color * texture(u_Texture, v_TexCoord);
+}
+"#;
+
+//imgui packs the color into bytes, the engine's layouts only take floats
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+struct UiVertex {
+    position: [f32; 2],
+    tex_coord: [f32; 2],
+    color: [f32; 4],
+}
+
+///draws imgui with the engine renderer's resources onto whatever is bound, normally the window
+pub struct GlUiBackend {
+    shader: Rc<Shader>,
+    vertex_array: VertexArray,
+    font: Option<Rc<Texture2D>>,
+    vertices: Vec<UiVertex>,
+    indices: Vec<u32>,
+    framebuffer_size: [u32; 2],
+}
+
+impl GlUiBackend {
+    ///needs a current gl context
+    pub fn new() -> Self {
+        let shader = Shader::from_source("ImGui", UI_VERTEX_SHADER, UI_FRAGMENT_SHADER)
+            .expect("failed to build the imgui shader");
+        shader.bind();
+        shader.set_int("u_Texture", 0);
+        Self::with_resources(Rc::new(shader), None)
+    }
+
+    ///a backend for another gl context sharing objects with this one, like the window of an
+    ///imgui viewport. the shader and font are shared but vertex arrays can't be, so it has to
+    ///be made and dropped while that context is current
+    pub fn share(&self) -> Self {
+        Self::with_resources(self.shader.clone(), self.font.clone())
+    }
+
+    fn with_resources(shader: Rc<Shader>, font: Option<Rc<Texture2D>>) -> Self {
+        let layout = BufferLayout::new(vec![
+            BufferElement::new(ShaderDataType::Float2, "a_Position"),
+            BufferElement::new(ShaderDataType::Float2, "a_TexCoord"),
+            BufferElement::new(ShaderDataType::Float4, "a_Color"),
+        ]);
+        let mut vertex_array = VertexArray::new();
+        vertex_array.add_vertex_buffer(VertexBuffer::with_capacity(
+            1024 * std::mem::size_of::<UiVertex>(),
+            layout,
+        ));
+        vertex_array.set_index_buffer(IndexBuffer::with_capacity(3 * 1024));
+        Self {
+            shader,
+            vertex_array,
+            font,
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            framebuffer_size: [0, 0],
+        }
+    }
+}
+
+impl Default for GlUiBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UiBackend for GlUiBackend {
+    fn set_font_texture(&mut self, width: u32, height: u32, pixels: &[u8]) {
+        let font = Texture2D::from_rgba(width, height, pixels);
+        font.set_filter(TextureFilter::Linear);
+        font.set_wrap(TextureWrap::ClampToEdge);
+        self.font = Some(Rc::new(font));
+    }
+
+    fn begin(&mut self, framebuffer_size: [u32; 2], projection: &Mat4) {
+        self.framebuffer_size = framebuffer_size;
+        //the state the 2d renderer uses too, so there is nothing to put back afterwards
+        RenderState {
+            blend: BlendMode::Alpha,
+            depth_test: false,
+            cull: CullMode::None,
+            ..RenderState::default()
+        }
+        .apply();
+        unsafe {
+            gl::Viewport(0, 0, framebuffer_size[0] as i32, framebuffer_size[1] as i32);
+            gl::Enable(gl::SCISSOR_TEST);
+        }
+        self.shader.bind();
+        self.shader.set_mat4("u_Projection", projection);
+    }
+
+    fn draw_list(
+        &mut self,
+        vertices: &[DrawVert],
+        indices: &[DrawIdx],
+        commands: &[UiDrawCommand],
+    ) {
+        if commands.is_empty() {
+            return;
+        }
+        self.vertices.clear();
+        self.vertices.extend(vertices.iter().map(|v| UiVertex {
+            position: v.pos,
+            tex_coord: v.uv,
+            color: v.col.map(|c| c as f32 / 255.0),
+        }));
+        self.indices.clear();
+        self.indices.extend(indices.iter().map(|&i| i as u32));
+        self.vertex_array.vertex_buffers()[0].set_data(&self.vertices);
+        if let Some(index_buffer) = self.vertex_array.index_buffer() {
+            index_buffer.set_data(&self.indices);
+        }
+
+        for command in commands {
+            match &command.texture {
+                UiTexture::Font => match &self.font {
+                    Some(font) => font.bind(0),
+                    None => continue,
+                },
+                UiTexture::Texture(texture) => texture.bind(0),
+                UiTexture::Gl(id) => unsafe { gl::BindTextureUnit(0, *id) },
+            }
+            let [x, y, width, height] = command.clip_rect;
+            unsafe { gl::Scissor(x, y, width, height) };
+            Renderer::draw_indexed_range(
+                &self.vertex_array,
+                command.first_index,
+                command.count,
+                command.base_vertex as i32,
+            );
+        }
+    }
+
+    fn end(&mut self) {
+        self.vertex_array.unbind();
+        //`Renderer::on_window_resize` keeps the scissor at the whole window
+        let [width, height] = self.framebuffer_size;
+        unsafe { gl::Scissor(0, 0, width as i32, height as i32) };
+    }
+}
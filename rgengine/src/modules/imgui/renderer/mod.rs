@@ -0,0 +1,206 @@
+//imgui's draw lists are turned into `UiDrawCommand`s with the texture ids resolved and the clip
+//rects in framebuffer pixels, then handed to a `UiBackend`. `GlUiBackend` draws them with the
+//engine's buffers, shaders and textures and `NullUiBackend` records them for tests
+mod gl;
+mod null;
+
+pub use gl::GlUiBackend;
+pub use null::NullUiBackend;
+
+use std::{cell::RefCell, rc::Rc};
+
+use imgui::{
+    BackendFlags, Context, DrawCmd, DrawCmdParams, DrawData, DrawIdx, DrawVert, TextureId,
+};
+
+use crate::{
+    math::Mat4,
+    renderer::{framebuffer::Framebuffer, texture::Texture2D},
+};
+
+///what a `TextureId` in a draw command stands for
+#[derive(Clone, Debug)]
+pub enum UiTexture {
+    ///imgui's font atlas, the backend owns it
+    Font,
+    Texture(Rc<Texture2D>),
+    ///a gl texture owned by something else, like a framebuffer attachment
+    Gl(u32),
+}
+
+//the font atlas always has this id, the textures of a frame come after it
+const FONT_TEXTURE: usize = 0;
+
+thread_local! {
+    //textures handed out since the last frame was drawn, id n is entry n - 1
+    static FRAME_TEXTURES: RefCell<Vec<UiTexture>> = const { RefCell::new(Vec::new()) };
+}
+
+///an id for `imgui::Image` and `ui.image_button`, valid until the current imgui frame is drawn.
+///the texture is kept alive until then
+pub fn texture_id(texture: UiTexture) -> TextureId {
+    if let UiTexture::Font = texture {
+        return TextureId::from(FONT_TEXTURE);
+    }
+    FRAME_TEXTURES.with_borrow_mut(|textures| {
+        textures.push(texture);
+        TextureId::from(textures.len())
+    })
+}
+
+///a color attachment of `framebuffer`, resolved if it's multisampled. gl textures start at
+///the bottom so flip the uvs (`uv0([0, 1])`, `uv1([1, 0])`) to show it the right way up
+pub fn framebuffer_texture_id(framebuffer: &Framebuffer, index: usize) -> TextureId {
+    texture_id(UiTexture::Gl(framebuffer.color_attachment_id(index)))
+}
+
+fn resolve_texture(id: TextureId) -> Option<UiTexture> {
+    match id.id() {
+        FONT_TEXTURE => Some(UiTexture::Font),
+        id => FRAME_TEXTURES.with_borrow(|textures| textures.get(id - 1).cloned()),
+    }
+}
+
+///one draw call, `count` indices from `first_index` on with `base_vertex` added to each
+#[derive(Clone, Debug)]
+pub struct UiDrawCommand {
+    pub texture: UiTexture,
+    ///x, y, width and height in framebuffer pixels with y going up, like `glScissor`
+    pub clip_rect: [i32; 4],
+    pub first_index: u32,
+    pub count: u32,
+    pub base_vertex: u32,
+}
+
+pub trait UiBackend {
+    ///imgui's font atlas as tightly packed rgba, set again when the fonts are rebuilt
+    fn set_font_texture(&mut self, width: u32, height: u32, pixels: &[u8]);
+    ///`projection` takes imgui's coordinates, in window pixels from the top left, to clip space
+    fn begin(&mut self, framebuffer_size: [u32; 2], projection: &Mat4);
+    ///the commands index into this list's own vertices and indices
+    fn draw_list(&mut self, vertices: &[DrawVert], indices: &[DrawIdx], commands: &[UiDrawCommand]);
+    fn end(&mut self);
+}
+
+///draws finished imgui frames through a `UiBackend`
+pub struct ImGuiRenderer<B: UiBackend> {
+    backend: B,
+    commands: Vec<UiDrawCommand>,
+}
+
+impl<B: UiBackend> ImGuiRenderer<B> {
+    ///builds the font atlas, so the fonts have to be added before this
+    pub fn new(imgui: &mut Context, backend: B) -> Self {
+        let mut renderer = Self {
+            backend,
+            commands: Vec::new(),
+        };
+        renderer.reload_fonts(imgui);
+        renderer
+    }
+
+    ///builds the font atlas again and gives it to the backend, for fonts added later or a
+    ///new context
+    pub fn reload_fonts(&mut self, imgui: &mut Context) {
+        //draws use a base vertex so lists can go past what 16 bit indices reach
+        imgui.io_mut().backend_flags |= BackendFlags::RENDERER_HAS_VTX_OFFSET;
+        let fonts = imgui.fonts();
+        let texture = fonts.build_rgba32_texture();
+        self.backend
+            .set_font_texture(texture.width, texture.height, texture.data);
+        fonts.tex_id = TextureId::from(FONT_TEXTURE);
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    ///ends the frame and draws it, the ids from `texture_id` stop working after this
+    pub fn render(&mut self, imgui: &mut Context) {
+        let draw_data = imgui.render();
+        self.render_draw_data(draw_data);
+        self.end_frame();
+    }
+
+    ///draws one viewport of a frame ended with `Context::render`
+    pub fn render_draw_data(&mut self, draw_data: &DrawData) {
+        draw(&mut self.backend, &mut self.commands, draw_data);
+    }
+
+    ///draws a viewport through another backend, e.g. one for a window imgui opened
+    pub fn render_draw_data_with(&mut self, backend: &mut B, draw_data: &DrawData) {
+        draw(backend, &mut self.commands, draw_data);
+    }
+
+    ///once every viewport is drawn, the ids from `texture_id` stop working after this
+    pub fn end_frame(&mut self) {
+        FRAME_TEXTURES.with_borrow_mut(Vec::clear);
+    }
+}
+
+fn draw<B: UiBackend>(backend: &mut B, commands: &mut Vec<UiDrawCommand>, draw_data: &DrawData) {
+    let [x, y] = draw_data.display_pos;
+    let [width, height] = draw_data.display_size;
+    let [scale_x, scale_y] = draw_data.framebuffer_scale;
+    let framebuffer_width = (width * scale_x) as i32;
+    let framebuffer_height = (height * scale_y) as i32;
+    //minimized, or nothing to draw. imgui-rs can't iterate over zero draw lists
+    if framebuffer_width <= 0 || framebuffer_height <= 0 || draw_data.total_vtx_count == 0 {
+        return;
+    }
+
+    let projection = Mat4::orthographic_rh_gl(x, x + width, y + height, y, -1.0, 1.0);
+    backend.begin(
+        [framebuffer_width as u32, framebuffer_height as u32],
+        &projection,
+    );
+    for list in draw_data.draw_lists() {
+        commands.clear();
+        for command in list.commands() {
+            let DrawCmd::Elements {
+                count,
+                cmd_params:
+                    DrawCmdParams {
+                        clip_rect,
+                        texture_id,
+                        vtx_offset,
+                        idx_offset,
+                    },
+            } = command
+            else {
+                //the backend sets its state once a frame and the engine registers no
+                //callbacks, so there is nothing to reset or call
+                continue;
+            };
+            let min_x = ((clip_rect[0] - x) * scale_x).max(0.0);
+            let min_y = ((clip_rect[1] - y) * scale_y).max(0.0);
+            let max_x = ((clip_rect[2] - x) * scale_x).min(framebuffer_width as f32);
+            let max_y = ((clip_rect[3] - y) * scale_y).min(framebuffer_height as f32);
+            if max_x <= min_x || max_y <= min_y {
+                continue;
+            }
+            //ids from an earlier frame draw nothing
+            let Some(texture) = resolve_texture(texture_id) else {
+                continue;
+            };
+            commands.push(UiDrawCommand {
+                texture,
+                clip_rect: [
+                    min_x as i32,
+                    framebuffer_height - max_y as i32,
+                    (max_x - min_x) as i32,
+                    (max_y - min_y) as i32,
+                ],
+                first_index: idx_offset as u32,
+                count: count as u32,
+                base_vertex: vtx_offset as u32,
+            });
+        }
+        backend.draw_list(list.vtx_buffer(), list.idx_buffer(), commands);
+    }
+    backend.end();
+}
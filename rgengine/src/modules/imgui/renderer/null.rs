@@ -0,0 +1,71 @@
+use imgui::{DrawIdx, DrawVert};
+
+use super::{UiBackend, UiDrawCommand, UiTexture};
+use crate::math::Mat4;
+
+///keeps what would be drawn instead of touching a gpu, for testing ui without a window
+#[derive(Default, Debug)]
+pub struct NullUiBackend {
+    ///size of the font atlas, once it's set
+    pub font_texture: Option<(u32, u32)>,
+    pub framebuffer_size: [u32; 2],
+    pub projection: Mat4,
+    ///every command of the last frame, in draw order
+    pub commands: Vec<UiDrawCommand>,
+    ///vertices and indices of the last frame, over all lists
+    pub vertex_count: usize,
+    pub index_count: usize,
+    pub frames: usize,
+}
+
+impl NullUiBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///commands drawing `texture`, compared by gl id or pointer
+    pub fn commands_with(&self, texture: &UiTexture) -> impl Iterator<Item = &UiDrawCommand> {
+        self.commands
+            .iter()
+            .filter(move |command| same_texture(&command.texture, texture))
+    }
+}
+
+fn same_texture(a: &UiTexture, b: &UiTexture) -> bool {
+    match (a, b) {
+        (UiTexture::Font, UiTexture::Font) => true,
+        (UiTexture::Texture(a), UiTexture::Texture(b)) => std::rc::Rc::ptr_eq(a, b),
+        (UiTexture::Gl(a), UiTexture::Gl(b)) => a == b,
+        _ => false,
+    }
+}
+
+impl UiBackend for NullUiBackend {
+    fn set_font_texture(&mut self, width: u32, height: u32, pixels: &[u8]) {
+        debug_assert_eq!(pixels.len(), (width * height * 4) as usize);
+        self.font_texture = Some((width, height));
+    }
+
+    fn begin(&mut self, framebuffer_size: [u32; 2], projection: &Mat4) {
+        self.framebuffer_size = framebuffer_size;
+        self.projection = *projection;
+        self.commands.clear();
+        self.vertex_count = 0;
+        self.index_count = 0;
+    }
+
+    fn draw_list(
+        &mut self,
+        vertices: &[DrawVert],
+        indices: &[DrawIdx],
+        commands: &[UiDrawCommand],
+    ) {
+        self.vertex_count += vertices.len();
+        self.index_count += indices.len();
+        self.commands.extend_from_slice(commands);
+    }
+
+    fn end(&mut self) {
+        self.frames += 1;
+    }
+}
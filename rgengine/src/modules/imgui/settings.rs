@@ -0,0 +1,194 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+//app sections are written after imgui's own, imgui skips section types it has no handler for
+const APP_SECTION: &str = "[App][";
+
+///imgui's window layout and the app's own settings, kept between runs in one ini file.
+///app settings are string values grouped into named sections
+#[derive(Default, Debug)]
+pub struct UiSettings {
+    path: Option<PathBuf>,
+    sections: BTreeMap<String, BTreeMap<String, String>>,
+    dirty: bool,
+}
+
+impl UiSettings {
+    ///`None` keeps everything in memory
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            path,
+            ..Self::default()
+        }
+    }
+
+    ///`imgui.ini` in the project's folder of the user's config directory, e.g.
+    ///`~/.config/rgengine/<project>/imgui.ini` on linux
+    pub fn for_project(project: &str) -> Self {
+        Self::new(config_dir().map(|dir| {
+            dir.join("rgengine")
+                .join(folder_name(project))
+                .join("imgui.ini")
+        }))
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    ///the next save goes here
+    pub fn set_path(&mut self, path: Option<PathBuf>) {
+        self.path = path;
+        self.dirty = true;
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.sections.get(section)?.get(key).map(String::as_str)
+    }
+
+    ///`None` when the value is missing or doesn't parse
+    pub fn get_parsed<T: FromStr>(&self, section: &str, key: &str) -> Option<T> {
+        self.get(section, key)?.parse().ok()
+    }
+
+    ///keys and values can't have line breaks, keys can't have `=`
+    pub fn set<T: ToString>(&mut self, section: &str, key: &str, value: T) {
+        let value = value.to_string();
+        debug_assert!(!key.contains(['=', '\n']) && !value.contains('\n'));
+        let entry = self
+            .sections
+            .entry(section.to_string())
+            .or_default()
+            .entry(key.to_string())
+            .or_default();
+        if *entry != value {
+            *entry = value;
+            self.dirty = true;
+        }
+    }
+
+    pub fn section(&self, name: &str) -> Option<&BTreeMap<String, String>> {
+        self.sections.get(name)
+    }
+
+    pub fn remove_section(&mut self, name: &str) {
+        if self.sections.remove(name).is_some() {
+            self.dirty = true;
+        }
+    }
+
+    ///app settings changed since the last load or save
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    ///reads the file, keeping the app sections and returning imgui's part for
+    ///`Context::load_ini_settings`. a missing file is an empty one
+    pub fn load(&mut self) -> io::Result<String> {
+        let Some(path) = &self.path else {
+            return Ok(String::new());
+        };
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(self.parse(&text))
+    }
+
+    ///writes imgui's settings, from `Context::save_ini_settings`, followed by the app sections
+    pub fn save(&mut self, imgui_ini: &str) -> io::Result<()> {
+        self.dirty = false;
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, self.to_ini(imgui_ini))
+    }
+
+    ///splits `text` into the app sections, which replace the current ones, and imgui's part
+    pub fn parse(&mut self, text: &str) -> String {
+        self.sections.clear();
+        self.dirty = false;
+        let mut imgui_ini = String::new();
+        let mut section: Option<&mut BTreeMap<String, String>> = None;
+        for line in text.lines() {
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') {
+                section = trimmed
+                    .strip_prefix(APP_SECTION)
+                    .and_then(|rest| rest.strip_suffix(']'))
+                    .map(|name| self.sections.entry(name.to_string()).or_default());
+            }
+            match &mut section {
+                Some(section) => {
+                    if let Some((key, value)) = trimmed.split_once('=') {
+                        section.insert(key.to_string(), value.to_string());
+                    }
+                }
+                None => {
+                    imgui_ini.push_str(line);
+                    imgui_ini.push('\n');
+                }
+            }
+        }
+        imgui_ini
+    }
+
+    ///`imgui_ini` followed by the app sections
+    pub fn to_ini(&self, imgui_ini: &str) -> String {
+        let mut text = imgui_ini.trim_end().to_string();
+        for (name, values) in &self.sections {
+            if !text.is_empty() {
+                text.push_str("\n\n");
+            }
+            let _ = write!(text, "{APP_SECTION}{name}]");
+            for (key, value) in values {
+                let _ = write!(text, "\n{key}={value}");
+            }
+        }
+        text.push('\n');
+        text
+    }
+}
+
+///per user config directory, `None` when the environment doesn't say where home is
+pub fn config_dir() -> Option<PathBuf> {
+    let var = |name: &str| std::env::var_os(name).filter(|v| !v.is_empty());
+    if cfg!(target_os = "windows") {
+        var("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        var("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| var("HOME").map(|home| PathBuf::from(home).join(".config")))
+    }
+}
+
+//"My Game" goes in `my-game`
+fn folder_name(project: &str) -> String {
+    let name: String = project
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    if name.is_empty() {
+        "default".to_string()
+    } else {
+        name
+    }
+}
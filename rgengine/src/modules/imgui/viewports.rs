@@ -0,0 +1,362 @@
+use std::{cell::RefCell, collections::HashMap, ffi::c_void, rc::Rc, sync::mpsc::Receiver};
+
+use glfw::{Action, Context as _, WindowEvent, WindowHint, WindowMode};
+use imgui::{
+    BackendFlags, Context, Id, Io, PlatformMonitor, PlatformViewportBackend, Viewport,
+    ViewportFlags,
+};
+
+use super::{
+    platform::{HeldModifiers, add_focus, add_key, add_mouse_button},
+    renderer::{GlUiBackend, ImGuiRenderer},
+};
+use crate::window::WindowManager;
+
+//an os window imgui opened for an imgui window dragged out of the main one
+struct ViewportWindow {
+    window: glfw::Window,
+    events: Receiver<(f64, WindowEvent)>,
+    //vertex arrays belong to one gl context, so this is made the first time the window is
+    //drawn and dropped with its context current
+    backend: Option<GlUiBackend>,
+}
+
+//the main window as it was at the start of the frame, imgui asks about it while the
+//window manager is busy elsewhere
+#[derive(Default)]
+struct MainWindow {
+    pos: [f32; 2],
+    size: [f32; 2],
+    focused: bool,
+    minimized: bool,
+}
+
+struct Windows {
+    glfw: glfw::Glfw,
+    main_handle: *mut glfw::ffi::GLFWwindow,
+    //only set while imgui can open windows, they share the main window's gl objects
+    share: *const glfw::Window,
+    main: MainWindow,
+    windows: HashMap<Id, ViewportWindow>,
+    //modifiers held down in the viewport windows
+    modifiers: HeldModifiers,
+}
+
+///the os windows behind imgui's viewports. the platform backend imgui calls opens and moves
+///them, the `ImGuiLayer` passes their input on and draws into them
+#[derive(Clone)]
+pub(super) struct ViewportWindows(Rc<RefCell<Windows>>);
+
+impl ViewportWindows {
+    pub fn new(window: &mut WindowManager) -> Self {
+        let glfw = *window.get_glfw();
+        Self(Rc::new(RefCell::new(Windows {
+            glfw,
+            main_handle: window.native_window().window_ptr(),
+            share: std::ptr::null(),
+            main: MainWindow::default(),
+            windows: HashMap::new(),
+            modifiers: HeldModifiers::default(),
+        })))
+    }
+
+    ///lets `imgui` open windows, whether it does is up to `ConfigFlags::VIEWPORTS_ENABLE`
+    pub fn install(&self, imgui: &mut Context) {
+        imgui.set_platform_backend(GlfwViewportBackend(self.clone()));
+        imgui.io_mut().backend_flags |=
+            BackendFlags::PLATFORM_HAS_VIEWPORTS | BackendFlags::RENDERER_HAS_VIEWPORTS;
+        let main_handle = self.0.borrow().main_handle;
+        imgui.main_viewport_mut().platform_handle = main_handle.cast();
+        self.update_monitors(imgui);
+    }
+
+    //imgui places new windows on these and keeps them from being lost off screen
+    fn update_monitors(&self, imgui: &mut Context) {
+        let mut glfw = self.0.borrow().glfw;
+        let monitors = glfw.with_connected_monitors(|_, monitors| {
+            monitors
+                .iter()
+                .filter_map(|monitor| {
+                    let mode = monitor.get_video_mode()?;
+                    let (x, y) = monitor.get_pos();
+                    let (work_x, work_y, work_width, work_height) = monitor.get_workarea();
+                    Some(PlatformMonitor {
+                        main_pos: [x as f32, y as f32],
+                        main_size: [mode.width as f32, mode.height as f32],
+                        work_pos: [work_x as f32, work_y as f32],
+                        work_size: [work_width as f32, work_height as f32],
+                        dpi_scale: monitor.get_content_scale().0,
+                    })
+                })
+                .collect::<Vec<_>>()
+        });
+        if !monitors.is_empty() {
+            imgui
+                .platform_io_mut()
+                .monitors
+                .replace_from_slice(&monitors);
+        }
+    }
+
+    ///remembers the main window and passes the input of the other windows to imgui, before
+    ///the frame starts
+    pub fn prepare_frame(&self, imgui: &mut Context, window: &mut WindowManager) {
+        let native = window.native_window();
+        let (x, y) = native.get_pos();
+        let (width, height) = native.get_size();
+        self.0.borrow_mut().main = MainWindow {
+            pos: [x as f32, y as f32],
+            size: [width as f32, height as f32],
+            focused: native.is_focused(),
+            minimized: native.is_iconified(),
+        };
+        self.update_monitors(imgui);
+
+        let mut events = Vec::new();
+        for (&id, viewport) in &self.0.borrow().windows {
+            let (x, y) = viewport.window.get_pos();
+            for (_, event) in glfw::flush_messages(&viewport.events) {
+                events.push((id, [x as f32, y as f32], event));
+            }
+        }
+        for (id, [x, y], event) in events {
+            match event {
+                WindowEvent::Close | WindowEvent::Pos(..) | WindowEvent::Size(..) => {
+                    if let Some(viewport) = imgui.viewport_by_id_mut(id) {
+                        match event {
+                            WindowEvent::Close => viewport.platform_request_close = true,
+                            WindowEvent::Pos(..) => viewport.platform_request_move = true,
+                            _ => viewport.platform_request_resize = true,
+                        }
+                    }
+                }
+                event => on_window_event(
+                    imgui.io_mut(),
+                    &mut self.0.borrow_mut().modifiers,
+                    [x, y],
+                    event,
+                ),
+            }
+        }
+    }
+
+    ///opens, moves and closes windows to match imgui's viewports, after the frame is rendered
+    pub fn update(&self, imgui: &mut Context, window: &mut WindowManager) {
+        self.0.borrow_mut().share = window.native_window();
+        imgui.update_platform_windows();
+        self.0.borrow_mut().share = std::ptr::null();
+    }
+
+    ///draws every viewport but the main one into its window, the main window's context is
+    ///current again afterwards
+    pub fn render(&self, imgui: &Context, renderer: &mut ImGuiRenderer<GlUiBackend>) {
+        let mut windows = self.0.borrow_mut();
+        let main_id = imgui.main_viewport().id;
+        let mut drew = false;
+        for viewport in imgui.viewports() {
+            if viewport.id == main_id || viewport.flags.contains(ViewportFlags::MINIMIZED) {
+                continue;
+            }
+            let Some(window) = windows.windows.get_mut(&viewport.id) else {
+                continue;
+            };
+            window.window.make_current();
+            drew = true;
+            let backend = window
+                .backend
+                .get_or_insert_with(|| renderer.backend().share());
+            let (width, height) = window.window.get_framebuffer_size();
+            unsafe {
+                gl::Viewport(0, 0, width, height);
+                if !viewport.flags.contains(ViewportFlags::NO_RENDERER_CLEAR) {
+                    gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+                    gl::Clear(gl::COLOR_BUFFER_BIT);
+                }
+            }
+            renderer.render_draw_data_with(backend, viewport.draw_data());
+            window.window.swap_buffers();
+        }
+        if drew {
+            unsafe { glfw::ffi::glfwMakeContextCurrent(windows.main_handle) };
+        }
+    }
+}
+
+fn on_window_event(
+    io: &mut Io,
+    modifiers: &mut HeldModifiers,
+    [x, y]: [f32; 2],
+    event: WindowEvent,
+) {
+    match event {
+        //imgui wants positions on the desktop
+        WindowEvent::CursorPos(cursor_x, cursor_y) => {
+            io.add_mouse_pos_event([cursor_x as f32 + x, cursor_y as f32 + y])
+        }
+        WindowEvent::MouseButton(button, action, _) => {
+            add_mouse_button(io, button, action != Action::Release)
+        }
+        WindowEvent::Scroll(x_offset, y_offset) => {
+            io.add_mouse_wheel_event([x_offset as f32, y_offset as f32])
+        }
+        WindowEvent::Key(key, _, action, _) => {
+            add_key(io, modifiers, key, action != Action::Release)
+        }
+        WindowEvent::Char(ch) => io.add_input_character(ch),
+        WindowEvent::Focus(focused) => add_focus(io, modifiers, focused),
+        _ => {}
+    }
+}
+
+struct GlfwViewportBackend(ViewportWindows);
+
+impl GlfwViewportBackend {
+    //`None` for the main window, which the engine owns
+    fn with_window<T>(
+        &mut self,
+        viewport: &Viewport,
+        f: impl FnOnce(&mut glfw::Window) -> T,
+    ) -> Option<T> {
+        self.0
+            .0
+            .borrow_mut()
+            .windows
+            .get_mut(&viewport.id)
+            .map(|window| f(&mut window.window))
+    }
+}
+
+impl PlatformViewportBackend for GlfwViewportBackend {
+    fn create_window(&mut self, viewport: &mut Viewport) {
+        let mut windows = self.0.0.borrow_mut();
+        //imgui only opens windows from `update_platform_windows`
+        let Some(share) = (unsafe { windows.share.as_ref() }) else {
+            return;
+        };
+        let mut glfw = windows.glfw;
+        glfw.window_hint(WindowHint::Visible(false));
+        glfw.window_hint(WindowHint::Focused(false));
+        glfw.window_hint(WindowHint::FocusOnShow(false));
+        glfw.window_hint(WindowHint::Decorated(
+            !viewport.flags.contains(ViewportFlags::NO_DECORATION),
+        ));
+        glfw.window_hint(WindowHint::Floating(
+            viewport.flags.contains(ViewportFlags::TOP_MOST),
+        ));
+        let created = share.create_shared(
+            viewport.size[0].max(1.0) as u32,
+            viewport.size[1].max(1.0) as u32,
+            "",
+            WindowMode::Windowed,
+        );
+        //back to what the main window was made with
+        glfw.window_hint(WindowHint::Visible(true));
+        glfw.window_hint(WindowHint::Focused(true));
+        glfw.window_hint(WindowHint::FocusOnShow(true));
+        glfw.window_hint(WindowHint::Decorated(true));
+        glfw.window_hint(WindowHint::Floating(false));
+
+        let Some((mut window, events)) = created else {
+            return;
+        };
+        window.set_pos(viewport.pos[0] as i32, viewport.pos[1] as i32);
+        window.set_all_polling(true);
+        //every window waiting for vsync would divide the frame rate by the window count
+        window.make_current();
+        glfw.set_swap_interval(glfw::SwapInterval::None);
+        glfw::make_context_current(Some(share));
+
+        viewport.platform_handle = window.window_ptr().cast::<c_void>();
+        windows.windows.insert(
+            viewport.id,
+            ViewportWindow {
+                window,
+                events,
+                backend: None,
+            },
+        );
+    }
+
+    fn destroy_window(&mut self, viewport: &mut Viewport) {
+        let mut windows = self.0.0.borrow_mut();
+        if let Some(mut window) = windows.windows.remove(&viewport.id)
+            && let Some(backend) = window.backend.take()
+        {
+            window.window.make_current();
+            drop(backend);
+            unsafe { glfw::ffi::glfwMakeContextCurrent(windows.main_handle) };
+        }
+        viewport.platform_handle = std::ptr::null_mut();
+    }
+
+    fn show_window(&mut self, viewport: &mut Viewport) {
+        self.with_window(viewport, |window| window.show());
+    }
+
+    fn set_window_pos(&mut self, viewport: &mut Viewport, pos: [f32; 2]) {
+        self.with_window(viewport, |window| {
+            window.set_pos(pos[0] as i32, pos[1] as i32)
+        });
+    }
+
+    fn get_window_pos(&mut self, viewport: &mut Viewport) -> [f32; 2] {
+        self.with_window(viewport, |window| {
+            let (x, y) = window.get_pos();
+            [x as f32, y as f32]
+        })
+        .unwrap_or_else(|| self.0.0.borrow().main.pos)
+    }
+
+    fn set_window_size(&mut self, viewport: &mut Viewport, size: [f32; 2]) {
+        self.with_window(viewport, |window| {
+            window.set_size(size[0] as i32, size[1] as i32)
+        });
+    }
+
+    fn get_window_size(&mut self, viewport: &mut Viewport) -> [f32; 2] {
+        self.with_window(viewport, |window| {
+            let (width, height) = window.get_size();
+            [width as f32, height as f32]
+        })
+        .unwrap_or_else(|| self.0.0.borrow().main.size)
+    }
+
+    fn set_window_focus(&mut self, viewport: &mut Viewport) {
+        self.with_window(viewport, |window| window.focus());
+    }
+
+    fn get_window_focus(&mut self, viewport: &mut Viewport) -> bool {
+        self.with_window(viewport, |window| window.is_focused())
+            .unwrap_or_else(|| self.0.0.borrow().main.focused)
+    }
+
+    fn get_window_minimized(&mut self, viewport: &mut Viewport) -> bool {
+        self.with_window(viewport, |window| window.is_iconified())
+            .unwrap_or_else(|| self.0.0.borrow().main.minimized)
+    }
+
+    fn set_window_title(&mut self, viewport: &mut Viewport, title: &str) {
+        self.with_window(viewport, |window| window.set_title(title));
+    }
+
+    fn set_window_alpha(&mut self, viewport: &mut Viewport, alpha: f32) {
+        self.with_window(viewport, |window| window.set_opacity(alpha));
+    }
+
+    fn update_window(&mut self, _viewport: &mut Viewport) {}
+
+    //`ViewportWindows::render` draws and swaps every window itself
+    fn render_window(&mut self, _viewport: &mut Viewport) {}
+
+    fn swap_buffers(&mut self, _viewport: &mut Viewport) {}
+
+    fn create_vk_surface(
+        &mut self,
+        _viewport: &mut Viewport,
+        _instance: u64,
+        _out_surface: &mut u64,
+    ) -> i32 {
+        //not a vulkan engine
+        -1
+    }
+}
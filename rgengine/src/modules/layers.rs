@@ -1,4 +1,8 @@
-use super::{events::RGEvent, window::WindowManager};
+use std::time::{Duration, Instant};
+
+use imgui::Ui;
+
+use super::{events::RGEvent, imgui::Editor, window::WindowManager};
 
 // may need to work on this trait
 pub trait Layer {
@@ -7,7 +11,28 @@ pub trait Layer {
     fn on_update(&mut self, _dt: f32) {}
     fn on_event(&mut self, _event: &RGEvent) {}
     fn on_render(&mut self, _window: &mut WindowManager) {}
+    ///add imgui windows, called once a frame inside the engine's imgui frame. panels added
+    ///through `editor` can be docked in the editor layout
+    fn on_imgui(&mut self, _ui: &Ui, _editor: &mut Editor) {}
     fn get_id(&self) -> usize;
+    ///shown by the layer stack panel
+    fn get_name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+///time a layer spent in its callbacks since `LayerStack::reset_timings`
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct LayerTiming {
+    pub update: Duration,
+    pub event: Duration,
+    pub render: Duration,
+}
+
+impl LayerTiming {
+    pub fn total(&self) -> Duration {
+        self.update + self.event + self.render
+    }
 }
 
 //may need to make the layers shared but rc refcell is a pain
@@ -15,24 +40,35 @@ pub trait Layer {
 ///vec contains both layers and overlays which are seperated by the insert pos
 pub struct LayerStack {
     layers: Vec<Box<dyn Layer>>,
+    //one per layer, in the same order
+    timings: Vec<LayerTiming>,
     insert_pos: usize,
 }
 
+impl Default for LayerStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl LayerStack {
     pub fn new() -> Self {
         Self {
             layers: Vec::new(),
+            timings: Vec::new(),
             insert_pos: 0,
         }
     }
 
     pub fn push_layer(&mut self, layer: Box<dyn Layer>) {
         self.layers.insert(self.insert_pos, layer);
+        self.timings.insert(self.insert_pos, LayerTiming::default());
         self.insert_pos += 1;
     }
 
     pub fn push_overlay(&mut self, overlay: Box<dyn Layer>) {
         self.layers.push(overlay);
+        self.timings.push(LayerTiming::default());
     }
 
     pub fn pop_layer(&mut self, target: Box<dyn Layer>) -> Option<Box<dyn Layer>> {
@@ -42,6 +78,7 @@ impl LayerStack {
             .position(|x| x.get_id() == target.get_id())
         {
             let mut old = self.layers.remove(pos);
+            self.timings.remove(pos);
             self.insert_pos -= 1;
             old.on_detach();
             return Some(old);
@@ -57,6 +94,7 @@ impl LayerStack {
             .map(|x| x + start)
         {
             let mut old = self.layers.remove(pos);
+            self.timings.remove(pos);
             old.on_detach();
             return Some(old);
         }
@@ -65,20 +103,72 @@ impl LayerStack {
 
     //these are temorary, maybe
     pub fn update(&mut self) {
-        for layer in self.layers.iter_mut().rev() {
+        for (layer, timing) in self.layers.iter_mut().zip(&mut self.timings).rev() {
+            let start = Instant::now();
             layer.on_update(0.016); // 60 FPS delta
+            timing.update += start.elapsed();
         }
     }
 
     pub fn render(&mut self, window: &mut WindowManager) {
-        for layer in &mut self.layers {
+        self.render_layers(window);
+        self.render_overlays(window);
+    }
+
+    pub fn render_layers(&mut self, window: &mut WindowManager) {
+        self.render_range(0..self.insert_pos, window);
+    }
+
+    pub fn render_overlays(&mut self, window: &mut WindowManager) {
+        self.render_range(self.insert_pos..self.layers.len(), window);
+    }
+
+    fn render_range(&mut self, range: std::ops::Range<usize>, window: &mut WindowManager) {
+        let layers = self.layers[range.clone()].iter_mut();
+        for (layer, timing) in layers.zip(&mut self.timings[range]) {
+            let start = Instant::now();
             layer.on_render(window);
+            timing.render += start.elapsed();
+        }
+    }
+
+    ///layers first so overlay windows end up on top
+    pub fn on_imgui(&mut self, ui: &Ui, editor: &mut Editor) {
+        for layer in &mut self.layers {
+            layer.on_imgui(ui, editor);
         }
     }
 
+    pub fn layers(&self) -> &[Box<dyn Layer>] {
+        &self.layers[..self.insert_pos]
+    }
+
+    pub fn overlays(&self) -> &[Box<dyn Layer>] {
+        &self.layers[self.insert_pos..]
+    }
+
+    ///from the top overlay down, events already handled (e.g. by imgui) are skipped
     pub fn on_event(&mut self, event: &RGEvent) {
-        for layer in self.layers.iter_mut().rev() {
-            layer.on_event(&event);
+        if event.is_handled() {
+            return;
         }
+        for (layer, timing) in self.layers.iter_mut().zip(&mut self.timings).rev() {
+            let start = Instant::now();
+            layer.on_event(event);
+            timing.event += start.elapsed();
+        }
+    }
+
+    ///layers then overlays, in the order of `layers` and `overlays`
+    pub fn timings(&self) -> impl Iterator<Item = (&dyn Layer, LayerTiming)> {
+        self.layers
+            .iter()
+            .map(|layer| layer.as_ref())
+            .zip(self.timings.iter().copied())
+    }
+
+    ///the engine calls this at the start of every frame, so the timings cover one frame
+    pub fn reset_timings(&mut self) {
+        self.timings.fill(LayerTiming::default());
     }
 }
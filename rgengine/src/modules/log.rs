@@ -4,16 +4,114 @@
 use spdlog::{
     formatter::{Formatter, FormatterContext},
     prelude::*,
-    sink::StdStreamSink,
-    Logger, Record, StringBuf,
+    sink::{Sink, StdStreamSink},
+    ErrorHandler, Logger, Record, StringBuf,
 };
-use std::sync::{Arc, OnceLock};
-use std::time::Duration;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{BufWriter, IsTerminal, Write as _};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, TryLockError};
+use std::time::{Duration, Instant, SystemTime};
 
-#[derive(Clone)]
-struct ColoredFormatter;
+use chrono::{DateTime, Local};
 
-impl Formatter for ColoredFormatter {
+//the client macros expand in the app, which doesn't depend on spdlog itself
+#[doc(hidden)]
+pub use spdlog;
+
+//the layout logs had before patterns, a colored header line and the payload under it
+pub const DEFAULT_PATTERN: &str = "{color}[{file}] [{level}] {logger}:\n{payload}{reset}";
+
+const EOL: &str = if cfg!(windows) { "\r\n" } else { "\n" };
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Field {
+    Date,
+    Time,
+    Level,
+    Logger,
+    File,
+    Line,
+    Thread,
+    Payload,
+    Color,
+    Reset,
+}
+
+#[derive(Clone, Debug)]
+enum Token {
+    Text(String),
+    Field(Field),
+}
+
+///lays records out with a pattern where `{name}` is replaced by part of the record: `{date}`,
+///`{time}` (local, with milliseconds), `{level}`, `{logger}`, `{file}`, `{line}`, `{thread}`
+///and `{payload}`. `{color}` starts the level's color and `{reset}` ends it, they write nothing
+///when colors are off. `{{` and `}}` are braces, every record ends with a new line
+#[derive(Clone, Debug)]
+pub struct PatternFormatter {
+    tokens: Vec<Token>,
+    colors: bool,
+}
+
+impl PatternFormatter {
+    pub fn new(pattern: &str, colors: bool) -> Result<Self, String> {
+        let mut tokens = Vec::new();
+        let mut text = String::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let Some(end) = rest.find('}') else {
+                        return Err(format!("unclosed `{{` in `{pattern}`"));
+                    };
+                    let field = match &rest[..end] {
+                        "date" => Field::Date,
+                        "time" => Field::Time,
+                        "level" => Field::Level,
+                        "logger" => Field::Logger,
+                        "file" => Field::File,
+                        "line" => Field::Line,
+                        "thread" => Field::Thread,
+                        "payload" => Field::Payload,
+                        "color" => Field::Color,
+                        "reset" => Field::Reset,
+                        name => return Err(format!("unknown field `{{{name}}}` in `{pattern}`")),
+                    };
+                    chars = rest[end + 1..].chars();
+                    if !text.is_empty() {
+                        tokens.push(Token::Text(std::mem::take(&mut text)));
+                    }
+                    tokens.push(Token::Field(field));
+                }
+                '}' => return Err(format!("unmatched `}}` in `{pattern}`")),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            tokens.push(Token::Text(text));
+        }
+        Ok(Self { tokens, colors })
+    }
+
+    pub fn colors(&self) -> bool {
+        self.colors
+    }
+}
+
+impl Formatter for PatternFormatter {
     fn format(
         &self,
         record: &Record<'_>,
@@ -22,44 +120,490 @@ impl Formatter for ColoredFormatter {
     ) -> spdlog::Result<()> {
         use std::fmt::Write;
 
-        // Define ANSI color codes for log levels
-        let color_code = match record.level() {
-            Level::Trace => "\x1b[90m",    // Gray
-            Level::Debug => "\x1b[36m",    // Cyan
-            Level::Info => "\x1b[32m",     // Green
-            Level::Warn => "\x1b[33m",     // Yellow
-            Level::Error => "\x1b[31m",    // Red
-            Level::Critical => "\x1b[35m", // Magenta
+        let time = || DateTime::<Local>::from(record.time());
+        let source = record.source_location();
+        for token in &self.tokens {
+            let written = match token {
+                Token::Text(text) => {
+                    //the pattern's new lines follow the platform like the one at the end
+                    for (i, line) in text.split('\n').enumerate() {
+                        if i > 0 {
+                            dest.push_str(EOL);
+                        }
+                        dest.push_str(line);
+                    }
+                    Ok(())
+                }
+                Token::Field(field) => match field {
+                    Field::Date => write!(dest, "{}", time().format("%Y-%m-%d")),
+                    Field::Time => write!(dest, "{}", time().format("%H:%M:%S%.3f")),
+                    Field::Level => write!(dest, "{}", record.level()),
+                    Field::Logger => write!(dest, "{}", record.logger_name().unwrap_or_default()),
+                    Field::File => write!(dest, "{}", source.map_or("unknown", |loc| loc.file())),
+                    Field::Line => write!(dest, "{}", source.map_or(0, |loc| loc.line())),
+                    Field::Thread => write!(dest, "{}", record.tid()),
+                    Field::Payload => write!(dest, "{}", record.payload()),
+                    Field::Color if self.colors => write!(dest, "{}", ansi_color(record.level())),
+                    Field::Reset if self.colors => write!(dest, "\x1b[0m"),
+                    Field::Color | Field::Reset => Ok(()),
+                },
+            };
+            written.map_err(spdlog::Error::FormatRecord)?;
+        }
+        dest.push_str(EOL);
+        Ok(())
+    }
+}
+
+fn ansi_color(level: Level) -> &'static str {
+    match level {
+        Level::Trace => "\x1b[90m",    // Gray
+        Level::Debug => "\x1b[36m",    // Cyan
+        Level::Info => "\x1b[32m",     // Green
+        Level::Warn => "\x1b[33m",     // Yellow
+        Level::Error => "\x1b[31m",    // Red
+        Level::Critical => "\x1b[35m", // Magenta
+    }
+}
+
+///whether the stderr sink writes colors
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum ColorMode {
+    ///when stderr is a terminal and `NO_COLOR` isn't set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorMode::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+                    && std::io::stderr().is_terminal()
+            }
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+}
+
+///`trace` to `critical` for that level and the ones above it, `all` or `off`, ignoring case
+pub fn parse_level_filter(level: &str) -> Option<LevelFilter> {
+    match level.to_ascii_lowercase().as_str() {
+        "all" => Some(LevelFilter::All),
+        "off" => Some(LevelFilter::Off),
+        level => level.parse().ok().map(LevelFilter::MoreSevereEqual),
+    }
+}
+
+///the name `parse_level_filter` takes for it
+pub fn level_filter_name(level_filter: LevelFilter) -> String {
+    match level_filter {
+        LevelFilter::All => "all".to_string(),
+        LevelFilter::Off => "off".to_string(),
+        LevelFilter::MoreSevereEqual(level) => level.as_str().to_string(),
+        other => format!("{other:?}"),
+    }
+}
+
+///how `init_with` sets up the loggers
+#[derive(Clone, Debug)]
+pub struct LogConfig {
+    ///see `PatternFormatter`
+    pub pattern: String,
+    pub colors: ColorMode,
+    ///for loggers without one in `levels`
+    pub default_level: LevelFilter,
+    ///by logger name, `client` or `engine`
+    pub levels: Vec<(String, LevelFilter)>,
+    ///`Some` also writes the logs to files
+    pub file: Option<LogFileConfig>,
+    ///buffered records are written this often, warnings and worse right away
+    pub flush_period: Duration,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            pattern: DEFAULT_PATTERN.to_string(),
+            colors: ColorMode::Auto,
+            default_level: LevelFilter::All,
+            levels: Vec::new(),
+            file: None,
+            flush_period: Duration::from_secs(3),
+        }
+    }
+}
+
+impl LogConfig {
+    ///the default with the levels from `RGE_LOG`, which is ignored with a message on stderr
+    ///when it can't be parsed
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(spec) = std::env::var("RGE_LOG")
+            && let Err(e) = config.parse_levels(&spec)
+        {
+            eprintln!("RGE_LOG: {e}");
+        }
+        config
+    }
+
+    ///comma separated `logger=level`, or a level on its own for every logger, e.g.
+    ///`engine=warn,client=debug` or `info,engine=error`. nothing changes when part of it is
+    ///wrong
+    pub fn parse_levels(&mut self, spec: &str) -> Result<(), String> {
+        let mut default_level = self.default_level;
+        let mut levels = self.levels.clone();
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (logger, level) = match part.split_once('=') {
+                Some((logger, level)) => (Some(logger.trim()), level.trim()),
+                None => (None, part),
+            };
+            let level_filter =
+                parse_level_filter(level).ok_or_else(|| format!("unknown level `{level}`"))?;
+            match logger {
+                Some(logger) => {
+                    levels.retain(|(name, _)| name != logger);
+                    levels.push((logger.to_string(), level_filter));
+                }
+                None => default_level = level_filter,
+            }
+        }
+        self.default_level = default_level;
+        self.levels = levels;
+        Ok(())
+    }
+
+    pub fn level(&self, logger: &str) -> LevelFilter {
+        self.levels
+            .iter()
+            .find(|(name, _)| name == logger)
+            .map_or(self.default_level, |(_, level)| *level)
+    }
+}
+
+///a record as the `RingBufferSink` keeps it
+#[derive(Clone, Debug)]
+pub struct LogEntry {
+    pub level: Level,
+    pub logger: String,
+    pub payload: String,
+    pub time: SystemTime,
+    ///file and line of the macro call, the file is relative to the workspace
+    pub source: Option<(&'static str, u32)>,
+}
+
+///sink keeping the last `capacity` records of the loggers it's added to, oldest first.
+///`init` adds one to both loggers so the console panel can show them
+pub struct RingBufferSink {
+    records: Mutex<VecDeque<LogEntry>>,
+    capacity: usize,
+    //records ever logged, including the ones pushed out or cleared
+    total: AtomicU64,
+    level_filter: Mutex<LevelFilter>,
+}
+
+impl RingBufferSink {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity: capacity.max(1),
+            total: AtomicU64::new(0),
+            level_filter: Mutex::new(LevelFilter::All),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    ///the kept records. nothing can be logged while they're borrowed, so don't log from the
+    ///thread holding them
+    pub fn records(&self) -> MutexGuard<'_, VecDeque<LogEntry>> {
+        self.records.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    ///counts every record logged, so it changes when new ones arrive even once the buffer is
+    ///full
+    pub fn total(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    pub fn clear(&self) {
+        self.records().clear();
+    }
+}
+
+impl Sink for RingBufferSink {
+    fn log(&self, record: &Record) -> spdlog::Result<()> {
+        let entry = LogEntry {
+            level: record.level(),
+            logger: record.logger_name().unwrap_or_default().to_string(),
+            payload: record.payload().to_string(),
+            time: record.time(),
+            source: record.source_location().map(|loc| (loc.file(), loc.line())),
         };
+        let Some(mut records) = lock(&self.records) else {
+            return Ok(());
+        };
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(entry);
+        self.total.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn flush(&self) -> spdlog::Result<()> {
+        Ok(())
+    }
+
+    fn level_filter(&self) -> LevelFilter {
+        *self.level_filter.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn set_level_filter(&self, level_filter: LevelFilter) {
+        *self.level_filter.lock().unwrap_or_else(|e| e.into_inner()) = level_filter;
+    }
 
-        let reset_code = "\x1b[0m"; // Reset color
-        let eol = if std::env::consts::OS == "windows" {
-            "\r\n"
-        } else {
-            "\n"
+    //records are kept unformatted, the console lays them out itself
+    fn set_formatter(&self, _formatter: Box<dyn Formatter>) {}
+
+    fn set_error_handler(&self, _handler: Option<ErrorHandler>) {}
+}
+
+//records in log files, with the date since players send them in later
+pub const FILE_PATTERN: &str = "[{date} {time}] [{thread}] [{level}] {logger}: {payload}";
+
+///where and when `LogFileSink` writes. every run is a session with files of its own
+#[derive(Clone, Debug)]
+pub struct LogFileConfig {
+    ///made when it's missing
+    pub directory: PathBuf,
+    ///names are `{prefix}_{session start}.log`, later files of a session add `.1`, `.2`...
+    pub prefix: String,
+    ///see `PatternFormatter`, files never get colors
+    pub pattern: String,
+    ///a new file once the current one would grow past this many bytes
+    pub max_size: Option<u64>,
+    ///a new file once the current one is this old
+    pub max_age: Option<Duration>,
+    ///the oldest files with the prefix are deleted once there are more, 0 keeps them all
+    pub max_files: usize,
+    pub level: LevelFilter,
+}
+
+impl LogFileConfig {
+    ///10 MiB files, the last 10 kept
+    pub fn new(directory: impl Into<PathBuf>, prefix: &str) -> Self {
+        Self {
+            directory: directory.into(),
+            prefix: prefix.to_string(),
+            pattern: FILE_PATTERN.to_string(),
+            max_size: Some(10 * 1024 * 1024),
+            max_age: None,
+            max_files: 10,
+            level: LevelFilter::All,
+        }
+    }
+}
+
+struct LogFile {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    size: u64,
+    opened: Instant,
+    //how many files the session had before this one
+    part: u32,
+}
+
+///writes records to a file per session, moving on to a new one when the current one gets too
+///big or old. records are buffered until the logger flushes, `init_with` makes that happen on
+///warnings and worse, on panics and every `LogConfig::flush_period`
+pub struct LogFileSink {
+    config: LogFileConfig,
+    session: String,
+    file: Mutex<LogFile>,
+    formatter: Mutex<Box<dyn Formatter>>,
+    level_filter: Mutex<LevelFilter>,
+}
+
+impl LogFileSink {
+    pub fn new(config: LogFileConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let formatter = PatternFormatter::new(&config.pattern, false)?;
+        fs::create_dir_all(&config.directory)?;
+        let stamp = Local::now().format("%Y%m%d_%H%M%S");
+        //a second run started within the same second gets a suffix
+        let mut session = format!("{}_{stamp}", config.prefix);
+        let mut run = 1;
+        while config.directory.join(format!("{session}.log")).exists() {
+            session = format!("{}_{stamp}-{run}", config.prefix);
+            run += 1;
+        }
+        let file = open_log_file(&config.directory.join(format!("{session}.log")), 0)?;
+        let current = file.path.clone();
+        let sink = Self {
+            level_filter: Mutex::new(config.level),
+            config,
+            session,
+            file: Mutex::new(file),
+            formatter: Mutex::new(Box::new(formatter)),
         };
+        sink.remove_old_files(&current)?;
+        Ok(sink)
+    }
+
+    ///the file being written, the one to ask players for
+    pub fn path(&self) -> PathBuf {
+        self.file().path.clone()
+    }
+
+    pub fn config(&self) -> &LogFileConfig {
+        &self.config
+    }
+
+    fn file(&self) -> MutexGuard<'_, LogFile> {
+        self.file.lock().unwrap_or_else(|e| e.into_inner())
+    }
 
-        // Write the formatted log message with colors
-        write!(
-            dest,
-            "{0}[{source}] [{level}] {logger}:{1}{payload}{2}{1}",
-            color_code,
-            eol,
-            reset_code,
-            source = record.source_location().map_or("unknown", |loc| loc.file()),
-            level = record.level(),
-            logger = record.logger_name().unwrap(),
-            payload = record.payload()
-        )
-        .expect("log failed");
+    fn rotate(&self, file: &mut LogFile) -> std::io::Result<()> {
+        file.writer.flush()?;
+        let part = file.part + 1;
+        let path = self
+            .config
+            .directory
+            .join(format!("{}.{part}.log", self.session));
+        *file = open_log_file(&path, part)?;
+        self.remove_old_files(&file.path)
+    }
+
+    //oldest first by modification time then session and part, the current file is never
+    //removed
+    fn remove_old_files(&self, current: &Path) -> std::io::Result<()> {
+        if self.config.max_files == 0 {
+            return Ok(());
+        }
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.config.directory)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if let Some((session, part)) = parse_file_name(&name, &self.config.prefix) {
+                let modified = entry.metadata()?.modified()?;
+                files.push((modified, session.to_string(), part, entry.path()));
+            }
+        }
+        files.sort();
+        let excess = files.len().saturating_sub(self.config.max_files);
+        let old = files
+            .into_iter()
+            .filter(|(.., p)| p != current)
+            .take(excess);
+        for (.., path) in old {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+//the session and part of a name `LogFileSink` gives its files,
+//`{prefix}_{%Y%m%d_%H%M%S}[-run][.part].log`. `None` for anything else, like the files of a
+//prefix that starts with this one
+fn parse_file_name<'a>(name: &'a str, prefix: &str) -> Option<(&'a str, u32)> {
+    let digits = |s: &str, len: Option<usize>| {
+        !s.is_empty()
+            && len.is_none_or(|len| s.len() == len)
+            && s.bytes().all(|b| b.is_ascii_digit())
+    };
+    let stem = name
+        .strip_prefix(prefix)?
+        .strip_prefix('_')?
+        .strip_suffix(".log")?;
+    let (session, part) = stem.split_once('.').unwrap_or((stem, "0"));
+    let (stamp, run) = session.split_once('-').unwrap_or((session, "0"));
+    let (date, time) = stamp.split_once('_')?;
+    let valid = digits(date, Some(8)) && digits(time, Some(6)) && digits(run, None);
+    (valid && digits(part, None)).then(|| (session, part.parse().unwrap_or(0)))
+}
+
+fn open_log_file(path: &Path, part: u32) -> std::io::Result<LogFile> {
+    let file = File::options().create(true).append(true).open(path)?;
+    let size = file.metadata()?.len();
+    Ok(LogFile {
+        writer: BufWriter::new(file),
+        path: path.to_path_buf(),
+        size,
+        opened: Instant::now(),
+        part,
+    })
+}
 
+impl Sink for LogFileSink {
+    fn log(&self, record: &Record) -> spdlog::Result<()> {
+        let mut text = StringBuf::new();
+        let (Some(formatter), Some(mut file)) = (lock(&self.formatter), lock(&self.file)) else {
+            return Ok(());
+        };
+        formatter.format(record, &mut text, &mut FormatterContext::new())?;
+        let too_big = self
+            .config
+            .max_size
+            .is_some_and(|max| file.size + text.len() as u64 > max);
+        let too_old = self
+            .config
+            .max_age
+            .is_some_and(|max| file.opened.elapsed() >= max);
+        //a record bigger than the limit still goes into a file of its own
+        if file.size > 0 && (too_big || too_old) {
+            self.rotate(&mut file).map_err(spdlog::Error::WriteRecord)?;
+        }
+        file.writer
+            .write_all(text.as_bytes())
+            .map_err(spdlog::Error::WriteRecord)?;
+        file.size += text.len() as u64;
         Ok(())
     }
+
+    fn flush(&self) -> spdlog::Result<()> {
+        let Some(mut file) = lock(&self.file) else {
+            return Ok(());
+        };
+        file.writer.flush().map_err(spdlog::Error::FlushBuffer)
+    }
+
+    fn level_filter(&self) -> LevelFilter {
+        *self.level_filter.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn set_level_filter(&self, level_filter: LevelFilter) {
+        *self.level_filter.lock().unwrap_or_else(|e| e.into_inner()) = level_filter;
+    }
+
+    fn set_formatter(&self, formatter: Box<dyn Formatter>) {
+        *self.formatter.lock().unwrap_or_else(|e| e.into_inner()) = formatter;
+    }
+
+    fn set_error_handler(&self, _handler: Option<ErrorHandler>) {}
 }
 
+impl Drop for LogFileSink {
+    fn drop(&mut self) {
+        let _ = self.file().writer.flush();
+    }
+}
+
+///records kept for the console
+pub const LOG_BUFFER_CAPACITY: usize = 4096;
+
 // Define a global static logger
 static CLIENT_LOGGER: OnceLock<Arc<Logger>> = OnceLock::new();
 static ENGINE_LOGGER: OnceLock<Arc<Logger>> = OnceLock::new();
+static LOG_BUFFER: OnceLock<Arc<RingBufferSink>> = OnceLock::new();
+static STDERR_SINK: OnceLock<Arc<StdStreamSink>> = OnceLock::new();
+static LOG_FILE: OnceLock<Arc<LogFileSink>> = OnceLock::new();
+//decided once by `init_with`, `set_pattern` keeps it
+static COLORS: OnceLock<bool> = OnceLock::new();
 
 pub fn get_client_logger() -> &'static Logger {
     CLIENT_LOGGER.get().expect("Logger not initialized")
@@ -68,24 +612,184 @@ pub fn get_engine_logger() -> &'static Logger {
     ENGINE_LOGGER.get().expect("Logger not initialized")
 }
 
+///`client` or `engine`, `None` for other names and before `init`
+pub fn get_logger(name: &str) -> Option<&'static Logger> {
+    match name {
+        "client" => CLIENT_LOGGER.get(),
+        "engine" => ENGINE_LOGGER.get(),
+        _ => None,
+    }
+    .map(|logger| logger.as_ref())
+}
+
+///the level of the engine logger, `None` before `init`
+pub fn level_filter() -> Option<LevelFilter> {
+    ENGINE_LOGGER.get().map(|logger| logger.level_filter())
+}
+
+///the most verbose level either logger writes, `None` when both are off or before `init`
+pub fn most_verbose_level() -> Option<Level> {
+    Level::iter()
+        .filter(|&level| {
+            [&CLIENT_LOGGER, &ENGINE_LOGGER]
+                .into_iter()
+                .filter_map(OnceLock::get)
+                .any(|logger| logger.level_filter().test(level))
+        })
+        .last()
+}
+
+///sets the level of both loggers, does nothing before `init`
+pub fn set_level_filter(level_filter: LevelFilter) {
+    for logger in [&CLIENT_LOGGER, &ENGINE_LOGGER] {
+        if let Some(logger) = logger.get() {
+            logger.set_level_filter(level_filter);
+        }
+    }
+}
+
+///false when there's no logger with that name
+pub fn set_logger_level(name: &str, level_filter: LevelFilter) -> bool {
+    get_logger(name)
+        .map(|logger| logger.set_level_filter(level_filter))
+        .is_some()
+}
+
+///sets levels the way `RGE_LOG` does, e.g. `engine=warn,client=debug`. loggers it doesn't
+///name keep their level unless it has a level on its own
+pub fn set_levels(spec: &str) -> Result<(), String> {
+    let mut config = LogConfig::default();
+    config.parse_levels(spec)?;
+    let has_default = spec
+        .split(',')
+        .any(|part| !part.trim().is_empty() && !part.contains('='));
+    if let Some((name, _)) = config
+        .levels
+        .iter()
+        .find(|(name, _)| get_logger(name).is_none())
+    {
+        return Err(format!("no logger named `{name}`"));
+    }
+    for name in ["client", "engine"] {
+        if has_default || config.levels.iter().any(|(logger, _)| logger == name) {
+            set_logger_level(name, config.level(name));
+        }
+    }
+    Ok(())
+}
+
+///replaces the stderr sink's format, see `PatternFormatter`. colors stay as `init` set them
+pub fn set_pattern(pattern: &str) -> Result<(), String> {
+    let Some(sink) = STDERR_SINK.get() else {
+        return Err("logging isn't initialized".to_string());
+    };
+    let colors = COLORS.get().copied().unwrap_or(false);
+    sink.set_formatter(Box::new(PatternFormatter::new(pattern, colors)?));
+    Ok(())
+}
+
+///the last records of both loggers, `None` before `init`
+pub fn get_log_buffer() -> Option<&'static Arc<RingBufferSink>> {
+    LOG_BUFFER.get()
+}
+
+///the file sink from `LogConfig::file`, `None` without one or before `init`
+pub fn get_log_file() -> Option<&'static Arc<LogFileSink>> {
+    LOG_FILE.get()
+}
+
+///writes out what every sink buffered, the engine does this when it's dropped
+pub fn flush() {
+    for logger in [&CLIENT_LOGGER, &ENGINE_LOGGER] {
+        if let Some(logger) = logger.get() {
+            logger.flush();
+        }
+    }
+}
+
+thread_local! {
+    static IN_PANIC_HOOK: Cell<bool> = const { Cell::new(false) };
+}
+
+//the panicking code may hold a sink's lock (a console reading the records, a formatter that
+//panicked), so inside the panic hook the sinks skip a locked one instead of waiting forever
+fn lock<T: ?Sized>(mutex: &Mutex<T>) -> Option<MutexGuard<'_, T>> {
+    if !IN_PANIC_HOOK.get() {
+        return Some(mutex.lock().unwrap_or_else(|e| e.into_inner()));
+    }
+    match mutex.try_lock() {
+        Ok(guard) => Some(guard),
+        Err(TryLockError::Poisoned(e)) => Some(e.into_inner()),
+        Err(TryLockError::WouldBlock) => None,
+    }
+}
+
+//panics are logged as critical so they end up in the log file, which is flushed before the
+//previous hook runs (and maybe aborts)
+fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        IN_PANIC_HOOK.set(true);
+        if let Some(logger) = ENGINE_LOGGER.get() {
+            spdlog::critical!(logger: logger, "{}", info);
+        }
+        flush();
+        IN_PANIC_HOOK.set(false);
+        previous(info);
+    }));
+}
+
+///`init_with` the default config and the levels from `RGE_LOG`
 pub fn init() -> Result<(), Box<dyn std::error::Error>> {
+    init_with(LogConfig::from_env())
+}
+
+///sets up the loggers, once per process, later calls fail and leave them as they are
+pub fn init_with(config: LogConfig) -> Result<(), Box<dyn std::error::Error>> {
+    if ENGINE_LOGGER.get().is_some() {
+        return Err("logging is already initialized".into());
+    }
+    //everything is built before the globals are set, so a failure leaves logging as it was
+    let colors = config.colors.enabled();
+    let formatter = PatternFormatter::new(&config.pattern, colors)?;
     let stdout_sink = StdStreamSink::builder();
     let stdout_sink = Arc::new(
         stdout_sink
             .stderr()
-            .formatter(Box::new(ColoredFormatter))
+            .formatter(Box::new(formatter))
             .build()?,
     );
+    let log_buffer = Arc::new(RingBufferSink::new(LOG_BUFFER_CAPACITY));
+    //logging to the terminal still works when the file can't be made
+    let log_file = match config.file.clone().map(LogFileSink::new).transpose() {
+        Ok(log_file) => log_file.map(Arc::new),
+        Err(e) => {
+            eprintln!("failed to open a log file: {e}");
+            None
+        }
+    };
 
-    let new_logger = Logger::builder()
+    let mut builder = Logger::builder();
+    builder
         .name("client")
-        .level_filter(LevelFilter::All)
+        .level_filter(config.level("client"))
         .flush_level_filter(LevelFilter::MoreSevereEqual(Level::Warn))
-        .sink(stdout_sink)
-        .build()?;
+        .sink(stdout_sink.clone())
+        .sink(log_buffer.clone());
+    if let Some(log_file) = &log_file {
+        builder.sink(log_file.clone());
+    }
+    let new_logger = builder.build()?;
+
+    let _ = COLORS.set(colors);
+    let _ = STDERR_SINK.set(stdout_sink);
+    let _ = LOG_BUFFER.set(log_buffer);
+    if let Some(log_file) = log_file {
+        let _ = LOG_FILE.set(log_file);
+    }
 
     let en = Arc::new(new_logger.clone());
-    en.set_flush_period(Some(Duration::from_secs(3)));
+    en.set_flush_period(Some(config.flush_period));
     // Fork the default logger and configure it
 
     // Store the new logger in the global static variable
@@ -93,12 +797,14 @@ pub fn init() -> Result<(), Box<dyn std::error::Error>> {
 
     let mut ci = new_logger.clone();
     let _ = ci.set_name(Some("engine"));
+    ci.set_level_filter(config.level("engine"));
     let ci = Arc::new(ci);
-    ci.set_flush_period(Some(Duration::from_secs(3)));
+    ci.set_flush_period(Some(config.flush_period));
     // Fork the default logger and configure it
 
     // Store the new logger in the global static variable
     ENGINE_LOGGER.set(ci).expect("Failed to set client logger");
+    install_panic_hook();
 
     Ok(())
 }
@@ -131,33 +837,32 @@ pub fn log_critical_internal(logger: &'static Logger, message: &str) {
 #[macro_export]
 macro_rules! rge_info {
     ($($args:tt)+) => {
-
-        $crate::log::log_info_internal($crate::log::get_client_logger(), &format!($($args)+))
+        $crate::log::spdlog::info!(logger: $crate::log::get_client_logger(), $($args)+)
     };
 }
 #[macro_export]
 macro_rules! rge_error {
     ($($args:tt)+) => {
-        $crate::log::log_error_internal($crate::log::get_client_logger(), &format!($($args)+))
+        $crate::log::spdlog::error!(logger: $crate::log::get_client_logger(), $($args)+)
     };
 }
 #[macro_export]
 macro_rules! rge_warn {
     ($($args:tt)+) => {
-        $crate::log::log_warn_internal($crate::log::get_client_logger(), &format!($($args)+))
+        $crate::log::spdlog::warn!(logger: $crate::log::get_client_logger(), $($args)+)
     };
 }
 
 #[macro_export]
 macro_rules! rge_trace {
     ($($args:tt)+) => {
-        $crate::log::log_trace_internal($crate::log::get_client_logger(), &format!($($args)+))
+        $crate::log::spdlog::trace!(logger: $crate::log::get_client_logger(), $($args)+)
     };
 }
 #[macro_export]
 macro_rules! rge_critical {
     ($($args:tt)+) => {
-        $crate::log::log_critical_internal($crate::log::get_client_logger(), &format!($($args)+))
+        $crate::log::spdlog::critical!(logger: $crate::log::get_client_logger(), $($args)+)
     };
 }
 //engine logging
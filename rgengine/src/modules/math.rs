@@ -0,0 +1,435 @@
+//glam does the heavy lifting, this adds the engine specific types on top of it
+//conventions: right handed, y up, column vectors (parent * child), angles in radians
+//and gl clip space, so x, y and z all go from -1 to 1 after the projection
+pub use glam::{EulerRot, IVec2, Mat3, Mat4, Quat, UVec2, Vec2, Vec3, Vec4};
+
+use std::ops::Mul;
+
+///translation, rotation and scale, applied to a point in the order scale -> rotate -> translate
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        translation: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        scale: Vec3::ONE,
+    };
+
+    pub fn new(translation: Vec3, rotation: Quat, scale: Vec3) -> Self {
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    pub fn from_translation(translation: Vec3) -> Self {
+        Self {
+            translation,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn from_rotation(rotation: Quat) -> Self {
+        Self {
+            rotation,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn from_scale(scale: Vec3) -> Self {
+        Self {
+            scale,
+            ..Self::IDENTITY
+        }
+    }
+
+    ///shear in the matrix is lost
+    pub fn from_matrix(matrix: &Mat4) -> Self {
+        let (scale, rotation, translation) = matrix.to_scale_rotation_translation();
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    pub fn with_translation(mut self, translation: Vec3) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: Quat) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Vec3) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+
+    ///`self * child`, the child is in the space of this transform.
+    ///non uniform scale combined with rotation can't be kept as trs, use the matrices for that
+    pub fn mul_transform(&self, child: &Transform) -> Transform {
+        Transform {
+            translation: self.transform_point(child.translation),
+            rotation: self.rotation * child.rotation,
+            scale: self.scale * child.scale,
+        }
+    }
+
+    ///exact when the scale is uniform, like mul_transform
+    pub fn inverse(&self) -> Transform {
+        let rotation = self.rotation.inverse();
+        let scale = self.scale.recip();
+        Transform {
+            translation: rotation * (-self.translation) * scale,
+            rotation,
+            scale,
+        }
+    }
+
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        self.rotation * (point * self.scale) + self.translation
+    }
+
+    ///like transform_point but ignores the translation
+    pub fn transform_vector(&self, vector: Vec3) -> Vec3 {
+        self.rotation * (vector * self.scale)
+    }
+
+    ///-z, the way cameras look
+    pub fn forward(&self) -> Vec3 {
+        self.rotation * Vec3::NEG_Z
+    }
+
+    pub fn right(&self) -> Vec3 {
+        self.rotation * Vec3::X
+    }
+
+    pub fn up(&self) -> Vec3 {
+        self.rotation * Vec3::Y
+    }
+
+    ///rotates so forward points at `target`
+    pub fn look_at(&mut self, target: Vec3, up: Vec3) {
+        let forward = (target - self.translation).normalize_or_zero();
+        let right = forward.cross(up).normalize_or_zero();
+        if forward == Vec3::ZERO || right == Vec3::ZERO {
+            return;
+        }
+        let up = right.cross(forward);
+        self.rotation = Quat::from_mat3(&Mat3::from_cols(right, up, -forward));
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mul for Transform {
+    type Output = Transform;
+
+    fn mul(self, rhs: Transform) -> Transform {
+        self.mul_transform(&rhs)
+    }
+}
+
+impl From<Transform> for Mat4 {
+    fn from(transform: Transform) -> Mat4 {
+        transform.matrix()
+    }
+}
+
+///2d axis aligned rectangle, also used for pixel regions like sprite sub rects
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Rect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            min: Vec2::new(x, y),
+            max: Vec2::new(x + width, y + height),
+        }
+    }
+
+    pub fn from_min_max(min: Vec2, max: Vec2) -> Self {
+        Self {
+            min: min.min(max),
+            max: min.max(max),
+        }
+    }
+
+    pub fn from_center_size(center: Vec2, size: Vec2) -> Self {
+        let half = size.abs() / 2.0;
+        Self {
+            min: center - half,
+            max: center + half,
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.max.x <= self.min.x || self.max.y <= self.min.y
+    }
+
+    ///min edges are inclusive and max edges exclusive so neighbouring rects don't share points
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.cmpge(self.min).all() && point.cmplt(self.max).all()
+    }
+
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.min.cmplt(other.max).all() && other.min.cmplt(self.max).all()
+    }
+
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let rect = Rect {
+            min: self.min.max(other.min),
+            max: self.max.min(other.max),
+        };
+        (!rect.is_empty()).then_some(rect)
+    }
+
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    pub fn expand(&self, amount: f32) -> Rect {
+        Rect {
+            min: self.min - Vec2::splat(amount),
+            max: self.max + Vec2::splat(amount),
+        }
+    }
+}
+
+///3d axis aligned bounding box
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn from_min_max(min: Vec3, max: Vec3) -> Self {
+        Self {
+            min: min.min(max),
+            max: min.max(max),
+        }
+    }
+
+    pub fn from_center_half_extents(center: Vec3, half_extents: Vec3) -> Self {
+        let half_extents = half_extents.abs();
+        Self {
+            min: center - half_extents,
+            max: center + half_extents,
+        }
+    }
+
+    ///`None` if there are no points
+    pub fn from_points<I: IntoIterator<Item = Vec3>>(points: I) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let mut aabb = Self {
+            min: first,
+            max: first,
+        };
+        for point in points {
+            aabb.expand_to_include(point);
+        }
+        Some(aabb)
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) / 2.0
+    }
+
+    pub fn size(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (a, b) = (self.min, self.max);
+        [
+            Vec3::new(a.x, a.y, a.z),
+            Vec3::new(b.x, a.y, a.z),
+            Vec3::new(a.x, b.y, a.z),
+            Vec3::new(b.x, b.y, a.z),
+            Vec3::new(a.x, a.y, b.z),
+            Vec3::new(b.x, a.y, b.z),
+            Vec3::new(a.x, b.y, b.z),
+            Vec3::new(b.x, b.y, b.z),
+        ]
+    }
+
+    pub fn contains(&self, point: Vec3) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    pub fn expand_to_include(&mut self, point: Vec3) {
+        self.min = self.min.min(point);
+        self.max = self.max.max(point);
+    }
+
+    ///box around the transformed corners, so it can only grow
+    pub fn transformed(&self, matrix: &Mat4) -> Aabb {
+        let center = matrix.transform_point3(self.center());
+        let half = self.half_extents();
+        let axes = Mat3::from_mat4(*matrix);
+        let extent =
+            axes.x_axis.abs() * half.x + axes.y_axis.abs() * half.y + axes.z_axis.abs() * half.z;
+        Aabb {
+            min: center - extent,
+            max: center + extent,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Ray {
+    pub origin: Vec3,
+    ///always normalized so the hit distances are in world units
+    pub direction: Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    ///ray through a pixel, `point` is in window coordinates with y going down like the mouse events
+    pub fn from_screen(point: Vec2, viewport_size: Vec2, view_projection: &Mat4) -> Self {
+        let ndc = Vec2::new(
+            point.x / viewport_size.x * 2.0 - 1.0,
+            1.0 - point.y / viewport_size.y * 2.0,
+        );
+        let inverse = view_projection.inverse();
+        let near = inverse.project_point3(ndc.extend(-1.0));
+        let far = inverse.project_point3(ndc.extend(1.0));
+        Self::new(near, far - near)
+    }
+
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    ///distance to where the ray enters the box, 0 if it starts inside. a ray running along a
+    ///face touches the box
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let (mut near, mut far) = (0.0f32, f32::INFINITY);
+        for axis in 0..3 {
+            let (origin, direction) = (self.origin[axis], self.direction[axis]);
+            let (min, max) = (aabb.min[axis], aabb.max[axis]);
+            //parallel to the slab, 0 * inf would be nan on its planes
+            if direction == 0.0 {
+                if origin < min || origin > max {
+                    return None;
+                }
+                continue;
+            }
+            let t1 = (min - origin) / direction;
+            let t2 = (max - origin) / direction;
+            near = near.max(t1.min(t2));
+            far = far.min(t1.max(t2));
+        }
+        (far >= near).then_some(near)
+    }
+
+    ///only hits in front of the origin, either side of the plane
+    pub fn intersect_plane(&self, plane_point: Vec3, plane_normal: Vec3) -> Option<f32> {
+        let denom = plane_normal.dot(self.direction);
+        if denom.abs() < f32::EPSILON {
+            return None;
+        }
+        let t = (plane_point - self.origin).dot(plane_normal) / denom;
+        (t >= 0.0).then_some(t)
+    }
+
+    pub fn intersect_sphere(&self, center: Vec3, radius: f32) -> Option<f32> {
+        let to_origin = self.origin - center;
+        let b = to_origin.dot(self.direction);
+        let c = to_origin.length_squared() - radius * radius;
+        let discriminant = b * b - c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        let near = -b - root;
+        let far = -b + root;
+        if far < 0.0 { None } else { Some(near.max(0.0)) }
+    }
+
+    ///moller-trumbore, hits both faces
+    pub fn intersect_triangle(&self, a: Vec3, b: Vec3, c: Vec3) -> Option<f32> {
+        let edge1 = b - a;
+        let edge2 = c - a;
+        let p = self.direction.cross(edge2);
+        let det = edge1.dot(p);
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+        let s = self.origin - a;
+        let u = s.dot(p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = s.cross(edge1);
+        let v = self.direction.dot(q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = edge2.dot(q) * inv_det;
+        (t >= 0.0).then_some(t)
+    }
+}
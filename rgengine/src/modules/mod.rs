@@ -1,9 +1,11 @@
 pub mod entry_point;
 pub mod log;
 
+pub mod console;
 pub mod engine;
 pub mod events;
 pub mod imgui;
 pub mod layers;
+pub mod math;
 pub mod renderer;
 pub mod window;
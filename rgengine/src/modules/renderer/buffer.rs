@@ -0,0 +1,447 @@
+use std::{cell::Cell, ffi::c_void};
+
+use gl::types::{GLenum, GLsizeiptr};
+
+use super::stats;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ShaderDataType {
+    Float,
+    Float2,
+    Float3,
+    Float4,
+    Int,
+    Mat4,
+}
+
+impl ShaderDataType {
+    pub fn size(&self) -> u32 {
+        self.component_count() * 4
+    }
+
+    pub fn component_count(&self) -> u32 {
+        match self {
+            ShaderDataType::Float | ShaderDataType::Int => 1,
+            ShaderDataType::Float2 => 2,
+            ShaderDataType::Float3 => 3,
+            ShaderDataType::Float4 => 4,
+            ShaderDataType::Mat4 => 16,
+        }
+    }
+
+    fn gl_type(&self) -> GLenum {
+        match self {
+            ShaderDataType::Int => gl::INT,
+            _ => gl::FLOAT,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BufferElement {
+    pub name: String,
+    pub data_type: ShaderDataType,
+    pub normalized: bool,
+    offset: u32,
+}
+
+impl BufferElement {
+    pub fn new(data_type: ShaderDataType, name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            data_type,
+            normalized: false,
+            offset: 0,
+        }
+    }
+}
+
+///describes how the vertices in a buffer are laid out, offsets and stride are worked out from the order
+#[derive(Clone, Debug, Default)]
+pub struct BufferLayout {
+    elements: Vec<BufferElement>,
+    stride: u32,
+    instanced: bool,
+}
+
+impl BufferLayout {
+    pub fn new(mut elements: Vec<BufferElement>) -> Self {
+        let mut offset = 0;
+        for element in &mut elements {
+            element.offset = offset;
+            offset += element.data_type.size();
+        }
+        Self {
+            elements,
+            stride: offset,
+            instanced: false,
+        }
+    }
+
+    ///attributes that advance once per instance instead of once per vertex
+    pub fn instanced(elements: Vec<BufferElement>) -> Self {
+        Self {
+            instanced: true,
+            ..Self::new(elements)
+        }
+    }
+
+    pub fn elements(&self) -> &[BufferElement] {
+        &self.elements
+    }
+
+    pub fn stride(&self) -> u32 {
+        self.stride
+    }
+
+    pub fn is_instanced(&self) -> bool {
+        self.instanced
+    }
+}
+
+pub struct VertexBuffer {
+    id: u32,
+    layout: BufferLayout,
+    size: Cell<usize>,
+}
+
+impl VertexBuffer {
+    ///static buffer filled once with `data`
+    pub fn new<T: Copy>(data: &[T], layout: BufferLayout) -> Self {
+        let size = std::mem::size_of_val(data);
+        let id = Self::create(size, data.as_ptr() as *const c_void, gl::STATIC_DRAW);
+        Self {
+            id,
+            layout,
+            size: Cell::new(size),
+        }
+    }
+
+    ///dynamic buffer of `size` bytes, filled later with set_data
+    pub fn with_capacity(size: usize, layout: BufferLayout) -> Self {
+        let id = Self::create(size, std::ptr::null(), gl::DYNAMIC_DRAW);
+        Self {
+            id,
+            layout,
+            size: Cell::new(size),
+        }
+    }
+
+    fn create(size: usize, data: *const c_void, usage: GLenum) -> u32 {
+        let mut id = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut id);
+            gl::BindBuffer(gl::ARRAY_BUFFER, id);
+            gl::BufferData(gl::ARRAY_BUFFER, size as GLsizeiptr, data, usage);
+        }
+        id
+    }
+
+    ///grows the buffer when `data` doesn't fit, the vertex arrays using it keep working
+    pub fn set_data<T: Copy>(&self, data: &[T]) {
+        let size = std::mem::size_of_val(data);
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.id);
+            if size > self.size.get() {
+                gl::BufferData(
+                    gl::ARRAY_BUFFER,
+                    size as GLsizeiptr,
+                    data.as_ptr() as *const c_void,
+                    gl::DYNAMIC_DRAW,
+                );
+                self.size.set(size);
+            } else {
+                gl::BufferSubData(
+                    gl::ARRAY_BUFFER,
+                    0,
+                    size as GLsizeiptr,
+                    data.as_ptr() as *const c_void,
+                );
+            }
+        }
+    }
+
+    pub fn bind(&self) {
+        unsafe { gl::BindBuffer(gl::ARRAY_BUFFER, self.id) }
+    }
+
+    ///in bytes
+    pub fn size(&self) -> usize {
+        self.size.get()
+    }
+
+    pub fn layout(&self) -> &BufferLayout {
+        &self.layout
+    }
+}
+
+impl Drop for VertexBuffer {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteBuffers(1, &self.id) }
+    }
+}
+
+pub struct IndexBuffer {
+    id: u32,
+    count: Cell<u32>,
+    size: Cell<usize>,
+}
+
+impl IndexBuffer {
+    ///static buffer filled once with `indices`
+    pub fn new(indices: &[u32]) -> Self {
+        let mut id = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut id);
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, id);
+            gl::BufferData(
+                gl::ELEMENT_ARRAY_BUFFER,
+                std::mem::size_of_val(indices) as GLsizeiptr,
+                indices.as_ptr() as *const c_void,
+                gl::STATIC_DRAW,
+            );
+        }
+        Self {
+            id,
+            count: Cell::new(indices.len() as u32),
+            size: Cell::new(std::mem::size_of_val(indices)),
+        }
+    }
+
+    ///dynamic buffer with room for `count` indices, empty until set_data
+    pub fn with_capacity(count: usize) -> Self {
+        let size = count * std::mem::size_of::<u32>();
+        let mut id = 0;
+        unsafe {
+            gl::CreateBuffers(1, &mut id);
+            gl::NamedBufferData(id, size as GLsizeiptr, std::ptr::null(), gl::DYNAMIC_DRAW);
+        }
+        Self {
+            id,
+            count: Cell::new(0),
+            size: Cell::new(size),
+        }
+    }
+
+    ///replaces the indices, growing the buffer when they don't fit. goes through dsa so the
+    ///element binding of whatever vertex array is bound stays as it is
+    pub fn set_data(&self, indices: &[u32]) {
+        let size = std::mem::size_of_val(indices);
+        let data = indices.as_ptr() as *const c_void;
+        unsafe {
+            if size > self.size.get() {
+                gl::NamedBufferData(self.id, size as GLsizeiptr, data, gl::DYNAMIC_DRAW);
+                self.size.set(size);
+            } else {
+                gl::NamedBufferSubData(self.id, 0, size as GLsizeiptr, data);
+            }
+        }
+        self.count.set(indices.len() as u32);
+    }
+
+    pub fn bind(&self) {
+        unsafe { gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.id) }
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count.get()
+    }
+}
+
+impl Drop for IndexBuffer {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteBuffers(1, &self.id) }
+    }
+}
+
+///the layout `glMultiDrawElementsIndirect` reads, one draw of `count` indices starting at
+///`first_index`, repeated `instance_count` times
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct DrawElementsIndirectCommand {
+    pub count: u32,
+    pub instance_count: u32,
+    pub first_index: u32,
+    ///added to every index before fetching the vertex
+    pub base_vertex: i32,
+    ///where the per instance attributes start
+    pub base_instance: u32,
+}
+
+///draw commands in gpu memory, so many meshes sharing a vertex array go out in one call
+pub struct IndirectBuffer {
+    id: u32,
+    count: Cell<u32>,
+    capacity: Cell<u32>,
+    triangles: Cell<u64>,
+}
+
+impl IndirectBuffer {
+    pub fn new(commands: &[DrawElementsIndirectCommand]) -> Self {
+        let mut id = 0;
+        unsafe { gl::GenBuffers(1, &mut id) };
+        let buffer = Self {
+            id,
+            count: Cell::new(0),
+            capacity: Cell::new(0),
+            triangles: Cell::new(0),
+        };
+        buffer.set_commands(commands);
+        buffer
+    }
+
+    ///grows the buffer when the commands don't fit
+    pub fn set_commands(&self, commands: &[DrawElementsIndirectCommand]) {
+        let size = std::mem::size_of_val(commands) as GLsizeiptr;
+        let data = commands.as_ptr() as *const c_void;
+        unsafe {
+            gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, self.id);
+            if commands.len() as u32 > self.capacity.get() {
+                gl::BufferData(gl::DRAW_INDIRECT_BUFFER, size, data, gl::DYNAMIC_DRAW);
+                self.capacity.set(commands.len() as u32);
+            } else {
+                gl::BufferSubData(gl::DRAW_INDIRECT_BUFFER, 0, size, data);
+            }
+        }
+        self.count.set(commands.len() as u32);
+        let triangles = commands
+            .iter()
+            .map(|c| c.count as u64 / 3 * c.instance_count as u64)
+            .sum();
+        self.triangles.set(triangles);
+    }
+
+    pub fn bind(&self) {
+        unsafe { gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, self.id) }
+    }
+
+    ///number of commands
+    pub fn count(&self) -> u32 {
+        self.count.get()
+    }
+
+    ///of every command and instance together
+    pub fn triangle_count(&self) -> u64 {
+        self.triangles.get()
+    }
+}
+
+impl Drop for IndirectBuffer {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteBuffers(1, &self.id) }
+    }
+}
+
+///owns its vertex buffers and index buffer so they live as long as the array
+pub struct VertexArray {
+    id: u32,
+    vertex_buffers: Vec<VertexBuffer>,
+    index_buffer: Option<IndexBuffer>,
+    attrib_index: u32,
+}
+
+impl VertexArray {
+    pub fn new() -> Self {
+        let mut id = 0;
+        unsafe { gl::GenVertexArrays(1, &mut id) };
+        Self {
+            id,
+            vertex_buffers: Vec::new(),
+            index_buffer: None,
+            attrib_index: 0,
+        }
+    }
+
+    pub fn bind(&self) {
+        unsafe { gl::BindVertexArray(self.id) }
+        stats::record_state_change();
+    }
+
+    pub fn unbind(&self) {
+        unsafe { gl::BindVertexArray(0) }
+    }
+
+    pub fn add_vertex_buffer(&mut self, buffer: VertexBuffer) {
+        self.bind();
+        buffer.bind();
+        let stride = buffer.layout().stride() as i32;
+        let first_attrib = self.attrib_index;
+        for element in buffer.layout().elements() {
+            let offset = element.offset as usize;
+            unsafe {
+                match element.data_type {
+                    ShaderDataType::Int => {
+                        gl::EnableVertexAttribArray(self.attrib_index);
+                        gl::VertexAttribIPointer(
+                            self.attrib_index,
+                            1,
+                            element.data_type.gl_type(),
+                            stride,
+                            offset as *const c_void,
+                        );
+                        self.attrib_index += 1;
+                    }
+                    //a mat4 takes up four attribute slots, one per column
+                    ShaderDataType::Mat4 => {
+                        for column in 0..4 {
+                            gl::EnableVertexAttribArray(self.attrib_index);
+                            gl::VertexAttribPointer(
+                                self.attrib_index,
+                                4,
+                                gl::FLOAT,
+                                element.normalized as u8,
+                                stride,
+                                (offset + column * 16) as *const c_void,
+                            );
+                            self.attrib_index += 1;
+                        }
+                    }
+                    _ => {
+                        gl::EnableVertexAttribArray(self.attrib_index);
+                        gl::VertexAttribPointer(
+                            self.attrib_index,
+                            element.data_type.component_count() as i32,
+                            element.data_type.gl_type(),
+                            element.normalized as u8,
+                            stride,
+                            offset as *const c_void,
+                        );
+                        self.attrib_index += 1;
+                    }
+                }
+            }
+        }
+        if buffer.layout().is_instanced() {
+            for index in first_attrib..self.attrib_index {
+                unsafe { gl::VertexAttribDivisor(index, 1) }
+            }
+        }
+        self.vertex_buffers.push(buffer);
+    }
+
+    pub fn set_index_buffer(&mut self, buffer: IndexBuffer) {
+        self.bind();
+        buffer.bind();
+        self.index_buffer = Some(buffer);
+    }
+
+    pub fn vertex_buffers(&self) -> &[VertexBuffer] {
+        &self.vertex_buffers
+    }
+
+    pub fn index_buffer(&self) -> Option<&IndexBuffer> {
+        self.index_buffer.as_ref()
+    }
+}
+
+impl Default for VertexArray {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for VertexArray {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteVertexArrays(1, &self.id) }
+    }
+}
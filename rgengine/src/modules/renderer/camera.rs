@@ -0,0 +1,238 @@
+use crate::math::{Mat4, Quat, Vec3};
+
+use crate::events::RGEvent;
+
+pub struct OrthographicCamera {
+    projection: Mat4,
+    view: Mat4,
+    view_projection: Mat4,
+    position: Vec3,
+    rotation: f32,
+    //top - bottom, kept when the viewport is resized so only the width changes
+    height: f32,
+}
+
+impl OrthographicCamera {
+    pub fn new(left: f32, right: f32, bottom: f32, top: f32) -> Self {
+        let projection = Mat4::orthographic_rh_gl(left, right, bottom, top, -1.0, 1.0);
+        Self {
+            projection,
+            view: Mat4::IDENTITY,
+            view_projection: projection,
+            position: Vec3::ZERO,
+            rotation: 0.0,
+            height: top - bottom,
+        }
+    }
+
+    pub fn set_projection(&mut self, left: f32, right: f32, bottom: f32, top: f32) {
+        self.projection = Mat4::orthographic_rh_gl(left, right, bottom, top, -1.0, 1.0);
+        self.height = top - bottom;
+        self.view_projection = self.projection * self.view;
+    }
+
+    ///keeps the vertical extent and centers the projection on the new aspect ratio
+    pub fn on_resize(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        let half_height = self.height / 2.0;
+        let half_width = half_height * width as f32 / height as f32;
+        self.set_projection(-half_width, half_width, -half_height, half_height);
+    }
+
+    pub fn on_event(&mut self, event: &RGEvent) {
+        if let RGEvent::WindowResize(e) = event {
+            self.on_resize(e.width as u32, e.height as u32);
+        }
+    }
+
+    pub fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: Vec3) {
+        self.position = position;
+        self.recalculate_view();
+    }
+
+    ///rotation around the z axis in radians
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    pub fn set_rotation(&mut self, rotation: f32) {
+        self.rotation = rotation;
+        self.recalculate_view();
+    }
+
+    pub fn projection(&self) -> &Mat4 {
+        &self.projection
+    }
+
+    pub fn view(&self) -> &Mat4 {
+        &self.view
+    }
+
+    pub fn view_projection(&self) -> &Mat4 {
+        &self.view_projection
+    }
+
+    fn recalculate_view(&mut self) {
+        let transform =
+            Mat4::from_rotation_translation(Quat::from_rotation_z(self.rotation), self.position);
+        self.view = transform.inverse();
+        self.view_projection = self.projection * self.view;
+    }
+}
+
+///right handed, y up, looks down -z when yaw and pitch are zero
+pub struct PerspectiveCamera {
+    projection: Mat4,
+    view: Mat4,
+    view_projection: Mat4,
+    position: Vec3,
+    yaw: f32,
+    pitch: f32,
+    fov_y: f32,
+    aspect_ratio: f32,
+    near: f32,
+    far: f32,
+}
+
+impl PerspectiveCamera {
+    ///`fov_y` is in radians
+    pub fn new(fov_y: f32, aspect_ratio: f32, near: f32, far: f32) -> Self {
+        let mut camera = Self {
+            projection: Mat4::IDENTITY,
+            view: Mat4::IDENTITY,
+            view_projection: Mat4::IDENTITY,
+            position: Vec3::ZERO,
+            yaw: 0.0,
+            pitch: 0.0,
+            fov_y,
+            aspect_ratio,
+            near,
+            far,
+        };
+        camera.recalculate_projection();
+        camera
+    }
+
+    pub fn on_resize(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        self.aspect_ratio = width as f32 / height as f32;
+        self.recalculate_projection();
+    }
+
+    pub fn on_event(&mut self, event: &RGEvent) {
+        if let RGEvent::WindowResize(e) = event {
+            self.on_resize(e.width as u32, e.height as u32);
+        }
+    }
+
+    pub fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: Vec3) {
+        self.position = position;
+        self.recalculate_view();
+    }
+
+    ///rotation around the y axis in radians
+    pub fn yaw(&self) -> f32 {
+        self.yaw
+    }
+
+    ///rotation around the camera's x axis in radians, clamped to just short of straight up or down
+    pub fn pitch(&self) -> f32 {
+        self.pitch
+    }
+
+    pub fn set_rotation(&mut self, yaw: f32, pitch: f32) {
+        let limit = std::f32::consts::FRAC_PI_2 - 0.001;
+        self.yaw = yaw;
+        self.pitch = pitch.clamp(-limit, limit);
+        self.recalculate_view();
+    }
+
+    pub fn look_at(&mut self, target: Vec3) {
+        let direction = (target - self.position).normalize_or_zero();
+        if direction == Vec3::ZERO {
+            return;
+        }
+        self.set_rotation(
+            (-direction.x).atan2(-direction.z),
+            direction.y.clamp(-1.0, 1.0).asin(),
+        );
+    }
+
+    pub fn orientation(&self) -> Quat {
+        Quat::from_rotation_y(self.yaw) * Quat::from_rotation_x(self.pitch)
+    }
+
+    pub fn forward(&self) -> Vec3 {
+        self.orientation() * Vec3::NEG_Z
+    }
+
+    pub fn right(&self) -> Vec3 {
+        self.orientation() * Vec3::X
+    }
+
+    pub fn up(&self) -> Vec3 {
+        self.orientation() * Vec3::Y
+    }
+
+    pub fn fov_y(&self) -> f32 {
+        self.fov_y
+    }
+
+    pub fn set_fov_y(&mut self, fov_y: f32) {
+        self.fov_y = fov_y;
+        self.recalculate_projection();
+    }
+
+    pub fn aspect_ratio(&self) -> f32 {
+        self.aspect_ratio
+    }
+
+    pub fn near(&self) -> f32 {
+        self.near
+    }
+
+    pub fn far(&self) -> f32 {
+        self.far
+    }
+
+    pub fn set_clip_planes(&mut self, near: f32, far: f32) {
+        self.near = near;
+        self.far = far;
+        self.recalculate_projection();
+    }
+
+    pub fn projection(&self) -> &Mat4 {
+        &self.projection
+    }
+
+    pub fn view(&self) -> &Mat4 {
+        &self.view
+    }
+
+    pub fn view_projection(&self) -> &Mat4 {
+        &self.view_projection
+    }
+
+    fn recalculate_projection(&mut self) {
+        self.projection =
+            Mat4::perspective_rh_gl(self.fov_y, self.aspect_ratio, self.near, self.far);
+        self.view_projection = self.projection * self.view;
+    }
+
+    fn recalculate_view(&mut self) {
+        self.view = Mat4::from_rotation_translation(self.orientation(), self.position).inverse();
+        self.view_projection = self.projection * self.view;
+    }
+}
@@ -0,0 +1,350 @@
+use std::collections::HashSet;
+
+use crate::math::{Vec2, Vec3};
+use glfw::{Key, MouseButton};
+
+use crate::{events::RGEvent, layers::Layer};
+
+use super::camera::{OrthographicCamera, PerspectiveCamera};
+
+//the controllers are layers so they can be pushed on their own, but most of the time
+//a game layer owns one and forwards on_update/on_event to it so it can use the camera
+
+///keys and buttons currently held, built up from the engine events
+#[derive(Default)]
+struct InputState {
+    keys: HashSet<Key>,
+    buttons: HashSet<MouseButton>,
+    cursor: Option<Vec2>,
+}
+
+impl InputState {
+    ///returns how far the cursor moved if the event was a mouse move
+    fn on_event(&mut self, event: &RGEvent) -> Option<Vec2> {
+        match event {
+            RGEvent::KeyPressed(e) => {
+                self.keys.insert(e.key);
+            }
+            RGEvent::KeyReleased(e) => {
+                self.keys.remove(&e.key);
+            }
+            RGEvent::MouseButtonPressed(e) => {
+                self.buttons.insert(e.button);
+            }
+            RGEvent::MouseButtonReleased(e) => {
+                self.buttons.remove(&e.button);
+            }
+            RGEvent::WindowLostFocus(_) => {
+                self.keys.clear();
+                self.buttons.clear();
+            }
+            RGEvent::MouseMoved(e) => {
+                let cursor = e.position();
+                let delta = self.cursor.map(|last| cursor - last);
+                self.cursor = Some(cursor);
+                return delta;
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn key(&self, key: Key) -> bool {
+        self.keys.contains(&key)
+    }
+
+    fn button(&self, button: MouseButton) -> bool {
+        self.buttons.contains(&button)
+    }
+
+    ///-1, 0 or 1 depending on which of the two keys are held
+    fn axis(&self, negative: Key, positive: Key) -> f32 {
+        self.key(positive) as i32 as f32 - self.key(negative) as i32 as f32
+    }
+}
+
+///wasd to pan, q/e to rotate if enabled, scroll to zoom and right mouse drag to pan
+pub struct OrthographicCameraController {
+    id: usize,
+    camera: OrthographicCamera,
+    input: InputState,
+    aspect_ratio: f32,
+    viewport_height: f32,
+    zoom_level: f32,
+    position: Vec3,
+    rotation: f32,
+    rotation_enabled: bool,
+    pub translation_speed: f32,
+    pub rotation_speed: f32,
+    pub zoom_speed: f32,
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+}
+
+impl OrthographicCameraController {
+    pub fn new(id: usize, width: u32, height: u32, rotation_enabled: bool) -> Self {
+        let aspect_ratio = width as f32 / height.max(1) as f32;
+        let zoom_level = 1.0;
+        Self {
+            id,
+            camera: OrthographicCamera::new(
+                -aspect_ratio * zoom_level,
+                aspect_ratio * zoom_level,
+                -zoom_level,
+                zoom_level,
+            ),
+            input: InputState::default(),
+            aspect_ratio,
+            viewport_height: height.max(1) as f32,
+            zoom_level,
+            position: Vec3::ZERO,
+            rotation: 0.0,
+            rotation_enabled,
+            translation_speed: 1.0,
+            rotation_speed: std::f32::consts::PI,
+            zoom_speed: 0.25,
+            min_zoom: 0.1,
+            max_zoom: 100.0,
+        }
+    }
+
+    pub fn camera(&self) -> &OrthographicCamera {
+        &self.camera
+    }
+
+    pub fn zoom_level(&self) -> f32 {
+        self.zoom_level
+    }
+
+    pub fn set_zoom_level(&mut self, zoom_level: f32) {
+        self.zoom_level = zoom_level.clamp(self.min_zoom, self.max_zoom);
+        self.update_projection();
+    }
+
+    pub fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: Vec3) {
+        self.position = position;
+        self.camera.set_position(position);
+    }
+
+    pub fn on_resize(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        self.aspect_ratio = width as f32 / height as f32;
+        self.viewport_height = height as f32;
+        self.update_projection();
+    }
+
+    fn update_projection(&mut self) {
+        let (zoom, aspect) = (self.zoom_level, self.aspect_ratio);
+        self.camera
+            .set_projection(-aspect * zoom, aspect * zoom, -zoom, zoom);
+    }
+
+    ///moves the camera along its own axes so panning follows the rotation
+    fn translate(&mut self, local: Vec2) {
+        let (sin, cos) = self.rotation.sin_cos();
+        self.position.x += local.x * cos - local.y * sin;
+        self.position.y += local.x * sin + local.y * cos;
+    }
+}
+
+impl Layer for OrthographicCameraController {
+    fn on_update(&mut self, dt: f32) {
+        //scale with zoom so panning feels the same at any zoom level
+        let speed = self.translation_speed * self.zoom_level * dt;
+        let direction = Vec2::new(
+            self.input.axis(Key::A, Key::D),
+            self.input.axis(Key::S, Key::W),
+        );
+        self.translate(direction * speed);
+
+        if self.rotation_enabled {
+            self.rotation += self.input.axis(Key::E, Key::Q) * self.rotation_speed * dt;
+            self.camera.set_rotation(self.rotation);
+        }
+        self.camera.set_position(self.position);
+    }
+
+    fn on_event(&mut self, event: &RGEvent) {
+        let delta = self.input.on_event(event);
+        match event {
+            RGEvent::MouseScrolled(e) => {
+                self.set_zoom_level(self.zoom_level - e.y_offset as f32 * self.zoom_speed);
+            }
+            RGEvent::WindowResize(e) => self.on_resize(e.width as u32, e.height as u32),
+            RGEvent::MouseMoved(_) if self.input.button(MouseButton::Button2) => {
+                if let Some(delta) = delta {
+                    //the view is 2 * zoom world units tall, screen y grows downwards
+                    let units_per_pixel = 2.0 * self.zoom_level / self.viewport_height;
+                    self.translate(Vec2::new(-delta.x, delta.y) * units_per_pixel);
+                    self.camera.set_position(self.position);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn get_id(&self) -> usize {
+        self.id
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CameraMode {
+    ///wasd + space/left shift to move, hold right mouse to look around
+    Fps,
+    ///left drag orbits the target, middle drag pans it, scroll changes the distance
+    Orbit,
+}
+
+pub struct PerspectiveCameraController {
+    id: usize,
+    camera: PerspectiveCamera,
+    input: InputState,
+    mode: CameraMode,
+    target: Vec3,
+    distance: f32,
+    yaw: f32,
+    pitch: f32,
+    pub move_speed: f32,
+    ///radians per pixel of mouse movement
+    pub look_sensitivity: f32,
+    pub zoom_speed: f32,
+}
+
+impl PerspectiveCameraController {
+    pub fn new(id: usize, width: u32, height: u32, mode: CameraMode) -> Self {
+        let aspect_ratio = width as f32 / height.max(1) as f32;
+        let mut controller = Self {
+            id,
+            camera: PerspectiveCamera::new(45f32.to_radians(), aspect_ratio, 0.1, 1000.0),
+            input: InputState::default(),
+            mode,
+            target: Vec3::ZERO,
+            distance: 5.0,
+            yaw: 0.0,
+            pitch: 0.0,
+            move_speed: 5.0,
+            look_sensitivity: 0.003,
+            zoom_speed: 0.5,
+        };
+        controller
+            .camera
+            .set_position(Vec3::new(0.0, 0.0, controller.distance));
+        controller.sync_camera();
+        controller
+    }
+
+    pub fn camera(&self) -> &PerspectiveCamera {
+        &self.camera
+    }
+
+    pub fn camera_mut(&mut self) -> &mut PerspectiveCamera {
+        &mut self.camera
+    }
+
+    pub fn mode(&self) -> CameraMode {
+        self.mode
+    }
+
+    ///switching keeps the camera where it is, orbit mode picks a target in front of it
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        if mode == CameraMode::Orbit && self.mode == CameraMode::Fps {
+            self.target = self.camera.position() + self.camera.forward() * self.distance;
+        }
+        self.yaw = self.camera.yaw();
+        self.pitch = self.camera.pitch();
+        self.mode = mode;
+        self.sync_camera();
+    }
+
+    pub fn target(&self) -> Vec3 {
+        self.target
+    }
+
+    pub fn set_target(&mut self, target: Vec3) {
+        self.target = target;
+        self.sync_camera();
+    }
+
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    pub fn set_distance(&mut self, distance: f32) {
+        self.distance = distance.max(0.01);
+        self.sync_camera();
+    }
+
+    fn sync_camera(&mut self) {
+        self.camera.set_rotation(self.yaw, self.pitch);
+        //the camera clamps the pitch, keep ours the same so it doesn't wind up
+        self.pitch = self.camera.pitch();
+        if self.mode == CameraMode::Orbit {
+            let position = self.target - self.camera.forward() * self.distance;
+            self.camera.set_position(position);
+        }
+    }
+}
+
+impl Layer for PerspectiveCameraController {
+    fn on_update(&mut self, dt: f32) {
+        if self.mode != CameraMode::Fps {
+            return;
+        }
+        let input = &self.input;
+        let direction = self.camera.forward() * input.axis(Key::S, Key::W)
+            + self.camera.right() * input.axis(Key::A, Key::D)
+            + Vec3::Y * input.axis(Key::LeftShift, Key::Space);
+        if direction != Vec3::ZERO {
+            let position = self.camera.position() + direction.normalize() * self.move_speed * dt;
+            self.camera.set_position(position);
+        }
+    }
+
+    fn on_event(&mut self, event: &RGEvent) {
+        let delta = self.input.on_event(event);
+        match event {
+            RGEvent::WindowResize(e) => self.camera.on_resize(e.width as u32, e.height as u32),
+            RGEvent::MouseScrolled(e) => match self.mode {
+                CameraMode::Orbit => self.set_distance(
+                    self.distance * (1.0 - e.y_offset as f32 * 0.1 * self.zoom_speed),
+                ),
+                CameraMode::Fps => {
+                    let fov = self.camera.fov_y()
+                        - e.y_offset as f32 * self.zoom_speed.to_radians() * 4.0;
+                    self.camera
+                        .set_fov_y(fov.clamp(10f32.to_radians(), 120f32.to_radians()));
+                }
+            },
+            RGEvent::MouseMoved(_) => {
+                let Some(delta) = delta else { return };
+                let look = match self.mode {
+                    CameraMode::Fps => self.input.button(MouseButton::Button2),
+                    CameraMode::Orbit => self.input.button(MouseButton::Button1),
+                };
+                if look {
+                    self.yaw -= delta.x * self.look_sensitivity;
+                    self.pitch -= delta.y * self.look_sensitivity;
+                    self.sync_camera();
+                } else if self.mode == CameraMode::Orbit && self.input.button(MouseButton::Button3)
+                {
+                    let scale = self.distance * self.look_sensitivity * 0.5;
+                    self.target +=
+                        (self.camera.up() * delta.y - self.camera.right() * delta.x) * scale;
+                    self.sync_camera();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn get_id(&self) -> usize {
+        self.id
+    }
+}
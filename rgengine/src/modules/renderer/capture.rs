@@ -0,0 +1,254 @@
+use std::{
+    ffi::c_void,
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Sender},
+    thread::{self, JoinHandle},
+};
+
+use glfw::Key;
+
+use crate::{
+    events::RGEvent,
+    log::{rge_engine_error, rge_engine_info},
+};
+
+use super::framebuffer::Framebuffer;
+
+///rgba8 pixels with the top row first, the way image files want them
+#[derive(Clone, Debug)]
+pub struct CapturedFrame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl CapturedFrame {
+    ///`pixels` rows go bottom to top like glReadPixels gives them
+    pub fn from_gl_rows(width: u32, height: u32, mut pixels: Vec<u8>) -> Self {
+        flip_rows(&mut pixels, width, height);
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    ///reads the back buffer of the window, call before swap_buffers
+    pub fn from_window(width: u32, height: u32) -> Self {
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+            gl::ReadBuffer(gl::BACK);
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::ReadPixels(
+                0,
+                0,
+                width as i32,
+                height as i32,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_mut_ptr() as *mut c_void,
+            );
+        }
+        Self::from_gl_rows(width, height, pixels)
+    }
+
+    pub fn from_framebuffer(framebuffer: &Framebuffer, attachment: usize) -> Self {
+        Self::from_gl_rows(
+            framebuffer.width(),
+            framebuffer.height(),
+            framebuffer.read_pixels(attachment),
+        )
+    }
+
+    ///encodes on the calling thread, `FrameCapture` does this off the main thread
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> image::ImageResult<()> {
+        image::save_buffer(
+            path,
+            &self.pixels,
+            self.width,
+            self.height,
+            image::ColorType::Rgba8,
+        )
+    }
+}
+
+pub fn flip_rows(pixels: &mut [u8], width: u32, height: u32) {
+    let row = (width * 4) as usize;
+    let height = height as usize;
+    for y in 0..height / 2 {
+        let (top, bottom) = pixels.split_at_mut((height - 1 - y) * row);
+        top[y * row..(y + 1) * row].swap_with_slice(&mut bottom[..row]);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CaptureSource {
+    ///everything on screen including the overlays
+    Window,
+    ///the engine's render target if it has one, the window otherwise
+    RenderTarget,
+}
+
+struct Sequence {
+    directory: PathBuf,
+    next_frame: u32,
+    remaining: u32,
+}
+
+struct EncodeJob {
+    path: PathBuf,
+    frame: CapturedFrame,
+}
+
+///screenshots and frame sequences, the pngs are written on a worker thread so
+///capturing doesn't stall the frame
+pub struct FrameCapture {
+    output_dir: PathBuf,
+    source: CaptureSource,
+    screenshot_key: Option<Key>,
+    screenshot_requested: bool,
+    sequence: Option<Sequence>,
+    sender: Option<Sender<EncodeJob>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl FrameCapture {
+    pub fn new<P: Into<PathBuf>>(output_dir: P) -> Self {
+        let (sender, receiver) = mpsc::channel::<EncodeJob>();
+        let worker = thread::Builder::new()
+            .name("frame capture".to_string())
+            .spawn(move || {
+                for job in receiver {
+                    if let Some(parent) = job.path.parent() {
+                        let _ = fs::create_dir_all(parent);
+                    }
+                    if let Err(e) = job.frame.save_png(&job.path) {
+                        rge_engine_error!("failed to save {}: {}", job.path.display(), e);
+                    }
+                }
+            })
+            .expect("failed to start the frame capture thread");
+
+        Self {
+            output_dir: output_dir.into(),
+            source: CaptureSource::Window,
+            screenshot_key: Some(Key::F12),
+            screenshot_requested: false,
+            sequence: None,
+            sender: Some(sender),
+            worker: Some(worker),
+        }
+    }
+
+    pub fn output_dir(&self) -> &Path {
+        &self.output_dir
+    }
+
+    pub fn set_output_dir<P: Into<PathBuf>>(&mut self, output_dir: P) {
+        self.output_dir = output_dir.into();
+    }
+
+    pub fn source(&self) -> CaptureSource {
+        self.source
+    }
+
+    pub fn set_source(&mut self, source: CaptureSource) {
+        self.source = source;
+    }
+
+    ///`None` turns the key binding off, F12 by default
+    pub fn set_screenshot_key(&mut self, key: Option<Key>) {
+        self.screenshot_key = key;
+    }
+
+    ///saved at the end of the current frame
+    pub fn request_screenshot(&mut self) {
+        self.screenshot_requested = true;
+    }
+
+    ///saves the next `count` frames as numbered pngs in their own folder
+    pub fn capture_frames(&mut self, count: u32) {
+        if count == 0 {
+            return;
+        }
+        let directory = self.output_dir.join(format!("sequence_{}", timestamp()));
+        rge_engine_info!("capturing {} frames to {}", count, directory.display());
+        self.sequence = Some(Sequence {
+            directory,
+            next_frame: 0,
+            remaining: count,
+        });
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        self.screenshot_requested || self.sequence.is_some()
+    }
+
+    pub fn on_event(&mut self, event: &RGEvent) {
+        if let RGEvent::KeyPressed(e) = event
+            && !e.repeat
+            && Some(e.key) == self.screenshot_key
+        {
+            self.request_screenshot();
+        }
+    }
+
+    ///called by the engine once everything is drawn, before the buffers are swapped. the size
+    ///is the window's framebuffer size in pixels
+    pub fn end_frame(&mut self, width: u32, height: u32, render_target: Option<&Framebuffer>) {
+        if !self.is_capturing() || width == 0 || height == 0 {
+            return;
+        }
+        let frame = match (self.source, render_target) {
+            (CaptureSource::RenderTarget, Some(framebuffer)) => {
+                CapturedFrame::from_framebuffer(framebuffer, 0)
+            }
+            _ => CapturedFrame::from_window(width, height),
+        };
+
+        if self.screenshot_requested {
+            self.screenshot_requested = false;
+            let path = self
+                .output_dir
+                .join(format!("screenshot_{}.png", timestamp()));
+            rge_engine_info!("saving screenshot to {}", path.display());
+            self.submit(path, frame.clone());
+        }
+
+        if let Some(sequence) = &mut self.sequence {
+            let path = sequence
+                .directory
+                .join(format!("frame_{:05}.png", sequence.next_frame));
+            sequence.next_frame += 1;
+            sequence.remaining -= 1;
+            if sequence.remaining == 0 {
+                self.sequence = None;
+            }
+            self.submit(path, frame);
+        }
+    }
+
+    fn submit(&self, path: PathBuf, frame: CapturedFrame) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(EncodeJob { path, frame });
+        }
+    }
+}
+
+impl Drop for FrameCapture {
+    ///waits for the queued images so none are lost on exit
+    fn drop(&mut self) {
+        self.sender = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn timestamp() -> String {
+    chrono::Local::now()
+        .format("%Y-%m-%d_%H-%M-%S-%3f")
+        .to_string()
+}
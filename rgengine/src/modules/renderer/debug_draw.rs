@@ -0,0 +1,445 @@
+//immediate mode debug shapes, callable from anywhere on the main thread (`Layer::on_update`
+//included). shapes are queued here and drawn on top of the frame in one batch by
+//`Renderer::end_frame`, by default for that frame only, `.lasting` keeps them longer.
+//
+//world shapes use the camera from `set_camera`, `ForwardRenderer::begin_scene` sets it too.
+//screen shapes are in pixels from the top left of the render target.
+//release builds and builds without the `debug-draw` feature compile the shapes out, every
+//function here does nothing in them
+
+use crate::math::{Aabb, Mat4, Quat, Vec2, Vec3, Vec4};
+
+//only read when drawing
+#[cfg_attr(not(all(feature = "debug-draw", debug_assertions)), allow(dead_code))]
+#[derive(Clone, Debug)]
+enum Shape {
+    Line {
+        from: Vec3,
+        to: Vec3,
+    },
+    Arrow {
+        from: Vec3,
+        to: Vec3,
+    },
+    Box {
+        transform: Mat4,
+    },
+    Sphere {
+        center: Vec3,
+        radius: f32,
+    },
+    Circle {
+        center: Vec3,
+        normal: Vec3,
+        radius: f32,
+    },
+    Grid {
+        transform: Mat4,
+        cells: u32,
+    },
+    Axes {
+        transform: Mat4,
+    },
+    Text {
+        position: Vec3,
+        text: String,
+    },
+    ScreenLine {
+        from: Vec2,
+        to: Vec2,
+    },
+    ScreenRect {
+        min: Vec2,
+        max: Vec2,
+    },
+    ScreenCircle {
+        center: Vec2,
+        radius: f32,
+    },
+    ScreenText {
+        position: Vec2,
+        text: String,
+    },
+}
+
+///a queued shape, drawn for the current frame unless it's made to last
+#[cfg_attr(not(all(feature = "debug-draw", debug_assertions)), allow(dead_code))]
+#[derive(Clone, Copy, Debug)]
+pub struct DebugShape(u64);
+
+impl DebugShape {
+    ///keeps drawing the shape every frame for `seconds`
+    pub fn lasting(self, seconds: f32) {
+        #[cfg(all(feature = "debug-draw", debug_assertions))]
+        imp::set_lifetime(self.0, seconds);
+        #[cfg(not(all(feature = "debug-draw", debug_assertions)))]
+        let _ = seconds;
+    }
+}
+
+fn submit(shape: Shape, color: Vec4) -> DebugShape {
+    #[cfg(all(feature = "debug-draw", debug_assertions))]
+    return DebugShape(imp::submit(shape, color));
+    #[cfg(not(all(feature = "debug-draw", debug_assertions)))]
+    {
+        let _ = (shape, color);
+        DebugShape(0)
+    }
+}
+
+///the view projection world shapes are drawn with
+pub fn set_camera(view_projection: &Mat4) {
+    #[cfg(all(feature = "debug-draw", debug_assertions))]
+    imp::set_camera(view_projection);
+    #[cfg(not(all(feature = "debug-draw", debug_assertions)))]
+    let _ = view_projection;
+}
+
+///drops every queued shape, lasting ones too
+pub fn clear() {
+    #[cfg(all(feature = "debug-draw", debug_assertions))]
+    imp::clear();
+}
+
+///shapes waiting to be drawn, including lasting ones that were drawn before
+pub fn shape_count() -> usize {
+    #[cfg(all(feature = "debug-draw", debug_assertions))]
+    return imp::shape_count();
+    #[cfg(not(all(feature = "debug-draw", debug_assertions)))]
+    0
+}
+
+pub fn line(from: Vec3, to: Vec3, color: Vec4) -> DebugShape {
+    submit(Shape::Line { from, to }, color)
+}
+
+///line with a small head at `to`
+pub fn arrow(from: Vec3, to: Vec3, color: Vec4) -> DebugShape {
+    submit(Shape::Arrow { from, to }, color)
+}
+
+///the unit cube centered on the origin, moved by `transform`
+pub fn wire_box(transform: &Mat4, color: Vec4) -> DebugShape {
+    submit(
+        Shape::Box {
+            transform: *transform,
+        },
+        color,
+    )
+}
+
+pub fn aabb(aabb: &Aabb, color: Vec4) -> DebugShape {
+    let transform = Mat4::from_scale_rotation_translation(
+        aabb.max - aabb.min,
+        Quat::IDENTITY,
+        (aabb.min + aabb.max) / 2.0,
+    );
+    wire_box(&transform, color)
+}
+
+///three circles around the axes
+pub fn sphere(center: Vec3, radius: f32, color: Vec4) -> DebugShape {
+    submit(Shape::Sphere { center, radius }, color)
+}
+
+///circle in the plane facing `normal`
+pub fn circle(center: Vec3, normal: Vec3, radius: f32, color: Vec4) -> DebugShape {
+    submit(
+        Shape::Circle {
+            center,
+            normal,
+            radius,
+        },
+        color,
+    )
+}
+
+///`cells` by `cells` squares of `cell_size` on the xz plane around `center`
+pub fn grid(center: Vec3, cell_size: f32, cells: u32, color: Vec4) -> DebugShape {
+    let transform =
+        Mat4::from_scale_rotation_translation(Vec3::splat(cell_size), Quat::IDENTITY, center);
+    grid_transform(&transform, cells, color)
+}
+
+///a grid of unit cells on the xz plane, moved by `transform`
+pub fn grid_transform(transform: &Mat4, cells: u32, color: Vec4) -> DebugShape {
+    submit(
+        Shape::Grid {
+            transform: *transform,
+            cells,
+        },
+        color,
+    )
+}
+
+///red, green and blue arrows along the x, y and z axes of `transform`, `size` long
+pub fn axes(transform: &Mat4, size: f32) -> DebugShape {
+    let transform = *transform * Mat4::from_scale(Vec3::splat(size));
+    submit(Shape::Axes { transform }, Vec4::ONE)
+}
+
+///a label at a world position, it faces the screen and keeps its size
+pub fn text(position: Vec3, text: &str, color: Vec4) -> DebugShape {
+    submit(
+        Shape::Text {
+            position,
+            text: text.to_string(),
+        },
+        color,
+    )
+}
+
+pub fn screen_line(from: Vec2, to: Vec2, color: Vec4) -> DebugShape {
+    submit(Shape::ScreenLine { from, to }, color)
+}
+
+pub fn screen_rect(min: Vec2, max: Vec2, color: Vec4) -> DebugShape {
+    submit(Shape::ScreenRect { min, max }, color)
+}
+
+pub fn screen_circle(center: Vec2, radius: f32, color: Vec4) -> DebugShape {
+    submit(Shape::ScreenCircle { center, radius }, color)
+}
+
+///`position` is the top left of the text
+pub fn screen_text(position: Vec2, text: &str, color: Vec4) -> DebugShape {
+    submit(
+        Shape::ScreenText {
+            position,
+            text: text.to_string(),
+        },
+        color,
+    )
+}
+
+#[cfg(all(feature = "debug-draw", debug_assertions))]
+pub(crate) use imp::DebugDrawRenderer;
+
+#[cfg(all(feature = "debug-draw", debug_assertions))]
+mod imp {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::Shape;
+    use crate::{
+        math::{Mat4, Vec2, Vec3, Vec4},
+        renderer::{
+            camera::OrthographicCamera,
+            line_renderer::LineRenderer,
+            renderer2d::Renderer2D,
+            text::{Font, FontAtlas, TextStyle},
+        },
+    };
+
+    const FONT: &[u8] = include_bytes!("../../../assets/fonts/Roboto-Regular.ttf");
+    const TEXT_SIZE: f32 = 14.0;
+
+    struct Entry {
+        id: u64,
+        shape: Shape,
+        color: Vec4,
+        //seconds left, drawn at least once even when it's already out
+        remaining: f32,
+    }
+
+    #[derive(Default)]
+    struct Queue {
+        entries: Vec<Entry>,
+        next_id: u64,
+        view_projection: Mat4,
+    }
+
+    thread_local! {
+        static QUEUE: RefCell<Queue> = RefCell::new(Queue::default());
+    }
+
+    pub(super) fn submit(shape: Shape, color: Vec4) -> u64 {
+        QUEUE.with_borrow_mut(|queue| {
+            let id = queue.next_id;
+            queue.next_id += 1;
+            queue.entries.push(Entry {
+                id,
+                shape,
+                color,
+                remaining: 0.0,
+            });
+            id
+        })
+    }
+
+    pub(super) fn set_lifetime(id: u64, seconds: f32) {
+        QUEUE.with_borrow_mut(|queue| {
+            //almost always the last one
+            if let Some(entry) = queue.entries.iter_mut().rev().find(|e| e.id == id) {
+                entry.remaining = seconds;
+            }
+        });
+    }
+
+    pub(super) fn set_camera(view_projection: &Mat4) {
+        QUEUE.with_borrow_mut(|queue| queue.view_projection = *view_projection);
+    }
+
+    pub(super) fn clear() {
+        QUEUE.with_borrow_mut(|queue| queue.entries.clear());
+    }
+
+    pub(super) fn shape_count() -> usize {
+        QUEUE.with_borrow(|queue| queue.entries.len())
+    }
+
+    ///gl resources for drawing the queue, made the first time there is something to draw
+    pub(crate) struct DebugDrawRenderer {
+        lines: LineRenderer,
+        renderer_2d: Renderer2D,
+        atlas: FontAtlas,
+    }
+
+    impl DebugDrawRenderer {
+        pub(crate) fn new() -> Self {
+            let font = Font::from_bytes("Roboto", FONT).expect("the debug font is built in");
+            Self {
+                lines: LineRenderer::new(),
+                renderer_2d: Renderer2D::new(),
+                atlas: FontAtlas::new(Rc::new(font), TEXT_SIZE),
+            }
+        }
+
+        ///draws the queue into the bound target and ages it by `dt` seconds
+        pub(crate) fn flush(&mut self, dt: f32) {
+            if shape_count() == 0 {
+                return;
+            }
+            let mut queue = QUEUE.take();
+            let mut viewport = [0; 4];
+            unsafe { gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr()) };
+            let screen = Vec2::new(viewport[2].max(1) as f32, viewport[3].max(1) as f32);
+            //pixels with y going down the screen
+            let screen_camera = OrthographicCamera::new(0.0, screen.x, -screen.y, 0.0);
+            let to_screen = |point: Vec2| Vec3::new(point.x, -point.y, 0.0);
+
+            self.lines.begin_scene(&queue.view_projection);
+            for entry in &queue.entries {
+                self.draw_world(&entry.shape, entry.color);
+            }
+            self.lines.end_scene();
+
+            self.lines.begin_scene(screen_camera.view_projection());
+            for entry in &queue.entries {
+                let color = entry.color;
+                match entry.shape {
+                    Shape::ScreenLine { from, to } => {
+                        self.lines.draw_line(to_screen(from), to_screen(to), color)
+                    }
+                    Shape::ScreenRect { min, max } => {
+                        let corners = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+                        for i in 0..4 {
+                            let (a, b) = (corners[i], corners[(i + 1) % 4]);
+                            self.lines.draw_line(to_screen(a), to_screen(b), color);
+                        }
+                    }
+                    Shape::ScreenCircle { center, radius } => {
+                        self.lines
+                            .draw_circle(to_screen(center), Vec3::Z, radius, color)
+                    }
+                    _ => {}
+                }
+            }
+            self.lines.end_scene();
+
+            self.renderer_2d.begin_scene(&screen_camera);
+            let style = TextStyle::new(TEXT_SIZE);
+            for entry in &queue.entries {
+                let (position, text) = match &entry.shape {
+                    Shape::ScreenText { position, text } => (*position, text),
+                    Shape::Text { position, text } => {
+                        let clip = queue.view_projection * position.extend(1.0);
+                        if clip.w <= 0.0 {
+                            continue;
+                        }
+                        let ndc = clip.truncate() / clip.w;
+                        let pixel = Vec2::new(ndc.x + 1.0, 1.0 - ndc.y) / 2.0 * screen;
+                        (pixel, text)
+                    }
+                    _ => continue,
+                };
+                self.renderer_2d.draw_text(
+                    &mut self.atlas,
+                    text,
+                    to_screen(position.round()),
+                    &style,
+                    entry.color,
+                );
+            }
+            self.renderer_2d.end_scene();
+
+            queue.entries.retain_mut(|entry| {
+                entry.remaining -= dt;
+                entry.remaining > 0.0
+            });
+            //shapes queued while drawing go after the ones that are still alive
+            QUEUE.with_borrow_mut(|new| {
+                queue.entries.append(&mut new.entries);
+                queue.next_id = new.next_id.max(queue.next_id);
+                *new = queue;
+            });
+        }
+
+        fn draw_world(&mut self, shape: &Shape, color: Vec4) {
+            let lines = &mut self.lines;
+            match *shape {
+                Shape::Line { from, to } => lines.draw_line(from, to, color),
+                Shape::Arrow { from, to } => lines.draw_arrow(from, to, color),
+                Shape::Box { transform } => {
+                    let corner = |i: u32| {
+                        let unit = Vec3::new(
+                            (i & 1) as f32 - 0.5,
+                            ((i >> 1) & 1) as f32 - 0.5,
+                            ((i >> 2) & 1) as f32 - 0.5,
+                        );
+                        transform.transform_point3(unit)
+                    };
+                    //corners one bit apart share an edge
+                    for i in 0..8u32 {
+                        for bit in [1, 2, 4] {
+                            if i & bit == 0 {
+                                lines.draw_line(corner(i), corner(i | bit), color);
+                            }
+                        }
+                    }
+                }
+                Shape::Sphere { center, radius } => {
+                    for normal in [Vec3::X, Vec3::Y, Vec3::Z] {
+                        lines.draw_circle(center, normal, radius, color);
+                    }
+                }
+                Shape::Circle {
+                    center,
+                    normal,
+                    radius,
+                } => lines.draw_circle(center, normal, radius, color),
+                Shape::Grid { transform, cells } => {
+                    let half = cells as f32 / 2.0;
+                    for i in 0..=cells {
+                        let offset = i as f32 - half;
+                        let point =
+                            |x: f32, z: f32| transform.transform_point3(Vec3::new(x, 0.0, z));
+                        lines.draw_line(point(offset, -half), point(offset, half), color);
+                        lines.draw_line(point(-half, offset), point(half, offset), color);
+                    }
+                }
+                Shape::Axes { transform } => {
+                    let origin = transform.transform_point3(Vec3::ZERO);
+                    let axes = [
+                        (Vec3::X, Vec4::new(1.0, 0.2, 0.2, 1.0)),
+                        (Vec3::Y, Vec4::new(0.2, 1.0, 0.2, 1.0)),
+                        (Vec3::Z, Vec4::new(0.2, 0.4, 1.0, 1.0)),
+                    ];
+                    for (axis, color) in axes {
+                        lines.draw_arrow(origin, transform.transform_point3(axis), color);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
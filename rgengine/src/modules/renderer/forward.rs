@@ -0,0 +1,216 @@
+use std::rc::Rc;
+
+use crate::math::{Mat4, Vec3, Vec4};
+
+use super::{
+    buffer::VertexArray,
+    camera::PerspectiveCamera,
+    debug_draw,
+    lighting::{LIGHTS_BINDING, LightEnvironment, LightingConfig, draw_light_gizmos},
+    line_renderer::LineRenderer,
+    material::{BlendMode, CullMode, Material, MaterialInstance, MaterialValue},
+    mesh::{AlphaMode, GpuModel, MeshMaterial},
+    render_queue::{RenderQueue, RenderQueueStats},
+    shader::Shader,
+    shadows::ShadowMaps,
+    texture::Texture2D,
+    uniform_buffer::UniformBuffer,
+};
+
+///forward shading for 3d scenes: every draw is lit by all the lights in one pass.
+///draws go through a `RenderQueue` so they are sorted by material
+pub struct ForwardRenderer {
+    config: LightingConfig,
+    shader: Rc<Shader>,
+    lights: UniformBuffer,
+    queue: RenderQueue,
+    lines: LineRenderer,
+    shadows: ShadowMaps,
+    white_texture: Rc<Texture2D>,
+    flat_normal_texture: Rc<Texture2D>,
+    ///draws the lights with `draw_light_gizmos` at the end of the scene, on in debug builds
+    pub show_light_gizmos: bool,
+}
+
+impl ForwardRenderer {
+    ///needs a current gl context
+    pub fn new(config: LightingConfig) -> Self {
+        let (vertex, fragment) = config.lit_shader_source();
+        let shader =
+            Shader::from_source("Lit", &vertex, &fragment).expect("failed to build the lit shader");
+        ShadowMaps::set_samplers(&shader);
+        Self {
+            config,
+            shader: Rc::new(shader),
+            lights: UniformBuffer::new(config.uniform_buffer_size(), LIGHTS_BINDING),
+            queue: RenderQueue::new(),
+            lines: LineRenderer::new(),
+            shadows: ShadowMaps::new(config.shadows),
+            white_texture: Rc::new(Texture2D::from_rgba(1, 1, &[255, 255, 255, 255])),
+            //straight up in tangent space
+            flat_normal_texture: Rc::new(Texture2D::from_rgba(1, 1, &[128, 128, 255, 255])),
+            show_light_gizmos: cfg!(debug_assertions),
+        }
+    }
+
+    pub fn config(&self) -> &LightingConfig {
+        &self.config
+    }
+
+    pub fn shader(&self) -> &Rc<Shader> {
+        &self.shader
+    }
+
+    ///the maps rendered in the last scene, for `shadow_debug_window`
+    pub fn shadows(&self) -> &ShadowMaps {
+        &self.shadows
+    }
+
+    ///a lit material with every parameter set to its default, a white dielectric
+    pub fn create_material(&self, name: &str) -> Material {
+        let mut material = Material::new(name, self.shader.clone());
+        let white = MaterialValue::Texture(self.white_texture.clone());
+        material.set("u_BaseColor", MaterialValue::Color(Vec4::ONE));
+        material.set("u_BaseColorTexture", white.clone());
+        material.set("u_Metallic", MaterialValue::Float(0.0));
+        material.set("u_Roughness", MaterialValue::Float(1.0));
+        material.set("u_MetallicRoughnessTexture", white.clone());
+        material.set(
+            "u_NormalTexture",
+            MaterialValue::Texture(self.flat_normal_texture.clone()),
+        );
+        material.set("u_Emissive", MaterialValue::Vec3(Vec3::ZERO));
+        material.set("u_EmissiveTexture", white);
+        material.set("u_AlphaCutoff", MaterialValue::Float(0.0));
+        material
+    }
+
+    ///`textures` are the uploaded images of the model the material came from
+    pub fn material_from_mesh(
+        &self,
+        mesh_material: &MeshMaterial,
+        textures: &[Rc<Texture2D>],
+    ) -> Material {
+        let mut material = self.create_material(&mesh_material.name);
+        let mut texture = |name: &str, index: Option<usize>| {
+            if let Some(texture) = index.and_then(|i| textures.get(i)) {
+                material.set(name, MaterialValue::Texture(texture.clone()));
+            }
+        };
+        texture("u_BaseColorTexture", mesh_material.base_color_texture);
+        texture(
+            "u_MetallicRoughnessTexture",
+            mesh_material.metallic_roughness_texture,
+        );
+        texture("u_NormalTexture", mesh_material.normal_texture);
+        texture("u_EmissiveTexture", mesh_material.emissive_texture);
+        material.set(
+            "u_BaseColor",
+            MaterialValue::Color(mesh_material.base_color),
+        );
+        material.set("u_Metallic", MaterialValue::Float(mesh_material.metallic));
+        material.set("u_Roughness", MaterialValue::Float(mesh_material.roughness));
+        material.set("u_Emissive", MaterialValue::Vec3(mesh_material.emissive));
+        if let AlphaMode::Mask(cutoff) = mesh_material.alpha_mode {
+            material.set("u_AlphaCutoff", MaterialValue::Float(cutoff));
+        }
+        material.state.blend = mesh_material.alpha_mode.blend_mode();
+        if material.state.blend != BlendMode::Opaque {
+            material.state.depth_write = false;
+        }
+        if mesh_material.double_sided {
+            material.state.cull = CullMode::None;
+        }
+        material
+    }
+
+    ///one material per `Model::materials` entry, in the same order
+    pub fn materials_from_model(
+        &self,
+        materials: &[MeshMaterial],
+        model: &GpuModel,
+    ) -> Vec<MaterialInstance> {
+        materials
+            .iter()
+            .map(|m| MaterialInstance::new(Rc::new(self.material_from_mesh(m, &model.textures))))
+            .collect()
+    }
+
+    pub fn begin_scene(&mut self, camera: &PerspectiveCamera, lights: &LightEnvironment) {
+        let data = lights.to_std140(&self.config, camera.position());
+        self.lights.bind();
+        self.lights.set_data(&data, 0);
+        self.shadows.update(camera, lights);
+        self.queue
+            .begin_scene(camera.view_projection(), camera.position());
+        self.lines.begin_scene(camera.view_projection());
+        debug_draw::set_camera(camera.view_projection());
+        if self.show_light_gizmos {
+            draw_light_gizmos(&mut self.lines, lights);
+        }
+    }
+
+    pub fn submit(
+        &mut self,
+        vertex_array: &Rc<VertexArray>,
+        material: &MaterialInstance,
+        transform: &Mat4,
+    ) {
+        self.queue.submit(vertex_array, material, transform);
+    }
+
+    ///a vertex array from `GpuPrimitive::upload_instanced`, `transform` applies to every instance
+    pub fn submit_instanced(
+        &mut self,
+        vertex_array: &Rc<VertexArray>,
+        material: &MaterialInstance,
+        transform: &Mat4,
+        instance_count: u32,
+    ) {
+        self.queue
+            .submit_instanced(vertex_array, material, transform, instance_count);
+    }
+
+    ///every node of the model, primitives without a material (or with one past the end of
+    ///`materials`) use `fallback`
+    pub fn submit_model(
+        &mut self,
+        model: &GpuModel,
+        materials: &[MaterialInstance],
+        fallback: &MaterialInstance,
+        transform: &Mat4,
+    ) {
+        for node in &model.nodes {
+            let node_transform = *transform * node.transform;
+            for primitive in &model.meshes[node.mesh].primitives {
+                let material = primitive
+                    .material
+                    .and_then(|i| materials.get(i))
+                    .unwrap_or(fallback);
+                self.queue
+                    .submit(&primitive.vertex_array, material, &node_transform);
+            }
+        }
+    }
+
+    ///the line renderer the gizmos go through, for drawing other debug lines in the same scene
+    pub fn lines(&mut self) -> &mut LineRenderer {
+        &mut self.lines
+    }
+
+    ///renders the shadow maps from the submitted draws, then the draws themselves
+    pub fn end_scene(&mut self) {
+        self.shadows.render(&self.queue);
+        self.shadows.bind_textures();
+        self.queue.end_scene();
+        self.lines.end_scene();
+    }
+
+    pub fn stats(&self) -> RenderQueueStats {
+        self.queue.stats()
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.queue.reset_stats();
+    }
+}
@@ -0,0 +1,451 @@
+use std::{cell::Cell, ffi::c_void};
+
+use gl::types::GLenum;
+
+use super::stats;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FramebufferTextureFormat {
+    Rgba8,
+    ///hdr color
+    Rgba16F,
+    ///one int per pixel, for things like entity ids under the mouse
+    RedInteger,
+    Depth24Stencil8,
+    ///depth only, can be sampled (shadow maps)
+    Depth32F,
+}
+
+impl FramebufferTextureFormat {
+    pub fn is_depth(&self) -> bool {
+        matches!(
+            self,
+            FramebufferTextureFormat::Depth24Stencil8 | FramebufferTextureFormat::Depth32F
+        )
+    }
+
+    pub(crate) fn internal_format(&self) -> GLenum {
+        match self {
+            FramebufferTextureFormat::Rgba8 => gl::RGBA8,
+            FramebufferTextureFormat::Rgba16F => gl::RGBA16F,
+            FramebufferTextureFormat::RedInteger => gl::R32I,
+            FramebufferTextureFormat::Depth24Stencil8 => gl::DEPTH24_STENCIL8,
+            FramebufferTextureFormat::Depth32F => gl::DEPTH_COMPONENT32F,
+        }
+    }
+
+    pub(crate) fn depth_attachment_point(&self) -> GLenum {
+        match self {
+            FramebufferTextureFormat::Depth24Stencil8 => gl::DEPTH_STENCIL_ATTACHMENT,
+            _ => gl::DEPTH_ATTACHMENT,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FramebufferSpec {
+    pub width: u32,
+    pub height: u32,
+    ///1 for no msaa
+    pub samples: u32,
+    ///color attachments in order, at most one depth format
+    pub attachments: Vec<FramebufferTextureFormat>,
+}
+
+impl FramebufferSpec {
+    pub fn new(width: u32, height: u32, attachments: Vec<FramebufferTextureFormat>) -> Self {
+        Self {
+            width,
+            height,
+            samples: 1,
+            attachments,
+        }
+    }
+
+    pub fn samples(mut self, samples: u32) -> Self {
+        self.samples = samples.max(1);
+        self
+    }
+}
+
+///off screen render target. with msaa the textures are multisampled and `resolve` copies
+///them into single sample textures, which are the ones handed out for sampling and readback
+pub struct Framebuffer {
+    id: u32,
+    spec: FramebufferSpec,
+    color_formats: Vec<FramebufferTextureFormat>,
+    depth_format: Option<FramebufferTextureFormat>,
+    color_attachments: Vec<u32>,
+    depth_attachment: Option<u32>,
+    resolve_target: Option<Box<Framebuffer>>,
+    previous_framebuffer: Cell<i32>,
+    previous_viewport: Cell<[i32; 4]>,
+    previous_scissor: Cell<[i32; 4]>,
+}
+
+impl Framebuffer {
+    pub const MAX_SIZE: u32 = 8192;
+
+    pub fn new(spec: FramebufferSpec) -> Self {
+        let color_formats = spec
+            .attachments
+            .iter()
+            .copied()
+            .filter(|f| !f.is_depth())
+            .collect();
+        let depth_format = spec.attachments.iter().copied().find(|f| f.is_depth());
+        let mut framebuffer = Self {
+            id: 0,
+            spec,
+            color_formats,
+            depth_format,
+            color_attachments: Vec::new(),
+            depth_attachment: None,
+            resolve_target: None,
+            previous_framebuffer: Cell::new(0),
+            previous_viewport: Cell::new([0; 4]),
+            previous_scissor: Cell::new([0; 4]),
+        };
+        framebuffer.invalidate();
+        framebuffer
+    }
+
+    pub fn spec(&self) -> &FramebufferSpec {
+        &self.spec
+    }
+
+    pub fn width(&self) -> u32 {
+        self.spec.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.spec.height
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn is_multisampled(&self) -> bool {
+        self.spec.samples > 1
+    }
+
+    ///recreates the attachments only when the size actually changed
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 || width > Self::MAX_SIZE || height > Self::MAX_SIZE {
+            return;
+        }
+        if width == self.spec.width && height == self.spec.height {
+            return;
+        }
+        self.spec.width = width;
+        self.spec.height = height;
+        self.invalidate();
+    }
+
+    ///recreates the attachments with a different msaa sample count, 1 turns msaa off
+    pub fn set_samples(&mut self, samples: u32) {
+        let samples = samples.max(1);
+        if samples == self.spec.samples {
+            return;
+        }
+        self.spec.samples = samples;
+        self.invalidate();
+    }
+
+    ///binds for drawing and sets the viewport to cover it, `unbind` puts the old
+    ///framebuffer and viewport back so framebuffers can be bound while drawing into another
+    pub fn bind(&self) {
+        let mut framebuffer = 0;
+        let mut viewport = [0; 4];
+        let mut scissor = [0; 4];
+        unsafe {
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut framebuffer);
+            gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+            gl::GetIntegerv(gl::SCISSOR_BOX, scissor.as_mut_ptr());
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.id);
+            gl::Viewport(0, 0, self.spec.width as i32, self.spec.height as i32);
+            gl::Scissor(0, 0, self.spec.width as i32, self.spec.height as i32);
+        }
+        self.previous_framebuffer.set(framebuffer);
+        self.previous_viewport.set(viewport);
+        self.previous_scissor.set(scissor);
+        stats::record_state_change();
+    }
+
+    pub fn unbind(&self) {
+        let [x, y, w, h] = self.previous_viewport.get();
+        let [sx, sy, sw, sh] = self.previous_scissor.get();
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.previous_framebuffer.get() as u32);
+            gl::Viewport(x, y, w, h);
+            gl::Scissor(sx, sy, sw, sh);
+        }
+    }
+
+    ///copies the multisampled attachments into the sampleable ones, does nothing without msaa
+    pub fn resolve(&self) {
+        let Some(target) = &self.resolve_target else {
+            return;
+        };
+        let (w, h) = (self.spec.width as i32, self.spec.height as i32);
+        unsafe {
+            //blits get clipped by the scissor box, which may be smaller than the framebuffer
+            let scissor = gl::IsEnabled(gl::SCISSOR_TEST) == gl::TRUE;
+            gl::Disable(gl::SCISSOR_TEST);
+            for index in 0..self.color_attachments.len() {
+                let attachment = gl::COLOR_ATTACHMENT0 + index as u32;
+                gl::NamedFramebufferReadBuffer(self.id, attachment);
+                gl::NamedFramebufferDrawBuffer(target.id, attachment);
+                gl::BlitNamedFramebuffer(
+                    self.id,
+                    target.id,
+                    0,
+                    0,
+                    w,
+                    h,
+                    0,
+                    0,
+                    w,
+                    h,
+                    gl::COLOR_BUFFER_BIT,
+                    gl::NEAREST,
+                );
+            }
+            if self.depth_attachment.is_some() {
+                gl::BlitNamedFramebuffer(
+                    self.id,
+                    target.id,
+                    0,
+                    0,
+                    w,
+                    h,
+                    0,
+                    0,
+                    w,
+                    h,
+                    gl::DEPTH_BUFFER_BIT,
+                    gl::NEAREST,
+                );
+            }
+            //put the draw buffers back, blitting changed which ones are active
+            self.set_draw_buffers();
+            target.set_draw_buffers();
+            if scissor {
+                gl::Enable(gl::SCISSOR_TEST);
+            }
+        }
+    }
+
+    ///texture to sample from, resolved if the framebuffer is multisampled
+    pub fn color_attachment_id(&self, index: usize) -> u32 {
+        match &self.resolve_target {
+            Some(target) => target.color_attachments[index],
+            None => self.color_attachments[index],
+        }
+    }
+
+    pub fn depth_attachment_id(&self) -> Option<u32> {
+        match &self.resolve_target {
+            Some(target) => target.depth_attachment,
+            None => self.depth_attachment,
+        }
+    }
+
+    pub fn color_attachment_count(&self) -> usize {
+        self.color_attachments.len()
+    }
+
+    pub fn clear_color_attachment(&self, index: usize, color: [f32; 4]) {
+        unsafe {
+            match self.color_formats[index] {
+                FramebufferTextureFormat::RedInteger => {
+                    let value = [color[0] as i32, 0, 0, 0];
+                    gl::ClearNamedFramebufferiv(self.id, gl::COLOR, index as i32, value.as_ptr());
+                }
+                _ => gl::ClearNamedFramebufferfv(self.id, gl::COLOR, index as i32, color.as_ptr()),
+            }
+        }
+    }
+
+    pub fn clear_depth(&self, depth: f32, stencil: i32) {
+        let Some(format) = self.depth_format else {
+            return;
+        };
+        unsafe {
+            match format {
+                FramebufferTextureFormat::Depth24Stencil8 => {
+                    gl::ClearNamedFramebufferfi(self.id, gl::DEPTH_STENCIL, 0, depth, stencil)
+                }
+                _ => gl::ClearNamedFramebufferfv(self.id, gl::DEPTH, 0, &depth),
+            }
+        }
+    }
+
+    ///rgba8 pixels of a color attachment, rows go bottom to top like gl. a `RedInteger`
+    ///attachment gives one native endian i32 per pixel instead.
+    ///multisampled framebuffers are resolved first
+    pub fn read_pixels(&self, index: usize) -> Vec<u8> {
+        self.resolve();
+        let source = self.resolve_target.as_deref().unwrap_or(self);
+        let (w, h) = (self.spec.width as i32, self.spec.height as i32);
+        let (format, kind) = match self.color_formats[index] {
+            FramebufferTextureFormat::RedInteger => (gl::RED_INTEGER, gl::INT),
+            _ => (gl::RGBA, gl::UNSIGNED_BYTE),
+        };
+        let mut pixels = vec![0u8; (w * h * 4) as usize];
+        unsafe {
+            gl::NamedFramebufferReadBuffer(source.id, gl::COLOR_ATTACHMENT0 + index as u32);
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, source.id);
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::ReadPixels(0, 0, w, h, format, kind, pixels.as_mut_ptr() as *mut c_void);
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+        }
+        pixels
+    }
+
+    ///one pixel of a `RedInteger` attachment, `y` counts from the bottom
+    pub fn read_pixel_int(&self, index: usize, x: i32, y: i32) -> i32 {
+        self.resolve();
+        let source = self.resolve_target.as_deref().unwrap_or(self);
+        let mut value = 0;
+        unsafe {
+            gl::NamedFramebufferReadBuffer(source.id, gl::COLOR_ATTACHMENT0 + index as u32);
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, source.id);
+            gl::ReadPixels(
+                x,
+                y,
+                1,
+                1,
+                gl::RED_INTEGER,
+                gl::INT,
+                &mut value as *mut i32 as *mut c_void,
+            );
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+        }
+        value
+    }
+
+    fn invalidate(&mut self) {
+        self.delete();
+
+        let (w, h) = (self.spec.width as i32, self.spec.height as i32);
+        let samples = self.spec.samples as i32;
+        let multisampled = samples > 1;
+        let texture_target = if multisampled {
+            gl::TEXTURE_2D_MULTISAMPLE
+        } else {
+            gl::TEXTURE_2D
+        };
+
+        unsafe {
+            gl::CreateFramebuffers(1, &mut self.id);
+
+            for (index, format) in self.color_formats.iter().enumerate() {
+                let texture = create_attachment(texture_target, *format, samples, w, h);
+                gl::NamedFramebufferTexture(
+                    self.id,
+                    gl::COLOR_ATTACHMENT0 + index as u32,
+                    texture,
+                    0,
+                );
+                self.color_attachments.push(texture);
+            }
+
+            if let Some(format) = self.depth_format {
+                let texture = create_attachment(texture_target, format, samples, w, h);
+                gl::NamedFramebufferTexture(self.id, format.depth_attachment_point(), texture, 0);
+                self.depth_attachment = Some(texture);
+            }
+
+            self.set_draw_buffers();
+
+            let status = gl::CheckNamedFramebufferStatus(self.id, gl::FRAMEBUFFER);
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                crate::log::rge_engine_error!("framebuffer is incomplete: {:#x}", status);
+            }
+        }
+
+        if multisampled {
+            let spec = FramebufferSpec {
+                samples: 1,
+                ..self.spec.clone()
+            };
+            self.resolve_target = Some(Box::new(Framebuffer::new(spec)));
+        }
+    }
+
+    fn set_draw_buffers(&self) {
+        let buffers: Vec<GLenum> = (0..self.color_attachments.len() as u32)
+            .map(|i| gl::COLOR_ATTACHMENT0 + i)
+            .collect();
+        unsafe {
+            if buffers.is_empty() {
+                gl::NamedFramebufferDrawBuffer(self.id, gl::NONE);
+                gl::NamedFramebufferReadBuffer(self.id, gl::NONE);
+            } else {
+                gl::NamedFramebufferDrawBuffers(self.id, buffers.len() as i32, buffers.as_ptr());
+            }
+        }
+    }
+
+    fn delete(&mut self) {
+        if self.id == 0 {
+            return;
+        }
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.id);
+            gl::DeleteTextures(
+                self.color_attachments.len() as i32,
+                self.color_attachments.as_ptr(),
+            );
+            if let Some(depth) = self.depth_attachment {
+                gl::DeleteTextures(1, &depth);
+            }
+        }
+        self.id = 0;
+        self.color_attachments.clear();
+        self.depth_attachment = None;
+        self.resolve_target = None;
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        self.delete();
+    }
+}
+
+pub(crate) unsafe fn create_attachment(
+    target: GLenum,
+    format: FramebufferTextureFormat,
+    samples: i32,
+    width: i32,
+    height: i32,
+) -> u32 {
+    let mut texture = 0;
+    unsafe {
+        gl::CreateTextures(target, 1, &mut texture);
+        if target == gl::TEXTURE_2D_MULTISAMPLE {
+            gl::TextureStorage2DMultisample(
+                texture,
+                samples,
+                format.internal_format(),
+                width,
+                height,
+                gl::FALSE,
+            );
+            return texture;
+        }
+        gl::TextureStorage2D(texture, 1, format.internal_format(), width, height);
+        let filter = match format {
+            FramebufferTextureFormat::RedInteger => gl::NEAREST,
+            _ => gl::LINEAR,
+        };
+        gl::TextureParameteri(texture, gl::TEXTURE_MIN_FILTER, filter as i32);
+        gl::TextureParameteri(texture, gl::TEXTURE_MAG_FILTER, filter as i32);
+        gl::TextureParameteri(texture, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+        gl::TextureParameteri(texture, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+    }
+    texture
+}
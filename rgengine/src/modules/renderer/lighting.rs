@@ -0,0 +1,477 @@
+use crate::math::{Vec3, Vec4};
+
+use super::{
+    line_renderer::LineRenderer,
+    shadows::{ShadowConfig, ShadowSettings},
+};
+
+///uniform block binding the `Lights` block is bound to
+pub const LIGHTS_BINDING: u32 = 1;
+
+///light colors are linear, `intensity` scales them. a white light with intensity 1 shining
+///straight at a white surface lights it to 1 in both shading models
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DirectionalLight {
+    ///the way the light travels, doesn't have to be normalized
+    pub direction: Vec3,
+    pub color: Vec3,
+    pub intensity: f32,
+    ///only the first directional light with shadows gets them
+    pub shadow: Option<ShadowSettings>,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vec3, color: Vec3, intensity: f32) -> Self {
+        Self {
+            direction,
+            color,
+            intensity,
+            shadow: None,
+        }
+    }
+
+    pub fn with_shadow(mut self, shadow: ShadowSettings) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PointLight {
+    pub position: Vec3,
+    pub color: Vec3,
+    pub intensity: f32,
+    ///the light fades out smoothly and is zero from here on
+    pub range: f32,
+}
+
+impl PointLight {
+    pub fn new(position: Vec3, color: Vec3, intensity: f32, range: f32) -> Self {
+        Self {
+            position,
+            color,
+            intensity,
+            range,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SpotLight {
+    pub position: Vec3,
+    pub direction: Vec3,
+    pub color: Vec3,
+    pub intensity: f32,
+    pub range: f32,
+    ///angles from the center of the cone in radians, full intensity inside `inner_angle`
+    ///fading to nothing at `outer_angle`
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+    pub shadow: Option<ShadowSettings>,
+}
+
+impl SpotLight {
+    pub fn new(
+        position: Vec3,
+        direction: Vec3,
+        color: Vec3,
+        intensity: f32,
+        range: f32,
+        inner_angle: f32,
+        outer_angle: f32,
+    ) -> Self {
+        Self {
+            position,
+            direction,
+            color,
+            intensity,
+            range,
+            inner_angle,
+            outer_angle,
+            shadow: None,
+        }
+    }
+
+    pub fn with_shadow(mut self, shadow: ShadowSettings) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+}
+
+///all the lights in a scene, lights past the limits in the `LightingConfig` are ignored
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct LightEnvironment {
+    ///added to every surface, linear
+    pub ambient: Vec3,
+    pub directional_lights: Vec<DirectionalLight>,
+    pub point_lights: Vec<PointLight>,
+    pub spot_lights: Vec<SpotLight>,
+}
+
+impl LightEnvironment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.directional_lights.clear();
+        self.point_lights.clear();
+        self.spot_lights.clear();
+    }
+
+    ///the `Lights` block in std140, every field is one or more vec4s
+    pub(crate) fn to_std140(&self, config: &LightingConfig, camera_position: Vec3) -> Vec<Vec4> {
+        let mut data = Vec::with_capacity(config.uniform_buffer_size() / 16);
+        let count = |count: usize, max: usize| f32::from_bits(count.min(max) as u32);
+        data.push(camera_position.extend(1.0));
+        data.push(self.ambient.extend(0.0));
+        //an ivec4 in glsl, the counts are stored as the bits of the floats
+        data.push(Vec4::new(
+            count(self.directional_lights.len(), config.max_directional_lights),
+            count(self.point_lights.len(), config.max_point_lights),
+            count(self.spot_lights.len(), config.max_spot_lights),
+            0.0,
+        ));
+
+        let mut directional = self.directional_lights.iter();
+        for _ in 0..config.max_directional_lights {
+            match directional.next() {
+                Some(light) => data.extend([
+                    light.direction.normalize_or(Vec3::NEG_Y).extend(0.0),
+                    (light.color * light.intensity).extend(0.0),
+                ]),
+                None => data.extend([Vec4::ZERO; 2]),
+            }
+        }
+        let mut point = self.point_lights.iter();
+        for _ in 0..config.max_point_lights {
+            match point.next() {
+                Some(light) => data.extend([
+                    light.position.extend(light.range.max(f32::EPSILON)),
+                    (light.color * light.intensity).extend(0.0),
+                ]),
+                None => data.extend([Vec4::ZERO; 2]),
+            }
+        }
+        let mut spot = self.spot_lights.iter();
+        for _ in 0..config.max_spot_lights {
+            match spot.next() {
+                Some(light) => {
+                    let outer = light.outer_angle.max(light.inner_angle + 0.001);
+                    data.extend([
+                        light.position.extend(light.range.max(f32::EPSILON)),
+                        light
+                            .direction
+                            .normalize_or(Vec3::NEG_Y)
+                            .extend(outer.cos()),
+                        (light.color * light.intensity).extend(light.inner_angle.cos()),
+                    ])
+                }
+                None => data.extend([Vec4::ZERO; 3]),
+            }
+        }
+        data
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ShadingModel {
+    BlinnPhong,
+    ///metallic-roughness, the same parameters as gltf
+    #[default]
+    Pbr,
+}
+
+///baked into the lit shader, changing it means building a new `ForwardRenderer`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LightingConfig {
+    pub shading: ShadingModel,
+    pub max_directional_lights: usize,
+    pub max_point_lights: usize,
+    pub max_spot_lights: usize,
+    ///encode the output to srgb, turn off when a later pass does it
+    pub gamma_correct: bool,
+    pub shadows: ShadowConfig,
+}
+
+impl Default for LightingConfig {
+    fn default() -> Self {
+        Self {
+            shading: ShadingModel::Pbr,
+            max_directional_lights: 4,
+            max_point_lights: 32,
+            max_spot_lights: 16,
+            gamma_correct: true,
+            shadows: ShadowConfig::default(),
+        }
+    }
+}
+
+impl LightingConfig {
+    ///size of the `Lights` block in bytes
+    pub fn uniform_buffer_size(&self) -> usize {
+        let vec4s = 3
+            + 2 * self.max_directional_lights.max(1)
+            + 2 * self.max_point_lights.max(1)
+            + 3 * self.max_spot_lights.max(1);
+        vec4s * 16
+    }
+
+    ///glsl doesn't allow empty arrays so every kind of light gets at least one slot
+    fn clamped(&self) -> Self {
+        Self {
+            max_directional_lights: self.max_directional_lights.max(1),
+            max_point_lights: self.max_point_lights.max(1),
+            max_spot_lights: self.max_spot_lights.max(1),
+            ..*self
+        }
+    }
+
+    pub(crate) fn lit_shader_source(&self) -> (String, String) {
+        let config = self.clamped();
+        let mut defines = format!(
+            "#define MAX_DIRECTIONAL_LIGHTS {}\n#define MAX_POINT_LIGHTS {}\n#define MAX_SPOT_LIGHTS {}\n#define LIGHTS_BINDING {}\n",
+            config.max_directional_lights,
+            config.max_point_lights,
+            config.max_spot_lights,
+            LIGHTS_BINDING
+        );
+        if config.shading == ShadingModel::Pbr {
+            defines.push_str("#define SHADING_PBR\n");
+        }
+        if config.gamma_correct {
+            defines.push_str("#define GAMMA_CORRECT\n");
+        }
+        let fragment = LIT_FRAGMENT_SHADER
+            .replace("//DEFINES\n", &defines)
+            .replace("//SHADOWS\n", &self.shadows.shader_source());
+        (LIT_VERTEX_SHADER.to_string(), fragment)
+    }
+}
+
+///arrows for directional lights (from above the origin since they have no position),
+///range spheres for point lights and cones for spot lights, in each light's color
+pub fn draw_light_gizmos(lines: &mut LineRenderer, lights: &LightEnvironment) {
+    let color = |c: Vec3| (c / c.max_element().max(f32::EPSILON)).extend(1.0);
+    for light in &lights.directional_lights {
+        let direction = light.direction.normalize_or(Vec3::NEG_Y);
+        let from = -direction * 3.0;
+        lines.draw_arrow(from, from + direction, color(light.color));
+    }
+    for light in &lights.point_lights {
+        let color = color(light.color);
+        for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+            lines.draw_circle(light.position, axis, light.range, color);
+            lines.draw_line(
+                light.position - axis * 0.1,
+                light.position + axis * 0.1,
+                color,
+            );
+        }
+    }
+    for light in &lights.spot_lights {
+        let color = color(light.color);
+        let direction = light.direction.normalize_or(Vec3::NEG_Y);
+        let outer = light.outer_angle.min(std::f32::consts::FRAC_PI_2 - 0.01);
+        let center = light.position + direction * light.range * outer.cos();
+        let radius = light.range * outer.sin();
+        lines.draw_circle(center, direction, radius, color);
+        let side = direction.any_orthonormal_vector();
+        let other = direction.cross(side);
+        for offset in [side, -side, other, -other] {
+            lines.draw_line(light.position, center + offset * radius, color);
+        }
+        lines.draw_arrow(light.position, light.position + direction * 0.5, color);
+    }
+}
+
+///vertices use the `mesh::Vertex` layout, instanced draws add `mesh::Instance`
+const LIT_VERTEX_SHADER: &str = r#"
+#version 450 core
+layout(location = 0) in vec3 a_Position;
+layout(location = 1) in vec3 a_Normal;
+layout(location = 2) in vec4 a_Tangent;
+layout(location = 3) in vec2 a_TexCoord;
+layout(location = 4) in vec4 a_Color;
+layout(location = 5) in mat4 a_InstanceTransform;
+layout(location = 9) in vec4 a_InstanceColor;
+
+uniform mat4 u_ViewProjection;
+uniform mat4 u_Transform;
+uniform bool u_Instanced;
+
+out vec3 v_WorldPosition;
+out vec2 v_TexCoord;
+out vec4 v_Color;
+out mat3 v_TBN;
+
+void main() {
+    mat4 transform = u_Instanced ? u_Transform * a_InstanceTransform : u_Transform;
+    vec4 world = transform * vec4(a_Position, 1.0);
+    mat3 normal_matrix = transpose(inverse(mat3(transform)));
+    vec3 N = normalize(normal_matrix * a_Normal);
+    vec3 T = normalize(mat3(transform) * a_Tangent.xyz);
+    T = normalize(T - dot(T, N) * N);
+    v_TBN = mat3(T, cross(N, T) * a_Tangent.w, N);
+    v_WorldPosition = world.xyz;
+    v_TexCoord = a_TexCoord;
+    v_Color = u_Instanced ? a_Color * a_InstanceColor : a_Color;
+    gl_Position = u_ViewProjection * world;
+}
+"#;
+
+const LIT_FRAGMENT_SHADER: &str = r#"
+#version 450 core
+//DEFINES
+layout(location = 0) out vec4 o_Color;
+
+in vec3 v_WorldPosition;
+in vec2 v_TexCoord;
+in vec4 v_Color;
+in mat3 v_TBN;
+
+struct DirectionalLight {
+    vec4 direction;
+    vec4 color;
+};
+
+struct PointLight {
+    vec4 position_range;
+    vec4 color;
+};
+
+struct SpotLight {
+    vec4 position_range;
+    vec4 direction_cos_outer;
+    vec4 color_cos_inner;
+};
+
+layout(std140, binding = LIGHTS_BINDING) uniform Lights {
+    vec4 u_CameraPosition;
+    vec4 u_Ambient;
+    ivec4 u_LightCounts;
+    DirectionalLight u_DirectionalLights[MAX_DIRECTIONAL_LIGHTS];
+    PointLight u_PointLights[MAX_POINT_LIGHTS];
+    SpotLight u_SpotLights[MAX_SPOT_LIGHTS];
+};
+//SHADOWS
+
+uniform vec4 u_BaseColor;
+uniform sampler2D u_BaseColorTexture;
+uniform float u_Metallic;
+uniform float u_Roughness;
+uniform sampler2D u_MetallicRoughnessTexture;
+uniform sampler2D u_NormalTexture;
+uniform vec3 u_Emissive;
+uniform sampler2D u_EmissiveTexture;
+uniform float u_AlphaCutoff;
+
+const float PI = 3.14159265;
+
+vec3 srgb_to_linear(vec3 c) {
+    return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+}
+
+vec3 linear_to_srgb(vec3 c) {
+    return mix(c * 12.92, 1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, c));
+}
+
+//inverse square with a window so the light reaches exactly zero at its range
+float attenuation(float distance, float range) {
+    float ratio = distance / range;
+    float window = clamp(1.0 - ratio * ratio * ratio * ratio, 0.0, 1.0);
+    return window * window / (distance * distance + 1.0);
+}
+
+#ifdef SHADING_PBR
+//cook-torrance with ggx, smith-schlick and schlick's fresnel, times pi so the
+//intensities mean the same as with blinn-phong
+vec3 shade(vec3 N, vec3 V, vec3 L, vec3 albedo, float metallic, float roughness) {
+    float NdotL = max(dot(N, L), 0.0);
+    if (NdotL <= 0.0) {
+        return vec3(0.0);
+    }
+    vec3 H = normalize(V + L);
+    float NdotV = max(dot(N, V), 1e-4);
+    float NdotH = max(dot(N, H), 0.0);
+    float a = roughness * roughness;
+    float a2 = a * a;
+    float d = NdotH * NdotH * (a2 - 1.0) + 1.0;
+    float D = a2 / (PI * d * d);
+    float k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    float G = NdotV / (NdotV * (1.0 - k) + k) * NdotL / (NdotL * (1.0 - k) + k);
+    vec3 F0 = mix(vec3(0.04), albedo, metallic);
+    vec3 F = F0 + (1.0 - F0) * pow(1.0 - max(dot(H, V), 0.0), 5.0);
+    vec3 specular = D * G * F / (4.0 * NdotV * NdotL);
+    vec3 diffuse = (1.0 - F) * (1.0 - metallic) * albedo / PI;
+    return (diffuse + specular) * NdotL * PI;
+}
+#else
+vec3 shade(vec3 N, vec3 V, vec3 L, vec3 albedo, float metallic, float roughness) {
+    float NdotL = max(dot(N, L), 0.0);
+    if (NdotL <= 0.0) {
+        return vec3(0.0);
+    }
+    vec3 H = normalize(V + L);
+    float a = roughness * roughness;
+    float shininess = max(2.0 / max(a * a, 1e-4) - 2.0, 1.0);
+    vec3 specular_color = mix(vec3(0.04), albedo, metallic);
+    vec3 specular = specular_color * (shininess + 8.0) / 8.0 * pow(max(dot(N, H), 0.0), shininess);
+    return ((1.0 - metallic) * albedo + specular) * NdotL;
+}
+#endif
+
+void main() {
+    vec4 texel = texture(u_BaseColorTexture, v_TexCoord);
+    vec4 base = u_BaseColor * v_Color * vec4(srgb_to_linear(texel.rgb), texel.a);
+    if (base.a < u_AlphaCutoff) {
+        discard;
+    }
+    vec2 metallic_roughness = texture(u_MetallicRoughnessTexture, v_TexCoord).bg;
+    float metallic = clamp(u_Metallic * metallic_roughness.x, 0.0, 1.0);
+    float roughness = clamp(u_Roughness * metallic_roughness.y, 0.04, 1.0);
+
+    vec3 N = normalize(v_TBN * (texture(u_NormalTexture, v_TexCoord).xyz * 2.0 - 1.0));
+    if (!gl_FrontFacing) {
+        N = -N;
+    }
+    vec3 V = normalize(u_CameraPosition.xyz - v_WorldPosition);
+    //shadows are offset along the surface, not the normal map
+    vec3 geometry_normal = normalize(v_TBN[2]) * (gl_FrontFacing ? 1.0 : -1.0);
+
+    vec3 color = u_Ambient.rgb * base.rgb;
+    for (int i = 0; i < min(u_LightCounts.x, MAX_DIRECTIONAL_LIGHTS); i++) {
+        DirectionalLight light = u_DirectionalLights[i];
+        vec3 L = -light.direction.xyz;
+        float shadow = directional_shadow(i, v_WorldPosition, geometry_normal);
+        color += shade(N, V, L, base.rgb, metallic, roughness) * light.color.rgb * shadow;
+    }
+    for (int i = 0; i < min(u_LightCounts.y, MAX_POINT_LIGHTS); i++) {
+        PointLight light = u_PointLights[i];
+        vec3 to_light = light.position_range.xyz - v_WorldPosition;
+        float distance = length(to_light);
+        vec3 L = to_light / max(distance, 1e-4);
+        float falloff = attenuation(distance, light.position_range.w);
+        color += shade(N, V, L, base.rgb, metallic, roughness) * light.color.rgb * falloff;
+    }
+    for (int i = 0; i < min(u_LightCounts.z, MAX_SPOT_LIGHTS); i++) {
+        SpotLight light = u_SpotLights[i];
+        vec3 to_light = light.position_range.xyz - v_WorldPosition;
+        float distance = length(to_light);
+        vec3 L = to_light / max(distance, 1e-4);
+        float cone = smoothstep(light.direction_cos_outer.w, light.color_cos_inner.w,
+                                dot(-L, light.direction_cos_outer.xyz));
+        float falloff = attenuation(distance, light.position_range.w) * cone;
+        if (falloff > 0.0) {
+            falloff *= spot_shadow(i, v_WorldPosition, geometry_normal, distance);
+        }
+        color += shade(N, V, L, base.rgb, metallic, roughness) * light.color_cos_inner.rgb * falloff;
+    }
+    color += u_Emissive * srgb_to_linear(texture(u_EmissiveTexture, v_TexCoord).rgb);
+
+#ifdef GAMMA_CORRECT
+    color = linear_to_srgb(clamp(color, 0.0, 1.0));
+#endif
+    o_Color = vec4(color, base.a);
+}
+"#;
@@ -0,0 +1,163 @@
+use crate::math::{Mat4, Quat, Vec3, Vec4};
+
+use super::{
+    buffer::{BufferElement, BufferLayout, ShaderDataType, VertexArray, VertexBuffer},
+    shader::Shader,
+    stats,
+};
+
+const MAX_LINES: usize = 10_000;
+const MAX_VERTICES: usize = MAX_LINES * 2;
+
+const LINE_VERTEX_SHADER: &str = r#"
+#version 450 core
+layout(location = 0) in vec3 a_Position;
+layout(location = 1) in vec4 a_Color;
+
+uniform mat4 u_ViewProjection;
+
+out vec4 v_Color;
+
+void main() {
+    v_Color = a_Color;
+    gl_Position = u_ViewProjection * vec4(a_Position, 1.0);
+}
+"#;
+
+const LINE_FRAGMENT_SHADER: &str = r#"
+#version 450 core
+layout(location = 0) out vec4 o_Color;
+
+in vec4 v_Color;
+
+void main() {
+    o_Color = v_Color;
+}
+"#;
+
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+struct LineVertex {
+    position: [f32; 3],
+    color: [f32; 4],
+}
+
+///batched 3d lines for gizmos and other debug shapes
+pub struct LineRenderer {
+    vertex_array: VertexArray,
+    shader: Shader,
+    vertices: Vec<LineVertex>,
+    view_projection: Mat4,
+    ///lines behind geometry are hidden when set, otherwise they are drawn on top
+    pub depth_test: bool,
+}
+
+impl LineRenderer {
+    ///needs a current gl context
+    pub fn new() -> Self {
+        let layout = BufferLayout::new(vec![
+            BufferElement::new(ShaderDataType::Float3, "a_Position"),
+            BufferElement::new(ShaderDataType::Float4, "a_Color"),
+        ]);
+        let mut vertex_array = VertexArray::new();
+        vertex_array.add_vertex_buffer(VertexBuffer::with_capacity(
+            MAX_VERTICES * std::mem::size_of::<LineVertex>(),
+            layout,
+        ));
+        vertex_array.unbind();
+        let shader = Shader::from_source("Lines", LINE_VERTEX_SHADER, LINE_FRAGMENT_SHADER)
+            .expect("failed to build the line shader");
+        Self {
+            vertex_array,
+            shader,
+            vertices: Vec::with_capacity(MAX_VERTICES),
+            view_projection: Mat4::IDENTITY,
+            depth_test: false,
+        }
+    }
+
+    pub fn begin_scene(&mut self, view_projection: &Mat4) {
+        self.view_projection = *view_projection;
+        self.vertices.clear();
+    }
+
+    pub fn end_scene(&mut self) {
+        self.flush();
+    }
+
+    pub fn draw_line(&mut self, from: Vec3, to: Vec3, color: Vec4) {
+        if self.vertices.len() >= MAX_VERTICES {
+            self.flush();
+        }
+        for position in [from, to] {
+            self.vertices.push(LineVertex {
+                position: position.into(),
+                color: color.into(),
+            });
+        }
+    }
+
+    ///circle in the plane facing `normal`
+    pub fn draw_circle(&mut self, center: Vec3, normal: Vec3, radius: f32, color: Vec4) {
+        const SEGMENTS: usize = 32;
+        let rotation = Quat::from_rotation_arc(Vec3::Z, normal.normalize_or(Vec3::Z));
+        let point = |i: usize| {
+            let angle = i as f32 / SEGMENTS as f32 * std::f32::consts::TAU;
+            center + rotation * Vec3::new(angle.cos(), angle.sin(), 0.0) * radius
+        };
+        for i in 0..SEGMENTS {
+            self.draw_line(point(i), point(i + 1), color);
+        }
+    }
+
+    ///line with a small head at `to`
+    pub fn draw_arrow(&mut self, from: Vec3, to: Vec3, color: Vec4) {
+        self.draw_line(from, to, color);
+        let direction = to - from;
+        let length = direction.length();
+        if length <= f32::EPSILON {
+            return;
+        }
+        let direction = direction / length;
+        let side = direction.any_orthonormal_vector();
+        let other = direction.cross(side);
+        let head = length * 0.15;
+        for offset in [side, -side, other, -other] {
+            self.draw_line(to, to - direction * head + offset * head * 0.5, color);
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.vertices.is_empty() {
+            return;
+        }
+        self.vertex_array.vertex_buffers()[0].set_data(&self.vertices);
+        self.shader.bind();
+        self.shader
+            .set_mat4("u_ViewProjection", &self.view_projection);
+        self.vertex_array.bind();
+        unsafe {
+            let depth_test = gl::IsEnabled(gl::DEPTH_TEST) == gl::TRUE;
+            if self.depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            } else {
+                gl::Disable(gl::DEPTH_TEST);
+            }
+            gl::DrawArrays(gl::LINES, 0, self.vertices.len() as i32);
+            stats::record_draw(0);
+            if depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            } else {
+                gl::Disable(gl::DEPTH_TEST);
+            }
+        }
+        self.vertex_array.unbind();
+        self.vertices.clear();
+    }
+}
+
+impl Default for LineRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,516 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use crate::math::{Mat4, Vec2, Vec3, Vec4};
+
+use super::{shader::Shader, texture::Texture2D};
+
+#[derive(Clone, Debug)]
+pub enum MaterialValue {
+    Int(i32),
+    Float(f32),
+    Vec2(Vec2),
+    Vec3(Vec3),
+    Vec4(Vec4),
+    ///same as Vec4 on the gpu, kept apart so editors know to show a color picker
+    Color(Vec4),
+    Mat4(Mat4),
+    Texture(Rc<Texture2D>),
+}
+
+impl MaterialValue {
+    ///textures take the next free unit and set the sampler to it
+    fn apply(&self, shader: &Shader, name: &str, texture_unit: &mut u32) {
+        match self {
+            MaterialValue::Int(v) => shader.set_int(name, *v),
+            MaterialValue::Float(v) => shader.set_float(name, *v),
+            MaterialValue::Vec2(v) => shader.set_float2(name, *v),
+            MaterialValue::Vec3(v) => shader.set_float3(name, *v),
+            MaterialValue::Vec4(v) | MaterialValue::Color(v) => shader.set_float4(name, *v),
+            MaterialValue::Mat4(v) => shader.set_mat4(name, v),
+            MaterialValue::Texture(texture) => {
+                texture.bind(*texture_unit);
+                shader.set_int(name, *texture_unit as i32);
+                *texture_unit += 1;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum BlendMode {
+    #[default]
+    Opaque,
+    Alpha,
+    Additive,
+    Multiply,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CullMode {
+    None,
+    #[default]
+    Back,
+    Front,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum DepthFunc {
+    Less,
+    #[default]
+    LessEqual,
+    Equal,
+    Greater,
+    Always,
+}
+
+///fixed function state a material needs, the render queue only changes what differs
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RenderState {
+    pub blend: BlendMode,
+    pub depth_test: bool,
+    pub depth_write: bool,
+    pub depth_func: DepthFunc,
+    pub cull: CullMode,
+}
+
+impl Default for RenderState {
+    fn default() -> Self {
+        Self {
+            blend: BlendMode::Opaque,
+            depth_test: true,
+            depth_write: true,
+            depth_func: DepthFunc::LessEqual,
+            cull: CullMode::Back,
+        }
+    }
+}
+
+impl RenderState {
+    pub fn is_transparent(&self) -> bool {
+        self.blend != BlendMode::Opaque
+    }
+
+    pub fn apply(&self) {
+        unsafe {
+            match self.blend {
+                BlendMode::Opaque => gl::Disable(gl::BLEND),
+                BlendMode::Alpha => {
+                    gl::Enable(gl::BLEND);
+                    gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+                }
+                BlendMode::Additive => {
+                    gl::Enable(gl::BLEND);
+                    gl::BlendFunc(gl::SRC_ALPHA, gl::ONE);
+                }
+                BlendMode::Multiply => {
+                    gl::Enable(gl::BLEND);
+                    gl::BlendFunc(gl::DST_COLOR, gl::ZERO);
+                }
+            }
+            if self.depth_test {
+                gl::Enable(gl::DEPTH_TEST);
+            } else {
+                gl::Disable(gl::DEPTH_TEST);
+            }
+            gl::DepthMask(self.depth_write as u8);
+            gl::DepthFunc(match self.depth_func {
+                DepthFunc::Less => gl::LESS,
+                DepthFunc::LessEqual => gl::LEQUAL,
+                DepthFunc::Equal => gl::EQUAL,
+                DepthFunc::Greater => gl::GREATER,
+                DepthFunc::Always => gl::ALWAYS,
+            });
+            match self.cull {
+                CullMode::None => gl::Disable(gl::CULL_FACE),
+                CullMode::Back => {
+                    gl::Enable(gl::CULL_FACE);
+                    gl::CullFace(gl::BACK);
+                }
+                CullMode::Front => {
+                    gl::Enable(gl::CULL_FACE);
+                    gl::CullFace(gl::FRONT);
+                }
+            }
+        }
+    }
+}
+
+static NEXT_MATERIAL_ID: AtomicU32 = AtomicU32::new(1);
+
+///a shader plus the values for its uniforms and the state it draws with
+pub struct Material {
+    id: u32,
+    name: String,
+    shader: Rc<Shader>,
+    params: HashMap<String, MaterialValue>,
+    pub state: RenderState,
+    ///drawn in order of layer first, then by shader and material
+    pub layer: u8,
+}
+
+impl Material {
+    pub fn new(name: &str, shader: Rc<Shader>) -> Self {
+        Self {
+            id: NEXT_MATERIAL_ID.fetch_add(1, Ordering::Relaxed),
+            name: name.to_string(),
+            shader,
+            params: HashMap::new(),
+            state: RenderState::default(),
+            layer: 0,
+        }
+    }
+
+    ///builds a material from a parsed asset, `texture` loads the texture paths in it
+    pub fn from_asset(
+        name: &str,
+        asset: &MaterialAsset,
+        shader: Rc<Shader>,
+        mut texture: impl FnMut(&Path) -> Result<Rc<Texture2D>, String>,
+    ) -> Result<Self, String> {
+        let mut material = Self::new(name, shader);
+        material.state = asset.state;
+        material.layer = asset.layer;
+        for (param, value) in &asset.params {
+            let value = match value {
+                AssetValue::Value(v) => v.clone(),
+                AssetValue::Texture(path) => MaterialValue::Texture(texture(path)?),
+            };
+            material.params.insert(param.clone(), value);
+        }
+        Ok(material)
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn shader(&self) -> &Rc<Shader> {
+        &self.shader
+    }
+
+    pub fn set(&mut self, name: &str, value: MaterialValue) {
+        self.params.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MaterialValue> {
+        self.params.get(name)
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = (&str, &MaterialValue)> {
+        self.params.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    ///sets every parameter on the shader, which must already be bound
+    pub fn apply(&self, overrides: Option<&HashMap<String, MaterialValue>>) {
+        let mut texture_unit = 0;
+        for (name, value) in &self.params {
+            if overrides.is_some_and(|o| o.contains_key(name)) {
+                continue;
+            }
+            value.apply(&self.shader, name, &mut texture_unit);
+        }
+        for (name, value) in overrides.into_iter().flatten() {
+            value.apply(&self.shader, name, &mut texture_unit);
+        }
+    }
+}
+
+///shares a material but replaces some of its parameters, e.g. a tint per object
+#[derive(Clone)]
+pub struct MaterialInstance {
+    material: Rc<Material>,
+    overrides: HashMap<String, MaterialValue>,
+}
+
+impl MaterialInstance {
+    pub fn new(material: Rc<Material>) -> Self {
+        Self {
+            material,
+            overrides: HashMap::new(),
+        }
+    }
+
+    pub fn material(&self) -> &Rc<Material> {
+        &self.material
+    }
+
+    pub fn set(&mut self, name: &str, value: MaterialValue) {
+        self.overrides.insert(name.to_string(), value);
+    }
+
+    pub fn clear(&mut self, name: &str) {
+        self.overrides.remove(name);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MaterialValue> {
+        self.overrides.get(name).or_else(|| self.material.get(name))
+    }
+
+    pub fn has_overrides(&self) -> bool {
+        !self.overrides.is_empty()
+    }
+
+    pub fn overrides(&self) -> &HashMap<String, MaterialValue> {
+        &self.overrides
+    }
+
+    pub fn apply(&self) {
+        let overrides = self.has_overrides().then_some(&self.overrides);
+        self.material.apply(overrides);
+    }
+}
+
+impl From<Rc<Material>> for MaterialInstance {
+    fn from(material: Rc<Material>) -> Self {
+        Self::new(material)
+    }
+}
+
+///sort key for a draw, sorting ascending gives the draw order:
+///opaque before transparent, then layer, then shader and material so binds are shared.
+///opaque draws go front to back inside a material, transparent ones back to front
+pub fn sort_key(material: &Material, view_depth: f32) -> u64 {
+    //depth is quantized into 16 bits over 0..1024 units in front of the camera
+    let depth = ((view_depth.max(0.0) / 1024.0).min(1.0) * 65535.0) as u64;
+    let transparent = material.state.is_transparent();
+    let depth = if transparent { 65535 - depth } else { depth };
+    let shader = (material.shader.id() as u64) & 0xfff;
+    let id = (material.id as u64) & 0xffff;
+    if transparent {
+        //transparent draws have to stay sorted by depth to blend right
+        (1 << 63) | ((material.layer as u64) << 55) | (depth << 28) | (shader << 16) | id
+    } else {
+        ((material.layer as u64) << 55) | (shader << 43) | (id << 27) | depth
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum AssetValue {
+    Value(MaterialValue),
+    ///resolved relative to the material file
+    Texture(PathBuf),
+}
+
+///a material file before anything is loaded on the gpu
+///
+///```text
+///#comments start with a hash
+///shader = shaders/lit.glsl
+///blend = alpha           #opaque, alpha, additive, multiply
+///depth_test = true
+///depth_write = false
+///depth_func = less_equal #less, less_equal, equal, greater, always
+///cull = back             #none, back, front
+///layer = 1
+///
+///[params]
+///u_Color = color 1.0 0.5 0.2 1.0
+///u_Roughness = float 0.5
+///u_Albedo = texture textures/brick.png
+///```
+#[derive(Clone, Debug, Default)]
+pub struct MaterialAsset {
+    pub shader: PathBuf,
+    pub state: RenderState,
+    pub layer: u8,
+    pub params: Vec<(String, AssetValue)>,
+}
+
+impl MaterialAsset {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut asset = MaterialAsset::default();
+        let mut in_params = false;
+        let mut has_shader = false;
+        for (number, line) in source.lines().enumerate() {
+            let error = |e: String| format!("line {}: {e}", number + 1);
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') {
+                in_params = match line {
+                    "[params]" => true,
+                    "[material]" => false,
+                    _ => return Err(error(format!("unknown section {line}"))),
+                };
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected `key = value`".into()))?;
+            let (key, value) = (key.trim(), value.trim());
+            if in_params {
+                let value = parse_param(value).map_err(error)?;
+                asset.params.push((key.to_string(), value));
+                continue;
+            }
+            let state = &mut asset.state;
+            match key {
+                "shader" => {
+                    asset.shader = PathBuf::from(unquote(value));
+                    has_shader = true;
+                }
+                "blend" => {
+                    state.blend = match value {
+                        "opaque" => BlendMode::Opaque,
+                        "alpha" => BlendMode::Alpha,
+                        "additive" => BlendMode::Additive,
+                        "multiply" => BlendMode::Multiply,
+                        _ => return Err(error(format!("unknown blend mode `{value}`"))),
+                    }
+                }
+                "depth_test" => state.depth_test = parse_bool(value).map_err(error)?,
+                "depth_write" => state.depth_write = parse_bool(value).map_err(error)?,
+                "depth_func" => {
+                    state.depth_func = match value {
+                        "less" => DepthFunc::Less,
+                        "less_equal" => DepthFunc::LessEqual,
+                        "equal" => DepthFunc::Equal,
+                        "greater" => DepthFunc::Greater,
+                        "always" => DepthFunc::Always,
+                        _ => return Err(error(format!("unknown depth func `{value}`"))),
+                    }
+                }
+                "cull" => {
+                    state.cull = match value {
+                        "none" => CullMode::None,
+                        "back" => CullMode::Back,
+                        "front" => CullMode::Front,
+                        _ => return Err(error(format!("unknown cull mode `{value}`"))),
+                    }
+                }
+                "layer" => {
+                    asset.layer = value
+                        .parse()
+                        .map_err(|_| error(format!("layer must be 0-255, got `{value}`")))?
+                }
+                _ => return Err(error(format!("unknown key `{key}`"))),
+            }
+        }
+        if !has_shader {
+            return Err("material has no shader".into());
+        }
+        Ok(asset)
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim_matches('"')
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "true" | "on" | "1" => Ok(true),
+        "false" | "off" | "0" => Ok(false),
+        _ => Err(format!("expected true or false, got `{value}`")),
+    }
+}
+
+fn parse_param(value: &str) -> Result<AssetValue, String> {
+    let (kind, rest) = value.split_once(char::is_whitespace).unwrap_or((value, ""));
+    let rest = rest.trim();
+    if kind == "texture" {
+        if rest.is_empty() {
+            return Err("texture needs a path".into());
+        }
+        return Ok(AssetValue::Texture(PathBuf::from(unquote(rest))));
+    }
+    let floats = || -> Result<Vec<f32>, String> {
+        rest.split_whitespace()
+            .map(|v| v.parse().map_err(|_| format!("`{v}` is not a number")))
+            .collect()
+    };
+    let expect = |count: usize| -> Result<Vec<f32>, String> {
+        let values = floats()?;
+        if values.len() != count {
+            return Err(format!("{kind} needs {count} values, got {}", values.len()));
+        }
+        Ok(values)
+    };
+    let value = match kind {
+        "int" => MaterialValue::Int(
+            rest.parse()
+                .map_err(|_| format!("`{rest}` is not an integer"))?,
+        ),
+        "float" => MaterialValue::Float(expect(1)?[0]),
+        "vec2" => MaterialValue::Vec2(Vec2::from_slice(&expect(2)?)),
+        "vec3" => MaterialValue::Vec3(Vec3::from_slice(&expect(3)?)),
+        "vec4" => MaterialValue::Vec4(Vec4::from_slice(&expect(4)?)),
+        "color" => {
+            let values = floats()?;
+            match values.len() {
+                3 => MaterialValue::Color(Vec3::from_slice(&values).extend(1.0)),
+                4 => MaterialValue::Color(Vec4::from_slice(&values)),
+                n => return Err(format!("color needs 3 or 4 values, got {n}")),
+            }
+        }
+        _ => return Err(format!("unknown parameter type `{kind}`")),
+    };
+    Ok(AssetValue::Value(value))
+}
+
+///loads material files and shares the shaders and textures they use
+#[derive(Default)]
+pub struct MaterialLibrary {
+    shaders: HashMap<PathBuf, Rc<Shader>>,
+    textures: HashMap<PathBuf, Rc<Texture2D>>,
+    materials: HashMap<PathBuf, Rc<Material>>,
+}
+
+impl MaterialLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<Rc<Material>, String> {
+        let path = path.as_ref();
+        if let Some(material) = self.materials.get(path) {
+            return Ok(material.clone());
+        }
+        let source =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let asset =
+            MaterialAsset::parse(&source).map_err(|e| format!("{}: {e}", path.display()))?;
+        let directory = path.parent().unwrap_or(Path::new(""));
+
+        let shader = self.shader(&directory.join(&asset.shader))?;
+        let name = path
+            .file_stem()
+            .map_or("material".into(), |s| s.to_string_lossy());
+        let textures = &mut self.textures;
+        let material = Material::from_asset(&name, &asset, shader, |texture| {
+            let texture_path = directory.join(texture);
+            if let Some(texture) = textures.get(&texture_path) {
+                return Ok(texture.clone());
+            }
+            let texture = Rc::new(
+                Texture2D::from_file(&texture_path)
+                    .map_err(|e| format!("{}: {e}", texture_path.display()))?,
+            );
+            textures.insert(texture_path, texture.clone());
+            Ok(texture)
+        })?;
+
+        let material = Rc::new(material);
+        self.materials.insert(path.to_path_buf(), material.clone());
+        Ok(material)
+    }
+
+    pub fn shader(&mut self, path: &Path) -> Result<Rc<Shader>, String> {
+        if let Some(shader) = self.shaders.get(path) {
+            return Ok(shader.clone());
+        }
+        let shader = Rc::new(Shader::from_file(path)?);
+        self.shaders.insert(path.to_path_buf(), shader.clone());
+        Ok(shader)
+    }
+}
@@ -0,0 +1,217 @@
+use std::path::Path;
+
+use gltf::{Document, image::Format, mesh::Mode};
+
+use crate::math::{Mat4, Vec3, Vec4};
+
+use super::{AlphaMode, ImageData, Mesh, MeshMaterial, MeshNode, Model, Primitive, Vertex};
+
+pub(super) fn load(path: &Path) -> Result<Model, String> {
+    let data = std::fs::read(path).map_err(|e| e.to_string())?;
+    from_slice(&data, path.parent())
+}
+
+pub(super) fn from_slice(data: &[u8], base_dir: Option<&Path>) -> Result<Model, String> {
+    let gltf::Gltf { document, blob } = gltf::Gltf::from_slice(data).map_err(|e| e.to_string())?;
+    let buffers = gltf::import_buffers(&document, base_dir, blob).map_err(|e| e.to_string())?;
+    let images = gltf::import_images(&document, base_dir, &buffers).map_err(|e| e.to_string())?;
+
+    let mut meshes = Vec::new();
+    for mesh in document.meshes() {
+        let name = mesh
+            .name()
+            .map_or_else(|| format!("mesh {}", mesh.index()), str::to_string);
+        let mut primitives = Vec::new();
+        for primitive in mesh.primitives() {
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+            let positions = reader
+                .read_positions()
+                .ok_or_else(|| format!("{name}: primitive without positions"))?;
+            let mut vertices: Vec<Vertex> = positions
+                .map(|position| Vertex {
+                    position,
+                    ..Vertex::default()
+                })
+                .collect();
+
+            let normals = reader.read_normals();
+            let has_normals = normals.is_some();
+            for (vertex, normal) in vertices.iter_mut().zip(normals.into_iter().flatten()) {
+                vertex.normal = normal;
+            }
+            let tangents = reader.read_tangents();
+            let has_tangents = tangents.is_some();
+            for (vertex, tangent) in vertices.iter_mut().zip(tangents.into_iter().flatten()) {
+                vertex.tangent = tangent;
+            }
+            let uvs = reader.read_tex_coords(0).map(|uvs| uvs.into_f32());
+            let has_uvs = uvs.is_some();
+            for (vertex, uv) in vertices.iter_mut().zip(uvs.into_iter().flatten()) {
+                vertex.uv = uv;
+            }
+            let colors = reader.read_colors(0).map(|colors| colors.into_rgba_f32());
+            for (vertex, color) in vertices.iter_mut().zip(colors.into_iter().flatten()) {
+                vertex.color = color;
+            }
+
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0..vertices.len() as u32).collect(),
+            };
+            if let Some(index) = indices.iter().find(|&&i| i as usize >= vertices.len()) {
+                return Err(format!("{name}: index {index} is out of range"));
+            }
+            let indices = match primitive.mode() {
+                Mode::Triangles => indices,
+                Mode::TriangleStrip => strip_to_list(&indices),
+                Mode::TriangleFan => fan_to_list(&indices),
+                mode => return Err(format!("{name}: {mode:?} primitives are not supported")),
+            };
+
+            let primitive = Primitive {
+                vertices,
+                indices,
+                material: primitive.material().index(),
+                bounds: None,
+            };
+            primitives.push(primitive.finish(has_normals, has_tangents, has_uvs));
+        }
+        meshes.push(Mesh { name, primitives });
+    }
+
+    let materials = document.materials().map(read_material).collect();
+
+    let images = images
+        .into_iter()
+        .zip(document.images())
+        .map(|(data, image)| {
+            Ok(ImageData {
+                name: image
+                    .name()
+                    .map_or_else(|| format!("image {}", image.index()), str::to_string),
+                width: data.width,
+                height: data.height,
+                pixels: to_rgba8(data.format, &data.pixels)?,
+            })
+        })
+        .collect::<Result<_, String>>()?;
+
+    Ok(Model {
+        nodes: read_nodes(&document),
+        meshes,
+        materials,
+        images,
+    })
+}
+
+fn read_material(material: gltf::Material<'_>) -> MeshMaterial {
+    let pbr = material.pbr_metallic_roughness();
+    let image = |texture: gltf::Texture<'_>| Some(texture.source().index());
+    MeshMaterial {
+        name: material.name().unwrap_or_default().to_string(),
+        base_color: Vec4::from(pbr.base_color_factor()),
+        base_color_texture: pbr.base_color_texture().and_then(|t| image(t.texture())),
+        metallic: pbr.metallic_factor(),
+        roughness: pbr.roughness_factor(),
+        metallic_roughness_texture: pbr
+            .metallic_roughness_texture()
+            .and_then(|t| image(t.texture())),
+        normal_texture: material.normal_texture().and_then(|t| image(t.texture())),
+        occlusion_texture: material
+            .occlusion_texture()
+            .and_then(|t| image(t.texture())),
+        emissive: Vec3::from(material.emissive_factor()),
+        emissive_texture: material.emissive_texture().and_then(|t| image(t.texture())),
+        alpha_mode: match material.alpha_mode() {
+            gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+            gltf::material::AlphaMode::Mask => {
+                AlphaMode::Mask(material.alpha_cutoff().unwrap_or(0.5))
+            }
+            gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+        },
+        double_sided: material.double_sided(),
+    }
+}
+
+///flattens the node tree of the default scene, or places every mesh at the origin if there is no scene
+fn read_nodes(document: &Document) -> Vec<MeshNode> {
+    let Some(scene) = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+    else {
+        return document
+            .meshes()
+            .map(|mesh| MeshNode {
+                mesh: mesh.index(),
+                transform: Mat4::IDENTITY,
+            })
+            .collect();
+    };
+    let mut nodes = Vec::new();
+    let mut stack: Vec<_> = scene.nodes().map(|node| (node, Mat4::IDENTITY)).collect();
+    while let Some((node, parent)) = stack.pop() {
+        let transform = parent * Mat4::from_cols_array_2d(&node.transform().matrix());
+        if let Some(mesh) = node.mesh() {
+            nodes.push(MeshNode {
+                mesh: mesh.index(),
+                transform,
+            });
+        }
+        stack.extend(node.children().map(|child| (child, transform)));
+    }
+    nodes
+}
+
+fn to_rgba8(format: Format, pixels: &[u8]) -> Result<Vec<u8>, String> {
+    let rgba = match format {
+        Format::R8G8B8A8 => pixels.to_vec(),
+        Format::R8G8B8 => pixels
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        //two channel pngs are gray and alpha
+        Format::R8G8 => pixels
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        Format::R8 => pixels.iter().flat_map(|&p| [p, p, p, 255]).collect(),
+        //16 bit pngs, keep the high byte of each channel
+        Format::R16G16B16A16 => pixels.chunks_exact(2).map(|c| c[1]).collect(),
+        Format::R16G16B16 => pixels
+            .chunks_exact(6)
+            .flat_map(|p| [p[1], p[3], p[5], 255])
+            .collect(),
+        Format::R16G16 => pixels
+            .chunks_exact(4)
+            .flat_map(|p| [p[1], p[1], p[1], p[3]])
+            .collect(),
+        Format::R16 => pixels
+            .chunks_exact(2)
+            .flat_map(|p| [p[1], p[1], p[1], 255])
+            .collect(),
+        format => return Err(format!("{format:?} images are not supported")),
+    };
+    Ok(rgba)
+}
+
+///every other triangle is flipped so the winding stays the same
+fn strip_to_list(indices: &[u32]) -> Vec<u32> {
+    indices
+        .windows(3)
+        .enumerate()
+        .flat_map(|(i, w)| {
+            if i % 2 == 0 {
+                [w[0], w[1], w[2]]
+            } else {
+                [w[1], w[0], w[2]]
+            }
+        })
+        .collect()
+}
+
+fn fan_to_list(indices: &[u32]) -> Vec<u32> {
+    match indices.split_first() {
+        Some((&center, rest)) => rest.windows(2).flat_map(|w| [center, w[0], w[1]]).collect(),
+        None => Vec::new(),
+    }
+}
@@ -0,0 +1,385 @@
+//importing only builds plain vectors so it can run without a gl context (and off the main thread),
+//`Model::upload` turns the result into vertex arrays and textures
+mod gltf;
+mod obj;
+
+use std::{path::Path, rc::Rc};
+
+use crate::math::{Aabb, Mat4, Vec2, Vec3, Vec4};
+
+use super::{
+    buffer::{BufferElement, BufferLayout, IndexBuffer, ShaderDataType, VertexArray, VertexBuffer},
+    material::BlendMode,
+    texture::Texture2D,
+};
+
+///uvs have their origin in the top left like gltf, obj uvs are flipped on import
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Vertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    ///xyz is the tangent, w is 1 or -1 and gives the direction of the bitangent
+    pub tangent: [f32; 4],
+    pub uv: [f32; 2],
+    pub color: [f32; 4],
+}
+
+impl Default for Vertex {
+    fn default() -> Self {
+        Self {
+            position: [0.0; 3],
+            normal: [0.0, 0.0, 1.0],
+            tangent: [1.0, 0.0, 0.0, 1.0],
+            uv: [0.0; 2],
+            color: [1.0; 4],
+        }
+    }
+}
+
+impl Vertex {
+    ///matches the fields above, attribute locations 0 to 4
+    pub fn layout() -> BufferLayout {
+        BufferLayout::new(vec![
+            BufferElement::new(ShaderDataType::Float3, "a_Position"),
+            BufferElement::new(ShaderDataType::Float3, "a_Normal"),
+            BufferElement::new(ShaderDataType::Float4, "a_Tangent"),
+            BufferElement::new(ShaderDataType::Float2, "a_TexCoord"),
+            BufferElement::new(ShaderDataType::Float4, "a_Color"),
+        ])
+    }
+}
+
+///per instance attributes for `GpuPrimitive::upload_instanced`, multiplied onto the draw's
+///transform and the vertex color by the lit and shadow shaders
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Instance {
+    pub transform: [f32; 16],
+    pub color: [f32; 4],
+}
+
+impl Instance {
+    pub fn new(transform: &Mat4, color: Vec4) -> Self {
+        Self {
+            transform: transform.to_cols_array(),
+            color: color.into(),
+        }
+    }
+
+    ///follows the mesh vertex, attribute locations 5 to 8 for the transform and 9 for the color
+    pub fn layout() -> BufferLayout {
+        BufferLayout::instanced(vec![
+            BufferElement::new(ShaderDataType::Mat4, "a_InstanceTransform"),
+            BufferElement::new(ShaderDataType::Float4, "a_InstanceColor"),
+        ])
+    }
+}
+
+impl Default for Instance {
+    fn default() -> Self {
+        Self::new(&Mat4::IDENTITY, Vec4::ONE)
+    }
+}
+
+///one draw worth of triangles with a single material
+#[derive(Clone, Debug, Default)]
+pub struct Primitive {
+    pub vertices: Vec<Vertex>,
+    ///triangle list
+    pub indices: Vec<u32>,
+    ///index into `Model::materials`
+    pub material: Option<usize>,
+    pub bounds: Option<Aabb>,
+}
+
+impl Primitive {
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    ///fills in what the file didn't have, tangents need uvs to be generated
+    fn finish(mut self, has_normals: bool, has_tangents: bool, has_uvs: bool) -> Self {
+        if !has_normals {
+            self.generate_normals();
+        }
+        if !has_tangents && has_uvs {
+            self.generate_tangents();
+        }
+        self.calculate_bounds();
+        self
+    }
+
+    fn calculate_bounds(&mut self) {
+        self.bounds = Aabb::from_points(self.vertices.iter().map(|v| Vec3::from(v.position)));
+    }
+
+    ///area weighted smooth normals
+    fn generate_normals(&mut self) {
+        let mut normals = vec![Vec3::ZERO; self.vertices.len()];
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] =
+                [0, 1, 2].map(|i| Vec3::from(self.vertices[triangle[i] as usize].position));
+            let normal = (b - a).cross(c - a);
+            for &index in triangle {
+                normals[index as usize] += normal;
+            }
+        }
+        for (vertex, normal) in self.vertices.iter_mut().zip(normals) {
+            vertex.normal = normal.try_normalize().unwrap_or(Vec3::Z).into();
+        }
+    }
+
+    ///per vertex tangents from the uv directions of the triangles around it
+    fn generate_tangents(&mut self) {
+        let mut tangents = vec![Vec3::ZERO; self.vertices.len()];
+        let mut bitangents = vec![Vec3::ZERO; self.vertices.len()];
+        for triangle in self.indices.chunks_exact(3) {
+            let [v0, v1, v2] = [0, 1, 2].map(|i| self.vertices[triangle[i] as usize]);
+            let edge1 = Vec3::from(v1.position) - Vec3::from(v0.position);
+            let edge2 = Vec3::from(v2.position) - Vec3::from(v0.position);
+            let duv1 = Vec2::from(v1.uv) - Vec2::from(v0.uv);
+            let duv2 = Vec2::from(v2.uv) - Vec2::from(v0.uv);
+            let det = duv1.x * duv2.y - duv2.x * duv1.y;
+            if det.abs() < f32::EPSILON {
+                continue;
+            }
+            let r = 1.0 / det;
+            let tangent = (edge1 * duv2.y - edge2 * duv1.y) * r;
+            let bitangent = (edge2 * duv1.x - edge1 * duv2.x) * r;
+            for &index in triangle {
+                tangents[index as usize] += tangent;
+                bitangents[index as usize] += bitangent;
+            }
+        }
+        for (i, vertex) in self.vertices.iter_mut().enumerate() {
+            let normal = Vec3::from(vertex.normal);
+            //gram-schmidt so the tangent is perpendicular to the normal
+            let tangent = (tangents[i] - normal * normal.dot(tangents[i]))
+                .try_normalize()
+                .unwrap_or_else(|| normal.any_orthonormal_vector());
+            //v goes down the image but normal maps have green pointing up it, so the
+            //bitangent (normal x tangent * w) has to point towards smaller v like in gltf
+            let handedness = if normal.cross(tangent).dot(bitangents[i]) > 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+            vertex.tangent = tangent.extend(handedness).into();
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    pub name: String,
+    pub primitives: Vec<Primitive>,
+}
+
+impl Mesh {
+    pub fn bounds(&self) -> Option<Aabb> {
+        self.primitives
+            .iter()
+            .filter_map(|p| p.bounds)
+            .reduce(|a, b| a.union(&b))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum AlphaMode {
+    #[default]
+    Opaque,
+    ///fragments below the cutoff are discarded
+    Mask(f32),
+    Blend,
+}
+
+impl AlphaMode {
+    pub fn blend_mode(&self) -> BlendMode {
+        match self {
+            AlphaMode::Blend => BlendMode::Alpha,
+            _ => BlendMode::Opaque,
+        }
+    }
+}
+
+///pbr metallic roughness parameters, the texture fields index into `Model::images`
+#[derive(Clone, Debug)]
+pub struct MeshMaterial {
+    pub name: String,
+    pub base_color: Vec4,
+    pub base_color_texture: Option<usize>,
+    pub metallic: f32,
+    pub roughness: f32,
+    ///metalness in blue, roughness in green
+    pub metallic_roughness_texture: Option<usize>,
+    pub normal_texture: Option<usize>,
+    pub occlusion_texture: Option<usize>,
+    pub emissive: Vec3,
+    pub emissive_texture: Option<usize>,
+    pub alpha_mode: AlphaMode,
+    pub double_sided: bool,
+}
+
+impl Default for MeshMaterial {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            base_color: Vec4::ONE,
+            base_color_texture: None,
+            metallic: 0.0,
+            roughness: 1.0,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            occlusion_texture: None,
+            emissive: Vec3::ZERO,
+            emissive_texture: None,
+            alpha_mode: AlphaMode::Opaque,
+            double_sided: false,
+        }
+    }
+}
+
+///rgba8, top row first like `Texture2D::from_rgba` wants it
+#[derive(Clone, Debug, Default)]
+pub struct ImageData {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl ImageData {
+    pub fn upload(&self) -> Texture2D {
+        Texture2D::from_rgba(self.width, self.height, &self.pixels)
+    }
+}
+
+///a mesh placed in the scene
+#[derive(Clone, Copy, Debug)]
+pub struct MeshNode {
+    pub mesh: usize,
+    pub transform: Mat4,
+}
+
+///everything read from a model file, still on the cpu
+#[derive(Clone, Debug, Default)]
+pub struct Model {
+    pub meshes: Vec<Mesh>,
+    pub materials: Vec<MeshMaterial>,
+    pub images: Vec<ImageData>,
+    ///obj files get one identity node per mesh
+    pub nodes: Vec<MeshNode>,
+}
+
+impl Model {
+    ///picks the importer from the extension: .gltf, .glb or .obj
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let model = match extension.as_str() {
+            "gltf" | "glb" => gltf::load(path),
+            "obj" => obj::load(path),
+            _ => Err(format!("unsupported model format `{extension}`")),
+        };
+        model.map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    ///a .gltf or .glb file already in memory, `base_dir` is where relative buffer and image uris are looked up
+    pub fn from_gltf_slice(data: &[u8], base_dir: Option<&Path>) -> Result<Self, String> {
+        gltf::from_slice(data, base_dir)
+    }
+
+    ///`base_dir` is where the mtl files and textures are looked up, without one materials are skipped
+    pub fn from_obj_str(source: &str, base_dir: Option<&Path>) -> Result<Self, String> {
+        obj::from_str(source, base_dir)
+    }
+
+    ///bounds of every node in model space
+    pub fn bounds(&self) -> Option<Aabb> {
+        self.nodes
+            .iter()
+            .filter_map(|node| {
+                self.meshes[node.mesh]
+                    .bounds()
+                    .map(|b| b.transformed(&node.transform))
+            })
+            .reduce(|a, b| a.union(&b))
+    }
+
+    ///needs a current gl context
+    pub fn upload(&self) -> GpuModel {
+        let meshes = self
+            .meshes
+            .iter()
+            .map(|mesh| GpuMesh {
+                name: mesh.name.clone(),
+                primitives: mesh.primitives.iter().map(GpuPrimitive::upload).collect(),
+            })
+            .collect();
+        let textures = self
+            .images
+            .iter()
+            .map(|image| Rc::new(image.upload()))
+            .collect();
+        GpuModel {
+            meshes,
+            textures,
+            nodes: self.nodes.clone(),
+        }
+    }
+}
+
+pub struct GpuPrimitive {
+    pub vertex_array: Rc<VertexArray>,
+    pub material: Option<usize>,
+    pub bounds: Option<Aabb>,
+}
+
+impl GpuPrimitive {
+    pub fn upload(primitive: &Primitive) -> Self {
+        let mut vertex_array = VertexArray::new();
+        vertex_array.add_vertex_buffer(VertexBuffer::new(&primitive.vertices, Vertex::layout()));
+        vertex_array.set_index_buffer(IndexBuffer::new(&primitive.indices));
+        vertex_array.unbind();
+        Self {
+            vertex_array: Rc::new(vertex_array),
+            material: primitive.material,
+            bounds: primitive.bounds,
+        }
+    }
+
+    ///with a second, dynamic buffer of per instance attributes. change them with
+    ///`vertex_array.vertex_buffers()[1].set_data`
+    pub fn upload_instanced(primitive: &Primitive, instances: &[Instance]) -> Self {
+        let mut vertex_array = VertexArray::new();
+        vertex_array.add_vertex_buffer(VertexBuffer::new(&primitive.vertices, Vertex::layout()));
+        let instance_buffer =
+            VertexBuffer::with_capacity(std::mem::size_of_val(instances), Instance::layout());
+        instance_buffer.set_data(instances);
+        vertex_array.add_vertex_buffer(instance_buffer);
+        vertex_array.set_index_buffer(IndexBuffer::new(&primitive.indices));
+        vertex_array.unbind();
+        Self {
+            vertex_array: Rc::new(vertex_array),
+            material: primitive.material,
+            bounds: primitive.bounds,
+        }
+    }
+}
+
+pub struct GpuMesh {
+    pub name: String,
+    pub primitives: Vec<GpuPrimitive>,
+}
+
+///the uploaded version of a `Model`, materials stay on the `Model` since they need a shader to become `Material`s
+pub struct GpuModel {
+    pub meshes: Vec<GpuMesh>,
+    ///same order as `Model::images`
+    pub textures: Vec<Rc<Texture2D>>,
+    pub nodes: Vec<MeshNode>,
+}
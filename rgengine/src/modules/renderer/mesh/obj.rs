@@ -0,0 +1,125 @@
+use std::{collections::HashMap, io::Cursor, path::Path};
+
+use crate::math::{Mat4, Vec3};
+
+use super::{AlphaMode, ImageData, Mesh, MeshMaterial, MeshNode, Model, Primitive, Vertex};
+
+pub(super) fn load(path: &Path) -> Result<Model, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    from_str(&source, path.parent())
+}
+
+pub(super) fn from_str(source: &str, base_dir: Option<&Path>) -> Result<Model, String> {
+    let options = tobj::LoadOptions {
+        single_index: true,
+        triangulate: true,
+        ignore_points: true,
+        ignore_lines: true,
+    };
+    let (models, materials) =
+        tobj::load_obj_buf(&mut Cursor::new(source), &options, |mtl| match base_dir {
+            Some(base_dir) => tobj::load_mtl(base_dir.join(mtl)),
+            None => Ok(Default::default()),
+        })
+        .map_err(|e| e.to_string())?;
+    let materials = materials.map_err(|e| format!("failed to load the mtl file: {e}"))?;
+
+    let mut images = Vec::new();
+    let mut image_indices = HashMap::new();
+    let mut texture = |name: &Option<String>| -> Result<Option<usize>, String> {
+        let (Some(name), Some(base_dir)) = (name, base_dir) else {
+            return Ok(None);
+        };
+        if let Some(&index) = image_indices.get(name) {
+            return Ok(Some(index));
+        }
+        let path = base_dir.join(name);
+        let image = image::open(&path)
+            .map_err(|e| format!("{}: {e}", path.display()))?
+            .to_rgba8();
+        images.push(ImageData {
+            name: name.clone(),
+            width: image.width(),
+            height: image.height(),
+            pixels: image.into_raw(),
+        });
+        image_indices.insert(name.clone(), images.len() - 1);
+        Ok(Some(images.len() - 1))
+    };
+
+    let mut mesh_materials = Vec::with_capacity(materials.len());
+    for material in &materials {
+        let alpha = material.dissolve.unwrap_or(1.0);
+        mesh_materials.push(MeshMaterial {
+            name: material.name.clone(),
+            base_color: Vec3::from(material.diffuse.unwrap_or([1.0; 3])).extend(alpha),
+            base_color_texture: texture(&material.diffuse_texture)?,
+            normal_texture: texture(&material.normal_texture)?,
+            //blinn-phong exponent to the roughness that gives about the same highlight
+            roughness: material
+                .shininess
+                .map_or(1.0, |shininess| (2.0 / (shininess.max(0.0) + 2.0)).sqrt()),
+            alpha_mode: if alpha < 1.0 {
+                AlphaMode::Blend
+            } else {
+                AlphaMode::Opaque
+            },
+            ..MeshMaterial::default()
+        });
+    }
+
+    let mut meshes = Vec::with_capacity(models.len());
+    for model in models {
+        let mesh = model.mesh;
+        let has_normals = !mesh.normals.is_empty();
+        let has_uvs = !mesh.texcoords.is_empty();
+        let vertices = mesh
+            .positions
+            .chunks_exact(3)
+            .enumerate()
+            .map(|(i, position)| {
+                let mut vertex = Vertex {
+                    position: [position[0], position[1], position[2]],
+                    ..Vertex::default()
+                };
+                if has_normals {
+                    vertex.normal = [
+                        mesh.normals[i * 3],
+                        mesh.normals[i * 3 + 1],
+                        mesh.normals[i * 3 + 2],
+                    ];
+                }
+                if has_uvs {
+                    vertex.uv = [mesh.texcoords[i * 2], 1.0 - mesh.texcoords[i * 2 + 1]];
+                }
+                if !mesh.vertex_color.is_empty() {
+                    let color = &mesh.vertex_color[i * 3..i * 3 + 3];
+                    vertex.color = [color[0], color[1], color[2], 1.0];
+                }
+                vertex
+            })
+            .collect();
+        let primitive = Primitive {
+            vertices,
+            indices: mesh.indices,
+            material: mesh.material_id.filter(|&id| id < mesh_materials.len()),
+            bounds: None,
+        };
+        meshes.push(Mesh {
+            name: model.name,
+            primitives: vec![primitive.finish(has_normals, false, has_uvs)],
+        });
+    }
+
+    Ok(Model {
+        nodes: (0..meshes.len())
+            .map(|mesh| MeshNode {
+                mesh,
+                transform: Mat4::IDENTITY,
+            })
+            .collect(),
+        meshes,
+        materials: mesh_materials,
+        images,
+    })
+}
@@ -0,0 +1,191 @@
+pub mod buffer;
+pub mod camera;
+pub mod camera_controller;
+pub mod capture;
+pub mod debug_draw;
+pub mod forward;
+pub mod framebuffer;
+pub mod lighting;
+pub mod line_renderer;
+pub mod material;
+pub mod mesh;
+pub mod post_process;
+pub mod render_graph;
+pub mod render_queue;
+pub mod renderer2d;
+pub mod shader;
+pub mod shadows;
+pub mod stats;
+pub mod text;
+pub mod texture;
+pub mod uniform_buffer;
+
+use std::{ffi::c_void, time::Instant};
+
+use buffer::{IndirectBuffer, VertexArray};
+use framebuffer::Framebuffer;
+
+use super::window::WindowManager;
+
+///where the layers draw to, overlays (imgui) always go to the window
+#[derive(Clone, Copy)]
+pub enum RenderTarget<'a> {
+    Window,
+    Framebuffer(&'a Framebuffer),
+}
+
+// very much a skeliton
+// nedd to add alot more to this
+pub struct Renderer {
+    clear_color: [f32; 4],
+    #[cfg(all(feature = "debug-draw", debug_assertions))]
+    debug_draw: Option<debug_draw::DebugDrawRenderer>,
+    last_frame: Option<Instant>,
+}
+
+impl Renderer {
+    pub fn new(window: &mut WindowManager) -> Self {
+        Self::from_loader(|s| window.window.get_proc_address(s) as *const _)
+    }
+
+    ///for a gl context that didn't come from a window, like the headless one the render tests use.
+    ///the context has to be current on this thread
+    pub fn from_loader<F: FnMut(&'static str) -> *const c_void>(loader: F) -> Self {
+        gl::load_with(loader);
+        unsafe {
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            gl::Enable(gl::SCISSOR_TEST);
+        }
+        Self {
+            clear_color: [0.1, 0.1, 0.1, 1.0],
+            #[cfg(all(feature = "debug-draw", debug_assertions))]
+            debug_draw: None,
+            last_frame: None,
+        }
+    }
+
+    pub fn on_window_resize(&mut self, width: u32, height: u32) {
+        unsafe {
+            gl::Viewport(0, 0, width as i32, height as i32);
+            gl::Scissor(0, 0, width as i32, height as i32);
+        }
+    }
+
+    pub fn clear_color(&self) -> [f32; 4] {
+        self.clear_color
+    }
+
+    pub fn set_clear_color(&mut self, color: [f32; 4]) {
+        self.clear_color = color;
+    }
+
+    ///binds and clears the target, the layers render into it until `end_frame`. starts a new
+    ///frame of `stats`
+    pub fn render_frame(&mut self, target: RenderTarget<'_>) {
+        stats::begin_frame();
+        if let RenderTarget::Framebuffer(framebuffer) = target {
+            framebuffer.bind();
+        }
+        self.clear();
+    }
+
+    ///draws the `debug_draw` shapes on top, then resolves a framebuffer target and goes back
+    ///to drawing on the window
+    pub fn end_frame(&mut self, target: RenderTarget<'_>) {
+        let now = Instant::now();
+        let dt = self
+            .last_frame
+            .map_or(0.0, |last| (now - last).as_secs_f32());
+        self.last_frame = Some(now);
+        #[cfg(all(feature = "debug-draw", debug_assertions))]
+        if debug_draw::shape_count() > 0 {
+            self.debug_draw
+                .get_or_insert_with(debug_draw::DebugDrawRenderer::new)
+                .flush(dt);
+        }
+        #[cfg(not(all(feature = "debug-draw", debug_assertions)))]
+        let _ = dt;
+
+        if let RenderTarget::Framebuffer(framebuffer) = target {
+            framebuffer.unbind();
+            framebuffer.resolve();
+            self.clear();
+        }
+    }
+
+    ///the whole index buffer as triangles, arrays without an index buffer draw nothing
+    pub fn draw_indexed(vertex_array: &VertexArray) {
+        Self::draw_instanced(vertex_array, 1);
+    }
+
+    ///the whole index buffer `instance_count` times, the `BufferLayout::instanced` buffers
+    ///of the array step once per instance
+    pub fn draw_instanced(vertex_array: &VertexArray, instance_count: u32) {
+        let Some(index_buffer) = vertex_array.index_buffer() else {
+            return;
+        };
+        vertex_array.bind();
+        unsafe {
+            gl::DrawElementsInstanced(
+                gl::TRIANGLES,
+                index_buffer.count() as i32,
+                gl::UNSIGNED_INT,
+                std::ptr::null(),
+                instance_count as i32,
+            );
+        }
+        stats::record_draw(index_buffer.count() as u64 / 3 * instance_count as u64);
+    }
+
+    ///`count` indices from `first_index` on, `base_vertex` is added to each of them
+    pub fn draw_indexed_range(
+        vertex_array: &VertexArray,
+        first_index: u32,
+        count: u32,
+        base_vertex: i32,
+    ) {
+        if vertex_array.index_buffer().is_none() || count == 0 {
+            return;
+        }
+        vertex_array.bind();
+        unsafe {
+            gl::DrawElementsBaseVertex(
+                gl::TRIANGLES,
+                count as i32,
+                gl::UNSIGNED_INT,
+                (first_index as usize * std::mem::size_of::<u32>()) as *const c_void,
+                base_vertex,
+            );
+        }
+        stats::record_draw(count as u64 / 3);
+    }
+
+    ///every command in `commands` with one call, they index into the array's index buffer
+    pub fn draw_indirect(vertex_array: &VertexArray, commands: &IndirectBuffer) {
+        if vertex_array.index_buffer().is_none() || commands.count() == 0 {
+            return;
+        }
+        vertex_array.bind();
+        commands.bind();
+        unsafe {
+            gl::MultiDrawElementsIndirect(
+                gl::TRIANGLES,
+                gl::UNSIGNED_INT,
+                std::ptr::null(),
+                commands.count() as i32,
+                0,
+            );
+            gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, 0);
+        }
+        stats::record_draw(commands.triangle_count());
+    }
+
+    fn clear(&self) {
+        let [r, g, b, a] = self.clear_color;
+        unsafe {
+            gl::ClearColor(r, g, b, a);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+    }
+}
@@ -0,0 +1,894 @@
+use std::{any::Any, path::Path, rc::Rc};
+
+use crate::{
+    events::RGEvent,
+    math::{Vec2, Vec3, Vec4},
+};
+
+use super::{
+    buffer::VertexArray,
+    framebuffer::{Framebuffer, FramebufferSpec, FramebufferTextureFormat},
+    shader::Shader,
+    stats,
+    texture::{Texture2D, TextureFilter, TextureWrap},
+};
+
+const MAX_BLOOM_ITERATIONS: usize = 8;
+
+///one triangle covering the screen, the corners come from gl_VertexID so no buffers are needed
+const SCREEN_VERTEX_SHADER: &str = r#"
+#version 450 core
+out vec2 v_TexCoord;
+
+void main() {
+    vec2 position = vec2((gl_VertexID << 1) & 2, gl_VertexID & 2);
+    v_TexCoord = position;
+    gl_Position = vec4(position * 2.0 - 1.0, 0.0, 1.0);
+}
+"#;
+
+const COPY_SHADER: &str = r#"
+#version 450 core
+layout(location = 0) out vec4 o_Color;
+
+in vec2 v_TexCoord;
+
+layout(binding = 0) uniform sampler2D u_Input;
+
+void main() {
+    o_Color = vec4(texture(u_Input, v_TexCoord).rgb, 1.0);
+}
+"#;
+
+//4 bilinear taps around the center, an average of 16 texels
+const BLOOM_DOWNSAMPLE_SHADER: &str = r#"
+#version 450 core
+layout(location = 0) out vec4 o_Color;
+
+in vec2 v_TexCoord;
+
+layout(binding = 0) uniform sampler2D u_Input;
+uniform vec2 u_TexelSize;
+uniform int u_Prefilter;
+//threshold, threshold - knee, knee * 2, 0.25 / knee
+uniform vec4 u_Threshold;
+
+vec3 prefilter(vec3 color) {
+    float brightness = max(color.r, max(color.g, color.b));
+    float soft = clamp(brightness - u_Threshold.y, 0.0, u_Threshold.z);
+    soft = soft * soft * u_Threshold.w;
+    float contribution = max(soft, brightness - u_Threshold.x) / max(brightness, 1e-5);
+    return color * contribution;
+}
+
+void main() {
+    vec4 d = u_TexelSize.xyxy * vec4(-1.0, -1.0, 1.0, 1.0);
+    vec3 color = texture(u_Input, v_TexCoord + d.xy).rgb;
+    color += texture(u_Input, v_TexCoord + d.zy).rgb;
+    color += texture(u_Input, v_TexCoord + d.xw).rgb;
+    color += texture(u_Input, v_TexCoord + d.zw).rgb;
+    color *= 0.25;
+    if (u_Prefilter == 1) {
+        color = prefilter(color);
+    }
+    o_Color = vec4(color, 1.0);
+}
+"#;
+
+//3x3 tent filter, blended additively onto the next bigger mip
+const BLOOM_UPSAMPLE_SHADER: &str = r#"
+#version 450 core
+layout(location = 0) out vec4 o_Color;
+
+in vec2 v_TexCoord;
+
+layout(binding = 0) uniform sampler2D u_Input;
+uniform vec2 u_TexelSize;
+uniform float u_Radius;
+
+void main() {
+    vec4 d = u_TexelSize.xyxy * vec4(1.0, 1.0, -1.0, 0.0) * u_Radius;
+    vec3 color = texture(u_Input, v_TexCoord - d.xy).rgb;
+    color += texture(u_Input, v_TexCoord - d.wy).rgb * 2.0;
+    color += texture(u_Input, v_TexCoord - d.zy).rgb;
+    color += texture(u_Input, v_TexCoord + d.zw).rgb * 2.0;
+    color += texture(u_Input, v_TexCoord).rgb * 4.0;
+    color += texture(u_Input, v_TexCoord + d.xw).rgb * 2.0;
+    color += texture(u_Input, v_TexCoord + d.zy).rgb;
+    color += texture(u_Input, v_TexCoord + d.wy).rgb * 2.0;
+    color += texture(u_Input, v_TexCoord + d.xy).rgb;
+    o_Color = vec4(color / 16.0, 1.0);
+}
+"#;
+
+const BLOOM_COMPOSITE_SHADER: &str = r#"
+#version 450 core
+layout(location = 0) out vec4 o_Color;
+
+in vec2 v_TexCoord;
+
+layout(binding = 0) uniform sampler2D u_Input;
+layout(binding = 1) uniform sampler2D u_Bloom;
+uniform float u_Intensity;
+
+void main() {
+    vec3 bloom = texture(u_Bloom, v_TexCoord).rgb;
+    o_Color = vec4(texture(u_Input, v_TexCoord).rgb + bloom * u_Intensity, 1.0);
+}
+"#;
+
+const TONEMAP_SHADER: &str = r#"
+#version 450 core
+layout(location = 0) out vec4 o_Color;
+
+in vec2 v_TexCoord;
+
+layout(binding = 0) uniform sampler2D u_Input;
+uniform float u_Exposure;
+uniform int u_Operator;
+uniform float u_Gamma;
+
+//narkowicz's fit of the aces filmic curve
+vec3 aces(vec3 x) {
+    return clamp((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14), 0.0, 1.0);
+}
+
+void main() {
+    vec3 color = texture(u_Input, v_TexCoord).rgb * u_Exposure;
+    switch (u_Operator) {
+        case 1: color = color / (color + 1.0); break;
+        case 2: color = aces(color); break;
+        default: color = clamp(color, 0.0, 1.0); break;
+    }
+    o_Color = vec4(pow(color, vec3(1.0 / u_Gamma)), 1.0);
+}
+"#;
+
+//the lut is a horizontal strip of `size` slices of size x size, blue picks the slice
+const COLOR_GRADING_SHADER: &str = r#"
+#version 450 core
+layout(location = 0) out vec4 o_Color;
+
+in vec2 v_TexCoord;
+
+layout(binding = 0) uniform sampler2D u_Input;
+layout(binding = 1) uniform sampler2D u_Lut;
+uniform float u_LutSize;
+uniform float u_Contribution;
+
+vec3 grade(vec3 color) {
+    float scale = u_LutSize - 1.0;
+    float slice = color.b * scale;
+    float slice0 = floor(slice);
+    float slice1 = min(slice0 + 1.0, scale);
+    float x = (color.r * scale + 0.5) / (u_LutSize * u_LutSize);
+    float y = (color.g * scale + 0.5) / u_LutSize;
+    vec3 a = texture(u_Lut, vec2(x + slice0 / u_LutSize, y)).rgb;
+    vec3 b = texture(u_Lut, vec2(x + slice1 / u_LutSize, y)).rgb;
+    return mix(a, b, slice - slice0);
+}
+
+void main() {
+    vec3 color = clamp(texture(u_Input, v_TexCoord).rgb, 0.0, 1.0);
+    o_Color = vec4(mix(color, grade(color), u_Contribution), 1.0);
+}
+"#;
+
+const VIGNETTE_SHADER: &str = r#"
+#version 450 core
+layout(location = 0) out vec4 o_Color;
+
+in vec2 v_TexCoord;
+
+layout(binding = 0) uniform sampler2D u_Input;
+uniform float u_Intensity;
+uniform float u_Radius;
+uniform float u_Smoothness;
+uniform vec3 u_Color;
+
+void main() {
+    vec3 color = texture(u_Input, v_TexCoord).rgb;
+    float distance = length((v_TexCoord - 0.5) * 2.0);
+    float inside = smoothstep(u_Radius + u_Smoothness, u_Radius, distance);
+    o_Color = vec4(mix(u_Color, color, mix(1.0 - u_Intensity, 1.0, inside)), 1.0);
+}
+"#;
+
+//the small fxaa from the nvidia whitepaper, blurs along the edge direction found from luma
+const FXAA_SHADER: &str = r#"
+#version 450 core
+layout(location = 0) out vec4 o_Color;
+
+in vec2 v_TexCoord;
+
+layout(binding = 0) uniform sampler2D u_Input;
+uniform vec2 u_TexelSize;
+uniform float u_SpanMax;
+uniform float u_ReduceMul;
+uniform float u_ReduceMin;
+
+void main() {
+    const vec3 to_luma = vec3(0.299, 0.587, 0.114);
+    vec3 rgb_m = texture(u_Input, v_TexCoord).rgb;
+    float luma_nw = dot(texture(u_Input, v_TexCoord + vec2(-1.0, -1.0) * u_TexelSize).rgb, to_luma);
+    float luma_ne = dot(texture(u_Input, v_TexCoord + vec2(1.0, -1.0) * u_TexelSize).rgb, to_luma);
+    float luma_sw = dot(texture(u_Input, v_TexCoord + vec2(-1.0, 1.0) * u_TexelSize).rgb, to_luma);
+    float luma_se = dot(texture(u_Input, v_TexCoord + vec2(1.0, 1.0) * u_TexelSize).rgb, to_luma);
+    float luma_m = dot(rgb_m, to_luma);
+    float luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    float luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+    vec2 direction = vec2(
+        -((luma_nw + luma_ne) - (luma_sw + luma_se)),
+        (luma_nw + luma_sw) - (luma_ne + luma_se)
+    );
+    float reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * u_ReduceMul, u_ReduceMin);
+    float scale = 1.0 / (min(abs(direction.x), abs(direction.y)) + reduce);
+    direction = clamp(direction * scale, -u_SpanMax, u_SpanMax) * u_TexelSize;
+
+    vec3 rgb_a = 0.5 * (texture(u_Input, v_TexCoord + direction * (1.0 / 3.0 - 0.5)).rgb
+        + texture(u_Input, v_TexCoord + direction * (2.0 / 3.0 - 0.5)).rgb);
+    vec3 rgb_b = rgb_a * 0.5 + 0.25 * (texture(u_Input, v_TexCoord - direction * 0.5).rgb
+        + texture(u_Input, v_TexCoord + direction * 0.5).rgb);
+    float luma_b = dot(rgb_b, to_luma);
+    o_Color = vec4(luma_b < luma_min || luma_b > luma_max ? rgb_a : rgb_b, 1.0);
+}
+"#;
+
+fn screen_shader(name: &str, fragment: &str) -> Shader {
+    Shader::from_source(name, SCREEN_VERTEX_SHADER, fragment)
+        .unwrap_or_else(|e| panic!("failed to build a post process shader: {e}"))
+}
+
+///what an effect draws with, the framebuffer it writes to is already bound
+pub struct PostContext<'a> {
+    ///the previous pass, or the scene for the first one
+    pub input: u32,
+    pub width: u32,
+    pub height: u32,
+    screen: &'a VertexArray,
+}
+
+impl PostContext<'_> {
+    pub fn texel_size(&self) -> Vec2 {
+        Vec2::new(1.0 / self.width as f32, 1.0 / self.height as f32)
+    }
+
+    ///draws `input` over the whole target with the bound shader
+    pub fn draw(&self) {
+        self.draw_texture(self.input);
+    }
+
+    ///same as `draw` with another texture on unit 0, for effects with passes of their own
+    pub fn draw_texture(&self, texture: u32) {
+        self.screen.bind();
+        unsafe {
+            gl::BindTextureUnit(0, texture);
+            gl::DrawArrays(gl::TRIANGLES, 0, 3);
+        }
+        stats::record_state_change();
+        stats::record_draw(1);
+    }
+}
+
+///one step of a `PostProcessStack`, it reads `PostContext::input` and writes the bound framebuffer
+pub trait PostEffect: Any {
+    ///used by `PostProcessStack::set_enabled` and `remove`
+    fn name(&self) -> &str;
+    fn enabled(&self) -> bool;
+    fn set_enabled(&mut self, enabled: bool);
+    fn render(&mut self, context: &PostContext);
+}
+
+///glow around everything brighter than `threshold`, blurred through a chain of half size mips
+pub struct Bloom {
+    pub enabled: bool,
+    ///brightness where the glow starts, in hdr units
+    pub threshold: f32,
+    ///softens the cut at the threshold, 0 is a hard cut
+    pub knee: f32,
+    pub intensity: f32,
+    ///upsample filter radius in texels
+    pub radius: f32,
+    ///number of mips, more spreads the glow further. at most 8
+    pub iterations: usize,
+    downsample: Shader,
+    upsample: Shader,
+    composite: Shader,
+    mips: Vec<Framebuffer>,
+    allocated: (u32, u32, usize),
+}
+
+impl Bloom {
+    ///needs a current gl context
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            threshold: 1.0,
+            knee: 0.5,
+            intensity: 0.6,
+            radius: 1.0,
+            iterations: 5,
+            downsample: screen_shader("BloomDownsample", BLOOM_DOWNSAMPLE_SHADER),
+            upsample: screen_shader("BloomUpsample", BLOOM_UPSAMPLE_SHADER),
+            composite: screen_shader("BloomComposite", BLOOM_COMPOSITE_SHADER),
+            mips: Vec::new(),
+            allocated: (0, 0, 0),
+        }
+    }
+
+    fn allocate(&mut self, width: u32, height: u32) {
+        let iterations = self.iterations.clamp(1, MAX_BLOOM_ITERATIONS);
+        if self.allocated == (width, height, iterations) {
+            return;
+        }
+        self.mips.clear();
+        let (mut w, mut h) = (width, height);
+        for _ in 0..iterations {
+            w = (w / 2).max(1);
+            h = (h / 2).max(1);
+            self.mips.push(Framebuffer::new(FramebufferSpec::new(
+                w,
+                h,
+                vec![FramebufferTextureFormat::Rgba16F],
+            )));
+            if w == 1 && h == 1 {
+                break;
+            }
+        }
+        self.allocated = (width, height, iterations);
+    }
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PostEffect for Bloom {
+    fn name(&self) -> &str {
+        "Bloom"
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn render(&mut self, context: &PostContext) {
+        self.allocate(context.width, context.height);
+
+        let knee = self.knee.max(0.0);
+        self.downsample.bind();
+        self.downsample.set_float4(
+            "u_Threshold",
+            Vec4::new(
+                self.threshold,
+                self.threshold - knee,
+                knee * 2.0,
+                0.25 / (knee + 1e-5),
+            ),
+        );
+        let mut input = context.input;
+        let mut texel_size = context.texel_size();
+        for (index, mip) in self.mips.iter().enumerate() {
+            mip.bind();
+            self.downsample.set_float2("u_TexelSize", texel_size);
+            self.downsample.set_int("u_Prefilter", (index == 0) as i32);
+            context.draw_texture(input);
+            mip.unbind();
+            input = mip.color_attachment_id(0);
+            texel_size = Vec2::new(1.0 / mip.width() as f32, 1.0 / mip.height() as f32);
+        }
+
+        self.upsample.bind();
+        self.upsample.set_float("u_Radius", self.radius);
+        unsafe {
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::ONE, gl::ONE);
+        }
+        for pair in self.mips.windows(2).rev() {
+            let (target, source) = (&pair[0], &pair[1]);
+            target.bind();
+            self.upsample.set_float2(
+                "u_TexelSize",
+                Vec2::new(1.0 / source.width() as f32, 1.0 / source.height() as f32),
+            );
+            context.draw_texture(source.color_attachment_id(0));
+            target.unbind();
+        }
+        unsafe {
+            gl::Disable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+        }
+
+        self.composite.bind();
+        self.composite.set_float("u_Intensity", self.intensity);
+        unsafe { gl::BindTextureUnit(1, self.mips[0].color_attachment_id(0)) };
+        context.draw();
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TonemapOperator {
+    ///clamps to 1
+    None,
+    Reinhard,
+    #[default]
+    Aces,
+}
+
+///maps hdr to 0..1 and encodes to srgb, effects after it work on display colors
+pub struct Tonemapping {
+    pub enabled: bool,
+    ///in stops, +1 doubles the brightness
+    pub exposure: f32,
+    pub operator: TonemapOperator,
+    pub gamma: f32,
+    shader: Shader,
+}
+
+impl Tonemapping {
+    ///needs a current gl context
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            exposure: 0.0,
+            operator: TonemapOperator::default(),
+            gamma: 2.2,
+            shader: screen_shader("Tonemapping", TONEMAP_SHADER),
+        }
+    }
+}
+
+impl Default for Tonemapping {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PostEffect for Tonemapping {
+    fn name(&self) -> &str {
+        "Tonemapping"
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn render(&mut self, context: &PostContext) {
+        self.shader.bind();
+        self.shader.set_float("u_Exposure", self.exposure.exp2());
+        self.shader.set_int("u_Operator", self.operator as i32);
+        self.shader.set_float("u_Gamma", self.gamma.max(0.01));
+        context.draw();
+    }
+}
+
+///pixels of a lut that changes nothing, `size` slices of size x size side by side with
+///red going right in a slice, green going down and blue picking the slice.
+///save it, grade it in an image editor and load it back with `ColorGrading::load_lut`
+pub fn neutral_lut(size: u32) -> Vec<u8> {
+    let scale = 255.0 / (size.max(2) - 1) as f32;
+    let mut pixels = Vec::with_capacity((size * size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size * size {
+            let (r, b) = (x % size, x / size);
+            pixels.extend_from_slice(&[
+                (r as f32 * scale).round() as u8,
+                (y as f32 * scale).round() as u8,
+                (b as f32 * scale).round() as u8,
+                255,
+            ]);
+        }
+    }
+    pixels
+}
+
+///remaps display colors through a lut, put it after `Tonemapping`.
+///off until a lut is set
+pub struct ColorGrading {
+    pub enabled: bool,
+    ///0 keeps the input, 1 is fully graded
+    pub contribution: f32,
+    lut: Option<Rc<Texture2D>>,
+    shader: Shader,
+}
+
+impl ColorGrading {
+    ///needs a current gl context
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            contribution: 1.0,
+            lut: None,
+            shader: screen_shader("ColorGrading", COLOR_GRADING_SHADER),
+        }
+    }
+
+    ///a strip laid out like `neutral_lut`, its height is the lut size. turns the effect on
+    pub fn set_lut(&mut self, lut: Rc<Texture2D>) -> Result<(), String> {
+        let size = lut.height();
+        if size < 2 || lut.width() != size * size {
+            return Err(format!(
+                "a lut has to be size * size by size pixels, got {}x{}",
+                lut.width(),
+                lut.height()
+            ));
+        }
+        lut.set_filter(TextureFilter::Linear);
+        lut.set_wrap(TextureWrap::ClampToEdge);
+        self.lut = Some(lut);
+        self.enabled = true;
+        Ok(())
+    }
+
+    pub fn load_lut<P: AsRef<Path>>(&mut self, path: P) -> Result<(), String> {
+        let path = path.as_ref();
+        let texture = Texture2D::from_file(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        self.set_lut(Rc::new(texture))
+            .map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    pub fn lut(&self) -> Option<&Rc<Texture2D>> {
+        self.lut.as_ref()
+    }
+
+    pub fn clear_lut(&mut self) {
+        self.lut = None;
+        self.enabled = false;
+    }
+}
+
+impl Default for ColorGrading {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PostEffect for ColorGrading {
+    fn name(&self) -> &str {
+        "ColorGrading"
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn render(&mut self, context: &PostContext) {
+        self.shader.bind();
+        match &self.lut {
+            Some(lut) => {
+                lut.bind(1);
+                self.shader.set_float("u_LutSize", lut.height() as f32);
+                self.shader
+                    .set_float("u_Contribution", self.contribution.clamp(0.0, 1.0));
+            }
+            None => {
+                self.shader.set_float("u_LutSize", 2.0);
+                self.shader.set_float("u_Contribution", 0.0);
+            }
+        }
+        context.draw();
+    }
+}
+
+///darkens towards the corners
+pub struct Vignette {
+    pub enabled: bool,
+    ///how dark the corners get, 0 to 1
+    pub intensity: f32,
+    ///where the darkening starts, 1 is the middle of the edges
+    pub radius: f32,
+    ///width of the fade
+    pub smoothness: f32,
+    pub color: Vec3,
+    shader: Shader,
+}
+
+impl Vignette {
+    ///needs a current gl context
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            intensity: 0.35,
+            radius: 0.8,
+            smoothness: 0.6,
+            color: Vec3::ZERO,
+            shader: screen_shader("Vignette", VIGNETTE_SHADER),
+        }
+    }
+}
+
+impl Default for Vignette {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PostEffect for Vignette {
+    fn name(&self) -> &str {
+        "Vignette"
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn render(&mut self, context: &PostContext) {
+        self.shader.bind();
+        self.shader
+            .set_float("u_Intensity", self.intensity.clamp(0.0, 1.0));
+        self.shader.set_float("u_Radius", self.radius);
+        self.shader
+            .set_float("u_Smoothness", self.smoothness.max(1e-3));
+        self.shader.set_float3("u_Color", self.color);
+        context.draw();
+    }
+}
+
+///fast approximate anti aliasing, put it last since it works on display colors
+pub struct Fxaa {
+    pub enabled: bool,
+    ///longest blur along an edge in pixels
+    pub span_max: f32,
+    pub reduce_mul: f32,
+    pub reduce_min: f32,
+    shader: Shader,
+}
+
+impl Fxaa {
+    ///needs a current gl context
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            span_max: 8.0,
+            reduce_mul: 1.0 / 8.0,
+            reduce_min: 1.0 / 128.0,
+            shader: screen_shader("Fxaa", FXAA_SHADER),
+        }
+    }
+}
+
+impl Default for Fxaa {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PostEffect for Fxaa {
+    fn name(&self) -> &str {
+        "Fxaa"
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn render(&mut self, context: &PostContext) {
+        self.shader.bind();
+        self.shader.set_float2("u_TexelSize", context.texel_size());
+        self.shader.set_float("u_SpanMax", self.span_max);
+        self.shader.set_float("u_ReduceMul", self.reduce_mul);
+        self.shader.set_float("u_ReduceMin", self.reduce_min);
+        context.draw();
+    }
+}
+
+///renders the scene into an hdr framebuffer and runs the enabled effects over it in order,
+///the last one writes to whatever was bound at `begin`. resizes itself on `WindowResize`,
+///call `resize` when it draws into a framebuffer that isn't window sized
+pub struct PostProcessStack {
+    scene: Framebuffer,
+    //ping pong targets between effects
+    targets: [Framebuffer; 2],
+    screen: VertexArray,
+    copy_shader: Shader,
+    effects: Vec<Box<dyn PostEffect>>,
+    active: bool,
+}
+
+impl PostProcessStack {
+    ///no effects, the scene is copied straight out. `samples` is the msaa of the scene target.
+    ///needs a current gl context
+    pub fn new(width: u32, height: u32, samples: u32) -> Self {
+        let color = || {
+            Framebuffer::new(FramebufferSpec::new(
+                width,
+                height,
+                vec![FramebufferTextureFormat::Rgba16F],
+            ))
+        };
+        Self {
+            scene: Framebuffer::new(
+                FramebufferSpec::new(
+                    width,
+                    height,
+                    vec![
+                        FramebufferTextureFormat::Rgba16F,
+                        FramebufferTextureFormat::Depth24Stencil8,
+                    ],
+                )
+                .samples(samples),
+            ),
+            targets: [color(), color()],
+            screen: VertexArray::new(),
+            copy_shader: screen_shader("Copy", COPY_SHADER),
+            effects: Vec::new(),
+            active: false,
+        }
+    }
+
+    ///bloom, tonemapping, color grading (off until it has a lut), vignette and fxaa
+    pub fn with_default_effects(width: u32, height: u32, samples: u32) -> Self {
+        let mut stack = Self::new(width, height, samples);
+        stack.push(Box::new(Bloom::new()));
+        stack.push(Box::new(Tonemapping::new()));
+        stack.push(Box::new(ColorGrading::new()));
+        stack.push(Box::new(Vignette::new()));
+        stack.push(Box::new(Fxaa::new()));
+        stack
+    }
+
+    pub fn width(&self) -> u32 {
+        self.scene.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.scene.height()
+    }
+
+    ///the hdr target the scene is drawn into
+    pub fn scene(&self) -> &Framebuffer {
+        &self.scene
+    }
+
+    pub fn push(&mut self, effect: Box<dyn PostEffect>) {
+        self.effects.push(effect);
+    }
+
+    pub fn insert(&mut self, index: usize, effect: Box<dyn PostEffect>) {
+        self.effects.insert(index, effect);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Box<dyn PostEffect>> {
+        let index = self.effects.iter().position(|e| e.name() == name)?;
+        Some(self.effects.remove(index))
+    }
+
+    ///in the order they run
+    pub fn effects(&self) -> &[Box<dyn PostEffect>] {
+        &self.effects
+    }
+
+    ///can be reordered in place
+    pub fn effects_mut(&mut self) -> &mut [Box<dyn PostEffect>] {
+        &mut self.effects
+    }
+
+    ///the first effect of type `T`, for tuning it
+    pub fn effect<T: PostEffect>(&self) -> Option<&T> {
+        self.effects
+            .iter()
+            .find_map(|e| (e.as_ref() as &dyn Any).downcast_ref())
+    }
+
+    pub fn effect_mut<T: PostEffect>(&mut self) -> Option<&mut T> {
+        self.effects
+            .iter_mut()
+            .find_map(|e| (e.as_mut() as &mut dyn Any).downcast_mut())
+    }
+
+    ///false if there is no effect with that name
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        match self.effects.iter_mut().find(|e| e.name() == name) {
+            Some(effect) => {
+                effect.set_enabled(enabled);
+                true
+            }
+            None => false,
+        }
+    }
+
+    ///msaa of the scene target, the effects run on the resolved image
+    pub fn set_samples(&mut self, samples: u32) {
+        self.scene.set_samples(samples);
+    }
+
+    pub fn samples(&self) -> u32 {
+        self.scene.spec().samples
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.scene.resize(width, height);
+        for target in &mut self.targets {
+            target.resize(width, height);
+        }
+    }
+
+    pub fn on_event(&mut self, event: &RGEvent) {
+        if let RGEvent::WindowResize(e) = event {
+            self.resize(e.width as u32, e.height as u32);
+        }
+    }
+
+    ///binds and clears the hdr target, draw the scene until `end`
+    pub fn begin(&mut self, clear_color: [f32; 4]) {
+        self.scene.bind();
+        self.scene.clear_color_attachment(0, clear_color);
+        self.scene.clear_depth(1.0, 0);
+        self.active = true;
+    }
+
+    ///runs the effects into the framebuffer that was bound before `begin`.
+    ///leaves blending on with depth testing and culling off
+    pub fn end(&mut self) {
+        if !std::mem::take(&mut self.active) {
+            return;
+        }
+        self.scene.unbind();
+        self.scene.resolve();
+        unsafe {
+            gl::Disable(gl::BLEND);
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Disable(gl::CULL_FACE);
+        }
+
+        let (width, height) = (self.width(), self.height());
+        let mut input = self.scene.color_attachment_id(0);
+        let enabled: Vec<usize> = (0..self.effects.len())
+            .filter(|&i| self.effects[i].enabled())
+            .collect();
+        if enabled.is_empty() {
+            self.copy_shader.bind();
+            PostContext {
+                input,
+                width,
+                height,
+                screen: &self.screen,
+            }
+            .draw();
+        }
+        for (pass, &index) in enabled.iter().enumerate() {
+            let last = pass + 1 == enabled.len();
+            let target = &self.targets[pass % 2];
+            if !last {
+                target.bind();
+            }
+            let context = PostContext {
+                input,
+                width,
+                height,
+                screen: &self.screen,
+            };
+            self.effects[index].render(&context);
+            if !last {
+                target.unbind();
+                input = target.color_attachment_id(0);
+            }
+        }
+
+        self.screen.unbind();
+        unsafe { gl::Enable(gl::BLEND) };
+    }
+}
@@ -0,0 +1,352 @@
+use std::{collections::HashMap, ffi::c_void};
+
+use gl::types::GLenum;
+
+use super::{Access, Barrier, BufferDesc, ClearValue, GraphBackend, TextureDesc};
+use crate::renderer::framebuffer::{Framebuffer, FramebufferTextureFormat, create_attachment};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GlTexture {
+    Texture {
+        id: u32,
+        width: u32,
+        height: u32,
+        format: FramebufferTextureFormat,
+    },
+    ///the window's default framebuffer, only usable as an attachment
+    Window { width: u32, height: u32 },
+}
+
+impl GlTexture {
+    ///an attachment of a framebuffer for `RenderGraph::import_texture`, the resolved one with msaa
+    pub fn from_framebuffer(framebuffer: &Framebuffer, index: usize) -> Self {
+        let format = framebuffer
+            .spec()
+            .attachments
+            .iter()
+            .copied()
+            .filter(|f| !f.is_depth())
+            .nth(index)
+            .expect("framebuffer has no color attachment at that index");
+        GlTexture::Texture {
+            id: framebuffer.color_attachment_id(index),
+            width: framebuffer.width(),
+            height: framebuffer.height(),
+            format,
+        }
+    }
+
+    ///0 for the window
+    pub fn id(&self) -> u32 {
+        match self {
+            GlTexture::Texture { id, .. } => *id,
+            GlTexture::Window { .. } => 0,
+        }
+    }
+
+    fn size(&self) -> (u32, u32) {
+        match *self {
+            GlTexture::Texture { width, height, .. } | GlTexture::Window { width, height } => {
+                (width, height)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GlBuffer {
+    pub id: u32,
+    pub size: usize,
+}
+
+///keeps the allocations of the last graph by slot and reuses them while the descs match,
+///framebuffers for the attachment combinations are cached the same way
+#[derive(Default)]
+pub struct GlBackend {
+    textures: Vec<Option<(TextureDesc, u32)>>,
+    buffers: Vec<Option<(BufferDesc, u32)>>,
+    framebuffers: HashMap<Vec<u32>, u32>,
+    previous: Option<(i32, [i32; 4], [i32; 4])>,
+}
+
+impl GlBackend {
+    ///needs a current gl context
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn framebuffer(&mut self, attachments: &[&GlTexture]) -> u32 {
+        let key: Vec<u32> = attachments.iter().map(|a| a.id()).collect();
+        if let Some(&id) = self.framebuffers.get(&key) {
+            return id;
+        }
+        let mut id = 0;
+        unsafe {
+            gl::CreateFramebuffers(1, &mut id);
+            let mut color = 0;
+            for attachment in attachments {
+                let GlTexture::Texture {
+                    id: texture,
+                    format,
+                    ..
+                } = **attachment
+                else {
+                    continue;
+                };
+                let point = if format.is_depth() {
+                    format.depth_attachment_point()
+                } else {
+                    color += 1;
+                    gl::COLOR_ATTACHMENT0 + color - 1
+                };
+                gl::NamedFramebufferTexture(id, point, texture, 0);
+            }
+            let buffers: Vec<GLenum> = (0..color).map(|i| gl::COLOR_ATTACHMENT0 + i).collect();
+            if buffers.is_empty() {
+                gl::NamedFramebufferDrawBuffer(id, gl::NONE);
+            } else {
+                gl::NamedFramebufferDrawBuffers(id, buffers.len() as i32, buffers.as_ptr());
+            }
+            let status = gl::CheckNamedFramebufferStatus(id, gl::FRAMEBUFFER);
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                crate::log::rge_engine_error!(
+                    "render graph framebuffer is incomplete: {:#x}",
+                    status
+                );
+            }
+        }
+        self.framebuffers.insert(key, id);
+        id
+    }
+
+    ///framebuffers that use a deleted texture would point at nothing
+    fn forget_framebuffers_with(&mut self, texture: u32) {
+        self.framebuffers.retain(|attachments, id| {
+            let keep = !attachments.contains(&texture);
+            if !keep {
+                unsafe { gl::DeleteFramebuffers(1, id) };
+            }
+            keep
+        });
+    }
+}
+
+impl GraphBackend for GlBackend {
+    type Texture = GlTexture;
+    type Buffer = GlBuffer;
+
+    fn create_texture(&mut self, slot: usize, _name: &str, desc: &TextureDesc) -> GlTexture {
+        if self.textures.len() <= slot {
+            self.textures.resize(slot + 1, None);
+        }
+        let matches = |cached: &TextureDesc| {
+            (cached.width, cached.height, cached.format, cached.samples)
+                == (desc.width, desc.height, desc.format, desc.samples)
+        };
+        let id = match self.textures[slot] {
+            Some((cached, id)) if matches(&cached) => id,
+            previous => {
+                if let Some((_, old)) = previous {
+                    unsafe { gl::DeleteTextures(1, &old) };
+                    self.forget_framebuffers_with(old);
+                }
+                let target = if desc.samples > 1 {
+                    gl::TEXTURE_2D_MULTISAMPLE
+                } else {
+                    gl::TEXTURE_2D
+                };
+                let id = unsafe {
+                    create_attachment(
+                        target,
+                        desc.format,
+                        desc.samples as i32,
+                        desc.width as i32,
+                        desc.height as i32,
+                    )
+                };
+                self.textures[slot] = Some((*desc, id));
+                id
+            }
+        };
+        GlTexture::Texture {
+            id,
+            width: desc.width,
+            height: desc.height,
+            format: desc.format,
+        }
+    }
+
+    fn create_buffer(&mut self, slot: usize, _name: &str, desc: &BufferDesc) -> GlBuffer {
+        if self.buffers.len() <= slot {
+            self.buffers.resize(slot + 1, None);
+        }
+        let id = match self.buffers[slot] {
+            Some((cached, id)) if cached.size == desc.size => id,
+            previous => {
+                let mut id = 0;
+                unsafe {
+                    if let Some((_, old)) = previous {
+                        gl::DeleteBuffers(1, &old);
+                    }
+                    gl::CreateBuffers(1, &mut id);
+                    gl::NamedBufferStorage(
+                        id,
+                        desc.size as isize,
+                        std::ptr::null(),
+                        gl::DYNAMIC_STORAGE_BIT,
+                    );
+                }
+                self.buffers[slot] = Some((*desc, id));
+                id
+            }
+        };
+        GlBuffer {
+            id,
+            size: desc.size,
+        }
+    }
+
+    fn clear_texture(&mut self, texture: &GlTexture, value: ClearValue) {
+        unsafe {
+            match (*texture, value) {
+                (GlTexture::Window { .. }, ClearValue::Color(color)) => {
+                    gl::ClearNamedFramebufferfv(0, gl::COLOR, 0, color.as_ptr())
+                }
+                (GlTexture::Window { .. }, ClearValue::Depth(depth)) => {
+                    gl::ClearNamedFramebufferfi(0, gl::DEPTH_STENCIL, 0, depth, 0)
+                }
+                (GlTexture::Texture { id, format, .. }, ClearValue::Color(color)) => {
+                    if format == FramebufferTextureFormat::RedInteger {
+                        let value = color[0] as i32;
+                        gl::ClearTexImage(
+                            id,
+                            0,
+                            gl::RED_INTEGER,
+                            gl::INT,
+                            &value as *const i32 as *const c_void,
+                        );
+                    } else {
+                        gl::ClearTexImage(
+                            id,
+                            0,
+                            gl::RGBA,
+                            gl::FLOAT,
+                            color.as_ptr() as *const c_void,
+                        );
+                    }
+                }
+                (GlTexture::Texture { id, format, .. }, ClearValue::Depth(depth)) => {
+                    if format == FramebufferTextureFormat::Depth24Stencil8 {
+                        //float depth followed by 24 unused bits and the stencil
+                        let value = [depth.to_bits(), 0];
+                        gl::ClearTexImage(
+                            id,
+                            0,
+                            gl::DEPTH_STENCIL,
+                            gl::FLOAT_32_UNSIGNED_INT_24_8_REV,
+                            value.as_ptr() as *const c_void,
+                        );
+                    } else {
+                        gl::ClearTexImage(
+                            id,
+                            0,
+                            gl::DEPTH_COMPONENT,
+                            gl::FLOAT,
+                            &depth as *const f32 as *const c_void,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn clear_buffer(&mut self, buffer: &GlBuffer) {
+        unsafe {
+            gl::ClearNamedBufferData(
+                buffer.id,
+                gl::R32UI,
+                gl::RED_INTEGER,
+                gl::UNSIGNED_INT,
+                std::ptr::null(),
+            )
+        }
+    }
+
+    ///gl orders everything itself except reads after incoherent image and storage buffer writes
+    fn barrier(&mut self, barrier: &Barrier) {
+        if barrier.from != Access::Storage {
+            return;
+        }
+        let bits = match barrier.to {
+            Access::Sampled => gl::TEXTURE_FETCH_BARRIER_BIT,
+            Access::Attachment => gl::FRAMEBUFFER_BARRIER_BIT,
+            Access::Storage => gl::SHADER_IMAGE_ACCESS_BARRIER_BIT | gl::SHADER_STORAGE_BARRIER_BIT,
+            Access::Uniform => gl::UNIFORM_BARRIER_BIT,
+            Access::Vertex => gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT | gl::ELEMENT_ARRAY_BARRIER_BIT,
+            Access::Indirect => gl::COMMAND_BARRIER_BIT,
+        };
+        unsafe { gl::MemoryBarrier(bits) }
+    }
+
+    ///binds a framebuffer with the attachments and covers it with the viewport,
+    ///`end_pass` puts the previous framebuffer and viewport back
+    fn begin_pass(&mut self, name: &str, attachments: &[&GlTexture]) {
+        unsafe {
+            gl::PushDebugGroup(
+                gl::DEBUG_SOURCE_APPLICATION,
+                0,
+                name.len() as i32,
+                name.as_ptr() as *const _,
+            );
+        }
+        let Some(first) = attachments.first() else {
+            return;
+        };
+        let framebuffer = if attachments
+            .iter()
+            .any(|a| matches!(a, GlTexture::Window { .. }))
+        {
+            0
+        } else {
+            self.framebuffer(attachments)
+        };
+        let (width, height) = first.size();
+        let mut previous = (0, [0; 4], [0; 4]);
+        unsafe {
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut previous.0);
+            gl::GetIntegerv(gl::VIEWPORT, previous.1.as_mut_ptr());
+            gl::GetIntegerv(gl::SCISSOR_BOX, previous.2.as_mut_ptr());
+            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+            gl::Viewport(0, 0, width as i32, height as i32);
+            gl::Scissor(0, 0, width as i32, height as i32);
+        }
+        self.previous = Some(previous);
+    }
+
+    fn end_pass(&mut self, _name: &str) {
+        if let Some((framebuffer, [x, y, w, h], [sx, sy, sw, sh])) = self.previous.take() {
+            unsafe {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer as u32);
+                gl::Viewport(x, y, w, h);
+                gl::Scissor(sx, sy, sw, sh);
+            }
+        }
+        unsafe { gl::PopDebugGroup() }
+    }
+}
+
+impl Drop for GlBackend {
+    fn drop(&mut self) {
+        unsafe {
+            for (_, id) in self.textures.iter().flatten() {
+                gl::DeleteTextures(1, id);
+            }
+            for (_, id) in self.buffers.iter().flatten() {
+                gl::DeleteBuffers(1, id);
+            }
+            for id in self.framebuffers.values() {
+                gl::DeleteFramebuffers(1, id);
+            }
+        }
+    }
+}
@@ -0,0 +1,886 @@
+//passes declare the textures and buffers they read and write, `compile` orders them, drops the
+//ones nothing uses, lets transient resources that are never alive at the same time share an
+//allocation and works out the clears and barriers. the compiled graph runs on a `GraphBackend`,
+//`GlBackend` for real frames and `NullBackend` to test graphs without a gpu
+mod gl;
+mod null;
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    fmt::Write,
+};
+
+pub use gl::{GlBackend, GlBuffer, GlTexture};
+pub use null::NullBackend;
+
+use super::framebuffer::FramebufferTextureFormat;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ClearValue {
+    Color([f32; 4]),
+    ///stencil is cleared to 0
+    Depth(f32),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TextureDesc {
+    pub width: u32,
+    pub height: u32,
+    pub format: FramebufferTextureFormat,
+    ///1 for no msaa
+    pub samples: u32,
+    ///applied before the first pass that writes it, without one the contents start undefined
+    pub clear: Option<ClearValue>,
+}
+
+impl TextureDesc {
+    pub fn new(width: u32, height: u32, format: FramebufferTextureFormat) -> Self {
+        Self {
+            width,
+            height,
+            format,
+            samples: 1,
+            clear: None,
+        }
+    }
+
+    pub fn samples(mut self, samples: u32) -> Self {
+        self.samples = samples.max(1);
+        self
+    }
+
+    pub fn clear(mut self, value: ClearValue) -> Self {
+        self.clear = Some(value);
+        self
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BufferDesc {
+    pub size: usize,
+    ///filled with zeros before the first pass that writes it
+    pub zeroed: bool,
+}
+
+impl BufferDesc {
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            zeroed: false,
+        }
+    }
+
+    pub fn zeroed(mut self) -> Self {
+        self.zeroed = true;
+        self
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ResourceDesc {
+    Texture(TextureDesc),
+    Buffer(BufferDesc),
+}
+
+impl ResourceDesc {
+    ///whether two transient resources can share an allocation, the clears don't matter
+    fn compatible(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ResourceDesc::Texture(a), ResourceDesc::Texture(b)) => {
+                (a.width, a.height, a.format, a.samples) == (b.width, b.height, b.format, b.samples)
+            }
+            (ResourceDesc::Buffer(a), ResourceDesc::Buffer(b)) => a.size == b.size,
+            _ => false,
+        }
+    }
+
+    fn needs_clear(&self) -> bool {
+        match self {
+            ResourceDesc::Texture(desc) => desc.clear.is_some(),
+            ResourceDesc::Buffer(desc) => desc.zeroed,
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            ResourceDesc::Texture(desc) if desc.samples > 1 => format!(
+                "{}x{} {:?} x{}",
+                desc.width, desc.height, desc.format, desc.samples
+            ),
+            ResourceDesc::Texture(desc) => {
+                format!("{}x{} {:?}", desc.width, desc.height, desc.format)
+            }
+            ResourceDesc::Buffer(desc) => format!("{} bytes", desc.size),
+        }
+    }
+}
+
+///how a pass touches a resource
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Access {
+    ///sampled texture, read only
+    Sampled,
+    ///color or depth attachment of the pass's framebuffer
+    Attachment,
+    ///image load/store or shader storage buffer
+    Storage,
+    ///read only
+    Uniform,
+    ///vertex or index buffer, read only
+    Vertex,
+    ///indirect draw arguments, read only
+    Indirect,
+}
+
+impl Access {
+    fn allowed(self, desc: &ResourceDesc, write: bool) -> bool {
+        match (self, desc) {
+            (Access::Attachment, ResourceDesc::Texture(_)) | (Access::Storage, _) => true,
+            (Access::Sampled, ResourceDesc::Texture(_)) => !write,
+            (Access::Uniform | Access::Vertex | Access::Indirect, ResourceDesc::Buffer(_)) => {
+                !write
+            }
+            _ => false,
+        }
+    }
+}
+
+///one version of a resource, every write makes a new one and reads name the version they want
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ResourceHandle {
+    resource: usize,
+    version: u32,
+}
+
+impl ResourceHandle {
+    pub fn resource(&self) -> usize {
+        self.resource
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    fn previous(&self) -> Self {
+        Self {
+            resource: self.resource,
+            version: self.version - 1,
+        }
+    }
+}
+
+///a pass has to wait for the allocation's previous access to finish
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Barrier {
+    pub resource: String,
+    ///index of the allocation, aliased resources share one
+    pub slot: usize,
+    pub from: Access,
+    pub to: Access,
+}
+
+///what a backend creates, textures and buffers live here while the graph runs
+pub trait GraphBackend {
+    type Texture;
+    type Buffer;
+
+    ///`slot` is the allocation index, the same graph gets the same slots every frame so a
+    ///backend can keep its allocations around and hand them out again
+    fn create_texture(&mut self, slot: usize, name: &str, desc: &TextureDesc) -> Self::Texture;
+    fn create_buffer(&mut self, slot: usize, name: &str, desc: &BufferDesc) -> Self::Buffer;
+    fn clear_texture(&mut self, texture: &Self::Texture, value: ClearValue);
+    fn clear_buffer(&mut self, buffer: &Self::Buffer);
+    fn barrier(&mut self, barrier: &Barrier);
+    ///`attachments` are the textures the pass writes as `Access::Attachment`, in declaration order
+    fn begin_pass(&mut self, name: &str, attachments: &[&Self::Texture]);
+    fn end_pass(&mut self, name: &str);
+}
+
+enum Physical<B: GraphBackend> {
+    Texture(B::Texture),
+    Buffer(B::Buffer),
+}
+
+struct ResourceNode<B: GraphBackend> {
+    name: String,
+    desc: ResourceDesc,
+    ///latest version
+    version: u32,
+    imported: bool,
+    external: Option<Physical<B>>,
+}
+
+type ExecuteFn<'a, B> = Box<dyn FnMut(&mut PassContext<B>) + 'a>;
+
+struct PassNode<'a, B: GraphBackend> {
+    name: String,
+    reads: Vec<(ResourceHandle, Access)>,
+    writes: Vec<(ResourceHandle, Access)>,
+    side_effects: bool,
+    execute: Option<ExecuteFn<'a, B>>,
+}
+
+///built again every frame, `'a` is how long the pass closures borrow for
+pub struct RenderGraph<'a, B: GraphBackend> {
+    resources: Vec<ResourceNode<B>>,
+    passes: Vec<PassNode<'a, B>>,
+    errors: Vec<String>,
+}
+
+impl<'a, B: GraphBackend> Default for RenderGraph<'a, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, B: GraphBackend> RenderGraph<'a, B> {
+    pub fn new() -> Self {
+        Self {
+            resources: Vec::new(),
+            passes: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    ///lives only inside the graph, a pass has to write it before anything reads it
+    pub fn create_texture(&mut self, name: &str, desc: TextureDesc) -> ResourceHandle {
+        self.add_resource(name, ResourceDesc::Texture(desc), None)
+    }
+
+    pub fn create_buffer(&mut self, name: &str, desc: BufferDesc) -> ResourceHandle {
+        self.add_resource(name, ResourceDesc::Buffer(desc), None)
+    }
+
+    ///something from outside like the window or a persistent target. passes writing it are
+    ///never culled and it is never aliased
+    pub fn import_texture(
+        &mut self,
+        name: &str,
+        desc: TextureDesc,
+        texture: B::Texture,
+    ) -> ResourceHandle {
+        self.add_resource(
+            name,
+            ResourceDesc::Texture(desc),
+            Some(Physical::Texture(texture)),
+        )
+    }
+
+    pub fn import_buffer(
+        &mut self,
+        name: &str,
+        desc: BufferDesc,
+        buffer: B::Buffer,
+    ) -> ResourceHandle {
+        self.add_resource(
+            name,
+            ResourceDesc::Buffer(desc),
+            Some(Physical::Buffer(buffer)),
+        )
+    }
+
+    pub fn add_pass(&mut self, name: &str) -> PassBuilder<'_, 'a, B> {
+        self.passes.push(PassNode {
+            name: name.to_string(),
+            reads: Vec::new(),
+            writes: Vec::new(),
+            side_effects: false,
+            execute: None,
+        });
+        PassBuilder {
+            pass: self.passes.len() - 1,
+            graph: self,
+        }
+    }
+
+    fn add_resource(
+        &mut self,
+        name: &str,
+        desc: ResourceDesc,
+        external: Option<Physical<B>>,
+    ) -> ResourceHandle {
+        if let ResourceDesc::Texture(texture) = &desc {
+            let mismatched = match texture.clear {
+                Some(ClearValue::Depth(_)) => !texture.format.is_depth(),
+                Some(ClearValue::Color(_)) => texture.format.is_depth(),
+                None => false,
+            };
+            if mismatched {
+                self.errors.push(format!(
+                    "`{name}` has a clear value that doesn't fit its {:?} format",
+                    texture.format
+                ));
+            }
+        }
+        self.resources.push(ResourceNode {
+            name: name.to_string(),
+            desc,
+            version: 0,
+            imported: external.is_some(),
+            external,
+        });
+        ResourceHandle {
+            resource: self.resources.len() - 1,
+            version: 0,
+        }
+    }
+
+    pub fn compile(self) -> Result<CompiledGraph<'a, B>, String> {
+        if !self.errors.is_empty() {
+            return Err(self.errors.join("\n"));
+        }
+        let pass_count = self.passes.len();
+
+        let mut producers = HashMap::new();
+        let mut readers: HashMap<ResourceHandle, Vec<usize>> = HashMap::new();
+        for (index, pass) in self.passes.iter().enumerate() {
+            for (handle, _) in &pass.writes {
+                producers.insert(*handle, index);
+            }
+            for (handle, _) in &pass.reads {
+                readers.entry(*handle).or_default().push(index);
+            }
+        }
+
+        //data dependencies (a pass needs what another wrote) decide what gets culled, a write
+        //also has to wait for the reads of the version before it but that only affects the order
+        let mut data_deps = vec![Vec::new(); pass_count];
+        let mut order_deps = vec![Vec::new(); pass_count];
+        for (index, pass) in self.passes.iter().enumerate() {
+            for (handle, _) in &pass.reads {
+                match producers.get(handle) {
+                    Some(&producer) if producer != index => data_deps[index].push(producer),
+                    Some(_) => {}
+                    None if self.resources[handle.resource].imported => {}
+                    None => {
+                        return Err(format!(
+                            "pass `{}` reads `{}` before any pass writes it",
+                            pass.name, self.resources[handle.resource].name
+                        ));
+                    }
+                }
+            }
+            for (handle, _) in &pass.writes {
+                let previous = handle.previous();
+                if let Some(&producer) = producers.get(&previous)
+                    && producer != index
+                {
+                    data_deps[index].push(producer);
+                }
+                for &reader in readers.get(&previous).into_iter().flatten() {
+                    if reader != index {
+                        order_deps[index].push(reader);
+                    }
+                }
+            }
+        }
+
+        let mut kept = vec![false; pass_count];
+        let mut stack: Vec<usize> = (0..pass_count)
+            .filter(|&i| {
+                let pass = &self.passes[i];
+                pass.side_effects
+                    || pass
+                        .writes
+                        .iter()
+                        .any(|(handle, _)| self.resources[handle.resource].imported)
+            })
+            .collect();
+        while let Some(index) = stack.pop() {
+            if !std::mem::replace(&mut kept[index], true) {
+                stack.extend(&data_deps[index]);
+            }
+        }
+
+        //kahn's algorithm, ties go to the pass declared first so the order is stable
+        let mut dependents = vec![Vec::new(); pass_count];
+        let mut waiting_on = vec![0; pass_count];
+        for index in (0..pass_count).filter(|&i| kept[i]) {
+            let mut deps: Vec<usize> = data_deps[index]
+                .iter()
+                .chain(&order_deps[index])
+                .copied()
+                .filter(|&d| kept[d])
+                .collect();
+            deps.sort_unstable();
+            deps.dedup();
+            waiting_on[index] = deps.len();
+            for dep in deps {
+                dependents[dep].push(index);
+            }
+        }
+        let mut ready: BinaryHeap<Reverse<usize>> = (0..pass_count)
+            .filter(|&i| kept[i] && waiting_on[i] == 0)
+            .map(Reverse)
+            .collect();
+        let mut order = Vec::new();
+        while let Some(Reverse(index)) = ready.pop() {
+            order.push(index);
+            for &dependent in &dependents[index] {
+                waiting_on[dependent] -= 1;
+                if waiting_on[dependent] == 0 {
+                    ready.push(Reverse(dependent));
+                }
+            }
+        }
+        if order.len() != kept.iter().filter(|&&k| k).count() {
+            let stuck: Vec<&str> = (0..pass_count)
+                .filter(|&i| kept[i] && waiting_on[i] > 0)
+                .map(|i| self.passes[i].name.as_str())
+                .collect();
+            return Err(format!("passes depend on each other: {}", stuck.join(", ")));
+        }
+
+        //lifetimes in execution order, then first fit into allocations that are free by then
+        let mut first_use = vec![None; self.resources.len()];
+        let mut last_use = vec![0; self.resources.len()];
+        for (position, &index) in order.iter().enumerate() {
+            let pass = &self.passes[index];
+            for (handle, _) in pass.reads.iter().chain(&pass.writes) {
+                first_use[handle.resource].get_or_insert(position);
+                last_use[handle.resource] = position;
+            }
+        }
+        let mut used: Vec<usize> = (0..self.resources.len())
+            .filter(|&r| first_use[r].is_some())
+            .collect();
+        used.sort_by_key(|&r| (first_use[r], r));
+        let mut slots: Vec<Slot> = Vec::new();
+        let mut resource_slot = vec![None; self.resources.len()];
+        for resource in used {
+            let node = &self.resources[resource];
+            let first = first_use[resource].unwrap_or_default();
+            let free = slots.iter().position(|slot| {
+                !slot.imported
+                    && !node.imported
+                    && slot.desc.compatible(&node.desc)
+                    && slot.free_after < first
+            });
+            let slot = match free {
+                Some(slot) => slot,
+                None => {
+                    slots.push(Slot {
+                        desc: node.desc,
+                        imported: node.imported,
+                        resources: Vec::new(),
+                        free_after: 0,
+                    });
+                    slots.len() - 1
+                }
+            };
+            slots[slot].resources.push(resource);
+            slots[slot].free_after = last_use[resource];
+            resource_slot[resource] = Some(slot);
+        }
+
+        //clears go before the first write, barriers between accesses of an allocation where
+        //one of them writes. attachment writes in a row are ordered by the rasterizer already
+        let mut written = vec![false; self.resources.len()];
+        let mut last_access: Vec<Option<(usize, Access, bool)>> = vec![None; slots.len()];
+        let mut steps = Vec::with_capacity(order.len());
+        for &index in &order {
+            let pass = &self.passes[index];
+            let mut step = Step {
+                pass: index,
+                clears: Vec::new(),
+                barriers: Vec::new(),
+            };
+            let accesses = pass
+                .reads
+                .iter()
+                .map(|&(h, a)| (h, a, false))
+                .chain(pass.writes.iter().map(|&(h, a)| (h, a, true)));
+            for (handle, access, write) in accesses {
+                let node = &self.resources[handle.resource];
+                let slot = resource_slot[handle.resource].unwrap_or_default();
+                if write
+                    && !std::mem::replace(&mut written[handle.resource], true)
+                    && node.desc.needs_clear()
+                {
+                    step.clears.push(handle.resource);
+                }
+                if let Some((previous_pass, from, previous_write)) = last_access[slot] {
+                    let hazard = previous_write || write;
+                    let ordered = from == Access::Attachment && access == Access::Attachment;
+                    let barrier = Barrier {
+                        resource: node.name.clone(),
+                        slot,
+                        from,
+                        to: access,
+                    };
+                    if previous_pass != index
+                        && hazard
+                        && !ordered
+                        && !step.barriers.contains(&barrier)
+                    {
+                        step.barriers.push(barrier);
+                    }
+                }
+                last_access[slot] = Some((index, access, write));
+            }
+            steps.push(step);
+        }
+
+        let culled = (0..pass_count).filter(|&i| !kept[i]).collect();
+        Ok(CompiledGraph {
+            resources: self.resources,
+            passes: self.passes,
+            steps,
+            slots,
+            resource_slot,
+            culled,
+        })
+    }
+}
+
+pub struct PassBuilder<'g, 'a, B: GraphBackend> {
+    graph: &'g mut RenderGraph<'a, B>,
+    pass: usize,
+}
+
+impl<'a, B: GraphBackend> PassBuilder<'_, 'a, B> {
+    ///sampled for textures, storage for buffers
+    pub fn read(&mut self, handle: ResourceHandle) -> ResourceHandle {
+        let access = match self.desc(handle) {
+            Some(ResourceDesc::Buffer(_)) => Access::Storage,
+            _ => Access::Sampled,
+        };
+        self.read_as(handle, access)
+    }
+
+    pub fn read_as(&mut self, handle: ResourceHandle, access: Access) -> ResourceHandle {
+        if self.check(handle, access, false) {
+            self.graph.passes[self.pass].reads.push((handle, access));
+        }
+        handle
+    }
+
+    ///attachment for textures, storage for buffers
+    pub fn write(&mut self, handle: ResourceHandle) -> ResourceHandle {
+        let access = match self.desc(handle) {
+            Some(ResourceDesc::Buffer(_)) => Access::Storage,
+            _ => Access::Attachment,
+        };
+        self.write_as(handle, access)
+    }
+
+    ///returns the new version, later passes read that one to depend on this pass.
+    ///`handle` has to be the latest version
+    pub fn write_as(&mut self, handle: ResourceHandle, access: Access) -> ResourceHandle {
+        if !self.check(handle, access, true) {
+            return handle;
+        }
+        let resource = &mut self.graph.resources[handle.resource];
+        if handle.version != resource.version {
+            let error = format!(
+                "pass `{}` writes `{}` v{} but v{} already exists",
+                self.graph.passes[self.pass].name, resource.name, handle.version, resource.version
+            );
+            self.graph.errors.push(error);
+            return handle;
+        }
+        resource.version += 1;
+        let written = ResourceHandle {
+            resource: handle.resource,
+            version: resource.version,
+        };
+        self.graph.passes[self.pass].writes.push((written, access));
+        written
+    }
+
+    ///never culled, for passes whose results leave the graph some other way
+    pub fn side_effects(&mut self) -> &mut Self {
+        self.graph.passes[self.pass].side_effects = true;
+        self
+    }
+
+    pub fn execute<F: FnMut(&mut PassContext<B>) + 'a>(&mut self, execute: F) -> &mut Self {
+        self.graph.passes[self.pass].execute = Some(Box::new(execute));
+        self
+    }
+
+    fn desc(&self, handle: ResourceHandle) -> Option<&ResourceDesc> {
+        self.graph.resources.get(handle.resource).map(|r| &r.desc)
+    }
+
+    fn check(&mut self, handle: ResourceHandle, access: Access, write: bool) -> bool {
+        let pass = &self.graph.passes[self.pass].name;
+        let error = match self.graph.resources.get(handle.resource) {
+            Some(resource) if handle.version > resource.version => {
+                format!(
+                    "pass `{pass}` uses `{}` v{} which doesn't exist",
+                    resource.name, handle.version
+                )
+            }
+            Some(resource) if !access.allowed(&resource.desc, write) => format!(
+                "pass `{pass}` can't {} `{}` as {access:?}",
+                if write { "write" } else { "read" },
+                resource.name
+            ),
+            Some(_) => return true,
+            None => format!("pass `{pass}` uses a resource from another graph"),
+        };
+        self.graph.errors.push(error);
+        false
+    }
+}
+
+///one allocation, shared by transient resources whose lifetimes don't overlap
+struct Slot {
+    desc: ResourceDesc,
+    imported: bool,
+    resources: Vec<usize>,
+    free_after: usize,
+}
+
+struct Step {
+    pass: usize,
+    clears: Vec<usize>,
+    barriers: Vec<Barrier>,
+}
+
+pub struct CompiledGraph<'a, B: GraphBackend> {
+    resources: Vec<ResourceNode<B>>,
+    passes: Vec<PassNode<'a, B>>,
+    steps: Vec<Step>,
+    slots: Vec<Slot>,
+    resource_slot: Vec<Option<usize>>,
+    culled: Vec<usize>,
+}
+
+impl<'a, B: GraphBackend> CompiledGraph<'a, B> {
+    ///pass names in the order they run
+    pub fn order(&self) -> Vec<&str> {
+        self.steps
+            .iter()
+            .map(|s| self.passes[s.pass].name.as_str())
+            .collect()
+    }
+
+    pub fn culled(&self) -> Vec<&str> {
+        self.culled
+            .iter()
+            .map(|&i| self.passes[i].name.as_str())
+            .collect()
+    }
+
+    ///textures and buffers the backend is asked for, imports not included
+    pub fn allocation_count(&self) -> usize {
+        self.slots.iter().filter(|s| !s.imported).count()
+    }
+
+    ///the allocation a resource ended up in, `None` if no pass that runs uses it
+    pub fn slot(&self, handle: ResourceHandle) -> Option<usize> {
+        self.resource_slot.get(handle.resource).copied().flatten()
+    }
+
+    ///the barriers issued before a pass, empty for culled or unknown passes
+    pub fn barriers(&self, pass: &str) -> &[Barrier] {
+        self.step(pass).map_or(&[], |s| &s.barriers)
+    }
+
+    ///names of the resources cleared before a pass
+    pub fn clears(&self, pass: &str) -> Vec<&str> {
+        self.step(pass).map_or(Vec::new(), |s| {
+            s.clears
+                .iter()
+                .map(|&r| self.resources[r].name.as_str())
+                .collect()
+        })
+    }
+
+    fn step(&self, pass: &str) -> Option<&Step> {
+        self.steps.iter().find(|s| self.passes[s.pass].name == pass)
+    }
+
+    ///graphviz source: passes are boxes numbered in run order (culled ones dashed), resource
+    ///versions are ellipses with their allocation, edges are labelled with the access
+    pub fn to_dot(&self) -> String {
+        let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut dot = String::from("digraph RenderGraph {\n    rankdir=LR;\n");
+        dot.push_str("    node [fontname=\"monospace\", fontsize=10];\n");
+        dot.push_str("    edge [fontname=\"monospace\", fontsize=9];\n");
+
+        for (index, pass) in self.passes.iter().enumerate() {
+            match self.steps.iter().position(|s| s.pass == index) {
+                Some(position) => {
+                    let step = &self.steps[position];
+                    let mut label = format!("{position}: {}", escape(&pass.name));
+                    for &resource in &step.clears {
+                        let _ =
+                            write!(label, "\\nclear {}", escape(&self.resources[resource].name));
+                    }
+                    for barrier in &step.barriers {
+                        let _ = write!(
+                            label,
+                            "\\nbarrier {} {:?} -> {:?}",
+                            escape(&barrier.resource),
+                            barrier.from,
+                            barrier.to
+                        );
+                    }
+                    let _ = writeln!(
+                        dot,
+                        "    p{index} [shape=box, style=filled, fillcolor=\"#cfe2f3\", label=\"{label}\"];"
+                    );
+                }
+                None => {
+                    let _ = writeln!(
+                        dot,
+                        "    p{index} [shape=box, style=\"filled,dashed\", fillcolor=\"#eeeeee\", label=\"{} (culled)\"];",
+                        escape(&pass.name)
+                    );
+                }
+            }
+        }
+
+        let mut versions: Vec<ResourceHandle> = self
+            .passes
+            .iter()
+            .flat_map(|p| p.reads.iter().chain(&p.writes).map(|(h, _)| *h))
+            .collect();
+        versions.sort_by_key(|h| (h.resource, h.version));
+        versions.dedup();
+        for handle in &versions {
+            let resource = &self.resources[handle.resource];
+            let slot = match self.resource_slot[handle.resource] {
+                Some(_) if resource.imported => "imported".to_string(),
+                Some(slot) => format!("slot {slot}"),
+                None => "unused".to_string(),
+            };
+            let shape = if resource.imported {
+                "doubleoctagon"
+            } else {
+                "ellipse"
+            };
+            let _ = writeln!(
+                dot,
+                "    r{}v{} [shape={shape}, label=\"{} v{}\\n{}\\n{slot}\"];",
+                handle.resource,
+                handle.version,
+                escape(&resource.name),
+                handle.version,
+                resource.desc.label()
+            );
+        }
+
+        for (index, pass) in self.passes.iter().enumerate() {
+            for (handle, access) in &pass.reads {
+                let _ = writeln!(
+                    dot,
+                    "    r{}v{} -> p{index} [label=\"{access:?}\"];",
+                    handle.resource, handle.version
+                );
+            }
+            for (handle, access) in &pass.writes {
+                let _ = writeln!(
+                    dot,
+                    "    p{index} -> r{}v{} [label=\"{access:?}\"];",
+                    handle.resource, handle.version
+                );
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    ///creates the allocations, then per pass: barriers, clears, `begin_pass`, the pass's
+    ///closure and `end_pass`
+    pub fn execute(mut self, backend: &mut B) {
+        let mut physical = Vec::with_capacity(self.slots.len());
+        for (index, slot) in self.slots.iter().enumerate() {
+            let resource = &mut self.resources[slot.resources[0]];
+            physical.push(match resource.external.take() {
+                Some(external) => external,
+                None => match &resource.desc {
+                    ResourceDesc::Texture(desc) => {
+                        Physical::Texture(backend.create_texture(index, &resource.name, desc))
+                    }
+                    ResourceDesc::Buffer(desc) => {
+                        Physical::Buffer(backend.create_buffer(index, &resource.name, desc))
+                    }
+                },
+            });
+        }
+
+        for step in &self.steps {
+            for barrier in &step.barriers {
+                backend.barrier(barrier);
+            }
+            for &resource in &step.clears {
+                let Some(slot) = self.resource_slot[resource] else {
+                    continue;
+                };
+                match (&self.resources[resource].desc, &physical[slot]) {
+                    (ResourceDesc::Texture(desc), Physical::Texture(texture)) => {
+                        if let Some(value) = desc.clear {
+                            backend.clear_texture(texture, value);
+                        }
+                    }
+                    (ResourceDesc::Buffer(_), Physical::Buffer(buffer)) => {
+                        backend.clear_buffer(buffer)
+                    }
+                    _ => {}
+                }
+            }
+
+            let pass = &mut self.passes[step.pass];
+            let attachments: Vec<&B::Texture> = pass
+                .writes
+                .iter()
+                .filter(|(_, access)| *access == Access::Attachment)
+                .filter_map(
+                    |(handle, _)| match &physical[self.resource_slot[handle.resource]?] {
+                        Physical::Texture(texture) => Some(texture),
+                        Physical::Buffer(_) => None,
+                    },
+                )
+                .collect();
+            backend.begin_pass(&pass.name, &attachments);
+            if let Some(execute) = &mut pass.execute {
+                execute(&mut PassContext {
+                    backend,
+                    physical: &physical,
+                    resource_slot: &self.resource_slot,
+                });
+            }
+            backend.end_pass(&pass.name);
+        }
+    }
+}
+
+///handed to a pass's closure while it runs
+pub struct PassContext<'r, B: GraphBackend> {
+    backend: &'r mut B,
+    physical: &'r [Physical<B>],
+    resource_slot: &'r [Option<usize>],
+}
+
+impl<B: GraphBackend> PassContext<'_, B> {
+    pub fn backend(&mut self) -> &mut B {
+        self.backend
+    }
+
+    ///panics if the handle isn't a texture some running pass uses
+    pub fn texture(&self, handle: ResourceHandle) -> &B::Texture {
+        match self.physical(handle) {
+            Physical::Texture(texture) => texture,
+            Physical::Buffer(_) => panic!("resource {} is a buffer", handle.resource),
+        }
+    }
+
+    ///panics if the handle isn't a buffer some running pass uses
+    pub fn buffer(&self, handle: ResourceHandle) -> &B::Buffer {
+        match self.physical(handle) {
+            Physical::Buffer(buffer) => buffer,
+            Physical::Texture(_) => panic!("resource {} is a texture", handle.resource),
+        }
+    }
+
+    fn physical(&self, handle: ResourceHandle) -> &Physical<B> {
+        let slot = self.resource_slot[handle.resource]
+            .unwrap_or_else(|| panic!("resource {} isn't used by the graph", handle.resource));
+        &self.physical[slot]
+    }
+}
@@ -0,0 +1,60 @@
+use super::{Barrier, BufferDesc, ClearValue, GraphBackend, ResourceDesc, TextureDesc};
+
+///records what the graph asks for instead of touching a gpu, resources are just their names.
+///for testing graphs and for tools that only need the plan
+#[derive(Default, Debug)]
+pub struct NullBackend {
+    pub commands: Vec<String>,
+}
+
+impl NullBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl GraphBackend for NullBackend {
+    type Texture = String;
+    type Buffer = String;
+
+    fn create_texture(&mut self, slot: usize, name: &str, desc: &TextureDesc) -> String {
+        self.commands.push(format!(
+            "create {slot} {name} {}",
+            ResourceDesc::Texture(*desc).label()
+        ));
+        name.to_string()
+    }
+
+    fn create_buffer(&mut self, slot: usize, name: &str, desc: &BufferDesc) -> String {
+        self.commands.push(format!(
+            "create {slot} {name} {}",
+            ResourceDesc::Buffer(*desc).label()
+        ));
+        name.to_string()
+    }
+
+    fn clear_texture(&mut self, texture: &String, value: ClearValue) {
+        self.commands.push(format!("clear {texture} {value:?}"));
+    }
+
+    fn clear_buffer(&mut self, buffer: &String) {
+        self.commands.push(format!("clear {buffer} zero"));
+    }
+
+    fn barrier(&mut self, barrier: &Barrier) {
+        self.commands.push(format!(
+            "barrier {} {:?} -> {:?}",
+            barrier.resource, barrier.from, barrier.to
+        ));
+    }
+
+    fn begin_pass(&mut self, name: &str, attachments: &[&String]) {
+        let attachments: Vec<&str> = attachments.iter().map(|a| a.as_str()).collect();
+        self.commands
+            .push(format!("begin {name} [{}]", attachments.join(", ")));
+    }
+
+    fn end_pass(&mut self, name: &str) {
+        self.commands.push(format!("end {name}"));
+    }
+}
@@ -0,0 +1,198 @@
+use std::rc::Rc;
+
+use crate::math::{Mat4, Vec3};
+
+use super::{
+    Renderer,
+    buffer::VertexArray,
+    material::{BlendMode, CullMode, Material, MaterialInstance, RenderState, sort_key},
+    shader::Shader,
+};
+
+///per frame numbers, reset with `reset_stats`
+#[derive(Clone, Copy, Default, Debug)]
+pub struct RenderQueueStats {
+    pub draw_calls: u32,
+    pub triangles: u32,
+    pub shader_binds: u32,
+    pub material_binds: u32,
+    pub state_changes: u32,
+}
+
+struct DrawCommand {
+    key: u64,
+    vertex_array: Rc<VertexArray>,
+    material: MaterialInstance,
+    transform: Mat4,
+    ///`None` for a plain draw, instanced draws can have zero instances
+    instances: Option<u32>,
+}
+
+impl DrawCommand {
+    fn set_uniforms(&self, shader: &Shader) {
+        shader.set_mat4("u_Transform", &self.transform);
+        shader.set_int("u_Instanced", self.instances.is_some() as i32);
+    }
+
+    fn draw(&self) {
+        Renderer::draw_instanced(&self.vertex_array, self.instances.unwrap_or(1));
+    }
+}
+
+///collects the draws of a scene and issues them sorted by material so the
+///shader, uniforms and fixed function state only change when they have to.
+///
+///the shaders get `u_ViewProjection`, `u_Transform` and `u_Instanced` on top of the material parameters
+pub struct RenderQueue {
+    commands: Vec<DrawCommand>,
+    view_projection: Mat4,
+    camera_position: Vec3,
+    stats: RenderQueueStats,
+}
+
+impl RenderQueue {
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+            view_projection: Mat4::IDENTITY,
+            camera_position: Vec3::ZERO,
+            stats: RenderQueueStats::default(),
+        }
+    }
+
+    ///`camera_position` is used to sort opaque draws front to back and transparent ones back to front
+    pub fn begin_scene(&mut self, view_projection: &Mat4, camera_position: Vec3) {
+        self.commands.clear();
+        self.view_projection = *view_projection;
+        self.camera_position = camera_position;
+    }
+
+    ///the vertex array needs an index buffer, draws without one are skipped
+    pub fn submit(
+        &mut self,
+        vertex_array: &Rc<VertexArray>,
+        material: &MaterialInstance,
+        transform: &Mat4,
+    ) {
+        self.push(vertex_array, material, transform, None);
+    }
+
+    ///draws the array `instance_count` times, its instance buffer is applied on top of `transform`.
+    ///sorted by `transform` alone, the instances are never sorted among themselves
+    pub fn submit_instanced(
+        &mut self,
+        vertex_array: &Rc<VertexArray>,
+        material: &MaterialInstance,
+        transform: &Mat4,
+        instance_count: u32,
+    ) {
+        self.push(vertex_array, material, transform, Some(instance_count));
+    }
+
+    fn push(
+        &mut self,
+        vertex_array: &Rc<VertexArray>,
+        material: &MaterialInstance,
+        transform: &Mat4,
+        instances: Option<u32>,
+    ) {
+        let depth = transform.w_axis.truncate().distance(self.camera_position);
+        self.commands.push(DrawCommand {
+            key: sort_key(material.material(), depth),
+            vertex_array: vertex_array.clone(),
+            material: material.clone(),
+            transform: *transform,
+            instances,
+        });
+    }
+
+    pub fn end_scene(&mut self) {
+        self.flush();
+    }
+
+    ///draws the opaque submissions so far with `shader`, which has to be bound and only gets
+    ///`u_Transform` and `u_Instanced`. for depth only passes like shadow maps, the material state is not applied
+    pub fn draw_shadow_casters(&self, shader: &Shader) {
+        for command in &self.commands {
+            if command.material.material().state.is_transparent() {
+                continue;
+            }
+            command.set_uniforms(shader);
+            command.draw();
+        }
+        if let Some(last) = self.commands.last() {
+            last.vertex_array.unbind();
+        }
+    }
+
+    pub fn stats(&self) -> RenderQueueStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = RenderQueueStats::default();
+    }
+
+    fn flush(&mut self) {
+        //stable so draws with the same key keep the order they were submitted in
+        self.commands.sort_by_key(|command| command.key);
+
+        let mut shader = None;
+        let mut material: Option<&Rc<Material>> = None;
+        let mut state: Option<RenderState> = None;
+        for command in &self.commands {
+            let Some(index_buffer) = command.vertex_array.index_buffer() else {
+                continue;
+            };
+            let base = command.material.material();
+
+            let next_state = base.state;
+            if state != Some(next_state) {
+                next_state.apply();
+                state = Some(next_state);
+                self.stats.state_changes += 1;
+            }
+            if shader != Some(base.shader().id()) {
+                base.shader().bind();
+                base.shader()
+                    .set_mat4("u_ViewProjection", &self.view_projection);
+                shader = Some(base.shader().id());
+                material = None;
+                self.stats.shader_binds += 1;
+            }
+            //instances with overrides have to set their uniforms every draw,
+            //the ones without can share the base material's
+            let same_material = material.is_some_and(|m| Rc::ptr_eq(m, base));
+            if !same_material || command.material.has_overrides() {
+                command.material.apply();
+                material = (!command.material.has_overrides()).then_some(base);
+                self.stats.material_binds += 1;
+            }
+            command.set_uniforms(base.shader());
+            command.draw();
+            self.stats.draw_calls += 1;
+            self.stats.triangles += index_buffer.count() / 3 * command.instances.unwrap_or(1);
+        }
+        if let Some(last) = self.commands.last() {
+            last.vertex_array.unbind();
+        }
+        //put back what the 2d renderer and imgui expect
+        if state.is_some() {
+            RenderState {
+                blend: BlendMode::Alpha,
+                depth_test: false,
+                depth_write: true,
+                cull: CullMode::None,
+                ..RenderState::default()
+            }
+            .apply();
+        }
+        self.commands.clear();
+    }
+}
+
+impl Default for RenderQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,402 @@
+use std::rc::Rc;
+
+use crate::math::{Mat4, Quat, Rect, Vec2, Vec3, Vec4};
+
+use super::{
+    buffer::{BufferElement, BufferLayout, IndexBuffer, ShaderDataType, VertexArray, VertexBuffer},
+    camera::OrthographicCamera,
+    shader::Shader,
+    stats,
+    text::{AtlasKind, FontAtlas, TextLayout, TextStyle},
+    texture::Texture2D,
+};
+
+const MAX_QUADS: usize = 10_000;
+const MAX_VERTICES: usize = MAX_QUADS * 4;
+const MAX_INDICES: usize = MAX_QUADS * 6;
+const MAX_TEXTURE_SLOTS: usize = 32;
+
+const QUAD_POSITIONS: [Vec4; 4] = [
+    Vec4::new(-0.5, -0.5, 0.0, 1.0),
+    Vec4::new(0.5, -0.5, 0.0, 1.0),
+    Vec4::new(0.5, 0.5, 0.0, 1.0),
+    Vec4::new(-0.5, 0.5, 0.0, 1.0),
+];
+
+const QUAD_VERTEX_SHADER: &str = r#"
+#version 450 core
+layout(location = 0) in vec3 a_Position;
+layout(location = 1) in vec4 a_Color;
+layout(location = 2) in vec2 a_TexCoord;
+layout(location = 3) in float a_TexIndex;
+layout(location = 4) in float a_Sdf;
+
+uniform mat4 u_ViewProjection;
+
+out vec4 v_Color;
+out vec2 v_TexCoord;
+flat out int v_TexIndex;
+flat out int v_Sdf;
+
+void main() {
+    v_Color = a_Color;
+    v_TexCoord = a_TexCoord;
+    v_TexIndex = int(a_TexIndex);
+    v_Sdf = int(a_Sdf);
+    gl_Position = u_ViewProjection * vec4(a_Position, 1.0);
+}
+"#;
+
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+struct QuadVertex {
+    position: [f32; 3],
+    color: [f32; 4],
+    tex_coord: [f32; 2],
+    tex_index: f32,
+    ///1 when the texture holds signed distances, like an sdf font atlas
+    sdf: f32,
+}
+
+///per frame numbers, reset with `reset_stats`
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Renderer2DStats {
+    pub draw_calls: u32,
+    pub quad_count: u32,
+    pub texture_count: u32,
+}
+
+impl Renderer2DStats {
+    pub fn vertex_count(&self) -> u32 {
+        self.quad_count * 4
+    }
+
+    pub fn index_count(&self) -> u32 {
+        self.quad_count * 6
+    }
+}
+
+///batches quads into one big vertex buffer and only draws when the batch is full,
+///out of texture slots, or the scene ends
+pub struct Renderer2D {
+    vertex_array: VertexArray,
+    shader: Shader,
+    white_texture: Rc<Texture2D>,
+
+    vertices: Vec<QuadVertex>,
+    //slot 0 is always the white texture so plain colored quads share the batch
+    texture_slots: Vec<Rc<Texture2D>>,
+    max_texture_slots: usize,
+
+    view_projection: Mat4,
+    stats: Renderer2DStats,
+}
+
+impl Renderer2D {
+    ///needs a current gl context, `Renderer::new` must have been called
+    pub fn new() -> Self {
+        let mut max_units = 0;
+        unsafe { gl::GetIntegerv(gl::MAX_TEXTURE_IMAGE_UNITS, &mut max_units) };
+        let max_texture_slots = (max_units.max(1) as usize).min(MAX_TEXTURE_SLOTS);
+
+        let layout = BufferLayout::new(vec![
+            BufferElement::new(ShaderDataType::Float3, "a_Position"),
+            BufferElement::new(ShaderDataType::Float4, "a_Color"),
+            BufferElement::new(ShaderDataType::Float2, "a_TexCoord"),
+            BufferElement::new(ShaderDataType::Float, "a_TexIndex"),
+            BufferElement::new(ShaderDataType::Float, "a_Sdf"),
+        ]);
+        let vertex_buffer =
+            VertexBuffer::with_capacity(MAX_VERTICES * std::mem::size_of::<QuadVertex>(), layout);
+
+        let mut indices = Vec::with_capacity(MAX_INDICES);
+        for quad in 0..MAX_QUADS as u32 {
+            let offset = quad * 4;
+            indices.extend_from_slice(&[
+                offset,
+                offset + 1,
+                offset + 2,
+                offset + 2,
+                offset + 3,
+                offset,
+            ]);
+        }
+
+        let mut vertex_array = VertexArray::new();
+        vertex_array.add_vertex_buffer(vertex_buffer);
+        vertex_array.set_index_buffer(IndexBuffer::new(&indices));
+        vertex_array.unbind();
+
+        let shader = Shader::from_source(
+            "Renderer2D Quad",
+            QUAD_VERTEX_SHADER,
+            &quad_fragment_shader(max_texture_slots),
+        )
+        .expect("failed to build the 2d quad shader");
+        let samplers: Vec<i32> = (0..max_texture_slots as i32).collect();
+        shader.bind();
+        shader.set_int_array("u_Textures", &samplers);
+
+        let white_texture = Rc::new(Texture2D::from_rgba(1, 1, &[255, 255, 255, 255]));
+
+        Self {
+            vertex_array,
+            shader,
+            texture_slots: vec![white_texture.clone()],
+            white_texture,
+            vertices: Vec::with_capacity(MAX_VERTICES),
+            max_texture_slots,
+            view_projection: Mat4::IDENTITY,
+            stats: Renderer2DStats::default(),
+        }
+    }
+
+    pub fn begin_scene(&mut self, camera: &OrthographicCamera) {
+        self.view_projection = *camera.view_projection();
+        self.start_batch();
+    }
+
+    pub fn end_scene(&mut self) {
+        self.flush();
+    }
+
+    pub fn draw_quad(&mut self, position: Vec3, size: Vec2, rotation: f32, color: Vec4) {
+        self.draw_quad_transform(&quad_transform(position, size, rotation), color);
+    }
+
+    pub fn draw_quad_transform(&mut self, transform: &Mat4, color: Vec4) {
+        self.submit_quad(transform, color, None, full_uvs(), false);
+    }
+
+    ///`sub_rect` is in pixels from the top left of the texture, `None` draws the whole texture
+    pub fn draw_sprite(
+        &mut self,
+        position: Vec3,
+        size: Vec2,
+        rotation: f32,
+        texture: &Rc<Texture2D>,
+        sub_rect: Option<Rect>,
+        tint: Vec4,
+    ) {
+        self.draw_sprite_transform(
+            &quad_transform(position, size, rotation),
+            texture,
+            sub_rect,
+            tint,
+        );
+    }
+
+    pub fn draw_sprite_transform(
+        &mut self,
+        transform: &Mat4,
+        texture: &Rc<Texture2D>,
+        sub_rect: Option<Rect>,
+        tint: Vec4,
+    ) {
+        let uvs = match sub_rect {
+            Some(rect) => sub_rect_uvs(texture, rect),
+            None => full_uvs(),
+        };
+        self.submit_quad(transform, tint, Some(texture), uvs, false);
+    }
+
+    ///lays out `text` and draws it with its top left corner at `position`, returns the size
+    ///of the text. the glyphs come from `atlas` and are added to it as needed
+    pub fn draw_text(
+        &mut self,
+        atlas: &mut FontAtlas,
+        text: &str,
+        position: Vec3,
+        style: &TextStyle,
+        color: Vec4,
+    ) -> Vec2 {
+        let layout = TextLayout::new(atlas.font(), text, style);
+        self.draw_text_layout(atlas, &layout, position, color);
+        layout.size
+    }
+
+    ///`layout` has to come from the atlas's font
+    pub fn draw_text_layout(
+        &mut self,
+        atlas: &mut FontAtlas,
+        layout: &TextLayout,
+        position: Vec3,
+        color: Vec4,
+    ) {
+        atlas.cache(layout);
+        atlas.upload();
+        let texture = atlas.texture().clone();
+        let scale = layout.font_size / atlas.pixel_size();
+        let sdf = matches!(atlas.kind(), AtlasKind::Sdf { .. });
+        for glyph in &layout.glyphs {
+            let Some(entry) = atlas.glyph(glyph.index) else {
+                continue;
+            };
+            let size = entry.rect.size() * scale;
+            //the layout goes down from the top, the world goes up
+            let min = Vec2::new(position.x + glyph.position.x, position.y - glyph.position.y)
+                + entry.offset * scale;
+            let transform = quad_transform((min + size / 2.0).extend(position.z), size, 0.0);
+            let uvs = sub_rect_uvs(&texture, entry.rect);
+            self.submit_quad(&transform, color, Some(&texture), uvs, sdf);
+        }
+    }
+
+    pub fn stats(&self) -> Renderer2DStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = Renderer2DStats::default();
+    }
+
+    fn submit_quad(
+        &mut self,
+        transform: &Mat4,
+        color: Vec4,
+        texture: Option<&Rc<Texture2D>>,
+        uvs: [Vec2; 4],
+        sdf: bool,
+    ) {
+        if self.vertices.len() >= MAX_VERTICES {
+            self.next_batch();
+        }
+        //after the batch check, a new batch starts without the texture
+        let tex_index = texture.map_or(0.0, |texture| self.texture_slot(texture));
+        for (corner, uv) in QUAD_POSITIONS.iter().zip(uvs) {
+            let position = *transform * *corner;
+            self.vertices.push(QuadVertex {
+                position: position.truncate().into(),
+                color: color.into(),
+                tex_coord: uv.into(),
+                tex_index,
+                sdf: sdf as u32 as f32,
+            });
+        }
+        self.stats.quad_count += 1;
+    }
+
+    ///finds the slot the texture is bound to this batch, or gives it a new one
+    fn texture_slot(&mut self, texture: &Rc<Texture2D>) -> f32 {
+        if let Some(slot) = self.texture_slots.iter().position(|t| **t == **texture) {
+            return slot as f32;
+        }
+        if self.texture_slots.len() >= self.max_texture_slots {
+            self.next_batch();
+        }
+        self.texture_slots.push(texture.clone());
+        self.stats.texture_count += 1;
+        (self.texture_slots.len() - 1) as f32
+    }
+
+    fn start_batch(&mut self) {
+        self.vertices.clear();
+        self.texture_slots.truncate(1);
+    }
+
+    fn next_batch(&mut self) {
+        self.flush();
+        self.start_batch();
+    }
+
+    fn flush(&mut self) {
+        if self.vertices.is_empty() {
+            return;
+        }
+        self.vertex_array.vertex_buffers()[0].set_data(&self.vertices);
+        for (slot, texture) in self.texture_slots.iter().enumerate() {
+            texture.bind(slot as u32);
+        }
+
+        self.shader.bind();
+        self.shader
+            .set_mat4("u_ViewProjection", &self.view_projection);
+        self.vertex_array.bind();
+        let index_count = (self.vertices.len() / 4 * 6) as i32;
+        unsafe {
+            gl::DrawElements(
+                gl::TRIANGLES,
+                index_count,
+                gl::UNSIGNED_INT,
+                std::ptr::null(),
+            );
+        }
+        self.vertex_array.unbind();
+        self.stats.draw_calls += 1;
+        stats::record_draw(index_count as u64 / 3);
+    }
+
+    pub fn white_texture(&self) -> &Rc<Texture2D> {
+        &self.white_texture
+    }
+}
+
+impl Default for Renderer2D {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn quad_transform(position: Vec3, size: Vec2, rotation: f32) -> Mat4 {
+    Mat4::from_scale_rotation_translation(
+        size.extend(1.0),
+        Quat::from_rotation_z(rotation),
+        position,
+    )
+}
+
+///uvs in the same order as QUAD_POSITIONS, textures are uploaded top row first so v is flipped
+fn full_uvs() -> [Vec2; 4] {
+    [
+        Vec2::new(0.0, 1.0),
+        Vec2::new(1.0, 1.0),
+        Vec2::new(1.0, 0.0),
+        Vec2::new(0.0, 0.0),
+    ]
+}
+
+fn sub_rect_uvs(texture: &Texture2D, rect: Rect) -> [Vec2; 4] {
+    let size = Vec2::new(texture.width() as f32, texture.height() as f32);
+    let min = rect.min / size;
+    let max = rect.max / size;
+    [
+        Vec2::new(min.x, max.y),
+        Vec2::new(max.x, max.y),
+        Vec2::new(max.x, min.y),
+        Vec2::new(min.x, min.y),
+    ]
+}
+
+//sampler arrays can't be indexed by a varying in glsl so every slot gets its own case
+fn quad_fragment_shader(slots: usize) -> String {
+    let cases: String = (0..slots)
+        .map(|i| {
+            format!("        case {i}: tex_color = texture(u_Textures[{i}], v_TexCoord); break;\n")
+        })
+        .collect();
+    format!(
+        r#"
+#version 450 core
+layout(location = 0) out vec4 o_Color;
+
+in vec4 v_Color;
+in vec2 v_TexCoord;
+flat in int v_TexIndex;
+flat in int v_Sdf;
+
+uniform sampler2D u_Textures[{slots}];
+
+void main() {{
+    vec4 tex_color = vec4(1.0);
+    switch (v_TexIndex) {{
+{cases}    }}
+    if (v_Sdf != 0) {{
+        //the outline is at 0.5, smoothed over about a pixel on screen
+        float width = max(fwidth(tex_color.a), 1e-4);
+        tex_color.a = smoothstep(0.5 - width, 0.5 + width, tex_color.a);
+    }}
+    o_Color = tex_color * v_Color;
+}}
+"#
+    )
+}
@@ -0,0 +1,215 @@
+use std::{cell::RefCell, collections::HashMap, ffi::CString, path::Path};
+
+use crate::math::{Mat4, Vec2, Vec3, Vec4};
+use gl::types::GLenum;
+
+use super::stats;
+
+pub struct Shader {
+    id: u32,
+    name: String,
+    uniform_locations: RefCell<HashMap<String, i32>>,
+}
+
+impl Shader {
+    pub fn from_source(name: &str, vertex_src: &str, fragment_src: &str) -> Result<Self, String> {
+        let vertex = compile(gl::VERTEX_SHADER, vertex_src).map_err(|e| format!("{name}: {e}"))?;
+        let fragment = match compile(gl::FRAGMENT_SHADER, fragment_src) {
+            Ok(f) => f,
+            Err(e) => {
+                unsafe { gl::DeleteShader(vertex) };
+                return Err(format!("{name}: {e}"));
+            }
+        };
+
+        let id = unsafe {
+            let program = gl::CreateProgram();
+            gl::AttachShader(program, vertex);
+            gl::AttachShader(program, fragment);
+            gl::LinkProgram(program);
+            gl::DetachShader(program, vertex);
+            gl::DetachShader(program, fragment);
+            gl::DeleteShader(vertex);
+            gl::DeleteShader(fragment);
+
+            let mut status = 0;
+            gl::GetProgramiv(program, gl::LINK_STATUS, &mut status);
+            if status == 0 {
+                let log = program_log(program);
+                gl::DeleteProgram(program);
+                return Err(format!("{name}: link failed: {log}"));
+            }
+            program
+        };
+
+        Ok(Self {
+            id,
+            name: name.to_string(),
+            uniform_locations: RefCell::new(HashMap::new()),
+        })
+    }
+
+    ///one file with both stages, each starting with a `#type vertex` or `#type fragment` line
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let source =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let name = path
+            .file_stem()
+            .map_or("shader".into(), |s| s.to_string_lossy());
+        let (vertex, fragment) = split_stages(&source).map_err(|e| format!("{name}: {e}"))?;
+        Self::from_source(&name, &vertex, &fragment)
+    }
+
+    pub fn bind(&self) {
+        unsafe { gl::UseProgram(self.id) }
+        stats::record_state_change();
+    }
+
+    pub fn unbind(&self) {
+        unsafe { gl::UseProgram(0) }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    //the setters expect the shader to be bound
+    pub fn set_int(&self, name: &str, value: i32) {
+        unsafe { gl::Uniform1i(self.location(name), value) }
+    }
+
+    pub fn set_int_array(&self, name: &str, values: &[i32]) {
+        unsafe { gl::Uniform1iv(self.location(name), values.len() as i32, values.as_ptr()) }
+    }
+
+    pub fn set_float(&self, name: &str, value: f32) {
+        unsafe { gl::Uniform1f(self.location(name), value) }
+    }
+
+    pub fn set_float2(&self, name: &str, value: Vec2) {
+        unsafe { gl::Uniform2f(self.location(name), value.x, value.y) }
+    }
+
+    pub fn set_float3(&self, name: &str, value: Vec3) {
+        unsafe { gl::Uniform3f(self.location(name), value.x, value.y, value.z) }
+    }
+
+    pub fn set_float4(&self, name: &str, value: Vec4) {
+        unsafe { gl::Uniform4f(self.location(name), value.x, value.y, value.z, value.w) }
+    }
+
+    pub fn set_mat4(&self, name: &str, value: &Mat4) {
+        unsafe {
+            gl::UniformMatrix4fv(
+                self.location(name),
+                1,
+                gl::FALSE,
+                value.to_cols_array().as_ptr(),
+            )
+        }
+    }
+
+    ///looks up and caches the uniform location, -1 (ignored by gl) if it does not exist
+    fn location(&self, name: &str) -> i32 {
+        if let Some(location) = self.uniform_locations.borrow().get(name) {
+            return *location;
+        }
+        let c_name = CString::new(name).expect("uniform name contains a nul byte");
+        let location = unsafe { gl::GetUniformLocation(self.id, c_name.as_ptr()) };
+        self.uniform_locations
+            .borrow_mut()
+            .insert(name.to_string(), location);
+        location
+    }
+}
+
+impl Drop for Shader {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteProgram(self.id) }
+    }
+}
+
+///splits a `from_file` source into its vertex and fragment stages
+pub fn split_stages(source: &str) -> Result<(String, String), String> {
+    let mut vertex = None;
+    let mut fragment = None;
+    let mut current: Option<&mut Option<String>> = None;
+    for line in source.lines() {
+        if let Some(stage) = line.trim().strip_prefix("#type") {
+            current = match stage.trim() {
+                "vertex" => Some(&mut vertex),
+                "fragment" | "pixel" => Some(&mut fragment),
+                other => return Err(format!("unknown shader stage `{other}`")),
+            };
+            if let Some(stage) = current.as_mut() {
+                stage.get_or_insert_with(String::new);
+            }
+            continue;
+        }
+        if let Some(Some(stage)) = current.as_mut() {
+            stage.push_str(line);
+            stage.push('\n');
+        }
+    }
+    match (vertex, fragment) {
+        (Some(vertex), Some(fragment)) => Ok((vertex, fragment)),
+        _ => Err("needs both a `#type vertex` and a `#type fragment` section".into()),
+    }
+}
+
+fn compile(kind: GLenum, src: &str) -> Result<u32, String> {
+    let c_src = CString::new(src).map_err(|e| e.to_string())?;
+    unsafe {
+        let shader = gl::CreateShader(kind);
+        gl::ShaderSource(shader, 1, &c_src.as_ptr(), std::ptr::null());
+        gl::CompileShader(shader);
+
+        let mut status = 0;
+        gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut status);
+        if status == 0 {
+            let mut len = 0;
+            gl::GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut len);
+            let mut buf = vec![0u8; len.max(1) as usize];
+            gl::GetShaderInfoLog(
+                shader,
+                len,
+                std::ptr::null_mut(),
+                buf.as_mut_ptr() as *mut _,
+            );
+            gl::DeleteShader(shader);
+            let stage = if kind == gl::VERTEX_SHADER {
+                "vertex"
+            } else {
+                "fragment"
+            };
+            return Err(format!(
+                "{stage} shader failed to compile: {}",
+                String::from_utf8_lossy(&buf).trim_end_matches('\0')
+            ));
+        }
+        Ok(shader)
+    }
+}
+
+unsafe fn program_log(program: u32) -> String {
+    let mut len = 0;
+    let mut buf;
+    unsafe {
+        gl::GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut len);
+        buf = vec![0u8; len.max(1) as usize];
+        gl::GetProgramInfoLog(
+            program,
+            len,
+            std::ptr::null_mut(),
+            buf.as_mut_ptr() as *mut _,
+        );
+    }
+    String::from_utf8_lossy(&buf)
+        .trim_end_matches('\0')
+        .to_string()
+}
@@ -0,0 +1,559 @@
+use imgui::{Condition, Image, Ui};
+
+use crate::{
+    imgui::{UiTexture, texture_id},
+    math::{Mat4, Vec3, Vec4},
+};
+
+use super::{
+    camera::PerspectiveCamera,
+    framebuffer::{Framebuffer, FramebufferSpec, FramebufferTextureFormat},
+    lighting::LightEnvironment,
+    material::{CullMode, RenderState},
+    render_queue::RenderQueue,
+    shader::Shader,
+    uniform_buffer::UniformBuffer,
+};
+
+///uniform block binding the `Shadows` block is bound to
+pub const SHADOWS_BINDING: u32 = 2;
+pub const MAX_CASCADES: usize = 4;
+pub const MAX_SPOT_SHADOWS: usize = 4;
+///the shadow maps are bound from this texture unit up, materials get the ones below it.
+///cascades first, then spot lights, 16 units in total which is what gl 4.5 guarantees
+pub const SHADOW_TEXTURE_UNIT: u32 = 8;
+
+const DEPTH_VERTEX_SHADER: &str = r#"
+#version 450 core
+layout(location = 0) in vec3 a_Position;
+layout(location = 5) in mat4 a_InstanceTransform;
+
+uniform mat4 u_ViewProjection;
+uniform mat4 u_Transform;
+uniform bool u_Instanced;
+
+void main() {
+    mat4 transform = u_Instanced ? u_Transform * a_InstanceTransform : u_Transform;
+    gl_Position = u_ViewProjection * transform * vec4(a_Position, 1.0);
+}
+"#;
+
+const DEPTH_FRAGMENT_SHADER: &str = r#"
+#version 450 core
+void main() {}
+"#;
+
+///per light, set on a `DirectionalLight` or `SpotLight` to make it cast shadows
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ShadowSettings {
+    ///subtracted from the depth before comparing, in 0..1 shadow map depth
+    pub depth_bias: f32,
+    ///moves the point being tested along the surface normal, in shadow map texels
+    pub normal_bias: f32,
+    ///percentage closer filtering kernel is (2 * radius + 1) texels wide, 0 is a hard edge.
+    ///capped at 3
+    pub pcf_radius: u32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            depth_bias: 0.0005,
+            normal_bias: 1.5,
+            pcf_radius: 1,
+        }
+    }
+}
+
+///part of `LightingConfig`, how many shadow maps there are and how big
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ShadowConfig {
+    pub enabled: bool,
+    ///cascades for the directional light, 1 to `MAX_CASCADES`
+    pub cascade_count: usize,
+    pub cascade_resolution: u32,
+    ///the cascades cover the view from the near plane to here, there are no shadows further out
+    pub max_distance: f32,
+    ///0 splits the distance evenly between the cascades, 1 logarithmically
+    pub split_lambda: f32,
+    ///how far towards the light from a cascade casters are still rendered
+    pub caster_distance: f32,
+    ///spot lights with shadows past this many don't get any, at most `MAX_SPOT_SHADOWS`
+    pub max_spot_shadows: usize,
+    pub spot_resolution: u32,
+}
+
+impl Default for ShadowConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            cascade_count: 4,
+            cascade_resolution: 1024,
+            max_distance: 100.0,
+            split_lambda: 0.75,
+            caster_distance: 50.0,
+            max_spot_shadows: MAX_SPOT_SHADOWS,
+            spot_resolution: 1024,
+        }
+    }
+}
+
+impl ShadowConfig {
+    pub(crate) fn cascade_count(&self) -> usize {
+        if self.enabled {
+            self.cascade_count.clamp(1, MAX_CASCADES)
+        } else {
+            0
+        }
+    }
+
+    pub(crate) fn spot_count(&self) -> usize {
+        if self.enabled {
+            self.max_spot_shadows.min(MAX_SPOT_SHADOWS)
+        } else {
+            0
+        }
+    }
+
+    ///the part of the lit shader that reads the `Shadows` block and samples the maps
+    pub(crate) fn shader_source(&self) -> String {
+        //like in Renderer2D, sampler arrays can only be indexed with constants
+        let mut cases = String::new();
+        for i in 0..MAX_CASCADES {
+            cases.push_str(&format!(
+                "        case {i}: return textureLod(u_CascadeMaps[{i}], uv, 0.0).r;\n"
+            ));
+        }
+        for i in 0..MAX_SPOT_SHADOWS {
+            cases.push_str(&format!(
+                "        case {}: return textureLod(u_SpotShadowMaps[{i}], uv, 0.0).r;\n",
+                MAX_CASCADES + i
+            ));
+        }
+        SHADOW_FUNCTIONS
+            .replace("//CASES\n", &cases)
+            .replace("MAX_CASCADES", &MAX_CASCADES.to_string())
+            .replace("MAX_SPOT_SHADOWS", &MAX_SPOT_SHADOWS.to_string())
+            .replace("SHADOWS_BINDING", &SHADOWS_BINDING.to_string())
+    }
+}
+
+///the depth maps and the matrices to look them up with, updated every scene by the `ForwardRenderer`
+pub struct ShadowMaps {
+    config: ShadowConfig,
+    cascades: Vec<Framebuffer>,
+    spots: Vec<Framebuffer>,
+    depth_shader: Shader,
+    uniform_buffer: UniformBuffer,
+    cascade_matrices: Vec<Mat4>,
+    ///view distance where each cascade ends
+    cascade_splits: Vec<f32>,
+    spot_matrices: Vec<Mat4>,
+}
+
+impl ShadowMaps {
+    ///needs a current gl context
+    pub fn new(config: ShadowConfig) -> Self {
+        let depth_map = |size: u32| {
+            let framebuffer = Framebuffer::new(FramebufferSpec::new(
+                size,
+                size,
+                vec![FramebufferTextureFormat::Depth32F],
+            ));
+            //so the debug view shows gray instead of red
+            if let Some(texture) = framebuffer.depth_attachment_id() {
+                let swizzle = [gl::RED, gl::RED, gl::RED, gl::ONE].map(|s| s as i32);
+                unsafe {
+                    gl::TextureParameteriv(texture, gl::TEXTURE_SWIZZLE_RGBA, swizzle.as_ptr())
+                }
+            }
+            framebuffer
+        };
+        let cascades = (0..config.cascade_count())
+            .map(|_| depth_map(config.cascade_resolution))
+            .collect();
+        let spots = (0..config.spot_count())
+            .map(|_| depth_map(config.spot_resolution))
+            .collect();
+        let depth_shader =
+            Shader::from_source("Shadow Depth", DEPTH_VERTEX_SHADER, DEPTH_FRAGMENT_SHADER)
+                .expect("failed to build the shadow depth shader");
+        let uniform_buffer = UniformBuffer::new(Self::uniform_buffer_size(), SHADOWS_BINDING);
+        Self {
+            config,
+            cascades,
+            spots,
+            depth_shader,
+            uniform_buffer,
+            cascade_matrices: Vec::new(),
+            cascade_splits: Vec::new(),
+            spot_matrices: Vec::new(),
+        }
+    }
+
+    pub fn config(&self) -> &ShadowConfig {
+        &self.config
+    }
+
+    ///the cascades rendered this scene, empty if no directional light has shadows
+    pub fn active_cascades(&self) -> &[Framebuffer] {
+        &self.cascades[..self.cascade_matrices.len()]
+    }
+
+    pub fn cascade_splits(&self) -> &[f32] {
+        &self.cascade_splits
+    }
+
+    pub fn cascade_matrices(&self) -> &[Mat4] {
+        &self.cascade_matrices
+    }
+
+    ///one per spot light with shadows, in the order of the lights
+    pub fn active_spot_maps(&self) -> &[Framebuffer] {
+        &self.spots[..self.spot_matrices.len()]
+    }
+
+    pub fn spot_matrices(&self) -> &[Mat4] {
+        &self.spot_matrices
+    }
+
+    fn uniform_buffer_size() -> usize {
+        //matrices, splits, texel sizes, camera forward, directional params, counts, spot params
+        (4 * MAX_CASCADES + 4 * MAX_SPOT_SHADOWS + 5 + MAX_SPOT_SHADOWS) * 16
+    }
+
+    ///fits the cascades to the camera, builds the spot light matrices and uploads them
+    pub(crate) fn update(&mut self, camera: &PerspectiveCamera, lights: &LightEnvironment) {
+        self.cascade_matrices.clear();
+        self.cascade_splits.clear();
+        self.spot_matrices.clear();
+
+        let mut texel_sizes = Vec4::ZERO;
+        let mut directional = Vec4::new(-1.0, 0.0, 0.0, 0.0);
+        let shadowed = lights
+            .directional_lights
+            .iter()
+            .enumerate()
+            .find_map(|(i, light)| light.shadow.map(|shadow| (i, light, shadow)));
+        if let Some((index, light, shadow)) = shadowed.filter(|_| !self.cascades.is_empty()) {
+            let far = camera.far().min(self.config.max_distance);
+            let splits = cascade_splits(
+                camera.near(),
+                far,
+                self.cascades.len(),
+                self.config.split_lambda,
+            );
+            let direction = light.direction.normalize_or(Vec3::NEG_Y);
+            let mut start = camera.near();
+            for (i, &end) in splits.iter().enumerate() {
+                let (matrix, texel_size) = self.fit_cascade(camera, start, end, direction);
+                self.cascade_matrices.push(matrix);
+                texel_sizes[i] = texel_size;
+                start = end;
+            }
+            self.cascade_splits = splits;
+            directional = Vec4::new(
+                index as f32,
+                shadow.depth_bias,
+                shadow.normal_bias,
+                shadow.pcf_radius.min(3) as f32,
+            );
+        }
+
+        let mut spot_params = Vec::new();
+        let shadowed_spots = lights
+            .spot_lights
+            .iter()
+            .enumerate()
+            .filter_map(|(i, light)| light.shadow.map(|shadow| (i, light, shadow)))
+            .take(self.spots.len());
+        for (index, light, shadow) in shadowed_spots {
+            let outer = light.outer_angle.clamp(0.01, 85f32.to_radians());
+            let direction = light.direction.normalize_or(Vec3::NEG_Y);
+            let view = Mat4::look_at_rh(
+                light.position,
+                light.position + direction,
+                up_for(direction),
+            );
+            let near = (light.range * 0.001).max(0.01);
+            let projection =
+                Mat4::perspective_rh_gl(2.0 * outer, 1.0, near, light.range.max(near * 2.0));
+            self.spot_matrices.push(projection * view);
+            //world size of a texel one unit away from the light
+            let texel_size = 2.0 * outer.tan() / self.config.spot_resolution as f32;
+            spot_params.push(Vec4::new(
+                index as f32,
+                shadow.depth_bias,
+                shadow.normal_bias * texel_size,
+                shadow.pcf_radius.min(3) as f32,
+            ));
+        }
+
+        let mut data = Vec::with_capacity(Self::uniform_buffer_size() / 16);
+        for i in 0..MAX_CASCADES {
+            let matrix = self.cascade_matrices.get(i).copied().unwrap_or_default();
+            data.extend(matrix.to_cols_array_2d().map(Vec4::from));
+        }
+        for i in 0..MAX_SPOT_SHADOWS {
+            let matrix = self.spot_matrices.get(i).copied().unwrap_or_default();
+            data.extend(matrix.to_cols_array_2d().map(Vec4::from));
+        }
+        let mut splits = Vec4::ZERO;
+        for (i, split) in self.cascade_splits.iter().enumerate() {
+            splits[i] = *split;
+        }
+        data.push(splits);
+        data.push(texel_sizes);
+        data.push(camera.forward().extend(0.0));
+        data.push(directional);
+        //an ivec4 in glsl
+        data.push(Vec4::new(
+            f32::from_bits(self.cascade_matrices.len() as u32),
+            f32::from_bits(self.spot_matrices.len() as u32),
+            0.0,
+            0.0,
+        ));
+        spot_params.resize(MAX_SPOT_SHADOWS, Vec4::new(-1.0, 0.0, 0.0, 0.0));
+        data.extend(spot_params);
+
+        self.uniform_buffer.bind();
+        self.uniform_buffer.set_data(&data, 0);
+    }
+
+    ///a light space matrix covering the bounding sphere of the slice of the view between
+    ///`start` and `end`. the sphere keeps the size the same when the camera turns and the
+    ///position is snapped to whole texels, so the shadow edges don't shimmer when it moves
+    fn fit_cascade(
+        &self,
+        camera: &PerspectiveCamera,
+        start: f32,
+        end: f32,
+        direction: Vec3,
+    ) -> (Mat4, f32) {
+        let tan_y = (camera.fov_y() / 2.0).tan();
+        let tan_x = tan_y * camera.aspect_ratio();
+        let (forward, right, up) = (camera.forward(), camera.right(), camera.up());
+        let mut corners = [Vec3::ZERO; 8];
+        for (i, distance) in [start, end].into_iter().enumerate() {
+            let center = camera.position() + forward * distance;
+            let (x, y) = (right * tan_x * distance, up * tan_y * distance);
+            corners[i * 4] = center - x - y;
+            corners[i * 4 + 1] = center + x - y;
+            corners[i * 4 + 2] = center + x + y;
+            corners[i * 4 + 3] = center - x + y;
+        }
+        let center = corners.iter().sum::<Vec3>() / 8.0;
+        let radius = corners
+            .iter()
+            .map(|corner| corner.distance(center))
+            .fold(0.0, f32::max);
+        //rounded so float noise doesn't change the size every frame
+        let radius = (radius * 16.0).ceil() / 16.0;
+
+        let distance = radius + self.config.caster_distance;
+        let view = Mat4::look_at_rh(center - direction * distance, center, up_for(direction));
+        let mut projection =
+            Mat4::orthographic_rh_gl(-radius, radius, -radius, radius, 0.0, distance + radius);
+
+        let resolution = self.config.cascade_resolution as f32;
+        let origin = (projection * view).project_point3(Vec3::ZERO) * resolution / 2.0;
+        let offset = (origin.round() - origin) * 2.0 / resolution;
+        projection.w_axis.x += offset.x;
+        projection.w_axis.y += offset.y;
+
+        (projection * view, 2.0 * radius / resolution)
+    }
+
+    ///renders the opaque draws in the queue into every active map
+    pub(crate) fn render(&self, queue: &RenderQueue) {
+        if self.cascade_matrices.is_empty() && self.spot_matrices.is_empty() {
+            return;
+        }
+        RenderState {
+            cull: CullMode::None,
+            ..RenderState::default()
+        }
+        .apply();
+        self.depth_shader.bind();
+        let maps = self
+            .cascades
+            .iter()
+            .zip(&self.cascade_matrices)
+            .chain(self.spots.iter().zip(&self.spot_matrices));
+        for (framebuffer, matrix) in maps {
+            framebuffer.bind();
+            framebuffer.clear_depth(1.0, 0);
+            self.depth_shader.set_mat4("u_ViewProjection", matrix);
+            queue.draw_shadow_casters(&self.depth_shader);
+            framebuffer.unbind();
+        }
+    }
+
+    pub(crate) fn bind_textures(&self) {
+        let maps = self
+            .cascades
+            .iter()
+            .enumerate()
+            .map(|(i, map)| (SHADOW_TEXTURE_UNIT + i as u32, map))
+            .chain(
+                self.spots
+                    .iter()
+                    .enumerate()
+                    .map(|(i, map)| (SHADOW_TEXTURE_UNIT + (MAX_CASCADES + i) as u32, map)),
+            );
+        for (unit, map) in maps {
+            if let Some(texture) = map.depth_attachment_id() {
+                unsafe { gl::BindTextureUnit(unit, texture) }
+            }
+        }
+    }
+
+    ///points the lit shader's samplers at the units `bind_textures` uses
+    pub(crate) fn set_samplers(shader: &Shader) {
+        let cascades: Vec<i32> = (0..MAX_CASCADES as i32)
+            .map(|i| SHADOW_TEXTURE_UNIT as i32 + i)
+            .collect();
+        let spots: Vec<i32> = (0..MAX_SPOT_SHADOWS as i32)
+            .map(|i| (SHADOW_TEXTURE_UNIT as usize + MAX_CASCADES) as i32 + i)
+            .collect();
+        shader.bind();
+        shader.set_int_array("u_CascadeMaps", &cascades);
+        shader.set_int_array("u_SpotShadowMaps", &spots);
+    }
+}
+
+///window with every shadow map rendered this scene. spot maps use perspective depth so
+///most of what they see is close to white
+pub fn shadow_debug_window(ui: &Ui, shadows: &ShadowMaps, opened: &mut bool) {
+    const SIZE: f32 = 160.0;
+    ui.window("Shadow Maps")
+        .size([2.0 * SIZE + 40.0, 3.0 * SIZE], Condition::FirstUseEver)
+        .opened(opened)
+        .build(|| {
+            let image = |map: &Framebuffer| {
+                if let Some(texture) = map.depth_attachment_id() {
+                    //gl textures start at the bottom
+                    Image::new(texture_id(UiTexture::Gl(texture)), [SIZE, SIZE])
+                        .uv0([0.0, 1.0])
+                        .uv1([1.0, 0.0])
+                        .build(ui);
+                }
+            };
+            if shadows.active_cascades().is_empty() {
+                ui.text("no directional light with shadows");
+            }
+            let mut start = 0.0;
+            for (i, (map, end)) in shadows
+                .active_cascades()
+                .iter()
+                .zip(shadows.cascade_splits())
+                .enumerate()
+            {
+                ui.text(format!("cascade {i}: {start:.1} - {end:.1}"));
+                image(map);
+                start = *end;
+            }
+            ui.separator();
+            if shadows.active_spot_maps().is_empty() {
+                ui.text("no spot lights with shadows");
+            }
+            for (i, map) in shadows.active_spot_maps().iter().enumerate() {
+                ui.text(format!("spot light shadow {i}"));
+                image(map);
+            }
+        });
+}
+
+///where each cascade ends, a blend of even and logarithmic splits
+pub fn cascade_splits(near: f32, far: f32, count: usize, lambda: f32) -> Vec<f32> {
+    let near = near.max(f32::EPSILON);
+    (1..=count)
+        .map(|i| {
+            let p = i as f32 / count as f32;
+            let logarithmic = near * (far / near).powf(p);
+            let uniform = near + (far - near) * p;
+            lambda * logarithmic + (1.0 - lambda) * uniform
+        })
+        .collect()
+}
+
+fn up_for(direction: Vec3) -> Vec3 {
+    if direction.cross(Vec3::Y).length_squared() < 1e-6 {
+        Vec3::Z
+    } else {
+        Vec3::Y
+    }
+}
+
+const SHADOW_FUNCTIONS: &str = r#"
+layout(std140, binding = SHADOWS_BINDING) uniform Shadows {
+    mat4 u_CascadeMatrices[MAX_CASCADES];
+    mat4 u_SpotShadowMatrices[MAX_SPOT_SHADOWS];
+    vec4 u_CascadeSplits;
+    vec4 u_CascadeTexelSizes;
+    vec4 u_CameraForward;
+    //x: index of the light or -1, y: depth bias, z: normal bias in texels, w: pcf radius
+    vec4 u_DirectionalShadow;
+    //x: cascades, y: spot lights with shadows
+    ivec4 u_ShadowCounts;
+    //x: index of the light, y: depth bias, z: normal bias per unit of distance, w: pcf radius
+    vec4 u_SpotShadowParams[MAX_SPOT_SHADOWS];
+};
+
+uniform sampler2D u_CascadeMaps[MAX_CASCADES];
+uniform sampler2D u_SpotShadowMaps[MAX_SPOT_SHADOWS];
+
+//cascades are maps 0 to MAX_CASCADES - 1, spot lights come after them
+float shadow_depth(int map, vec2 uv) {
+    switch (map) {
+//CASES
+    }
+    return 1.0;
+}
+
+//1 when lit, 0 when fully in shadow
+float sample_shadow(int map, vec4 light_space, float bias, int radius) {
+    vec3 p = light_space.xyz / light_space.w * 0.5 + 0.5;
+    if (p.z > 1.0 || any(lessThan(p.xy, vec2(0.0))) || any(greaterThan(p.xy, vec2(1.0)))) {
+        return 1.0;
+    }
+    vec2 texel = 1.0 / vec2(map < MAX_CASCADES ? textureSize(u_CascadeMaps[0], 0)
+                                               : textureSize(u_SpotShadowMaps[0], 0));
+    float lit = 0.0;
+    for (int x = -radius; x <= radius; x++) {
+        for (int y = -radius; y <= radius; y++) {
+            float depth = shadow_depth(map, p.xy + vec2(x, y) * texel);
+            lit += p.z - bias > depth ? 0.0 : 1.0;
+        }
+    }
+    float width = float(2 * radius + 1);
+    return lit / (width * width);
+}
+
+float directional_shadow(int light, vec3 position, vec3 normal) {
+    if (light != int(u_DirectionalShadow.x)) {
+        return 1.0;
+    }
+    float view_depth = dot(position - u_CameraPosition.xyz, u_CameraForward.xyz);
+    for (int i = 0; i < u_ShadowCounts.x; i++) {
+        if (view_depth < u_CascadeSplits[i]) {
+            vec3 offset = normal * u_DirectionalShadow.z * u_CascadeTexelSizes[i];
+            vec4 light_space = u_CascadeMatrices[i] * vec4(position + offset, 1.0);
+            return sample_shadow(i, light_space, u_DirectionalShadow.y, int(u_DirectionalShadow.w));
+        }
+    }
+    return 1.0;
+}
+
+float spot_shadow(int light, vec3 position, vec3 normal, float distance) {
+    for (int i = 0; i < u_ShadowCounts.y; i++) {
+        vec4 params = u_SpotShadowParams[i];
+        if (int(params.x) == light) {
+            vec3 offset = normal * params.z * distance;
+            vec4 light_space = u_SpotShadowMatrices[i] * vec4(position + offset, 1.0);
+            return sample_shadow(MAX_CASCADES + i, light_space, params.y, int(params.w));
+        }
+    }
+    return 1.0;
+}
+"#;
@@ -0,0 +1,55 @@
+//counters for everything drawn on the main thread since the last `Renderer::render_frame`.
+//the draw functions of `Renderer` and the 2d, line and post process renderers count their
+//draws, binding a shader, texture, vertex array or framebuffer counts as a state change.
+//imgui's own draws aren't counted
+
+use std::cell::Cell;
+
+///what the gpu was asked to do in a frame
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct RenderStats {
+    pub draw_calls: u32,
+    pub triangles: u64,
+    pub state_changes: u32,
+}
+
+thread_local! {
+    static CURRENT: Cell<RenderStats> = Cell::new(RenderStats::default());
+    static LAST: Cell<RenderStats> = Cell::new(RenderStats::default());
+}
+
+fn update(f: impl FnOnce(&mut RenderStats)) {
+    CURRENT.with(|current| {
+        let mut stats = current.get();
+        f(&mut stats);
+        current.set(stats);
+    });
+}
+
+///one draw call of `triangles` triangles, lines and points count as none
+pub fn record_draw(triangles: u64) {
+    update(|stats| {
+        stats.draw_calls += 1;
+        stats.triangles += triangles;
+    });
+}
+
+pub fn record_state_change() {
+    update(|stats| stats.state_changes += 1);
+}
+
+///the frame so far
+pub fn current() -> RenderStats {
+    CURRENT.with(Cell::get)
+}
+
+///the frame before the last `begin_frame`
+pub fn last_frame() -> RenderStats {
+    LAST.with(Cell::get)
+}
+
+///keeps the counts as `last_frame` and starts over, `Renderer::render_frame` calls it
+pub fn begin_frame() {
+    let finished = CURRENT.with(|current| current.replace(RenderStats::default()));
+    LAST.with(|last| last.set(finished));
+}
@@ -0,0 +1,264 @@
+use std::{collections::HashMap, rc::Rc};
+
+use super::{Font, TextLayout};
+use crate::{
+    log::rge_engine_warn,
+    math::{Rect, Vec2},
+    renderer::texture::{Texture2D, TextureFilter, TextureWrap},
+};
+
+const INITIAL_SIZE: u32 = 256;
+const MAX_SIZE: u32 = 4096;
+//keeps linear filtering from bleeding into the neighbours
+const PADDING: u32 = 1;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AtlasKind {
+    ///coverage, sharpest when drawn at the atlas pixel size
+    Bitmap,
+    ///signed distance to the outline, stays sharp when scaled up. `spread` is how many pixels
+    ///around the outline the distance covers
+    Sdf { spread: u32 },
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AtlasGlyph {
+    ///in atlas pixels from the top left
+    pub rect: Rect,
+    ///bottom left corner of the glyph from the pen position, y up, in atlas pixels
+    pub offset: Vec2,
+}
+
+//glyphs are packed left to right into rows as tall as the tallest glyph in them
+#[derive(Default)]
+struct Shelf {
+    x: u32,
+    y: u32,
+    height: u32,
+}
+
+///glyphs of one font rasterized at one size into a single channel texture (white with the
+///coverage or distance in alpha). glyphs are added the first time they are drawn and the
+///atlas doubles in size when it runs out of room
+pub struct FontAtlas {
+    font: Rc<Font>,
+    pixel_size: f32,
+    kind: AtlasKind,
+    size: u32,
+    pixels: Vec<u8>,
+    texture: Rc<Texture2D>,
+    //`None` for glyphs with nothing to draw, like spaces
+    glyphs: HashMap<u16, Option<AtlasGlyph>>,
+    shelf: Shelf,
+    dirty: bool,
+}
+
+impl FontAtlas {
+    ///needs a current gl context
+    pub fn new(font: Rc<Font>, pixel_size: f32) -> Self {
+        Self::with_kind(font, pixel_size, AtlasKind::Bitmap)
+    }
+
+    ///an sdf atlas, 32 to 64 pixels with a spread of 4 to 8 works for most text sizes
+    pub fn sdf(font: Rc<Font>, pixel_size: f32, spread: u32) -> Self {
+        Self::with_kind(font, pixel_size, AtlasKind::Sdf { spread })
+    }
+
+    fn with_kind(font: Rc<Font>, pixel_size: f32, kind: AtlasKind) -> Self {
+        Self {
+            font,
+            pixel_size,
+            kind,
+            size: INITIAL_SIZE,
+            pixels: vec![0; (INITIAL_SIZE * INITIAL_SIZE) as usize],
+            texture: Rc::new(create_texture(INITIAL_SIZE)),
+            glyphs: HashMap::new(),
+            shelf: Shelf::default(),
+            dirty: false,
+        }
+    }
+
+    pub fn font(&self) -> &Rc<Font> {
+        &self.font
+    }
+
+    pub fn pixel_size(&self) -> f32 {
+        self.pixel_size
+    }
+
+    pub fn kind(&self) -> AtlasKind {
+        self.kind
+    }
+
+    ///width and height in pixels
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    ///only has the glyphs added before the last `upload`
+    pub fn texture(&self) -> &Rc<Texture2D> {
+        &self.texture
+    }
+
+    ///glyphs rasterized so far, including empty ones
+    pub fn glyph_count(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    ///rasterizes every glyph of the layout ahead of drawing it
+    pub fn cache(&mut self, layout: &TextLayout) {
+        for glyph in &layout.glyphs {
+            self.glyph(glyph.index);
+        }
+    }
+
+    ///where the glyph is in the atlas, rasterizing it on first use. `None` for glyphs without
+    ///pixels and when the atlas is full
+    pub fn glyph(&mut self, index: u16) -> Option<AtlasGlyph> {
+        if let Some(glyph) = self.glyphs.get(&index) {
+            return *glyph;
+        }
+        let glyph = self.rasterize(index);
+        self.glyphs.insert(index, glyph);
+        glyph
+    }
+
+    ///copies new glyphs to the texture, a grown atlas gets a new texture.
+    ///`Renderer2D::draw_text` calls this itself
+    pub fn upload(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        if self.texture.width() != self.size {
+            //quads already batched keep the old texture alive until they are drawn
+            self.texture = Rc::new(create_texture(self.size));
+        }
+        let rgba: Vec<u8> = self
+            .pixels
+            .iter()
+            .flat_map(|&alpha| [255, 255, 255, alpha])
+            .collect();
+        self.texture.set_data(&rgba);
+        self.dirty = false;
+    }
+
+    fn rasterize(&mut self, index: u16) -> Option<AtlasGlyph> {
+        let (metrics, coverage) = self.font.rasterize(index, self.pixel_size);
+        if metrics.width == 0 || metrics.height == 0 {
+            return None;
+        }
+        let (width, height, bitmap, border) = match self.kind {
+            AtlasKind::Bitmap => (metrics.width, metrics.height, coverage, 0),
+            AtlasKind::Sdf { spread } => {
+                let (width, height, distances) =
+                    signed_distance(&coverage, metrics.width, metrics.height, spread);
+                (width, height, distances, spread as i32)
+            }
+        };
+        let Some((x, y)) = self.pack(width as u32, height as u32) else {
+            rge_engine_warn!(
+                "font atlas for {} is full, glyph {} is skipped",
+                self.font.name(),
+                index
+            );
+            return None;
+        };
+        for row in 0..height {
+            let start = (y as usize + row) * self.size as usize + x as usize;
+            self.pixels[start..start + width].copy_from_slice(&bitmap[row * width..][..width]);
+        }
+        self.dirty = true;
+        Some(AtlasGlyph {
+            rect: Rect::new(x as f32, y as f32, width as f32, height as f32),
+            offset: Vec2::new(
+                (metrics.xmin - border) as f32,
+                (metrics.ymin - border) as f32,
+            ),
+        })
+    }
+
+    fn pack(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        loop {
+            if self.shelf.x + width + PADDING > self.size {
+                self.shelf.y += self.shelf.height;
+                self.shelf.x = 0;
+                self.shelf.height = 0;
+            }
+            if self.shelf.x + width + PADDING <= self.size
+                && self.shelf.y + height + PADDING <= self.size
+            {
+                let position = (self.shelf.x + PADDING, self.shelf.y + PADDING);
+                self.shelf.x += width + PADDING;
+                self.shelf.height = self.shelf.height.max(height + PADDING);
+                return Some(position);
+            }
+            if self.size >= MAX_SIZE {
+                return None;
+            }
+            self.grow();
+        }
+    }
+
+    ///doubles the size, glyphs keep their pixel positions
+    fn grow(&mut self) {
+        let size = self.size * 2;
+        let mut pixels = vec![0; (size * size) as usize];
+        for (row, old) in self.pixels.chunks(self.size as usize).enumerate() {
+            let start = row * size as usize;
+            pixels[start..start + old.len()].copy_from_slice(old);
+        }
+        self.pixels = pixels;
+        self.size = size;
+        self.dirty = true;
+    }
+}
+
+fn create_texture(size: u32) -> Texture2D {
+    let texture = Texture2D::new(size, size);
+    texture.set_filter(TextureFilter::Linear);
+    texture.set_wrap(TextureWrap::ClampToEdge);
+    texture
+}
+
+///distance from each pixel to the closest pixel on the other side of the outline, searched
+///within `spread` pixels. the result is `spread` pixels bigger on every side, 128 is the
+///outline and 0 or 255 are `spread` pixels outside or inside it
+fn signed_distance(
+    coverage: &[u8],
+    width: usize,
+    height: usize,
+    spread: u32,
+) -> (usize, usize, Vec<u8>) {
+    let spread = spread.max(1) as i32;
+    let inside = |x: i32, y: i32| {
+        x >= 0
+            && y >= 0
+            && (x as usize) < width
+            && (y as usize) < height
+            && coverage[y as usize * width + x as usize] >= 128
+    };
+    let out_width = width + 2 * spread as usize;
+    let out_height = height + 2 * spread as usize;
+    let mut distances = Vec::with_capacity(out_width * out_height);
+    for out_y in 0..out_height as i32 {
+        for out_x in 0..out_width as i32 {
+            let (x, y) = (out_x - spread, out_y - spread);
+            let state = inside(x, y);
+            let mut closest = (spread * spread) as f32;
+            for dy in -spread..=spread {
+                for dx in -spread..=spread {
+                    let squared = (dx * dx + dy * dy) as f32;
+                    if squared < closest && inside(x + dx, y + dy) != state {
+                        closest = squared;
+                    }
+                }
+            }
+            //the outline runs between the pixel centers
+            let distance = (closest.sqrt() - 0.5).max(0.0);
+            let signed = if state { distance } else { -distance };
+            let value = 0.5 + signed / (2.0 * spread as f32);
+            distances.push((value.clamp(0.0, 1.0) * 255.0).round() as u8);
+        }
+    }
+    (out_width, out_height, distances)
+}
@@ -0,0 +1,332 @@
+//fonts and layout only need the font file, the glyphs go into a texture through `FontAtlas`
+//and `Renderer2D::draw_text` draws them as quads
+mod atlas;
+
+pub use atlas::{AtlasGlyph, AtlasKind, FontAtlas};
+
+use std::{cell::RefCell, collections::HashMap, ops::Range, path::Path};
+
+use fontdue::FontSettings;
+use ttf_parser::{
+    Face, GlyphId, Tag,
+    gpos::{PairAdjustment, PositioningSubtable},
+};
+
+use crate::math::Vec2;
+
+///a parsed ttf or otf file. glyphs come from the font's character map and are kerned with the
+///`kern` table or the gpos kerning pairs, there is no further shaping so scripts that need
+///ligatures or reordering won't come out right
+pub struct Font {
+    name: String,
+    font: fontdue::Font,
+    data: Vec<u8>,
+    //the gpos lookups of the kern feature, pairs are looked up the first time they're used
+    kern_lookups: Vec<u16>,
+    kerning: RefCell<HashMap<(u16, u16), f32>>,
+}
+
+impl Font {
+    pub fn from_bytes(name: &str, data: &[u8]) -> Result<Self, String> {
+        let font = fontdue::Font::from_bytes(data, FontSettings::default())
+            .map_err(|e| format!("failed to parse font {name}: {e}"))?;
+        let face = Face::parse(data, 0).map_err(|e| format!("failed to parse font {name}: {e}"))?;
+        let mut kern_lookups = Vec::new();
+        if let Some(gpos) = face.tables().gpos {
+            let features = (0..gpos.features.len()).filter_map(|i| gpos.features.get(i));
+            for feature in features.filter(|f| f.tag == Tag::from_bytes(b"kern")) {
+                kern_lookups.extend(feature.lookup_indices);
+            }
+        }
+        kern_lookups.sort_unstable();
+        kern_lookups.dedup();
+        Ok(Self {
+            name: name.to_string(),
+            font,
+            data: data.to_vec(),
+            kern_lookups,
+            kerning: RefCell::new(HashMap::new()),
+        })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let data = std::fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Self::from_bytes(&name, &data)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn has_glyph(&self, ch: char) -> bool {
+        self.font.has_glyph(ch)
+    }
+
+    ///distance from the baseline to the top of the tallest glyph at `size`
+    pub fn ascent(&self, size: f32) -> f32 {
+        self.line_metrics(size).ascent
+    }
+
+    ///distance from the baseline to the bottom of the lowest glyph at `size`, negative
+    pub fn descent(&self, size: f32) -> f32 {
+        self.line_metrics(size).descent
+    }
+
+    ///baseline to baseline distance the font asks for at `size`
+    pub fn line_height(&self, size: f32) -> f32 {
+        self.line_metrics(size).new_line_size
+    }
+
+    ///how far the pen moves after `ch` at `size`
+    pub fn advance(&self, ch: char, size: f32) -> f32 {
+        self.font.metrics(ch, size).advance_width
+    }
+
+    ///added to the advance between `left` and `right`, usually negative
+    pub fn kerning(&self, left: char, right: char, size: f32) -> f32 {
+        if let Some(kerning) = self.font.horizontal_kern(left, right, size) {
+            return kerning;
+        }
+        if self.kern_lookups.is_empty() {
+            return 0.0;
+        }
+        let pair = (self.glyph_index(left), self.glyph_index(right));
+        let units = *self
+            .kerning
+            .borrow_mut()
+            .entry(pair)
+            .or_insert_with(|| self.gpos_kerning(pair.0, pair.1));
+        units * self.font.scale_factor(size)
+    }
+
+    ///the x advance adjustments of every kern lookup with the pair, in font units
+    fn gpos_kerning(&self, left: u16, right: u16) -> f32 {
+        let Some(gpos) = Face::parse(&self.data, 0)
+            .ok()
+            .and_then(|f| f.tables().gpos)
+        else {
+            return 0.0;
+        };
+        let (left, right) = (GlyphId(left), GlyphId(right));
+        let mut total = 0;
+        for lookup in self
+            .kern_lookups
+            .iter()
+            .filter_map(|&i| gpos.lookups.get(i))
+        {
+            //only the first subtable covering the pair applies
+            let adjustment = lookup
+                .subtables
+                .into_iter::<PositioningSubtable>()
+                .find_map(|subtable| {
+                    let PositioningSubtable::Pair(pair) = subtable else {
+                        return None;
+                    };
+                    let index = pair.coverage().get(left)?;
+                    let (first, _) = match pair {
+                        PairAdjustment::Format1 { sets, .. } => sets.get(index)?.get(right)?,
+                        PairAdjustment::Format2 {
+                            classes, matrix, ..
+                        } => matrix.get((classes.0.get(left), classes.1.get(right)))?,
+                    };
+                    Some(first.x_advance)
+                });
+            total += adjustment.unwrap_or(0) as i32;
+        }
+        total as f32
+    }
+
+    ///0 for characters the font doesn't have, which draws its missing glyph box
+    pub fn glyph_index(&self, ch: char) -> u16 {
+        self.font.lookup_glyph_index(ch)
+    }
+
+    pub(crate) fn rasterize(&self, index: u16, size: f32) -> (fontdue::Metrics, Vec<u8>) {
+        self.font.rasterize_indexed(index, size)
+    }
+
+    fn line_metrics(&self, size: f32) -> fontdue::LineMetrics {
+        //fonts without the horizontal header still lay out, just without any line spacing
+        self.font
+            .horizontal_line_metrics(size)
+            .unwrap_or(fontdue::LineMetrics {
+                ascent: size,
+                descent: 0.0,
+                line_gap: 0.0,
+                new_line_size: size,
+            })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TextStyle {
+    ///em size in world units, with a pixel camera this is the font size in pixels
+    pub size: f32,
+    ///lines are aligned inside `max_width`, or inside the widest line without one
+    pub align: TextAlign,
+    ///lines wrap at whitespace to stay inside this width, words longer than a line are broken
+    ///between characters
+    pub max_width: Option<f32>,
+    ///multiplies the font's line height
+    pub line_spacing: f32,
+    ///tab stops, in spaces
+    pub tab_width: u32,
+}
+
+impl TextStyle {
+    pub fn new(size: f32) -> Self {
+        Self {
+            size,
+            align: TextAlign::Left,
+            max_width: None,
+            line_spacing: 1.0,
+            tab_width: 4,
+        }
+    }
+
+    pub fn with_align(mut self, align: TextAlign) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn with_max_width(mut self, max_width: f32) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LayoutGlyph {
+    pub ch: char,
+    ///the glyph in the font, for `FontAtlas::glyph`
+    pub index: u16,
+    ///the pen position on the baseline, from the top left of the text with y going down
+    pub position: Vec2,
+    pub advance: f32,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TextLine {
+    ///the line's range in `TextLayout::glyphs`
+    pub glyphs: Range<usize>,
+    ///without trailing whitespace
+    pub width: f32,
+    ///from the top of the text, y going down
+    pub baseline: f32,
+}
+
+///where every glyph of a string goes, worked out once so text can be measured before it's drawn
+#[derive(Clone, PartialEq, Debug)]
+pub struct TextLayout {
+    pub glyphs: Vec<LayoutGlyph>,
+    pub lines: Vec<TextLine>,
+    ///the widest line by the height of all lines
+    pub size: Vec2,
+    pub font_size: f32,
+}
+
+impl TextLayout {
+    ///`\n` starts a new line, `\r` is ignored
+    pub fn new(font: &Font, text: &str, style: &TextStyle) -> Self {
+        let size = style.size;
+        let line_height = font.line_height(size) * style.line_spacing;
+        let tab_stop = font.advance(' ', size) * style.tab_width.max(1) as f32;
+        let mut layout = Self {
+            glyphs: Vec::new(),
+            lines: Vec::new(),
+            size: Vec2::ZERO,
+            font_size: size,
+        };
+
+        for paragraph in text.split('\n') {
+            let mut line: Vec<LayoutGlyph> = Vec::new();
+            let mut pen = 0.0;
+            //where the line can be broken, just after the last whitespace
+            let mut break_at = None;
+            for ch in paragraph.chars().filter(|&c| c != '\r') {
+                let kerning = line.last().map_or(0.0, |g| font.kerning(g.ch, ch, size));
+                let mut x = pen + kerning;
+                let advance = if ch == '\t' {
+                    ((pen / tab_stop).floor() + 1.0) * tab_stop - pen
+                } else {
+                    font.advance(ch, size)
+                };
+
+                let overflows = style
+                    .max_width
+                    .is_some_and(|max_width| x + advance > max_width);
+                let has_content = line.iter().any(|g| !g.ch.is_whitespace());
+                if overflows && !ch.is_whitespace() && has_content {
+                    let split = break_at.unwrap_or(line.len());
+                    let mut rest = line.split_off(split);
+                    layout.push_line(line);
+                    let shift = rest.first().map_or(0.0, |g| g.position.x);
+                    for glyph in &mut rest {
+                        glyph.position.x -= shift;
+                    }
+                    pen = rest.last().map_or(0.0, |g| g.position.x + g.advance);
+                    x = pen + rest.last().map_or(0.0, |g| font.kerning(g.ch, ch, size));
+                    line = rest;
+                    break_at = None;
+                }
+
+                line.push(LayoutGlyph {
+                    ch,
+                    index: font.glyph_index(ch),
+                    position: Vec2::new(x, 0.0),
+                    advance,
+                });
+                pen = x + advance;
+                if ch.is_whitespace() {
+                    break_at = Some(line.len());
+                }
+            }
+            layout.push_line(line);
+        }
+
+        let box_width = style.max_width.unwrap_or(layout.size.x);
+        let ascent = font.ascent(size);
+        for (i, line) in layout.lines.iter_mut().enumerate() {
+            line.baseline = ascent + i as f32 * line_height;
+            let offset = match style.align {
+                TextAlign::Left => 0.0,
+                TextAlign::Center => (box_width - line.width) / 2.0,
+                TextAlign::Right => box_width - line.width,
+            };
+            for glyph in &mut layout.glyphs[line.glyphs.clone()] {
+                glyph.position += Vec2::new(offset, line.baseline);
+            }
+        }
+        layout.size.y = (layout.lines.len() - 1) as f32 * line_height + ascent - font.descent(size);
+        layout
+    }
+
+    fn push_line(&mut self, glyphs: Vec<LayoutGlyph>) {
+        let width = glyphs
+            .iter()
+            .rev()
+            .find(|g| !g.ch.is_whitespace())
+            .map_or(0.0, |g| g.position.x + g.advance);
+        let start = self.glyphs.len();
+        self.glyphs.extend(glyphs);
+        self.lines.push(TextLine {
+            glyphs: start..self.glyphs.len(),
+            width,
+            baseline: 0.0,
+        });
+        self.size.x = self.size.x.max(width);
+    }
+}
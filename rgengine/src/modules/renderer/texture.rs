@@ -0,0 +1,128 @@
+use std::{ffi::c_void, path::Path};
+
+use super::stats;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextureFilter {
+    Linear,
+    Nearest,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextureWrap {
+    Repeat,
+    ClampToEdge,
+}
+
+///rgba8 texture, linear when minified and nearest when magnified so scaled up sprites stay
+///sharp. `set_filter` picks one for both
+#[derive(Debug)]
+pub struct Texture2D {
+    id: u32,
+    width: u32,
+    height: u32,
+}
+
+impl Texture2D {
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut id = 0;
+        unsafe {
+            gl::CreateTextures(gl::TEXTURE_2D, 1, &mut id);
+            gl::TextureStorage2D(id, 1, gl::RGBA8, width as i32, height as i32);
+            gl::TextureParameteri(id, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TextureParameteri(id, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
+            gl::TextureParameteri(id, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
+            gl::TextureParameteri(id, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+        }
+        Self { id, width, height }
+    }
+
+    ///`pixels` is tightly packed rgba, top row first
+    pub fn from_rgba(width: u32, height: u32, pixels: &[u8]) -> Self {
+        let texture = Self::new(width, height);
+        texture.set_data(pixels);
+        texture
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, image::ImageError> {
+        let image = image::open(path)?.to_rgba8();
+        Ok(Self::from_rgba(
+            image.width(),
+            image.height(),
+            image.as_raw(),
+        ))
+    }
+
+    pub fn set_data(&self, pixels: &[u8]) {
+        assert_eq!(
+            pixels.len(),
+            (self.width * self.height * 4) as usize,
+            "texture data must cover the whole texture"
+        );
+        unsafe {
+            gl::TextureSubImage2D(
+                self.id,
+                0,
+                0,
+                0,
+                self.width as i32,
+                self.height as i32,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_ptr() as *const c_void,
+            );
+        }
+    }
+
+    ///used for both minifying and magnifying
+    pub fn set_filter(&self, filter: TextureFilter) {
+        let filter = match filter {
+            TextureFilter::Linear => gl::LINEAR,
+            TextureFilter::Nearest => gl::NEAREST,
+        };
+        unsafe {
+            gl::TextureParameteri(self.id, gl::TEXTURE_MIN_FILTER, filter as i32);
+            gl::TextureParameteri(self.id, gl::TEXTURE_MAG_FILTER, filter as i32);
+        }
+    }
+
+    pub fn set_wrap(&self, wrap: TextureWrap) {
+        let wrap = match wrap {
+            TextureWrap::Repeat => gl::REPEAT,
+            TextureWrap::ClampToEdge => gl::CLAMP_TO_EDGE,
+        };
+        unsafe {
+            gl::TextureParameteri(self.id, gl::TEXTURE_WRAP_S, wrap as i32);
+            gl::TextureParameteri(self.id, gl::TEXTURE_WRAP_T, wrap as i32);
+        }
+    }
+
+    pub fn bind(&self, slot: u32) {
+        unsafe { gl::BindTextureUnit(slot, self.id) }
+        stats::record_state_change();
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+impl PartialEq for Texture2D {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Drop for Texture2D {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteTextures(1, &self.id) }
+    }
+}
@@ -0,0 +1,65 @@
+use std::ffi::c_void;
+
+use gl::types::{GLintptr, GLsizeiptr};
+
+///a std140 uniform block's storage, bound to a fixed binding point so shaders can use
+///`layout(std140, binding = N)` instead of looking the block up
+pub struct UniformBuffer {
+    id: u32,
+    size: usize,
+    binding: u32,
+}
+
+impl UniformBuffer {
+    pub fn new(size: usize, binding: u32) -> Self {
+        let mut id = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut id);
+            gl::BindBuffer(gl::UNIFORM_BUFFER, id);
+            gl::BufferData(
+                gl::UNIFORM_BUFFER,
+                size as GLsizeiptr,
+                std::ptr::null(),
+                gl::DYNAMIC_DRAW,
+            );
+            gl::BindBufferBase(gl::UNIFORM_BUFFER, binding, id);
+        }
+        Self { id, size, binding }
+    }
+
+    ///`T` has to match the std140 layout of the block, writes past the end are cut off
+    pub fn set_data<T: Copy>(&self, data: &[T], offset: usize) {
+        let size = std::mem::size_of_val(data).min(self.size.saturating_sub(offset));
+        if size == 0 {
+            return;
+        }
+        unsafe {
+            gl::BindBuffer(gl::UNIFORM_BUFFER, self.id);
+            gl::BufferSubData(
+                gl::UNIFORM_BUFFER,
+                offset as GLintptr,
+                size as GLsizeiptr,
+                data.as_ptr() as *const c_void,
+            );
+        }
+    }
+
+    ///binds it again, for when something else used the binding point in between
+    pub fn bind(&self) {
+        unsafe { gl::BindBufferBase(gl::UNIFORM_BUFFER, self.binding, self.id) }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn binding(&self) -> u32 {
+        self.binding
+    }
+}
+
+impl Drop for UniformBuffer {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteBuffers(1, &self.id) }
+    }
+}
@@ -1,6 +1,6 @@
-use std::sync::mpsc::Receiver;
+use std::{collections::VecDeque, sync::mpsc::Receiver};
 
-use glfw::{Action, Callback, Context, Error, WindowEvent, WindowHint};
+use glfw::{Callback, Context, Error, GamepadAxis, GamepadButton, JoystickId, WindowHint};
 
 use crate::{log::rge_engine_error, rgevent};
 
@@ -9,6 +9,7 @@ use super::events::*;
 //todo need to make all the public stuff private and acessed form methods
 pub struct WindowData {
     title: String,
+    ///size in screen coordinates, not pixels
     pub width: u32,
     pub height: u32,
     vsync: bool,
@@ -20,6 +21,45 @@ pub struct WindowManager {
     glfw: glfw::Glfw,
     pub window: glfw::Window,
     events: Receiver<(f64, glfw::WindowEvent)>,
+    pending: VecDeque<RGEvent>,
+    gamepad: GamepadSnapshot,
+}
+
+const GAMEPAD_BUTTONS: [GamepadButton; 15] = [
+    GamepadButton::ButtonA,
+    GamepadButton::ButtonB,
+    GamepadButton::ButtonX,
+    GamepadButton::ButtonY,
+    GamepadButton::ButtonLeftBumper,
+    GamepadButton::ButtonRightBumper,
+    GamepadButton::ButtonBack,
+    GamepadButton::ButtonStart,
+    GamepadButton::ButtonGuide,
+    GamepadButton::ButtonLeftThumb,
+    GamepadButton::ButtonRightThumb,
+    GamepadButton::ButtonDpadUp,
+    GamepadButton::ButtonDpadRight,
+    GamepadButton::ButtonDpadDown,
+    GamepadButton::ButtonDpadLeft,
+];
+
+const GAMEPAD_AXES: [GamepadAxis; 6] = [
+    GamepadAxis::AxisLeftX,
+    GamepadAxis::AxisLeftY,
+    GamepadAxis::AxisRightX,
+    GamepadAxis::AxisRightY,
+    GamepadAxis::AxisLeftTrigger,
+    GamepadAxis::AxisRightTrigger,
+];
+
+//smaller axis changes are stick noise
+const AXIS_THRESHOLD: f32 = 0.01;
+
+//what the gamepad looked like at the last poll, the events are the differences
+#[derive(Default)]
+struct GamepadSnapshot {
+    buttons: [bool; 15],
+    axes: [f32; 6],
 }
 
 impl WindowManager {
@@ -60,48 +100,94 @@ impl WindowManager {
             glfw,
             window,
             events,
+            pending: VecDeque::new(),
+            gamepad: GamepadSnapshot::default(),
         }
     }
 
-    pub fn handle_events(&mut self) -> Option<RGEvent> {
+    ///polls the window once and queues what happened since the last call for `handle_events`,
+    ///the engine calls this once a frame
+    pub fn pump_events(&mut self) {
         self.glfw.poll_events();
         for (_, event) in glfw::flush_messages(&self.events) {
-            match event {
+            let event = match event {
                 glfw::WindowEvent::Key(key, _, action, _) => match action {
-                    glfw::Action::Press => {
-                        return Some(rgevent!(KeyPressed, key, false));
-                    }
-                    glfw::Action::Release => {
-                        return Some(rgevent!(KeyReleased, key));
-                    }
-                    glfw::Action::Repeat => {
-                        return Some(rgevent!(KeyPressed, key, true));
-                    }
+                    glfw::Action::Press => rgevent!(KeyPressed, key, false),
+                    glfw::Action::Release => rgevent!(KeyReleased, key),
+                    glfw::Action::Repeat => rgevent!(KeyPressed, key, true),
                 },
+                glfw::WindowEvent::Char(ch) => rgevent!(KeyTyped, ch),
                 glfw::WindowEvent::MouseButton(button, action, _) => match action {
                     glfw::Action::Press | glfw::Action::Repeat => {
-                        return Some(rgevent!(MouseButtonPressed, button));
-                    }
-                    glfw::Action::Release => {
-                        return Some(rgevent!(MouseButtonReleased, button));
+                        rgevent!(MouseButtonPressed, button)
                     }
+                    glfw::Action::Release => rgevent!(MouseButtonReleased, button),
                 },
-                glfw::WindowEvent::CursorPos(x, y) => return Some(rgevent!(MouseMoved, x, y)),
-                glfw::WindowEvent::Scroll(x, y) => return Some(rgevent!(MouseScrolled, x, y)),
-                glfw::WindowEvent::Pos(x, y) => return Some(rgevent!(WindowMoved, x, y)),
+                glfw::WindowEvent::CursorPos(x, y) => rgevent!(MouseMoved, x, y),
+                glfw::WindowEvent::Scroll(x, y) => rgevent!(MouseScrolled, x, y),
+                glfw::WindowEvent::Pos(x, y) => rgevent!(WindowMoved, x, y),
+                //the logical size, on hidpi screens the framebuffer has more pixels than this
                 glfw::WindowEvent::Size(x, y) => {
                     self.data.width = x as u32;
                     self.data.height = y as u32;
-                    return Some(rgevent!(WindowResize, x, y));
+                    continue;
                 }
-                glfw::WindowEvent::Focus(true) => return Some(rgevent!(WindowFocus)),
-                glfw::WindowEvent::Focus(false) => return Some(rgevent!(WindowLostFocus)),
-                glfw::WindowEvent::Close => return Some(rgevent!(WindowClose)),
+                glfw::WindowEvent::FramebufferSize(x, y) => rgevent!(WindowResize, x, y),
+                glfw::WindowEvent::Focus(true) => rgevent!(WindowFocus),
+                glfw::WindowEvent::Focus(false) => rgevent!(WindowLostFocus),
+                glfw::WindowEvent::Close => rgevent!(WindowClose),
 
                 _ => continue, // Skip unsupported events
+            };
+            self.pending.push_back(event);
+        }
+        self.poll_gamepad();
+    }
+
+    ///the next event queued by `pump_events`, `None` once they have all been handled. events
+    ///that come in while these are handled wait for the next frame
+    pub fn handle_events(&mut self) -> Option<RGEvent> {
+        self.pending.pop_front()
+    }
+
+    //glfw has no gamepad callbacks so the state is compared with the last poll
+    fn poll_gamepad(&mut self) {
+        let state = (JoystickId::Joystick1 as i32..=JoystickId::Joystick16 as i32)
+            .filter_map(JoystickId::from_i32)
+            .map(|id| self.glfw.get_joystick(id))
+            .filter(|joystick| joystick.is_gamepad())
+            .find_map(|joystick| joystick.get_gamepad_state());
+        //a disconnected gamepad releases everything
+        let mut current = GamepadSnapshot::default();
+        if let Some(state) = &state {
+            for (i, &button) in GAMEPAD_BUTTONS.iter().enumerate() {
+                current.buttons[i] = state.get_button_state(button) != glfw::Action::Release;
+            }
+            for (i, &axis) in GAMEPAD_AXES.iter().enumerate() {
+                current.axes[i] = state.get_axis(axis);
+            }
+        }
+
+        for (i, &button) in GAMEPAD_BUTTONS.iter().enumerate() {
+            match (self.gamepad.buttons[i], current.buttons[i]) {
+                (false, true) => self
+                    .pending
+                    .push_back(rgevent!(GamepadButtonPressed, button)),
+                (true, false) => self
+                    .pending
+                    .push_back(rgevent!(GamepadButtonReleased, button)),
+                _ => {}
+            }
+        }
+        for (i, &axis) in GAMEPAD_AXES.iter().enumerate() {
+            let value = current.axes[i];
+            if (value - self.gamepad.axes[i]).abs() > AXIS_THRESHOLD {
+                self.gamepad.axes[i] = value;
+                self.pending
+                    .push_back(rgevent!(GamepadAxisMoved, axis, value));
             }
         }
-        None // Return None if no events are found
+        self.gamepad.buttons = current.buttons;
     }
     pub fn poll_events<F: FnMut(glfw::WindowEvent)>(&mut self, mut callback: F) {
         for (_, event) in glfw::flush_messages(&self.events) {
@@ -113,6 +199,10 @@ impl WindowManager {
         self.window.swap_buffers();
     }
 
+    pub fn title(&self) -> &str {
+        &self.data.title
+    }
+
     pub fn get_glfw(&self) -> &glfw::Glfw {
         &self.glfw
     }
@@ -123,11 +213,17 @@ impl WindowManager {
     pub fn is_vsync(&self) -> bool {
         self.data.vsync
     }
+    ///applies to the window's context, which has to be current
     pub fn set_vsync(&mut self, b: bool) {
         self.data.vsync = b;
+        self.glfw.set_swap_interval(if b {
+            glfw::SwapInterval::Sync(1)
+        } else {
+            glfw::SwapInterval::None
+        });
     }
 }
 
-fn glfw_error_callback<'a>(error: Error, description: String, _context: &'a ()) {
+fn glfw_error_callback(error: Error, description: String, _context: &()) {
     rge_engine_error!("GLFW Error {:?}: {}", error, description);
 }
@@ -0,0 +1,190 @@
+//headless rendering for the golden image tests. uses mesa's surfaceless egl platform so it
+//runs on llvmpipe without a gpu or a display, install libegl-mesa0 (or your distro's mesa
+//egl package) to run them. set RGE_UPDATE_GOLDENS=1 to write new goldens
+#![allow(dead_code)]
+
+use std::{
+    ffi::c_void,
+    path::{Path, PathBuf},
+};
+
+use khronos_egl as egl;
+use rgengine::renderer::{
+    RenderTarget, Renderer,
+    capture::CapturedFrame,
+    framebuffer::{Framebuffer, FramebufferSpec, FramebufferTextureFormat},
+};
+
+const PLATFORM_SURFACELESS_MESA: egl::Enum = 0x31DD;
+
+pub struct HeadlessContext {
+    egl: egl::Instance<egl::Static>,
+    display: egl::Display,
+    context: egl::Context,
+}
+
+impl HeadlessContext {
+    ///gl 4.5 core context, current on this thread
+    pub fn new() -> Result<Self, String> {
+        let egl = egl::Instance::new(egl::Static);
+        let display = unsafe {
+            egl.get_platform_display(
+                PLATFORM_SURFACELESS_MESA,
+                egl::DEFAULT_DISPLAY,
+                &[egl::ATTRIB_NONE],
+            )
+        }
+        .map_err(|e| format!("no surfaceless egl display: {e}"))?;
+        egl.initialize(display)
+            .map_err(|e| format!("egl initialize failed: {e}"))?;
+        egl.bind_api(egl::OPENGL_API)
+            .map_err(|e| format!("no desktop gl in egl: {e}"))?;
+
+        let config_attribs = [
+            egl::RENDERABLE_TYPE,
+            egl::OPENGL_BIT,
+            egl::SURFACE_TYPE,
+            egl::PBUFFER_BIT,
+            egl::NONE,
+        ];
+        let config = egl
+            .choose_first_config(display, &config_attribs)
+            .map_err(|e| e.to_string())?
+            .ok_or("no egl config with desktop gl")?;
+
+        let context_attribs = [
+            egl::CONTEXT_MAJOR_VERSION,
+            4,
+            egl::CONTEXT_MINOR_VERSION,
+            5,
+            egl::CONTEXT_OPENGL_PROFILE_MASK,
+            egl::CONTEXT_OPENGL_CORE_PROFILE_BIT,
+            egl::NONE,
+        ];
+        let context = egl
+            .create_context(display, config, None, &context_attribs)
+            .map_err(|e| format!("no gl 4.5 core context: {e}"))?;
+        egl.make_current(display, None, None, Some(context))
+            .map_err(|e| format!("make current failed: {e}"))?;
+
+        Ok(Self {
+            egl,
+            display,
+            context,
+        })
+    }
+
+    pub fn renderer(&self) -> Renderer {
+        Renderer::from_loader(|name| {
+            self.egl
+                .get_proc_address(name)
+                .map_or(std::ptr::null(), |f| f as *const c_void)
+        })
+    }
+}
+
+impl Drop for HeadlessContext {
+    fn drop(&mut self) {
+        let _ = self.egl.make_current(self.display, None, None, None);
+        let _ = self.egl.destroy_context(self.display, self.context);
+    }
+}
+
+///renders `frames` frames into an off screen target and reads the last one back.
+///`draw` gets the frame index and runs between render_frame and end_frame like a layer
+pub fn render<F: FnMut(&mut Renderer, u32)>(
+    width: u32,
+    height: u32,
+    frames: u32,
+    mut draw: F,
+) -> CapturedFrame {
+    let context = HeadlessContext::new()
+        .unwrap_or_else(|e| panic!("can't create a headless gl context: {e}"));
+    let mut renderer = context.renderer();
+    let framebuffer = Framebuffer::new(FramebufferSpec::new(
+        width,
+        height,
+        vec![
+            FramebufferTextureFormat::Rgba8,
+            FramebufferTextureFormat::Depth24Stencil8,
+        ],
+    ));
+    for frame in 0..frames {
+        let target = RenderTarget::Framebuffer(&framebuffer);
+        renderer.render_frame(target);
+        draw(&mut renderer, frame);
+        renderer.end_frame(target);
+    }
+    CapturedFrame::from_framebuffer(&framebuffer, 0)
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.png"))
+}
+
+fn diff_dir() -> PathBuf {
+    Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden-diff")
+}
+
+///fails if any channel of any pixel is more than `tolerance` away from the golden.
+///on failure the actual image and a diff (mismatches in red) are written next to the build
+pub fn assert_golden(name: &str, actual: &CapturedFrame, tolerance: u8) {
+    let path = golden_path(name);
+    if std::env::var_os("RGE_UPDATE_GOLDENS").is_some() {
+        actual.save_png(&path).expect("failed to write golden");
+        return;
+    }
+
+    let expected = image::open(&path)
+        .unwrap_or_else(|e| {
+            panic!(
+                "missing golden {}: {e}, run with RGE_UPDATE_GOLDENS=1",
+                path.display()
+            )
+        })
+        .to_rgba8();
+    assert_eq!(
+        (expected.width(), expected.height()),
+        (actual.width, actual.height),
+        "{name}: size differs from the golden"
+    );
+
+    let mut diff = Vec::with_capacity(actual.pixels.len());
+    let mut mismatched = 0;
+    for (a, e) in actual.pixels.chunks(4).zip(expected.as_raw().chunks(4)) {
+        let bad = a.iter().zip(e).any(|(a, e)| a.abs_diff(*e) > tolerance);
+        if bad {
+            mismatched += 1;
+            diff.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            let gray = ((e[0] as u32 + e[1] as u32 + e[2] as u32) / 12) as u8;
+            diff.extend_from_slice(&[gray, gray, gray, 255]);
+        }
+    }
+    if mismatched == 0 {
+        return;
+    }
+
+    let dir = diff_dir();
+    std::fs::create_dir_all(&dir).expect("failed to create the diff folder");
+    let actual_path = dir.join(format!("{name}.actual.png"));
+    let diff_path = dir.join(format!("{name}.diff.png"));
+    actual
+        .save_png(&actual_path)
+        .expect("failed to write the actual image");
+    CapturedFrame {
+        width: actual.width,
+        height: actual.height,
+        pixels: diff,
+    }
+    .save_png(&diff_path)
+    .expect("failed to write the diff image");
+    panic!(
+        "{name}: {mismatched} pixels differ from {} by more than {tolerance}, see {} and {}",
+        path.display(),
+        actual_path.display(),
+        diff_path.display()
+    );
+}
@@ -0,0 +1,159 @@
+use std::{cell::Cell, rc::Rc};
+
+use rgengine::console::{self, CVar, Command, OutputKind, Value, ValueKind};
+
+fn output() -> Vec<(OutputKind, String)> {
+    console::with_output(|lines| lines.iter().map(|l| (l.kind, l.text.clone())).collect())
+}
+
+fn last_output() -> String {
+    output()
+        .last()
+        .map(|(_, text)| text.clone())
+        .unwrap_or_default()
+}
+
+//each test runs on its own thread, so with its own console
+#[test]
+fn commands_get_typed_arguments() {
+    let spawned = Rc::new(Cell::new(None));
+    let seen = spawned.clone();
+    console::register_command(
+        Command::new("spawn", "spawns a quad", move |args| {
+            seen.set(Some((
+                args.float("x"),
+                args.int("count"),
+                args.string("name").len(),
+            )));
+            Ok(())
+        })
+        .arg("x", ValueKind::Float)
+        .optional_arg("count", 1)
+        .optional_arg("name", "quad"),
+    );
+
+    console::run("spawn 1.5").unwrap();
+    assert_eq!(spawned.get(), Some((1.5, 1, 4)));
+    console::run(r#"spawn -2 3 "big quad"; spawn 0 2 a b c // the rest is a comment"#).unwrap();
+    assert_eq!(spawned.get(), Some((0.0, 2, 5)));
+
+    let error = console::run("spawn left").unwrap_err();
+    assert!(error.contains("x: `left` isn't a valid float"), "{error}");
+    assert!(
+        error.contains("spawn <x:float> [count:int=1] [name:string=quad]"),
+        "{error}"
+    );
+    assert!(console::run("spawn").is_err());
+    assert!(console::run("despawn").unwrap_err().contains("unknown"));
+
+    console::execute("spawn nope");
+    let lines = output();
+    //the error and the usage under it
+    assert_eq!(
+        lines[lines.len() - 3..]
+            .iter()
+            .map(|(kind, _)| *kind)
+            .collect::<Vec<_>>(),
+        [OutputKind::Input, OutputKind::Error, OutputKind::Error]
+    );
+    assert_eq!(lines[lines.len() - 3].1, "> spawn nope");
+    assert_eq!(console::history(), ["spawn nope"]);
+}
+
+#[test]
+fn cvars_are_converted_and_validated() {
+    console::register_cvar(CVar::new("r_vsync", true, "vsync"));
+    console::register_cvar(CVar::new("r_scale", 1.0, "render scale"));
+    console::register_cvar(CVar::new("log_level", "info", "log level").choices(&["info", "trace"]));
+
+    console::run("r_vsync off; r_scale 2").unwrap();
+    assert_eq!(console::cvar_bool("r_vsync"), Some(false));
+    assert_eq!(console::cvar_float("r_scale"), Some(2.0));
+    console::set_cvar("r_scale", 3).unwrap();
+    assert_eq!(console::cvar("r_scale"), Some(Value::Float(3.0)));
+    console::run("toggle r_vsync").unwrap();
+    assert_eq!(console::cvar_bool("r_vsync"), Some(true));
+
+    assert!(console::run("r_vsync maybe").is_err());
+    assert!(
+        console::run("log_level loud")
+            .unwrap_err()
+            .contains("info, trace")
+    );
+    console::run("log_level trace").unwrap();
+    console::run("reset log_level").unwrap();
+    assert_eq!(console::cvar_string("log_level").as_deref(), Some("info"));
+
+    console::run("r_scale").unwrap();
+    assert_eq!(last_output(), "r_scale = 3 (render scale)");
+
+    //registering again, e.g. when a layer is attached again, keeps the value
+    console::register_cvar(CVar::new("r_scale", 1.0, "render scale"));
+    assert_eq!(console::cvar_float("r_scale"), Some(3.0));
+}
+
+#[test]
+fn archived_cvars_are_restored_when_registered() {
+    console::load_archived([
+        ("sb_grid".to_string(), "20".to_string()),
+        ("r_msaa".to_string(), "8".to_string()),
+    ]);
+    let generation = console::archive_generation();
+    console::register_cvar(CVar::new("sb_grid", 10, "grid").archived());
+    console::register_cvar(CVar::new("r_msaa", 4, "msaa"));
+    assert_eq!(console::cvar_int("sb_grid"), Some(20));
+    //only archived cvars take saved values
+    assert_eq!(console::cvar_int("r_msaa"), Some(4));
+
+    console::run("sb_grid 30; r_msaa 2").unwrap();
+    assert!(console::archive_generation() > generation);
+    assert_eq!(
+        console::archived_cvars(),
+        [("sb_grid".to_string(), "30".to_string())]
+    );
+    console::run("reset sb_grid").unwrap();
+    assert!(console::archived_cvars().is_empty());
+}
+
+#[test]
+fn tab_completes_names_and_values() {
+    console::register_cvar(CVar::new("r_vsync", true, "vsync"));
+    console::register_cvar(CVar::new("r_msaa", 4, "msaa").choices(&["1", "2", "4", "8"]));
+    console::register_cvar(CVar::new("log_level", "info", "").choices(&["info", "warn"]));
+
+    let completion = console::complete("r_");
+    assert_eq!(completion.line, "r_");
+    assert_eq!(completion.candidates, ["r_msaa", "r_vsync"]);
+    assert_eq!(console::complete("r_v").line, "r_vsync ");
+    assert!(console::complete("r_v").candidates.is_empty());
+    assert_eq!(console::complete("hel").line, "help ");
+    assert_eq!(console::complete("r_vsync ").candidates, ["0", "1"]);
+    assert_eq!(console::complete("log_level w").line, "log_level warn ");
+    assert_eq!(
+        console::complete("r_msaa 1; toggle r_v").line,
+        "r_msaa 1; toggle r_vsync "
+    );
+    assert_eq!(console::complete("nothing").line, "nothing");
+}
+
+#[test]
+fn scripts_run_every_line() {
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("autoexec.cfg");
+    std::fs::write(
+        &path,
+        "// startup\nr_vsync 0\nunknown_command\necho hello there\n",
+    )
+    .unwrap();
+    console::register_cvar(CVar::new("r_vsync", true, "vsync"));
+
+    console::run(&format!("exec \"{}\"", path.display())).unwrap();
+    assert_eq!(console::cvar_bool("r_vsync"), Some(false));
+    let lines = output();
+    assert!(
+        lines
+            .iter()
+            .any(|(kind, text)| *kind == OutputKind::Error && text.contains("autoexec.cfg:3"))
+    );
+    assert_eq!(last_output(), "hello there");
+    assert!(console::exec_file(&path.with_extension("missing")).is_err());
+}
@@ -0,0 +1,75 @@
+#![cfg(all(feature = "debug-draw", debug_assertions))]
+
+mod common;
+
+use common::{assert_golden, render};
+use rgengine::{
+    math::{Aabb, Mat4, Quat, Vec2, Vec3, Vec4},
+    renderer::{camera::PerspectiveCamera, debug_draw},
+};
+
+const WIDTH: u32 = 160;
+const HEIGHT: u32 = 120;
+const TOLERANCE: u8 = 3;
+
+fn camera() -> PerspectiveCamera {
+    let mut camera =
+        PerspectiveCamera::new(50f32.to_radians(), WIDTH as f32 / HEIGHT as f32, 0.1, 100.0);
+    camera.set_position(Vec3::new(3.0, 3.0, 5.0));
+    camera.look_at(Vec3::ZERO);
+    camera
+}
+
+#[test]
+fn debug_shapes() {
+    let frame = render(WIDTH, HEIGHT, 1, |_, _| {
+        let gray = Vec4::new(0.4, 0.4, 0.4, 1.0);
+        debug_draw::set_camera(camera().view_projection());
+        debug_draw::grid(Vec3::ZERO, 0.5, 8, gray);
+        debug_draw::axes(&Mat4::IDENTITY, 1.0);
+        debug_draw::aabb(
+            &Aabb::from_min_max(Vec3::new(-1.8, 0.0, -0.5), Vec3::new(-0.8, 1.0, 0.5)),
+            Vec4::new(1.0, 0.8, 0.2, 1.0),
+        );
+        debug_draw::wire_box(
+            &Mat4::from_rotation_translation(Quat::from_rotation_y(0.6), Vec3::new(1.2, 0.5, -1.0)),
+            Vec4::new(0.9, 0.3, 0.9, 1.0),
+        );
+        debug_draw::sphere(Vec3::new(0.5, 0.6, 1.2), 0.5, Vec4::new(0.3, 0.9, 0.9, 1.0));
+        debug_draw::arrow(
+            Vec3::new(-1.0, 2.0, 0.0),
+            Vec3::new(-1.3, 1.1, 0.0),
+            Vec4::ONE,
+        );
+        debug_draw::text(Vec3::new(-1.3, 1.0, 0.0), "box", Vec4::ONE);
+        debug_draw::screen_rect(
+            Vec2::new(4.0, 4.0),
+            Vec2::new(60.0, 22.0),
+            Vec4::new(0.2, 1.0, 0.4, 1.0),
+        );
+        debug_draw::screen_text(Vec2::new(7.0, 5.0), "debug", Vec4::new(0.2, 1.0, 0.4, 1.0));
+        debug_draw::screen_circle(Vec2::new(140.0, 100.0), 12.0, Vec4::ONE);
+        debug_draw::screen_line(Vec2::new(128.0, 100.0), Vec2::new(152.0, 100.0), Vec4::ONE);
+    });
+    assert_eq!(debug_draw::shape_count(), 0);
+    assert_golden("debug_shapes", &frame, TOLERANCE);
+}
+
+#[test]
+fn shapes_last_for_their_lifetime() {
+    render(32, 32, 3, |_, frame| {
+        match frame {
+            0 => {
+                debug_draw::line(Vec3::ZERO, Vec3::X, Vec4::ONE);
+                debug_draw::line(Vec3::ZERO, Vec3::Y, Vec4::ONE).lasting(60.0);
+                debug_draw::screen_text(Vec2::ZERO, "gone", Vec4::ONE).lasting(0.0);
+                assert_eq!(debug_draw::shape_count(), 3);
+            }
+            //only the lasting line is still there
+            _ => assert_eq!(debug_draw::shape_count(), 1),
+        }
+    });
+    assert_eq!(debug_draw::shape_count(), 1);
+    debug_draw::clear();
+    assert_eq!(debug_draw::shape_count(), 0);
+}
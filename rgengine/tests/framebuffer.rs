@@ -0,0 +1,45 @@
+mod common;
+
+use rgengine::renderer::framebuffer::{Framebuffer, FramebufferSpec, FramebufferTextureFormat};
+
+fn ints(pixels: &[u8]) -> Vec<i32> {
+    pixels
+        .chunks_exact(4)
+        .map(|p| i32::from_ne_bytes(p.try_into().unwrap()))
+        .collect()
+}
+
+#[test]
+fn pixels_read_back_in_the_attachment_format() {
+    let context = common::HeadlessContext::new().unwrap();
+    let _renderer = context.renderer();
+    for samples in [1, 4] {
+        let framebuffer = Framebuffer::new(
+            FramebufferSpec::new(
+                4,
+                2,
+                vec![
+                    FramebufferTextureFormat::Rgba8,
+                    FramebufferTextureFormat::RedInteger,
+                    FramebufferTextureFormat::Depth24Stencil8,
+                ],
+            )
+            .samples(samples),
+        );
+        //clears go through the scissor box, binding sets it to cover the framebuffer
+        framebuffer.bind();
+        framebuffer.clear_color_attachment(0, [1.0, 0.0, 1.0, 1.0]);
+        framebuffer.clear_color_attachment(1, [-7.0, 0.0, 0.0, 0.0]);
+        framebuffer.unbind();
+
+        let color = framebuffer.read_pixels(0);
+        assert_eq!(color.len(), 4 * 2 * 4, "samples {samples}");
+        assert!(
+            color.chunks_exact(4).all(|p| p == [255, 0, 255, 255]),
+            "samples {samples}: {color:?}"
+        );
+        let ids = ints(&framebuffer.read_pixels(1));
+        assert_eq!(ids, vec![-7; 8], "samples {samples}");
+        assert_eq!(framebuffer.read_pixel_int(1, 3, 1), -7, "samples {samples}");
+    }
+}
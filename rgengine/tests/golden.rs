@@ -0,0 +1,183 @@
+mod common;
+
+use std::rc::Rc;
+
+use common::{assert_golden, render};
+use rgengine::{
+    math::{Rect, Vec2, Vec3, Vec4},
+    renderer::{camera::OrthographicCamera, renderer2d::Renderer2D, texture::Texture2D},
+};
+
+const WIDTH: u32 = 128;
+const HEIGHT: u32 = 96;
+const TOLERANCE: u8 = 2;
+
+fn camera() -> OrthographicCamera {
+    OrthographicCamera::new(
+        -(WIDTH as f32) / 2.0,
+        WIDTH as f32 / 2.0,
+        -(HEIGHT as f32) / 2.0,
+        HEIGHT as f32 / 2.0,
+    )
+}
+
+///2x2 checker of red, green, blue and white
+fn checker() -> Texture2D {
+    #[rustfmt::skip]
+    let pixels = [
+        255, 0, 0, 255,   0, 255, 0, 255,
+        0, 0, 255, 255,   255, 255, 255, 255,
+    ];
+    Texture2D::from_rgba(2, 2, &pixels)
+}
+
+#[test]
+fn clear_color() {
+    let frame = render(WIDTH, HEIGHT, 1, |renderer, _| {
+        renderer.set_clear_color([0.2, 0.4, 0.6, 1.0]);
+    });
+    assert_golden("clear_color", &frame, TOLERANCE);
+}
+
+#[test]
+fn quads_2d() {
+    let mut renderer_2d = None;
+    let frame = render(WIDTH, HEIGHT, 3, |_, frame| {
+        let renderer_2d = renderer_2d.get_or_insert_with(Renderer2D::new);
+        renderer_2d.reset_stats();
+        renderer_2d.begin_scene(&camera());
+        renderer_2d.draw_quad(
+            Vec3::new(-30.0, 0.0, 0.0),
+            Vec2::new(40.0, 60.0),
+            0.0,
+            Vec4::new(1.0, 0.5, 0.0, 1.0),
+        );
+        renderer_2d.draw_quad(
+            Vec3::new(25.0, 10.0, 0.0),
+            Vec2::splat(30.0),
+            std::f32::consts::FRAC_PI_4,
+            Vec4::new(0.2, 0.8, 0.3, 1.0),
+        );
+        //translucent on top of both
+        renderer_2d.draw_quad(
+            Vec3::new(0.0, -20.0, 0.0),
+            Vec2::new(100.0, 20.0),
+            0.0,
+            Vec4::new(1.0, 1.0, 1.0, 0.5),
+        );
+        renderer_2d.end_scene();
+
+        let stats = renderer_2d.stats();
+        assert_eq!(stats.quad_count, 3, "frame {frame}");
+        assert_eq!(stats.draw_calls, 1, "frame {frame}");
+    });
+    assert_golden("quads_2d", &frame, TOLERANCE);
+}
+
+#[test]
+fn sprites_and_sub_rects() {
+    let mut state = None;
+    let frame = render(WIDTH, HEIGHT, 1, |_, _| {
+        let (renderer_2d, texture) =
+            state.get_or_insert_with(|| (Renderer2D::new(), Rc::new(checker())));
+        renderer_2d.begin_scene(&camera());
+        let tint = Vec4::ONE;
+        renderer_2d.draw_sprite(
+            Vec3::new(-32.0, 0.0, 0.0),
+            Vec2::splat(48.0),
+            0.0,
+            texture,
+            None,
+            tint,
+        );
+        //just the blue texel in the bottom left of the image
+        let blue = Rect::new(0.0, 1.0, 1.0, 1.0);
+        renderer_2d.draw_sprite(
+            Vec3::new(32.0, 16.0, 0.0),
+            Vec2::splat(24.0),
+            0.0,
+            texture,
+            Some(blue),
+            tint,
+        );
+        renderer_2d.draw_sprite(
+            Vec3::new(32.0, -16.0, 0.0),
+            Vec2::splat(24.0),
+            0.0,
+            texture,
+            None,
+            Vec4::new(1.0, 1.0, 1.0, 0.5),
+        );
+        renderer_2d.end_scene();
+        assert_eq!(renderer_2d.stats().texture_count, 1);
+    });
+    assert_golden("sprites_and_sub_rects", &frame, TOLERANCE);
+}
+
+#[test]
+fn batches_split_when_texture_slots_run_out() {
+    let mut state = None;
+    let frame = render(WIDTH, HEIGHT, 1, |_, _| {
+        let (renderer_2d, textures) = state.get_or_insert_with(|| {
+            let textures: Vec<_> = (0..40u8)
+                .map(|i| Rc::new(Texture2D::from_rgba(1, 1, &[i * 6, 255 - i * 6, 128, 255])))
+                .collect();
+            (Renderer2D::new(), textures)
+        });
+        renderer_2d.begin_scene(&camera());
+        for (i, texture) in textures.iter().enumerate() {
+            let position = Vec3::new(
+                (i % 8) as f32 * 14.0 - 49.0,
+                (i / 8) as f32 * 14.0 - 28.0,
+                0.0,
+            );
+            renderer_2d.draw_sprite(position, Vec2::splat(12.0), 0.0, texture, None, Vec4::ONE);
+        }
+        renderer_2d.end_scene();
+        let stats = renderer_2d.stats();
+        assert_eq!(stats.quad_count, 40);
+        assert_eq!(stats.texture_count, 40);
+        assert!(stats.draw_calls >= 2);
+    });
+    assert_golden("texture_slot_batches", &frame, TOLERANCE);
+}
+
+//the batch is 10000 quads, the sprite after them starts a new batch that has to bind its
+//texture again. the two sprites after it take the next slots, so a slot left over from the
+//old batch would show one of their colors
+#[test]
+fn textured_quad_starting_a_batch_keeps_its_texture() {
+    let mut state = None;
+    let frame = render(WIDTH, HEIGHT, 1, |_, _| {
+        let (renderer_2d, textures) = state.get_or_insert_with(|| {
+            let texture = |rgba: [u8; 4]| Rc::new(Texture2D::from_rgba(1, 1, &rgba));
+            (
+                Renderer2D::new(),
+                [
+                    texture([255, 0, 0, 255]),
+                    texture([0, 255, 0, 255]),
+                    texture([0, 0, 255, 255]),
+                    texture([255, 255, 0, 255]),
+                ],
+            )
+        });
+        let [red, green, blue, yellow] = &*textures;
+        renderer_2d.reset_stats();
+        renderer_2d.begin_scene(&camera());
+        for _ in 0..10_000 {
+            let corner = Vec3::new(-60.0, -44.0, 0.0);
+            renderer_2d.draw_sprite(corner, Vec2::splat(2.0), 0.0, red, None, Vec4::ONE);
+        }
+        renderer_2d.draw_sprite(Vec3::ZERO, Vec2::splat(32.0), 0.0, green, None, Vec4::ONE);
+        for (x, texture) in [(-40.0, blue), (40.0, yellow)] {
+            let position = Vec3::new(x, 30.0, 0.0);
+            renderer_2d.draw_sprite(position, Vec2::splat(4.0), 0.0, texture, None, Vec4::ONE);
+        }
+        renderer_2d.end_scene();
+        let stats = renderer_2d.stats();
+        assert_eq!(stats.quad_count, 10_003);
+        assert_eq!(stats.draw_calls, 2);
+    });
+    let center = ((HEIGHT / 2 * WIDTH + WIDTH / 2) * 4) as usize;
+    assert_eq!(frame.pixels[center..center + 4], [0, 255, 0, 255]);
+}
@@ -0,0 +1,325 @@
+mod common;
+
+use std::{rc::Rc, sync::Mutex};
+
+use common::{assert_golden, render};
+use glfw::{GamepadAxis, GamepadButton, Key};
+use imgui::{BackendFlags, Condition, Context, Image, Key as ImGuiKey};
+use rgengine::{
+    events::{
+        GamepadAxisMoved, GamepadButtonPressed, KeyPressed, KeyReleased, MouseMoved, RGEvent,
+    },
+    imgui::{
+        DockArea, Editor, GlUiBackend, ImGuiPlatform, ImGuiRenderer, NullUiBackend, StandardPanel,
+        UiSettings, UiTexture, texture_id,
+    },
+    renderer::texture::{Texture2D, TextureFilter},
+};
+
+//imgui has one global context, tests that make one take turns
+static CONTEXT: Mutex<()> = Mutex::new(());
+
+fn context(width: f32, height: f32) -> Context {
+    let mut imgui = Context::create();
+    imgui.set_ini_filename(None);
+    imgui.io_mut().display_size = [width, height];
+    imgui
+}
+
+#[test]
+fn windows_become_draw_commands() {
+    let _lock = CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+    let mut imgui = context(320.0, 240.0);
+    let mut renderer = ImGuiRenderer::new(&mut imgui, NullUiBackend::new());
+    assert!(renderer.backend().font_texture.is_some());
+
+    let ui = imgui.new_frame();
+    ui.window("Panel")
+        .position([10.0, 20.0], Condition::Always)
+        .size([200.0, 100.0], Condition::Always)
+        .build(|| {
+            ui.text("hello");
+            Image::new(texture_id(UiTexture::Gl(42)), [64.0, 32.0]).build(ui);
+        });
+    renderer.render(&mut imgui);
+
+    let backend = renderer.backend();
+    assert_eq!(backend.frames, 1);
+    assert_eq!(backend.framebuffer_size, [320, 240]);
+    assert!(backend.commands_with(&UiTexture::Font).count() > 0);
+    let images: Vec<_> = backend.commands_with(&UiTexture::Gl(42)).collect();
+    assert_eq!(images.len(), 1);
+    //an image is one quad
+    assert_eq!(images[0].count, 6);
+
+    let drawn: u32 = backend.commands.iter().map(|c| c.count).sum();
+    assert!(drawn as usize <= backend.index_count);
+    //clip rects are gl scissor boxes, y going up from the bottom. the window frame is clipped
+    //to the screen and its contents to the window
+    for command in &backend.commands {
+        let [x, y, width, height] = command.clip_rect;
+        assert!(x >= 0 && y >= 0 && x + width <= 320 && y + height <= 240);
+    }
+    let [x, y, width, height] = images[0].clip_rect;
+    assert!(x >= 10 && x + width <= 210, "{:?}", images[0].clip_rect);
+    assert!(
+        y >= 240 - 120 && y + height <= 240 - 20,
+        "{:?}",
+        images[0].clip_rect
+    );
+}
+
+#[test]
+fn texture_ids_last_one_frame() {
+    let _lock = CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+    let mut imgui = context(200.0, 200.0);
+    let mut renderer = ImGuiRenderer::new(&mut imgui, NullUiBackend::new());
+
+    let mut stale = None;
+    for frame in 0..2 {
+        let ui = imgui.new_frame();
+        ui.window("Images")
+            .position([0.0, 0.0], Condition::Always)
+            .size([200.0, 200.0], Condition::Always)
+            .build(|| {
+                let id = *stale.get_or_insert_with(|| texture_id(UiTexture::Gl(7)));
+                Image::new(id, [16.0, 16.0]).build(ui);
+            });
+        renderer.render(&mut imgui);
+        let images = renderer.backend().commands_with(&UiTexture::Gl(7)).count();
+        assert_eq!(images, if frame == 0 { 1 } else { 0 });
+    }
+}
+
+#[test]
+fn clip_rects_follow_the_framebuffer_scale() {
+    let _lock = CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+    let mut imgui = context(100.0, 80.0);
+    imgui.io_mut().display_framebuffer_scale = [2.0, 2.0];
+    let mut renderer = ImGuiRenderer::new(&mut imgui, NullUiBackend::new());
+
+    let ui = imgui.new_frame();
+    ui.window("Scaled")
+        .position([0.0, 0.0], Condition::Always)
+        .size([50.0, 40.0], Condition::Always)
+        .build(|| Image::new(texture_id(UiTexture::Gl(3)), [8.0, 8.0]).build(ui));
+    renderer.render(&mut imgui);
+
+    let backend = renderer.backend();
+    assert_eq!(backend.framebuffer_size, [200, 160]);
+    let image = backend.commands_with(&UiTexture::Gl(3)).next().unwrap();
+    let [x, y, width, height] = image.clip_rect;
+    assert!(x >= 0 && x + width <= 100, "{:?}", image.clip_rect);
+    assert!(y >= 160 - 80 && y + height <= 160, "{:?}", image.clip_rect);
+    assert!(
+        backend
+            .commands
+            .iter()
+            .any(|c| c.clip_rect == [0, 0, 200, 160])
+    );
+}
+
+#[test]
+fn imgui_window() {
+    let _lock = CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+    let mut imgui = context(160.0, 120.0);
+    let mut scene = None;
+    //imgui fades windows in, the second frame has them fully shown
+    let frame = render(160, 120, 2, |_, _| {
+        let (renderer, checker) = scene.get_or_insert_with(|| {
+            let white = [255; 4];
+            let blue = [40, 80, 220, 255];
+            let checker = Texture2D::from_rgba(2, 2, &[white, blue, blue, white].concat());
+            checker.set_filter(TextureFilter::Nearest);
+            (
+                ImGuiRenderer::new(&mut imgui, GlUiBackend::new()),
+                Rc::new(checker),
+            )
+        });
+        let ui = imgui.new_frame();
+        ui.window("Engine")
+            .position([8.0, 8.0], Condition::Always)
+            .size([144.0, 104.0], Condition::Always)
+            .build(|| {
+                ui.text("imgui");
+                Image::new(
+                    texture_id(UiTexture::Texture(checker.clone())),
+                    [48.0, 48.0],
+                )
+                .build(ui);
+            });
+        renderer.render(&mut imgui);
+    });
+    assert_golden("imgui_window", &frame, 3);
+}
+
+fn settings_path(name: &str) -> std::path::PathBuf {
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn app_settings_are_saved_next_to_the_layout() {
+    let path = settings_path("settings_roundtrip/imgui.ini");
+    let mut settings = UiSettings::new(Some(path.clone()));
+    settings.set("editor", "theme", "dark");
+    settings.set("editor", "zoom", 1.5);
+    settings.set("recent", "0", "levels/one.scene");
+    assert!(settings.is_dirty());
+    settings
+        .save("[Window][Scene]\nPos=10,20\nSize=300,200\nCollapsed=0\n")
+        .unwrap();
+    assert!(!settings.is_dirty());
+
+    let mut loaded = UiSettings::new(Some(path.clone()));
+    let imgui_ini = loaded.load().unwrap();
+    assert!(imgui_ini.contains("[Window][Scene]\nPos=10,20"));
+    assert!(!imgui_ini.contains("[App]"));
+    assert_eq!(loaded.get("editor", "theme"), Some("dark"));
+    assert_eq!(loaded.get_parsed::<f32>("editor", "zoom"), Some(1.5));
+    assert_eq!(loaded.section("recent").map(|s| s.len()), Some(1));
+    assert_eq!(loaded.get("editor", "missing"), None);
+
+    loaded.remove_section("recent");
+    assert!(loaded.is_dirty());
+    let ini = loaded.to_ini(&imgui_ini);
+    assert!(ini.contains("[App][editor]\ntheme=dark\nzoom=1.5"));
+    assert!(!ini.contains("[App][recent]"));
+
+    let mut missing = UiSettings::new(Some(settings_path("settings_missing.ini")));
+    assert_eq!(missing.load().unwrap(), "");
+}
+
+#[test]
+fn saved_layout_puts_windows_back() {
+    let _lock = CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+    let mut settings = UiSettings::new(None);
+    let mut imgui = context(320.0, 240.0);
+    imgui.load_ini_settings(
+        &settings.parse(
+            "[Window][Saved]\nPos=30,40\nSize=120,80\nCollapsed=0\n\n[App][editor]\ngrid=on\n",
+        ),
+    );
+    assert_eq!(settings.get("editor", "grid"), Some("on"));
+    let mut renderer = ImGuiRenderer::new(&mut imgui, NullUiBackend::new());
+
+    let ui = imgui.new_frame();
+    let mut placed = None;
+    ui.window("Saved")
+        .position([0.0, 0.0], Condition::FirstUseEver)
+        .size([50.0, 50.0], Condition::FirstUseEver)
+        .build(|| placed = Some((ui.window_pos(), ui.window_size())));
+    renderer.render(&mut imgui);
+    assert_eq!(placed, Some(([30.0, 40.0], [120.0, 80.0])));
+
+    let mut ini = String::new();
+    imgui.save_ini_settings(&mut ini);
+    assert!(settings.to_ini(&ini).ends_with("[App][editor]\ngrid=on\n"));
+}
+
+#[test]
+fn closed_panels_are_skipped() {
+    let _lock = CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+    let mut imgui = context(320.0, 240.0);
+    let mut renderer = ImGuiRenderer::new(&mut imgui, NullUiBackend::new());
+    let mut editor = Editor::new();
+    editor.set_panel_open(StandardPanel::Log.title(), false);
+
+    let ui = imgui.new_frame();
+    let log = editor.standard_panel(ui, StandardPanel::Log, || ());
+    let stats = editor.standard_panel(ui, StandardPanel::Stats, || ui.text("fps"));
+    //several layers can add to one panel
+    let more_stats = editor.standard_panel(ui, StandardPanel::Stats, || ui.text("quads"));
+    let custom = editor.panel(ui, "Tools", DockArea::Right, || 7);
+    renderer.render(&mut imgui);
+
+    assert_eq!(
+        (log, stats, more_stats, custom),
+        (None, Some(()), Some(()), Some(7))
+    );
+    assert!(!editor.is_panel_open("Log"));
+    assert!(editor.is_panel_open("Tools"));
+}
+
+#[test]
+fn platform_maps_keys_modifiers_and_gamepads() {
+    let _lock = CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+    let mut imgui = context(320.0, 240.0);
+    let mut renderer = ImGuiRenderer::new(&mut imgui, NullUiBackend::new());
+    let mut platform = ImGuiPlatform::detached();
+
+    let events = [
+        RGEvent::KeyPressed(KeyPressed::new(Key::RightControl, false)),
+        RGEvent::KeyPressed(KeyPressed::new(Key::A, false)),
+        RGEvent::KeyPressed(KeyPressed::new(Key::F5, true)),
+        RGEvent::GamepadButtonPressed(GamepadButtonPressed::new(GamepadButton::ButtonA)),
+        RGEvent::GamepadAxisMoved(GamepadAxisMoved::new(GamepadAxis::AxisLeftTrigger, 1.0)),
+        RGEvent::GamepadAxisMoved(GamepadAxisMoved::new(GamepadAxis::AxisLeftX, -1.0)),
+        RGEvent::GamepadAxisMoved(GamepadAxisMoved::new(GamepadAxis::AxisRightY, 0.2)),
+    ];
+    for event in &events {
+        platform.on_event(imgui.io_mut(), event);
+    }
+    assert!(imgui.io().backend_flags.contains(BackendFlags::HAS_GAMEPAD));
+
+    let ui = imgui.new_frame();
+    //either side of a modifier sets it
+    assert!(ui.io().key_ctrl && !ui.io().key_shift);
+    for key in [
+        ImGuiKey::RightCtrl,
+        ImGuiKey::A,
+        ImGuiKey::F5,
+        ImGuiKey::GamepadFaceDown,
+        ImGuiKey::GamepadL2,
+        ImGuiKey::GamepadLStickLeft,
+    ] {
+        assert!(ui.is_key_down(key), "{key:?}");
+    }
+    //the other direction and a stick inside the dead zone
+    for key in [
+        ImGuiKey::LeftCtrl,
+        ImGuiKey::GamepadLStickRight,
+        ImGuiKey::GamepadRStickDown,
+    ] {
+        assert!(!ui.is_key_down(key), "{key:?}");
+    }
+    renderer.render(&mut imgui);
+
+    //imgui holds back a mouse move that comes after a key change until the next frame
+    for event in [
+        RGEvent::MouseMoved(MouseMoved::new(12.0, 34.0)),
+        RGEvent::KeyReleased(KeyReleased::new(Key::RightControl)),
+    ] {
+        platform.on_event(imgui.io_mut(), &event);
+    }
+    let ui = imgui.new_frame();
+    assert_eq!(ui.io().mouse_pos, [12.0, 34.0]);
+    assert!(!ui.io().key_ctrl);
+    assert!(!ui.is_key_down(ImGuiKey::RightCtrl));
+    assert!(ui.is_key_down(ImGuiKey::A));
+    renderer.render(&mut imgui);
+
+    //letting go of one shift leaves it down while the other is held. a frame each, imgui
+    //holds back a second change of the same key
+    for (event, shift) in [
+        (
+            RGEvent::KeyPressed(KeyPressed::new(Key::LeftShift, false)),
+            true,
+        ),
+        (
+            RGEvent::KeyPressed(KeyPressed::new(Key::RightShift, false)),
+            true,
+        ),
+        (RGEvent::KeyReleased(KeyReleased::new(Key::LeftShift)), true),
+        (
+            RGEvent::KeyReleased(KeyReleased::new(Key::RightShift)),
+            false,
+        ),
+    ] {
+        platform.on_event(imgui.io_mut(), &event);
+        let ui = imgui.new_frame();
+        assert_eq!(ui.io().key_shift, shift, "{event:?}");
+        renderer.render(&mut imgui);
+    }
+}
@@ -0,0 +1,268 @@
+mod common;
+
+use std::rc::Rc;
+
+use common::{assert_golden, render};
+use rgengine::{
+    math::{Mat4, Quat, Vec3, Vec4},
+    renderer::{
+        Renderer,
+        buffer::{
+            BufferElement, BufferLayout, DrawElementsIndirectCommand, IndexBuffer, IndirectBuffer,
+            ShaderDataType, VertexArray, VertexBuffer,
+        },
+        camera::PerspectiveCamera,
+        capture::CapturedFrame,
+        forward::ForwardRenderer,
+        lighting::{DirectionalLight, LightEnvironment, LightingConfig},
+        material::{MaterialInstance, MaterialValue},
+        mesh::{GpuPrimitive, Instance, Primitive, Vertex},
+        shader::Shader,
+        shadows::ShadowSettings,
+    },
+};
+
+const WIDTH: u32 = 160;
+const HEIGHT: u32 = 120;
+const TOLERANCE: u8 = 3;
+
+fn cube(size: f32) -> Primitive {
+    let h = size / 2.0;
+    let mut primitive = Primitive::default();
+    let faces = [Vec3::X, -Vec3::X, Vec3::Y, -Vec3::Y, Vec3::Z, -Vec3::Z];
+    for normal in faces {
+        let tangent = if normal.y.abs() > 0.5 {
+            Vec3::X
+        } else {
+            normal.cross(Vec3::Y)
+        };
+        let bitangent = normal.cross(tangent);
+        let base = primitive.vertices.len() as u32;
+        for (u, v) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            primitive.vertices.push(Vertex {
+                position: ((normal + tangent * u + bitangent * v) * h).into(),
+                normal: normal.into(),
+                tangent: tangent.extend(1.0).into(),
+                uv: [(u + 1.0) / 2.0, (v + 1.0) / 2.0],
+                ..Vertex::default()
+            });
+        }
+        primitive
+            .indices
+            .extend_from_slice(&[base, base + 1, base + 2, base + 2, base + 3, base]);
+    }
+    primitive
+}
+
+///a 6 by 4 grid of tinted, turned cubes
+fn instances() -> Vec<Instance> {
+    let mut instances = Vec::new();
+    for z in 0..4 {
+        for x in 0..6 {
+            let position = Vec3::new(x as f32 - 2.5, 0.3, z as f32 - 1.5);
+            let rotation = Quat::from_rotation_y((x * 4 + z) as f32 * 0.3);
+            let hue = (x + z) as f32 / 8.0;
+            instances.push(Instance::new(
+                &Mat4::from_rotation_translation(rotation, position),
+                Vec4::new(1.0 - hue, 0.4 + hue * 0.4, hue, 1.0),
+            ));
+        }
+    }
+    instances
+}
+
+fn lights() -> LightEnvironment {
+    LightEnvironment {
+        ambient: Vec3::splat(0.05),
+        directional_lights: vec![
+            DirectionalLight::new(Vec3::new(1.0, -2.0, 0.7), Vec3::ONE, 0.9)
+                .with_shadow(ShadowSettings::default()),
+        ],
+        ..LightEnvironment::default()
+    }
+}
+
+fn camera() -> PerspectiveCamera {
+    let mut camera =
+        PerspectiveCamera::new(50f32.to_radians(), WIDTH as f32 / HEIGHT as f32, 0.1, 100.0);
+    camera.set_position(Vec3::new(0.0, 4.0, 5.0));
+    camera.look_at(Vec3::ZERO);
+    camera
+}
+
+///the grid on a floor, either with one instanced draw or one draw per cube with the tint as a
+///material override
+fn render_grid(instanced: bool) -> CapturedFrame {
+    let mut scene = None;
+    render(WIDTH, HEIGHT, 2, |renderer, _| {
+        renderer.set_clear_color([0.0, 0.0, 0.0, 1.0]);
+        let (forward, floor, cubes, floor_material, tints) = scene.get_or_insert_with(|| {
+            let forward = ForwardRenderer::new(LightingConfig::default());
+            let mut floor = cube(1.0);
+            for vertex in &mut floor.vertices {
+                vertex.position = [
+                    vertex.position[0] * 8.0,
+                    vertex.position[1] * 0.1 - 0.05,
+                    vertex.position[2] * 6.0,
+                ];
+            }
+            let floor = GpuPrimitive::upload(&floor);
+            let cubes = if instanced {
+                GpuPrimitive::upload_instanced(&cube(0.6), &instances())
+            } else {
+                GpuPrimitive::upload(&cube(0.6))
+            };
+            let floor_material = MaterialInstance::new(Rc::new(forward.create_material("floor")));
+            let base = Rc::new(forward.create_material("cube"));
+            let tints: Vec<MaterialInstance> = instances()
+                .iter()
+                .map(|instance| {
+                    let mut tint = MaterialInstance::new(base.clone());
+                    if !instanced {
+                        tint.set("u_BaseColor", MaterialValue::Color(instance.color.into()));
+                    }
+                    tint
+                })
+                .collect();
+            (forward, floor, cubes, floor_material, tints)
+        });
+
+        forward.show_light_gizmos = false;
+        forward.reset_stats();
+        forward.begin_scene(&camera(), &lights());
+        forward.submit(&floor.vertex_array, floor_material, &Mat4::IDENTITY);
+        if instanced {
+            let count = instances().len() as u32;
+            forward.submit_instanced(&cubes.vertex_array, &tints[0], &Mat4::IDENTITY, count);
+        } else {
+            for (instance, tint) in instances().iter().zip(tints.iter()) {
+                let transform = Mat4::from_cols_array(&instance.transform);
+                forward.submit(&cubes.vertex_array, tint, &transform);
+            }
+        }
+        forward.end_scene();
+
+        let stats = forward.stats();
+        let cubes = instances().len() as u32;
+        assert_eq!(stats.draw_calls, if instanced { 2 } else { 1 + cubes });
+        assert_eq!(stats.triangles, 12 + 12 * cubes);
+    })
+}
+
+#[test]
+fn instanced_forward_grid() {
+    assert_golden("instanced_forward_grid", &render_grid(true), TOLERANCE);
+}
+
+#[test]
+fn instanced_draws_match_separate_draws() {
+    let instanced = render_grid(true);
+    let separate = render_grid(false);
+    let worst = instanced
+        .pixels
+        .iter()
+        .zip(&separate.pixels)
+        .map(|(a, b)| a.abs_diff(*b))
+        .max()
+        .unwrap();
+    assert!(worst <= 1, "frames differ by up to {worst}");
+}
+
+const FLAT_VERTEX_SHADER: &str = r#"
+#version 450 core
+layout(location = 0) in vec2 a_Position;
+layout(location = 1) in vec2 a_Offset;
+layout(location = 2) in vec4 a_Color;
+
+out vec4 v_Color;
+
+void main() {
+    v_Color = a_Color;
+    gl_Position = vec4(a_Position + a_Offset, 0.0, 1.0);
+}
+"#;
+
+const FLAT_FRAGMENT_SHADER: &str = r#"
+#version 450 core
+in vec4 v_Color;
+layout(location = 0) out vec4 o_Color;
+
+void main() {
+    o_Color = v_Color;
+}
+"#;
+
+fn pixel(frame: &CapturedFrame, x: f32, y: f32) -> [u8; 4] {
+    let px = ((x + 1.0) / 2.0 * frame.width as f32) as usize;
+    let py = ((1.0 - y) / 2.0 * frame.height as f32) as usize;
+    let i = (py * frame.width as usize + px) * 4;
+    frame.pixels[i..i + 4].try_into().unwrap()
+}
+
+#[test]
+fn multi_draw_indirect_draws_every_command() {
+    //a square and a triangle in one vertex array, each drawn twice at its own offsets
+    let frame = render(64, 64, 1, |_, _| {
+        let shader = Shader::from_source("Flat", FLAT_VERTEX_SHADER, FLAT_FRAGMENT_SHADER).unwrap();
+        let positions: [[f32; 2]; 7] = [
+            [-0.2, -0.2],
+            [0.2, -0.2],
+            [0.2, 0.2],
+            [-0.2, 0.2],
+            [-0.2, -0.2],
+            [0.2, -0.2],
+            [0.0, 0.2],
+        ];
+        //offset xy then rgba
+        let instances: [[f32; 6]; 4] = [
+            [-0.5, 0.5, 1.0, 0.0, 0.0, 1.0],
+            [0.5, 0.5, 0.0, 1.0, 0.0, 1.0],
+            [-0.5, -0.5, 0.0, 0.0, 1.0, 1.0],
+            [0.5, -0.5, 1.0, 1.0, 0.0, 1.0],
+        ];
+        let mut vertex_array = VertexArray::new();
+        vertex_array.add_vertex_buffer(VertexBuffer::new(
+            &positions,
+            BufferLayout::new(vec![BufferElement::new(
+                ShaderDataType::Float2,
+                "a_Position",
+            )]),
+        ));
+        vertex_array.add_vertex_buffer(VertexBuffer::new(
+            &instances,
+            BufferLayout::instanced(vec![
+                BufferElement::new(ShaderDataType::Float2, "a_Offset"),
+                BufferElement::new(ShaderDataType::Float4, "a_Color"),
+            ]),
+        ));
+        vertex_array.set_index_buffer(IndexBuffer::new(&[0, 1, 2, 2, 3, 0, 0, 1, 2]));
+        let commands = IndirectBuffer::new(&[
+            DrawElementsIndirectCommand {
+                count: 6,
+                instance_count: 2,
+                first_index: 0,
+                base_vertex: 0,
+                base_instance: 0,
+            },
+            DrawElementsIndirectCommand {
+                count: 3,
+                instance_count: 2,
+                first_index: 6,
+                base_vertex: 4,
+                base_instance: 2,
+            },
+        ]);
+        assert_eq!(commands.count(), 2);
+        shader.bind();
+        Renderer::draw_indirect(&vertex_array, &commands);
+        vertex_array.unbind();
+    });
+
+    assert_eq!(pixel(&frame, -0.5, 0.5), [255, 0, 0, 255]);
+    assert_eq!(pixel(&frame, 0.5, 0.5), [0, 255, 0, 255]);
+    assert_eq!(pixel(&frame, -0.5, -0.5), [0, 0, 255, 255]);
+    assert_eq!(pixel(&frame, 0.5, -0.5), [255, 255, 0, 255]);
+    //the top corners of the triangles are cut off, the squares' aren't
+    assert_eq!(pixel(&frame, -0.32, 0.68), [255, 0, 0, 255]);
+    assert_ne!(pixel(&frame, -0.32, -0.32), [0, 0, 255, 255]);
+}
@@ -0,0 +1,116 @@
+use std::{cell::RefCell, rc::Rc};
+
+use glfw::Key;
+use imgui::{Context, Ui};
+use rgengine::{
+    events::{KeyPressed, RGEvent, WindowResize},
+    imgui::{DockArea, Editor, ImGuiRenderer, NullUiBackend},
+    layers::{Layer, LayerStack},
+    rgevent,
+};
+
+type Calls = Rc<RefCell<Vec<String>>>;
+
+//writes down the callbacks it gets
+struct RecordingLayer {
+    id: usize,
+    name: &'static str,
+    calls: Calls,
+}
+
+impl Layer for RecordingLayer {
+    fn on_event(&mut self, event: &RGEvent) {
+        let name = self.name;
+        self.calls
+            .borrow_mut()
+            .push(format!("{name} {:?}", event.get_type()));
+    }
+
+    fn on_imgui(&mut self, ui: &Ui, editor: &mut Editor) {
+        self.calls.borrow_mut().push(format!("{} imgui", self.name));
+        editor.panel(ui, self.name, DockArea::Right, || ui.text(self.name));
+    }
+
+    fn get_id(&self) -> usize {
+        self.id
+    }
+
+    fn get_name(&self) -> &str {
+        self.name
+    }
+}
+
+fn layer(id: usize, name: &'static str, calls: &Calls) -> Box<dyn Layer> {
+    Box::new(RecordingLayer {
+        id,
+        name,
+        calls: calls.clone(),
+    })
+}
+
+#[test]
+fn imgui_goes_to_layers_then_overlays() {
+    let mut imgui = Context::create();
+    imgui.set_ini_filename(None);
+    imgui.io_mut().display_size = [320.0, 240.0];
+    let mut renderer = ImGuiRenderer::new(&mut imgui, NullUiBackend::new());
+    let mut editor = Editor::new();
+
+    let calls = Calls::default();
+    let mut layer_stack = LayerStack::new();
+    layer_stack.push_overlay(layer(3, "overlay", &calls));
+    layer_stack.push_layer(layer(1, "first", &calls));
+    layer_stack.push_layer(layer(2, "second", &calls));
+
+    let ui = imgui.new_frame();
+    layer_stack.on_imgui(ui, &mut editor);
+    renderer.render(&mut imgui);
+
+    assert_eq!(
+        *calls.borrow(),
+        ["first imgui", "second imgui", "overlay imgui"]
+    );
+    assert!(!renderer.backend().commands.is_empty());
+}
+
+#[test]
+fn events_can_be_marked_handled() {
+    let mut event = RGEvent::KeyPressed(KeyPressed::new(Key::Space, false));
+    assert!(!event.is_handled());
+    event.set_handled(true);
+    assert!(event.is_handled());
+    assert!(matches!(&event, RGEvent::KeyPressed(e) if e.handled && e.key == Key::Space));
+    event.set_handled(false);
+    assert!(!event.is_handled());
+
+    let (width, height) = (640, 480);
+    let mut event = rgevent!(WindowResize, width, height);
+    event.set_handled(true);
+    assert!(matches!(event, RGEvent::WindowResize(e) if e.is_handled()));
+}
+
+#[test]
+fn handled_events_skip_the_layers() {
+    let calls = Calls::default();
+    let mut layer_stack = LayerStack::new();
+    layer_stack.push_layer(layer(1, "first", &calls));
+    layer_stack.push_overlay(layer(3, "overlay", &calls));
+    layer_stack.push_layer(layer(2, "second", &calls));
+
+    let mut event = RGEvent::KeyPressed(KeyPressed::new(Key::Space, false));
+    layer_stack.on_event(&event);
+    //from the top overlay down
+    assert_eq!(
+        *calls.borrow(),
+        [
+            "overlay KeyPressed",
+            "second KeyPressed",
+            "first KeyPressed"
+        ]
+    );
+
+    calls.borrow_mut().clear();
+    event.set_handled(true);
+    layer_stack.on_event(&event);
+    assert!(calls.borrow().is_empty());
+}
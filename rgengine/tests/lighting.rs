@@ -0,0 +1,216 @@
+mod common;
+
+use std::rc::Rc;
+
+use common::{assert_golden, render};
+use rgengine::{
+    math::{Mat4, Vec3, Vec4},
+    renderer::{
+        camera::PerspectiveCamera,
+        forward::ForwardRenderer,
+        lighting::{
+            DirectionalLight, LightEnvironment, LightingConfig, PointLight, ShadingModel, SpotLight,
+        },
+        material::{MaterialInstance, MaterialValue},
+        mesh::{GpuPrimitive, Primitive, Vertex},
+        shadows::{ShadowConfig, ShadowSettings, cascade_splits},
+    },
+};
+
+const WIDTH: u32 = 160;
+const HEIGHT: u32 = 120;
+const TOLERANCE: u8 = 3;
+
+fn plane(size: f32) -> Primitive {
+    let h = size / 2.0;
+    let corners = [[-h, 0.0, h], [h, 0.0, h], [h, 0.0, -h], [-h, 0.0, -h]];
+    let uvs = [[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]];
+    Primitive {
+        vertices: corners
+            .iter()
+            .zip(uvs)
+            .map(|(&position, uv)| Vertex {
+                position,
+                normal: [0.0, 1.0, 0.0],
+                tangent: [1.0, 0.0, 0.0, 1.0],
+                uv,
+                ..Vertex::default()
+            })
+            .collect(),
+        indices: vec![0, 1, 2, 2, 3, 0],
+        ..Primitive::default()
+    }
+}
+
+fn sphere(radius: f32, rings: u32, segments: u32) -> Primitive {
+    use std::f32::consts::{PI, TAU};
+    let mut primitive = Primitive::default();
+    for ring in 0..=rings {
+        let v = ring as f32 / rings as f32;
+        let (sin_theta, cos_theta) = (v * PI).sin_cos();
+        for segment in 0..=segments {
+            let u = segment as f32 / segments as f32;
+            let (sin_phi, cos_phi) = (u * TAU).sin_cos();
+            let normal = Vec3::new(sin_theta * cos_phi, cos_theta, -sin_theta * sin_phi);
+            primitive.vertices.push(Vertex {
+                position: (normal * radius).into(),
+                normal: normal.into(),
+                tangent: [-sin_phi, 0.0, -cos_phi, 1.0],
+                uv: [u, v],
+                ..Vertex::default()
+            });
+        }
+    }
+    let stride = segments + 1;
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let a = ring * stride + segment;
+            let b = a + stride;
+            primitive
+                .indices
+                .extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+        }
+    }
+    primitive
+}
+
+fn lights() -> LightEnvironment {
+    LightEnvironment {
+        ambient: Vec3::splat(0.03),
+        directional_lights: vec![DirectionalLight::new(
+            Vec3::new(-1.0, -2.0, -1.0),
+            Vec3::ONE,
+            0.4,
+        )],
+        point_lights: vec![PointLight::new(
+            Vec3::new(-2.0, 0.6, 1.0),
+            Vec3::new(1.0, 0.2, 0.1),
+            6.0,
+            4.0,
+        )],
+        spot_lights: vec![SpotLight::new(
+            Vec3::new(1.8, 3.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.2, 0.4, 1.0),
+            20.0,
+            6.0,
+            15f32.to_radians(),
+            25f32.to_radians(),
+        )],
+    }
+}
+
+fn render_scene(
+    config: LightingConfig,
+    lights: LightEnvironment,
+) -> rgengine::renderer::capture::CapturedFrame {
+    let mut scene = None;
+    render(WIDTH, HEIGHT, 2, |renderer, _| {
+        renderer.set_clear_color([0.0, 0.0, 0.0, 1.0]);
+        let (forward, floor, ball, floor_material, ball_material) =
+            scene.get_or_insert_with(|| {
+                let forward = ForwardRenderer::new(config);
+                let floor = GpuPrimitive::upload(&plane(8.0));
+                let ball = GpuPrimitive::upload(&sphere(1.0, 24, 32));
+
+                let mut floor_material = forward.create_material("floor");
+                floor_material.set(
+                    "u_BaseColor",
+                    MaterialValue::Color(Vec4::new(0.8, 0.8, 0.8, 1.0)),
+                );
+                let mut ball_material = forward.create_material("ball");
+                ball_material.set("u_Roughness", MaterialValue::Float(0.35));
+                let floor_material = MaterialInstance::new(Rc::new(floor_material));
+                //an override, like a per object tint
+                let mut ball_material = MaterialInstance::new(Rc::new(ball_material));
+                ball_material.set(
+                    "u_BaseColor",
+                    MaterialValue::Color(Vec4::new(1.0, 0.85, 0.6, 1.0)),
+                );
+                (forward, floor, ball, floor_material, ball_material)
+            });
+
+        let mut camera =
+            PerspectiveCamera::new(50f32.to_radians(), WIDTH as f32 / HEIGHT as f32, 0.1, 100.0);
+        camera.set_position(Vec3::new(0.0, 3.0, 6.0));
+        camera.look_at(Vec3::new(0.0, 0.5, 0.0));
+
+        forward.show_light_gizmos = true;
+        forward.reset_stats();
+        forward.begin_scene(&camera, &lights);
+        forward.submit(&floor.vertex_array, floor_material, &Mat4::IDENTITY);
+        forward.submit(
+            &ball.vertex_array,
+            ball_material,
+            &Mat4::from_translation(Vec3::new(0.0, 1.0, 0.0)),
+        );
+        forward.end_scene();
+
+        let stats = forward.stats();
+        assert_eq!(stats.draw_calls, 2);
+        assert_eq!(stats.shader_binds, 1);
+    })
+}
+
+#[test]
+fn forward_lighting_pbr() {
+    assert_golden(
+        "forward_lighting_pbr",
+        &render_scene(LightingConfig::default(), lights()),
+        TOLERANCE,
+    );
+}
+
+#[test]
+fn forward_lighting_blinn_phong() {
+    assert_golden(
+        "forward_lighting_blinn_phong",
+        &render_scene(
+            LightingConfig {
+                shading: ShadingModel::BlinnPhong,
+                ..LightingConfig::default()
+            },
+            lights(),
+        ),
+        TOLERANCE,
+    );
+}
+
+#[test]
+fn forward_shadows() {
+    let mut lights = lights();
+    //both shadows fall on the floor in front of the camera
+    lights.directional_lights[0] = DirectionalLight::new(Vec3::new(1.0, -1.5, 0.5), Vec3::ONE, 0.8)
+        .with_shadow(ShadowSettings::default());
+    lights.spot_lights[0] = SpotLight::new(
+        Vec3::new(-1.0, 4.0, -0.5),
+        Vec3::new(0.3, -1.0, 0.15),
+        Vec3::new(0.2, 0.4, 1.0),
+        20.0,
+        8.0,
+        20f32.to_radians(),
+        30f32.to_radians(),
+    )
+    .with_shadow(ShadowSettings {
+        pcf_radius: 2,
+        ..ShadowSettings::default()
+    });
+    let config = LightingConfig {
+        shadows: ShadowConfig {
+            cascade_count: 2,
+            max_distance: 20.0,
+            ..ShadowConfig::default()
+        },
+        ..LightingConfig::default()
+    };
+    assert_golden("forward_shadows", &render_scene(config, lights), TOLERANCE);
+}
+
+#[test]
+fn cascade_splits_blend_even_and_logarithmic() {
+    let even = cascade_splits(1.0, 100.0, 4, 0.0);
+    assert_eq!(even, [25.75, 50.5, 75.25, 100.0]);
+    let log = cascade_splits(1.0, 100.0, 2, 1.0);
+    assert!((log[0] - 10.0).abs() < 1e-4);
+    assert!((log[1] - 100.0).abs() < 1e-3);
+}
@@ -0,0 +1,407 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex, MutexGuard, Once},
+    time::Duration,
+};
+
+use imgui::Context;
+use rgengine::{
+    imgui::{ImGuiRenderer, LogConsole, NullUiBackend},
+    log::{
+        self, ColorMode, LogConfig, LogEntry, LogFileConfig, LogFileSink, PatternFormatter,
+        RingBufferSink, parse_level_filter,
+    },
+};
+use spdlog::{Level, LevelFilter, Logger, sink::WriteSink};
+
+fn logger(sink: &Arc<RingBufferSink>) -> Logger {
+    Logger::builder()
+        .name("test")
+        .level_filter(LevelFilter::All)
+        .sink(sink.clone())
+        .build()
+        .unwrap()
+}
+
+fn payloads(sink: &RingBufferSink) -> Vec<String> {
+    sink.records().iter().map(|e| e.payload.clone()).collect()
+}
+
+#[test]
+fn ring_buffer_keeps_the_newest_records() {
+    let sink = Arc::new(RingBufferSink::new(3));
+    let logger = logger(&sink);
+    for i in 0..5 {
+        spdlog::info!(logger: logger, "record {}", i);
+    }
+    spdlog::warn!(logger: logger, "careful");
+    let warned_at = line!() - 1;
+
+    assert_eq!(payloads(&sink), ["record 3", "record 4", "careful"]);
+    assert_eq!(sink.total(), 6);
+    let records = sink.records();
+    let last = records.back().unwrap();
+    assert_eq!(last.level, Level::Warn);
+    assert_eq!(last.logger, "test");
+    let (file, line) = last.source.unwrap();
+    assert!(file.ends_with("log.rs"), "{file}");
+    assert_eq!(line, warned_at);
+    drop(records);
+
+    sink.clear();
+    assert!(payloads(&sink).is_empty());
+    assert_eq!(sink.total(), 6);
+}
+
+#[test]
+fn console_filters_by_level_and_text() {
+    let sink = Arc::new(RingBufferSink::new(16));
+    let logger = logger(&sink);
+    spdlog::trace!(logger: logger, "frame 12");
+    spdlog::error!(logger: logger, "Shader failed to compile");
+    spdlog::info!(logger: logger, "loaded shader");
+
+    let mut console = LogConsole::new();
+    console.set_buffer(Some(sink.clone()));
+    let visible = |console: &LogConsole| -> Vec<String> {
+        let records = sink.records();
+        let visible = records.iter().filter(|e: &&LogEntry| console.is_visible(e));
+        visible.map(|e| e.payload.clone()).collect()
+    };
+    assert_eq!(visible(&console).len(), 3);
+
+    console.set_level_shown(Level::Trace, false);
+    assert_eq!(
+        visible(&console),
+        ["Shader failed to compile", "loaded shader"]
+    );
+    console.set_search("SHADER");
+    assert_eq!(visible(&console).len(), 2);
+    console.set_level_shown(Level::Error, false);
+    assert_eq!(visible(&console), ["loaded shader"]);
+    console.set_search("test");
+    assert_eq!(visible(&console), ["loaded shader"]);
+}
+
+#[test]
+fn console_draws_new_records_and_filter_changes() {
+    let sink = Arc::new(RingBufferSink::new(16));
+    let logger = logger(&sink);
+    let mut console = LogConsole::new();
+    console.set_buffer(Some(sink.clone()));
+
+    let mut imgui = Context::create();
+    imgui.set_ini_filename(None);
+    imgui.io_mut().display_size = [640.0, 480.0];
+    let mut renderer = ImGuiRenderer::new(&mut imgui, NullUiBackend::new());
+    //indices drawn, more records mean more text
+    let mut draw = |console: &mut LogConsole| {
+        let ui = imgui.new_frame();
+        ui.window("Log")
+            .size([600.0, 400.0], imgui::Condition::Always)
+            .build(|| console.draw(ui));
+        renderer.render(&mut imgui);
+        renderer.backend().index_count
+    };
+
+    spdlog::info!(logger: logger, "first");
+    //a new window is laid out on its first frame
+    draw(&mut console);
+    let one = draw(&mut console);
+    assert_eq!(draw(&mut console), one);
+    for i in 0..4 {
+        spdlog::debug!(logger: logger, "more {}", i);
+    }
+    let five = draw(&mut console);
+    assert!(five > one, "{five} <= {one}");
+    //less than the one record, the debug checkbox loses its check mark too
+    console.set_level_shown(Level::Debug, false);
+    let hidden = draw(&mut console);
+    assert!(hidden < one, "{hidden} >= {one}");
+    console.set_level_shown(Level::Debug, true);
+    console.set_search("MORE");
+    let four = draw(&mut console);
+    assert!(one < four && four < five, "{one} {four} {five}");
+    sink.clear();
+    assert!(draw(&mut console) < one);
+}
+
+fn formatted(pattern: &str, colors: bool, log: impl FnOnce(&Logger)) -> String {
+    let sink = Arc::new(
+        WriteSink::builder()
+            .target(Vec::new())
+            .formatter(Box::new(PatternFormatter::new(pattern, colors).unwrap()))
+            .build()
+            .unwrap(),
+    );
+    let logger = Logger::builder()
+        .name("test")
+        .level_filter(LevelFilter::All)
+        .sink(sink.clone())
+        .build()
+        .unwrap();
+    log(&logger);
+    String::from_utf8(sink.clone_target()).unwrap()
+}
+
+#[test]
+fn patterns_lay_out_records() {
+    let eol = if cfg!(windows) { "\r\n" } else { "\n" };
+    let mut line = 0;
+    let text = formatted("{level} {logger}:{line} {{{payload}}}", false, |logger| {
+        spdlog::warn!(logger: logger, "hello");
+        line = line!() - 1;
+    });
+    assert_eq!(text, format!("warn test:{line} {{hello}}{eol}"));
+
+    let text = formatted("{color}{level}{reset}\n{payload}", true, |logger| {
+        spdlog::error!(logger: logger, "oops");
+    });
+    assert_eq!(text, format!("\x1b[31merror\x1b[0m{eol}oops{eol}"));
+    let text = formatted("{color}{payload}{reset}", false, |logger| {
+        spdlog::error!(logger: logger, "plain");
+    });
+    assert_eq!(text, format!("plain{eol}"));
+
+    for pattern in ["{nope}", "{level", "level}"] {
+        assert!(PatternFormatter::new(pattern, false).is_err(), "{pattern}");
+    }
+    assert!(ColorMode::Always.enabled());
+    assert!(!ColorMode::Never.enabled());
+}
+
+#[test]
+fn levels_parse_like_rge_log() {
+    let warn = LevelFilter::MoreSevereEqual(Level::Warn);
+    assert_eq!(parse_level_filter("WARN"), Some(warn));
+    assert_eq!(parse_level_filter("all"), Some(LevelFilter::All));
+    assert_eq!(parse_level_filter("off"), Some(LevelFilter::Off));
+    assert_eq!(parse_level_filter("loud"), None);
+
+    let mut config = LogConfig::default();
+    config.parse_levels("engine=warn, info").unwrap();
+    assert_eq!(config.level("engine"), warn);
+    assert_eq!(
+        config.level("client"),
+        LevelFilter::MoreSevereEqual(Level::Info)
+    );
+    assert!(config.parse_levels("client=debug,engine=loud").is_err());
+    assert_eq!(
+        config.level("client"),
+        LevelFilter::MoreSevereEqual(Level::Info)
+    );
+}
+
+fn log_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn log_files(dir: &PathBuf) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+fn file_logger(config: LogFileConfig) -> (Arc<LogFileSink>, Logger) {
+    let sink = Arc::new(LogFileSink::new(config).unwrap());
+    let logger = Logger::builder()
+        .name("test")
+        .level_filter(LevelFilter::All)
+        .sink(sink.clone())
+        .build()
+        .unwrap();
+    (sink, logger)
+}
+
+#[test]
+fn log_files_rotate_by_size_and_old_ones_go() {
+    let dir = log_dir("log_rotation");
+    fs::create_dir_all(&dir).unwrap();
+    for old in [
+        "game_20000101_000000.log",
+        "game_20000101_000000.1.log",
+        "game_20000101_000000-1.2.log",
+        "notes.log",
+        "game_notes.log",
+        "game_editor_20000101_000000.log",
+    ] {
+        fs::write(dir.join(old), "old\n").unwrap();
+    }
+    let config = LogFileConfig {
+        pattern: "{payload}".to_string(),
+        max_size: Some(64),
+        max_files: 3,
+        ..LogFileConfig::new(&dir, "game")
+    };
+    let (sink, logger) = file_logger(config);
+    let first = sink.path();
+    for i in 0..10 {
+        spdlog::info!(logger: logger, "record number {:02}", i);
+    }
+    logger.flush();
+
+    let files = log_files(&dir);
+    assert_eq!(files.len(), 6, "{files:?}");
+    //other files in the folder are left alone, even with the prefix in their name. the oldest
+    //sessions go first
+    for other in [
+        "notes.log",
+        "game_notes.log",
+        "game_editor_20000101_000000.log",
+    ] {
+        assert!(files.contains(&other.to_string()), "{files:?}");
+    }
+    assert!(
+        !files.iter().any(|f| f.starts_with("game_2000")),
+        "{files:?}"
+    );
+    assert_ne!(sink.path(), first);
+    let current = fs::read_to_string(sink.path()).unwrap();
+    assert!(
+        current.trim_end().ends_with("record number 09"),
+        "{current}"
+    );
+    for file in files.iter().filter(|f| f.starts_with("game_2")) {
+        assert!(fs::metadata(dir.join(file)).unwrap().len() <= 64);
+    }
+}
+
+#[test]
+fn log_files_rotate_by_age() {
+    let dir = log_dir("log_age");
+    let config = LogFileConfig {
+        max_size: None,
+        max_age: Some(Duration::ZERO),
+        max_files: 0,
+        ..LogFileConfig::new(&dir, "session")
+    };
+    let (sink, logger) = file_logger(config);
+    for i in 0..3 {
+        spdlog::warn!(logger: logger, "record {}", i);
+    }
+    let files = log_files(&dir);
+    assert_eq!(files.len(), 3, "{files:?}");
+    let path = sink.path();
+    let name = path.file_name().unwrap().to_string_lossy();
+    assert!(
+        name.starts_with("session_") && name.ends_with(".2.log"),
+        "{name}"
+    );
+    logger.flush();
+    assert!(
+        fs::read_to_string(&path)
+            .unwrap()
+            .contains("[warn] test: record 2")
+    );
+}
+
+//the loggers are global and set up once per process, the tests using them share that setup
+//and take turns
+static LOGGERS: Mutex<()> = Mutex::new(());
+static INIT: Once = Once::new();
+
+//engine=error, a log file flushed on warnings, panics and `log::flush`
+fn init_loggers() -> MutexGuard<'static, ()> {
+    let lock = LOGGERS.lock().unwrap_or_else(|e| e.into_inner());
+    INIT.call_once(|| {
+        //a config that fails leaves nothing behind for the next try
+        let broken = LogConfig {
+            pattern: "{bad}".to_string(),
+            ..LogConfig::default()
+        };
+        assert!(log::init_with(broken).is_err());
+        assert!(log::get_log_buffer().is_none() && log::get_logger("engine").is_none());
+        let mut config = LogConfig {
+            colors: ColorMode::Never,
+            file: Some(LogFileConfig::new(log_dir("log_init"), "engine")),
+            flush_period: Duration::from_secs(60),
+            ..LogConfig::default()
+        };
+        config.parse_levels("engine=error").unwrap();
+        log::init_with(config).unwrap();
+    });
+    lock
+}
+
+fn logged() -> String {
+    fs::read_to_string(log::get_log_file().unwrap().path()).unwrap()
+}
+
+fn level(name: &str) -> LevelFilter {
+    log::get_logger(name).unwrap().level_filter()
+}
+
+#[test]
+fn init_configures_the_loggers() {
+    let _lock = init_loggers();
+    assert_eq!(level("engine"), LevelFilter::MoreSevereEqual(Level::Error));
+    assert_eq!(level("client"), LevelFilter::All);
+    //once per process
+    assert!(log::init_with(LogConfig::default()).is_err());
+    assert_eq!(level("engine"), LevelFilter::MoreSevereEqual(Level::Error));
+    let engine = log::get_logger("engine").unwrap();
+    spdlog::info!(logger: engine, "not written");
+    spdlog::critical!(logger: engine, "written right away");
+    assert!(!logged().contains("not written"));
+    assert!(logged().contains("written right away"));
+}
+
+#[test]
+fn panics_are_logged_and_flushed() {
+    let _lock = init_loggers();
+    //before the program would go down
+    let panicked = std::panic::catch_unwind(|| panic!("boom"));
+    assert!(panicked.is_err());
+    assert!(logged().contains("boom"), "{}", logged());
+}
+
+#[test]
+fn levels_change_at_runtime() {
+    let _lock = init_loggers();
+    log::set_levels("client=debug").unwrap();
+    assert_eq!(level("client"), LevelFilter::MoreSevereEqual(Level::Debug));
+    assert_eq!(level("engine"), LevelFilter::MoreSevereEqual(Level::Error));
+    assert_eq!(log::most_verbose_level(), Some(Level::Debug));
+    log::set_levels("warn").unwrap();
+    assert_eq!(level("engine"), LevelFilter::MoreSevereEqual(Level::Warn));
+    assert!(log::set_levels("renderer=info").is_err());
+    assert!(log::set_logger_level("client", LevelFilter::Off));
+    assert_eq!(level("client"), LevelFilter::Off);
+    assert_eq!(log::most_verbose_level(), Some(Level::Warn));
+    log::set_levels("off").unwrap();
+    assert_eq!(log::most_verbose_level(), None);
+
+    assert!(log::set_pattern("{time} {payload}").is_ok());
+    assert!(log::set_pattern("{bad}").is_err());
+
+    log::set_levels("all").unwrap();
+    let engine = log::get_logger("engine").unwrap();
+    spdlog::debug!(logger: engine, "buffered");
+    assert!(!logged().contains("buffered"));
+    log::flush();
+    assert!(logged().contains("buffered"));
+
+    //back to how `init_loggers` left them for the other tests
+    log::set_levels("engine=error,client=all").unwrap();
+    log::set_pattern(log::DEFAULT_PATTERN).unwrap();
+}
+
+#[test]
+fn panic_hook_skips_locked_sinks() {
+    let _lock = init_loggers();
+    //like a panic while the console reads the buffer, waiting on the lock would hang
+    let buffer = log::get_log_buffer().unwrap();
+    let records = buffer.records();
+    let panicked = std::panic::catch_unwind(|| panic!("while locked"));
+    assert!(panicked.is_err());
+    assert!(!records.iter().any(|e| e.payload.contains("while locked")));
+    drop(records);
+    //the sinks that weren't locked still got it
+    assert!(logged().contains("while locked"), "{}", logged());
+}
@@ -0,0 +1,257 @@
+mod common;
+
+use std::{path::Path, rc::Rc};
+
+use common::render;
+use rgengine::{
+    math::{Mat4, Vec3},
+    renderer::{
+        material::{
+            AssetValue, BlendMode, CullMode, DepthFunc, Material, MaterialAsset, MaterialInstance,
+            MaterialValue, RenderState, sort_key,
+        },
+        mesh::{GpuPrimitive, Primitive, Vertex},
+        render_queue::RenderQueue,
+        shader::{Shader, split_stages},
+    },
+};
+
+const ASSET: &str = r#"
+#a lit, see through material
+shader = "shaders/lit.glsl"
+blend = alpha           #blended
+depth_test = true
+depth_write = off
+depth_func = less
+cull = none
+layer = 3
+
+[params]
+u_Color = color 1.0 0.5 0.25
+u_Tiling = vec2 2 4
+u_Roughness = float 0.5
+u_Steps = int 8
+u_Albedo = texture textures/brick.png
+"#;
+
+#[test]
+fn material_assets_parse() {
+    let asset = MaterialAsset::parse(ASSET).unwrap();
+    assert_eq!(asset.shader, Path::new("shaders/lit.glsl"));
+    assert_eq!(
+        asset.state,
+        RenderState {
+            blend: BlendMode::Alpha,
+            depth_test: true,
+            depth_write: false,
+            depth_func: DepthFunc::Less,
+            cull: CullMode::None,
+        }
+    );
+    assert_eq!(asset.layer, 3);
+
+    let names: Vec<&str> = asset.params.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        ["u_Color", "u_Tiling", "u_Roughness", "u_Steps", "u_Albedo"]
+    );
+    let values: Vec<&AssetValue> = asset.params.iter().map(|(_, value)| value).collect();
+    //three color values get an opaque alpha
+    assert!(
+        matches!(values[0], AssetValue::Value(MaterialValue::Color(c)) if c.to_array() == [1.0, 0.5, 0.25, 1.0])
+    );
+    assert!(
+        matches!(values[1], AssetValue::Value(MaterialValue::Vec2(v)) if v.to_array() == [2.0, 4.0])
+    );
+    assert!(matches!(
+        values[2],
+        AssetValue::Value(MaterialValue::Float(0.5))
+    ));
+    assert!(matches!(
+        values[3],
+        AssetValue::Value(MaterialValue::Int(8))
+    ));
+    assert!(
+        matches!(values[4], AssetValue::Texture(path) if path == Path::new("textures/brick.png"))
+    );
+
+    //everything but the shader has a default
+    let asset = MaterialAsset::parse("[material]\nshader = flat.glsl").unwrap();
+    assert_eq!(asset.state, RenderState::default());
+    assert_eq!(asset.layer, 0);
+    assert!(asset.params.is_empty());
+}
+
+#[test]
+fn material_asset_errors_name_the_line() {
+    let error = |source: &str| MaterialAsset::parse(source).unwrap_err();
+    assert_eq!(error("blend = alpha"), "material has no shader");
+    assert_eq!(
+        error("shader = a.glsl\n[textures]"),
+        "line 2: unknown section [textures]"
+    );
+    assert_eq!(
+        error("shader = a.glsl\nshininess = 4"),
+        "line 2: unknown key `shininess`"
+    );
+    assert_eq!(
+        error("shader = a.glsl\nlayer"),
+        "line 2: expected `key = value`"
+    );
+    assert_eq!(
+        error("shader = a.glsl\n\nblend = screen"),
+        "line 3: unknown blend mode `screen`"
+    );
+    assert_eq!(
+        error("shader = a.glsl\ndepth_write = maybe"),
+        "line 2: expected true or false, got `maybe`"
+    );
+    assert_eq!(
+        error("shader = a.glsl\nlayer = 256"),
+        "line 2: layer must be 0-255, got `256`"
+    );
+    assert_eq!(
+        error("shader = a.glsl\n[params]\nu_Offset = vec3 1 2"),
+        "line 3: vec3 needs 3 values, got 2"
+    );
+    assert_eq!(
+        error("shader = a.glsl\n[params]\nu_Color = color 1 x 1"),
+        "line 3: `x` is not a number"
+    );
+    assert_eq!(
+        error("shader = a.glsl\n[params]\nu_Albedo = texture"),
+        "line 3: texture needs a path"
+    );
+    assert_eq!(
+        error("shader = a.glsl\n[params]\nu_Data = mat3 1"),
+        "line 3: unknown parameter type `mat3`"
+    );
+}
+
+const VERTEX: &str = "#version 450 core
+layout(location = 0) in vec3 a_Position;
+void main() { gl_Position = vec4(a_Position, 1.0); }
+";
+const FRAGMENT: &str = "#version 450 core
+layout(location = 0) out vec4 o_Color;
+void main() { o_Color = vec4(1.0); }
+";
+
+fn material(shader: &Rc<Shader>, blend: BlendMode, layer: u8) -> Material {
+    let mut material = Material::new("test", shader.clone());
+    material.state.blend = blend;
+    material.layer = layer;
+    material
+}
+
+#[test]
+fn sort_keys_order_draws() {
+    let context = common::HeadlessContext::new().unwrap();
+    let _renderer = context.renderer();
+    let shader = Rc::new(Shader::from_source("test", VERTEX, FRAGMENT).unwrap());
+    let opaque = material(&shader, BlendMode::Opaque, 0);
+    let opaque_layer_1 = material(&shader, BlendMode::Opaque, 1);
+    let transparent = material(&shader, BlendMode::Alpha, 0);
+    let transparent_layer_1 = material(&shader, BlendMode::Additive, 1);
+
+    //opaque before transparent, whatever the layer and depth
+    assert!(sort_key(&opaque_layer_1, 1000.0) < sort_key(&transparent, 0.0));
+    //then by layer, before depth
+    assert!(sort_key(&opaque, 1000.0) < sort_key(&opaque_layer_1, 0.0));
+    assert!(sort_key(&transparent, 0.0) < sort_key(&transparent_layer_1, 1000.0));
+    //opaque front to back, transparent back to front
+    assert!(sort_key(&opaque, 10.0) < sort_key(&opaque, 100.0));
+    assert!(sort_key(&transparent, 100.0) < sort_key(&transparent, 10.0));
+    //behind the camera counts as 0, past the far end as the far end
+    assert_eq!(sort_key(&opaque, -5.0), sort_key(&opaque, 0.0));
+    assert_eq!(sort_key(&opaque, 2048.0), sort_key(&opaque, 1024.0));
+
+    //opaque draws of one material stay together, whatever their depth
+    let other = material(&shader, BlendMode::Opaque, 0);
+    let (first, second) = if opaque.id() < other.id() {
+        (&opaque, &other)
+    } else {
+        (&other, &opaque)
+    };
+    assert!(sort_key(first, 1000.0) < sort_key(second, 0.0));
+}
+
+fn quad() -> Primitive {
+    let corners = [
+        [-1.0, -1.0, 0.0],
+        [1.0, -1.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [-1.0, 1.0, 0.0],
+    ];
+    Primitive {
+        vertices: corners
+            .iter()
+            .map(|&position| Vertex {
+                position,
+                ..Vertex::default()
+            })
+            .collect(),
+        indices: vec![0, 1, 2, 2, 3, 0],
+        ..Primitive::default()
+    }
+}
+
+#[test]
+fn render_queue_batches_binds_by_material() {
+    let mut stats = None;
+    render(16, 16, 1, |_, _| {
+        let first = Rc::new(Shader::from_source("first", VERTEX, FRAGMENT).unwrap());
+        let second = Rc::new(Shader::from_source("second", VERTEX, FRAGMENT).unwrap());
+        let opaque = MaterialInstance::new(Rc::new(material(&first, BlendMode::Opaque, 0)));
+        let other = MaterialInstance::new(Rc::new(material(&second, BlendMode::Opaque, 0)));
+        let transparent = MaterialInstance::new(Rc::new(material(&first, BlendMode::Alpha, 0)));
+        let mut tinted = opaque.clone();
+        tinted.set("u_Tint", MaterialValue::Float(0.5));
+        let quad = GpuPrimitive::upload(&quad()).vertex_array;
+
+        let mut queue = RenderQueue::new();
+        queue.begin_scene(&Mat4::IDENTITY, Vec3::ZERO);
+        //submitted interleaved and drawn by shader, then front to back. tinted shares its base
+        //material with opaque so the two mix by depth, and it has overrides so it sets its
+        //uniforms every draw and the opaque draw after it sets them again
+        for (i, material) in [&transparent, &opaque, &other, &tinted]
+            .into_iter()
+            .cycle()
+            .take(8)
+            .enumerate()
+        {
+            let transform = Mat4::from_translation(Vec3::new(0.0, 0.0, -(i as f32)));
+            queue.submit(&quad, material, &transform);
+        }
+        queue.end_scene();
+        stats = Some(queue.stats());
+    });
+    let stats = stats.unwrap();
+    assert_eq!((stats.draw_calls, stats.triangles), (8, 16));
+    //first, second, then first again for the transparent draws
+    assert_eq!(stats.shader_binds, 3);
+    //opaque, tinted, opaque, tinted, other, transparent
+    assert_eq!(stats.material_binds, 6);
+    assert_eq!(stats.state_changes, 2);
+}
+
+#[test]
+fn shader_files_split_into_stages() {
+    let source = "//shared header is ignored\n#type vertex\nvoid main() {}\n  #type pixel\nout vec4 c;\nvoid main() {}\n";
+    let (vertex, fragment) = split_stages(source).unwrap();
+    assert_eq!(vertex, "void main() {}\n");
+    assert_eq!(fragment, "out vec4 c;\nvoid main() {}\n");
+
+    //the stages can come in any order and an empty one is still there
+    let (vertex, fragment) = split_stages("#type fragment\nx\n#type vertex\n").unwrap();
+    assert_eq!((vertex.as_str(), fragment.as_str()), ("", "x\n"));
+
+    assert_eq!(
+        split_stages("#type geometry\n").unwrap_err(),
+        "unknown shader stage `geometry`"
+    );
+    assert_eq!(
+        split_stages("#type vertex\nvoid main() {}\n").unwrap_err(),
+        "needs both a `#type vertex` and a `#type fragment` section"
+    );
+}
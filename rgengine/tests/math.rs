@@ -0,0 +1,238 @@
+use rgengine::{
+    math::{Aabb, Mat4, Quat, Ray, Rect, Transform, Vec2, Vec3, Vec4},
+    renderer::camera::{OrthographicCamera, PerspectiveCamera},
+};
+
+const EPSILON: f32 = 1e-4;
+
+fn assert_close(a: Vec3, b: Vec3) {
+    assert!(a.abs_diff_eq(b, EPSILON), "{a} != {b}");
+}
+
+fn to_ndc(view_projection: &Mat4, point: Vec3) -> Vec3 {
+    let clip = *view_projection * point.extend(1.0);
+    clip.truncate() / clip.w
+}
+
+#[test]
+fn orthographic_camera_maps_bounds_to_gl_clip_space() {
+    let camera = OrthographicCamera::new(-2.0, 2.0, -1.0, 1.0);
+    let vp = camera.view_projection();
+    assert_close(
+        to_ndc(vp, Vec3::new(-2.0, -1.0, 0.0)),
+        Vec3::new(-1.0, -1.0, 0.0),
+    );
+    assert_close(
+        to_ndc(vp, Vec3::new(2.0, 1.0, 0.0)),
+        Vec3::new(1.0, 1.0, 0.0),
+    );
+    //near is -1 and far is 1 along -z, like glOrtho
+    assert_close(
+        to_ndc(vp, Vec3::new(0.0, 0.0, 1.0)),
+        Vec3::new(0.0, 0.0, -1.0),
+    );
+    assert_close(
+        to_ndc(vp, Vec3::new(0.0, 0.0, -1.0)),
+        Vec3::new(0.0, 0.0, 1.0),
+    );
+}
+
+#[test]
+fn orthographic_camera_view_is_inverse_of_its_transform() {
+    let mut camera = OrthographicCamera::new(-1.0, 1.0, -1.0, 1.0);
+    camera.set_position(Vec3::new(3.0, 4.0, 0.0));
+    camera.set_rotation(std::f32::consts::FRAC_PI_2);
+    //the camera's position ends up in the middle of the screen
+    assert_close(
+        to_ndc(camera.view_projection(), Vec3::new(3.0, 4.0, 0.0)),
+        Vec3::ZERO,
+    );
+    //rotating the camera a quarter turn counter clockwise makes world +x point up on screen
+    assert_close(
+        to_ndc(camera.view_projection(), Vec3::new(3.5, 4.0, 0.0)),
+        Vec3::new(0.0, -0.5, 0.0),
+    );
+}
+
+#[test]
+fn orthographic_resize_keeps_height() {
+    let mut camera = OrthographicCamera::new(-1.0, 1.0, -1.0, 1.0);
+    camera.on_resize(200, 100);
+    let vp = camera.view_projection();
+    assert_close(
+        to_ndc(vp, Vec3::new(2.0, 1.0, 0.0)),
+        Vec3::new(1.0, 1.0, 0.0),
+    );
+}
+
+#[test]
+fn perspective_camera_maps_near_and_far_planes_to_gl_depth() {
+    let mut camera = PerspectiveCamera::new(90f32.to_radians(), 1.0, 0.5, 100.0);
+    camera.set_position(Vec3::new(0.0, 0.0, 10.0));
+    let vp = camera.view_projection();
+    assert_close(
+        to_ndc(vp, Vec3::new(0.0, 0.0, 9.5)),
+        Vec3::new(0.0, 0.0, -1.0),
+    );
+    assert_close(
+        to_ndc(vp, Vec3::new(0.0, 0.0, -90.0)),
+        Vec3::new(0.0, 0.0, 1.0),
+    );
+    //90 degree fov, so at distance 1 the top of the view is 1 unit up
+    let top = to_ndc(vp, Vec3::new(0.0, 1.0, 9.0));
+    assert!((top.y - 1.0).abs() < EPSILON);
+    //behind the camera has negative w and gets clipped
+    let behind = *vp * Vec4::new(0.0, 0.0, 11.0, 1.0);
+    assert!(behind.w < 0.0);
+}
+
+#[test]
+fn perspective_camera_look_at_points_forward_at_target() {
+    let mut camera = PerspectiveCamera::new(1.0, 1.0, 0.1, 100.0);
+    camera.set_position(Vec3::new(5.0, 2.0, 5.0));
+    camera.look_at(Vec3::ZERO);
+    assert_close(camera.forward(), (-camera.position()).normalize());
+    let ndc = to_ndc(camera.view_projection(), Vec3::ZERO);
+    assert!(ndc.x.abs() < EPSILON && ndc.y.abs() < EPSILON);
+}
+
+#[test]
+fn transform_applies_scale_then_rotation_then_translation() {
+    let transform = Transform::new(
+        Vec3::new(1.0, 0.0, 0.0),
+        Quat::from_rotation_z(std::f32::consts::FRAC_PI_2),
+        Vec3::splat(2.0),
+    );
+    let point = Vec3::new(1.0, 0.0, 0.0);
+    assert_close(transform.transform_point(point), Vec3::new(1.0, 2.0, 0.0));
+    assert_close(
+        transform.matrix().transform_point3(point),
+        Vec3::new(1.0, 2.0, 0.0),
+    );
+}
+
+#[test]
+fn transform_composition_matches_matrix_product() {
+    let parent = Transform::new(
+        Vec3::new(0.0, 5.0, 0.0),
+        Quat::from_rotation_y(0.7),
+        Vec3::splat(3.0),
+    );
+    let child = Transform::new(
+        Vec3::new(1.0, 2.0, 3.0),
+        Quat::from_rotation_x(-0.3),
+        Vec3::splat(0.5),
+    );
+    let combined = (parent * child).matrix();
+    let expected = parent.matrix() * child.matrix();
+    assert!(combined.abs_diff_eq(expected, EPSILON));
+
+    let round_trip = parent * parent.inverse();
+    assert_close(
+        round_trip.transform_point(Vec3::new(4.0, -2.0, 1.0)),
+        Vec3::new(4.0, -2.0, 1.0),
+    );
+    assert!(
+        Transform::from_matrix(&parent.matrix())
+            .matrix()
+            .abs_diff_eq(parent.matrix(), EPSILON)
+    );
+}
+
+#[test]
+fn transform_look_at_faces_target() {
+    let mut transform = Transform::from_translation(Vec3::new(0.0, 0.0, 5.0));
+    transform.look_at(Vec3::new(5.0, 0.0, 5.0), Vec3::Y);
+    assert_close(transform.forward(), Vec3::X);
+    assert_close(transform.up(), Vec3::Y);
+}
+
+#[test]
+fn rect_queries() {
+    let a = Rect::new(0.0, 0.0, 10.0, 10.0);
+    let b = Rect::new(5.0, 5.0, 10.0, 10.0);
+    assert!(a.contains(Vec2::new(0.0, 0.0)));
+    assert!(!a.contains(Vec2::new(10.0, 5.0)));
+    assert!(a.intersects(&b));
+    assert_eq!(a.intersection(&b), Some(Rect::new(5.0, 5.0, 5.0, 5.0)));
+    assert_eq!(a.union(&b), Rect::new(0.0, 0.0, 15.0, 15.0));
+    assert!(!a.intersects(&Rect::new(10.0, 0.0, 1.0, 1.0)));
+    assert_eq!(a.intersection(&Rect::new(10.0, 0.0, 1.0, 1.0)), None);
+}
+
+#[test]
+fn aabb_from_points_and_transform() {
+    let aabb = Aabb::from_points([Vec3::new(1.0, -1.0, 0.0), Vec3::new(-1.0, 1.0, 2.0)]).unwrap();
+    assert_close(aabb.min, Vec3::new(-1.0, -1.0, 0.0));
+    assert_close(aabb.max, Vec3::new(1.0, 1.0, 2.0));
+    assert!(Aabb::from_points([]).is_none());
+
+    let rotation = Mat4::from_rotation_z(std::f32::consts::FRAC_PI_4);
+    let rotated = aabb.transformed(&rotation);
+    let grown = Aabb::from_min_max(
+        rotated.min - Vec3::splat(EPSILON),
+        rotated.max + Vec3::splat(EPSILON),
+    );
+    for corner in aabb.corners() {
+        assert!(grown.contains(rotation.transform_point3(corner)));
+    }
+    assert!((rotated.max.x - 2f32.sqrt()).abs() < EPSILON);
+}
+
+#[test]
+fn ray_intersections() {
+    let ray = Ray::new(Vec3::new(0.0, 0.0, 10.0), Vec3::new(0.0, 0.0, -2.0));
+    let unit_box = Aabb::from_center_half_extents(Vec3::ZERO, Vec3::ONE);
+    assert_eq!(ray.intersect_aabb(&unit_box), Some(9.0));
+    assert_eq!(ray.intersect_sphere(Vec3::ZERO, 2.0), Some(8.0));
+    assert_eq!(ray.intersect_plane(Vec3::ZERO, Vec3::Z), Some(10.0));
+    let hit = ray
+        .intersect_triangle(
+            Vec3::new(-1.0, -1.0, 0.0),
+            Vec3::new(1.0, -1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        )
+        .unwrap();
+    assert!((hit - 10.0).abs() < EPSILON);
+
+    let away = Ray::new(Vec3::new(0.0, 0.0, 10.0), Vec3::Z);
+    assert_eq!(away.intersect_aabb(&unit_box), None);
+    assert_eq!(away.intersect_sphere(Vec3::ZERO, 2.0), None);
+    assert_eq!(away.intersect_plane(Vec3::ZERO, Vec3::Z), None);
+
+    let inside = Ray::new(Vec3::ZERO, Vec3::X);
+    assert_eq!(inside.intersect_aabb(&unit_box), Some(0.0));
+}
+
+#[test]
+fn axis_aligned_rays_graze_boxes() {
+    let unit_box = Aabb::from_center_half_extents(Vec3::ZERO, Vec3::ONE);
+    //starts on the x and y max planes and runs down an edge of the box
+    let edge = Ray::new(Vec3::new(1.0, 1.0, 10.0), Vec3::NEG_Z);
+    assert_eq!(edge.intersect_aabb(&unit_box), Some(9.0));
+    let face = Ray::new(Vec3::new(-1.0, 0.5, -10.0), Vec3::Z);
+    assert_eq!(face.intersect_aabb(&unit_box), Some(9.0));
+    //on the plane of a face but past the box on another axis
+    let beside = Ray::new(Vec3::new(1.0, 1.5, 10.0), Vec3::NEG_Z);
+    assert_eq!(beside.intersect_aabb(&unit_box), None);
+    let behind = Ray::new(Vec3::new(1.0, 0.0, -10.0), Vec3::NEG_Z);
+    assert_eq!(behind.intersect_aabb(&unit_box), None);
+    let along = Ray::new(Vec3::new(-5.0, 1.0, 1.0), Vec3::X);
+    assert_eq!(along.intersect_aabb(&unit_box), Some(4.0));
+}
+
+#[test]
+fn screen_ray_goes_through_the_pixel() {
+    let mut camera = PerspectiveCamera::new(60f32.to_radians(), 16.0 / 9.0, 0.1, 100.0);
+    camera.set_position(Vec3::new(0.0, 0.0, 5.0));
+    let viewport = Vec2::new(1280.0, 720.0);
+
+    //the middle of the window looks straight down -z
+    let center = Ray::from_screen(viewport / 2.0, viewport, camera.view_projection());
+    assert_close(center.direction, Vec3::NEG_Z);
+
+    //the top left pixel maps back to the top left of clip space
+    let corner = Ray::from_screen(Vec2::ZERO, viewport, camera.view_projection());
+    let ndc = to_ndc(camera.view_projection(), corner.at(10.0));
+    assert!((ndc.x + 1.0).abs() < EPSILON && (ndc.y - 1.0).abs() < EPSILON);
+}
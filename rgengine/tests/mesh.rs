@@ -0,0 +1,367 @@
+//the importers only touch the cpu, so these run without a gl context
+use std::path::PathBuf;
+
+use rgengine::{
+    math::{Vec3, Vec4},
+    renderer::mesh::{AlphaMode, Model},
+};
+
+const EPSILON: f32 = 1e-5;
+
+fn assert_close(a: &[f32], b: &[f32]) {
+    assert_eq!(a.len(), b.len());
+    for (x, y) in a.iter().zip(b) {
+        assert!((x - y).abs() < EPSILON, "{a:?} != {b:?}");
+    }
+}
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join("mesh")
+        .join(name);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn bytes<T: Copy>(values: &[T]) -> Vec<u8> {
+    let size = std::mem::size_of_val(values);
+    unsafe { std::slice::from_raw_parts(values.as_ptr() as *const u8, size) }.to_vec()
+}
+
+fn pad(data: &mut Vec<u8>, with: u8) {
+    while !data.len().is_multiple_of(4) {
+        data.push(with);
+    }
+}
+
+fn glb(json: &str, mut bin: Vec<u8>) -> Vec<u8> {
+    let mut json = json.as_bytes().to_vec();
+    pad(&mut json, b' ');
+    pad(&mut bin, 0);
+    let total = 12 + 8 + json.len() + 8 + bin.len();
+    let mut out = Vec::with_capacity(total);
+    out.extend_from_slice(b"glTF");
+    out.extend_from_slice(&2u32.to_le_bytes());
+    out.extend_from_slice(&(total as u32).to_le_bytes());
+    out.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    out.extend_from_slice(b"JSON");
+    out.extend_from_slice(&json);
+    out.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    out.extend_from_slice(b"BIN\0");
+    out.extend_from_slice(&bin);
+    out
+}
+
+fn png(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+    let image = image::RgbaImage::from_raw(width, height, pixels.to_vec()).unwrap();
+    let mut out = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut out, image::ImageFormat::Png).unwrap();
+    out.into_inner()
+}
+
+const QUAD_POSITIONS: [[f32; 3]; 4] = [
+    [-1.0, -1.0, 0.0],
+    [1.0, -1.0, 0.0],
+    [1.0, 1.0, 0.0],
+    [-1.0, 1.0, 0.0],
+];
+//top left origin, so the bottom of the quad has v = 1
+const QUAD_UVS: [[f32; 2]; 4] = [[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]];
+const CHECKER: [u8; 16] = [
+    255, 0, 0, 255, 0, 255, 0, 255, //
+    0, 0, 255, 255, 255, 255, 255, 255,
+];
+
+#[test]
+fn glb_with_primitives_materials_and_embedded_texture() {
+    let colors: [[f32; 4]; 4] = [
+        [1.0, 0.0, 0.0, 1.0],
+        [0.0, 1.0, 0.0, 1.0],
+        [0.0, 0.0, 1.0, 1.0],
+        [1.0, 1.0, 1.0, 0.5],
+    ];
+    let mut bin = bytes(&QUAD_POSITIONS); //0..48
+    bin.extend(bytes(&QUAD_UVS)); //48..80
+    bin.extend(bytes(&[0u16, 1, 2, 2, 3, 0])); //80..92
+    bin.extend(bytes(&colors)); //92..156
+    let image = png(2, 2, &CHECKER);
+    let image_len = image.len();
+    bin.extend(image); //156..
+
+    let json = format!(
+        r#"{{
+        "asset": {{ "version": "2.0" }},
+        "scene": 0,
+        "scenes": [{{ "nodes": [0] }}],
+        "nodes": [
+            {{ "translation": [0, 0, -5], "children": [1] }},
+            {{ "mesh": 0, "scale": [2, 2, 2] }}
+        ],
+        "meshes": [{{
+            "name": "quad",
+            "primitives": [
+                {{ "attributes": {{ "POSITION": 0, "TEXCOORD_0": 1 }}, "indices": 2, "material": 0 }},
+                {{ "attributes": {{ "POSITION": 0, "COLOR_0": 3 }}, "indices": 2, "material": 1 }}
+            ]
+        }}],
+        "materials": [
+            {{
+                "name": "textured",
+                "pbrMetallicRoughness": {{ "baseColorTexture": {{ "index": 0 }}, "metallicFactor": 0.25 }},
+                "alphaMode": "MASK",
+                "alphaCutoff": 0.3
+            }},
+            {{
+                "name": "red",
+                "pbrMetallicRoughness": {{ "baseColorFactor": [1, 0, 0, 1] }},
+                "alphaMode": "BLEND",
+                "doubleSided": true
+            }}
+        ],
+        "textures": [{{ "source": 0 }}],
+        "images": [{{ "name": "checker", "bufferView": 4, "mimeType": "image/png" }}],
+        "accessors": [
+            {{ "bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3", "min": [-1, -1, 0], "max": [1, 1, 0] }},
+            {{ "bufferView": 1, "componentType": 5126, "count": 4, "type": "VEC2" }},
+            {{ "bufferView": 2, "componentType": 5123, "count": 6, "type": "SCALAR" }},
+            {{ "bufferView": 3, "componentType": 5126, "count": 4, "type": "VEC4" }}
+        ],
+        "bufferViews": [
+            {{ "buffer": 0, "byteOffset": 0, "byteLength": 48 }},
+            {{ "buffer": 0, "byteOffset": 48, "byteLength": 32 }},
+            {{ "buffer": 0, "byteOffset": 80, "byteLength": 12 }},
+            {{ "buffer": 0, "byteOffset": 92, "byteLength": 64 }},
+            {{ "buffer": 0, "byteOffset": 156, "byteLength": {image_len} }}
+        ],
+        "buffers": [{{ "byteLength": {} }}]
+    }}"#,
+        bin.len()
+    );
+
+    let model = Model::from_gltf_slice(&glb(&json, bin), None).unwrap();
+
+    assert_eq!(model.meshes.len(), 1);
+    let mesh = &model.meshes[0];
+    assert_eq!(mesh.name, "quad");
+    assert_eq!(mesh.primitives.len(), 2);
+
+    let textured = &mesh.primitives[0];
+    assert_eq!(textured.indices, [0, 1, 2, 2, 3, 0]);
+    assert_eq!(textured.material, Some(0));
+    for (i, vertex) in textured.vertices.iter().enumerate() {
+        assert_close(&vertex.position, &QUAD_POSITIONS[i]);
+        assert_close(&vertex.uv, &QUAD_UVS[i]);
+        //not in the file, so generated
+        assert_close(&vertex.normal, &[0.0, 0.0, 1.0]);
+        assert_close(&vertex.tangent, &[1.0, 0.0, 0.0, 1.0]);
+        assert_close(&vertex.color, &[1.0; 4]);
+    }
+
+    let colored = &mesh.primitives[1];
+    assert_eq!(colored.material, Some(1));
+    for (vertex, color) in colored.vertices.iter().zip(colors) {
+        assert_close(&vertex.color, &color);
+    }
+
+    let bounds = mesh.bounds().unwrap();
+    assert_eq!(bounds.min, Vec3::new(-1.0, -1.0, 0.0));
+    assert_eq!(bounds.max, Vec3::new(1.0, 1.0, 0.0));
+
+    let textured = &model.materials[0];
+    assert_eq!(textured.name, "textured");
+    assert_eq!(textured.base_color_texture, Some(0));
+    assert_eq!(textured.metallic, 0.25);
+    assert_eq!(textured.roughness, 1.0);
+    assert_eq!(textured.alpha_mode, AlphaMode::Mask(0.3));
+    let red = &model.materials[1];
+    assert_eq!(red.base_color, Vec4::new(1.0, 0.0, 0.0, 1.0));
+    assert_eq!(red.base_color_texture, None);
+    assert_eq!(red.alpha_mode, AlphaMode::Blend);
+    assert!(red.double_sided);
+
+    assert_eq!(model.images.len(), 1);
+    assert_eq!(model.images[0].name, "checker");
+    assert_eq!((model.images[0].width, model.images[0].height), (2, 2));
+    assert_eq!(model.images[0].pixels, CHECKER);
+
+    assert_eq!(model.nodes.len(), 1);
+    let corner = model.nodes[0]
+        .transform
+        .transform_point3(Vec3::new(1.0, 1.0, 0.0));
+    assert_close(&corner.to_array(), &[2.0, 2.0, -5.0]);
+    let bounds = model.bounds().unwrap();
+    assert_close(&bounds.min.to_array(), &[-2.0, -2.0, -5.0]);
+    assert_close(&bounds.max.to_array(), &[2.0, 2.0, -5.0]);
+}
+
+#[test]
+fn gray_alpha_images_become_rgba() {
+    //gray, alpha pairs
+    let gray_alpha = [0u8, 255, 128, 64, 255, 0, 32, 128];
+    let mut png_8 = std::io::Cursor::new(Vec::new());
+    image::GrayAlphaImage::from_raw(2, 2, gray_alpha.to_vec())
+        .unwrap()
+        .write_to(&mut png_8, image::ImageFormat::Png)
+        .unwrap();
+    let mut png_16 = std::io::Cursor::new(Vec::new());
+    let wide: Vec<u16> = gray_alpha.iter().map(|&v| v as u16 * 257).collect();
+    image::ImageBuffer::<image::LumaA<u16>, _>::from_raw(2, 2, wide)
+        .unwrap()
+        .write_to(&mut png_16, image::ImageFormat::Png)
+        .unwrap();
+    let (png_8, png_16) = (png_8.into_inner(), png_16.into_inner());
+    let (len_8, len_16) = (png_8.len(), png_16.len());
+    let mut bin = png_8;
+    pad(&mut bin, 0);
+    let offset_16 = bin.len();
+    bin.extend(png_16);
+
+    let json = format!(
+        r#"{{
+        "asset": {{ "version": "2.0" }},
+        "images": [
+            {{ "bufferView": 0, "mimeType": "image/png" }},
+            {{ "bufferView": 1, "mimeType": "image/png" }}
+        ],
+        "bufferViews": [
+            {{ "buffer": 0, "byteOffset": 0, "byteLength": {len_8} }},
+            {{ "buffer": 0, "byteOffset": {offset_16}, "byteLength": {len_16} }}
+        ],
+        "buffers": [{{ "byteLength": {} }}]
+    }}"#,
+        bin.len()
+    );
+    let model = Model::from_gltf_slice(&glb(&json, bin), None).unwrap();
+
+    let expected: Vec<u8> = gray_alpha
+        .chunks_exact(2)
+        .flat_map(|p| [p[0], p[0], p[0], p[1]])
+        .collect();
+    assert_eq!(model.images.len(), 2);
+    for image in &model.images {
+        assert_eq!((image.width, image.height), (2, 2));
+        assert_eq!(image.pixels, expected, "{}", image.name);
+    }
+}
+
+#[test]
+fn gltf_with_external_buffer_and_triangle_strip() {
+    let dir = test_dir("strip");
+    let positions: [[f32; 3]; 4] = [
+        [0.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0],
+        [1.0, 0.0, 0.0],
+        [1.0, 0.0, 1.0],
+    ];
+    let mut bin = bytes(&positions);
+    bin.extend(bytes(&[[0.0f32, 1.0, 0.0]; 4]));
+    std::fs::write(dir.join("strip.bin"), &bin).unwrap();
+    let json = r#"{
+        "asset": { "version": "2.0" },
+        "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0, "NORMAL": 1 }, "mode": 5 }] }],
+        "accessors": [
+            { "bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3", "min": [0, 0, 0], "max": [1, 0, 1] },
+            { "bufferView": 1, "componentType": 5126, "count": 4, "type": "VEC3" }
+        ],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": 0, "byteLength": 48 },
+            { "buffer": 0, "byteOffset": 48, "byteLength": 48 }
+        ],
+        "buffers": [{ "uri": "strip.bin", "byteLength": 96 }]
+    }"#;
+    std::fs::write(dir.join("strip.gltf"), json).unwrap();
+
+    let model = Model::load(dir.join("strip.gltf")).unwrap();
+
+    let primitive = &model.meshes[0].primitives[0];
+    //the second triangle is flipped back so both face +y
+    assert_eq!(primitive.indices, [0, 1, 2, 2, 1, 3]);
+    assert_eq!(primitive.material, None);
+    for vertex in &primitive.vertices {
+        assert_close(&vertex.normal, &[0.0, 1.0, 0.0]);
+    }
+    //no scene, so every mesh is placed at the origin
+    assert_eq!(model.nodes.len(), 1);
+    assert_eq!(model.nodes[0].transform, rgengine::math::Mat4::IDENTITY);
+}
+
+#[test]
+fn obj_with_mtl_and_texture() {
+    let dir = test_dir("obj");
+    std::fs::write(dir.join("brick.png"), png(2, 2, &CHECKER)).unwrap();
+    std::fs::write(
+        dir.join("quad.mtl"),
+        "newmtl Brick\nKd 0.5 0.25 1.0\nd 0.5\nNs 98\nmap_Kd brick.png\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("quad.obj"),
+        "mtllib quad.mtl
+o Quad
+v -1 -1 0 1 0 0
+v 1 -1 0 0 1 0
+v 1 1 0 0 0 1
+v -1 1 0 1 1 1
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+usemtl Brick
+f 1/1 2/2 3/3 4/4
+",
+    )
+    .unwrap();
+
+    let model = Model::load(dir.join("quad.obj")).unwrap();
+
+    assert_eq!(model.meshes.len(), 1);
+    assert_eq!(model.meshes[0].name, "Quad");
+    let primitive = &model.meshes[0].primitives[0];
+    assert_eq!(primitive.triangle_count(), 2);
+    assert_eq!(primitive.material, Some(0));
+    for (i, vertex) in primitive.vertices.iter().enumerate() {
+        assert_close(&vertex.position, &QUAD_POSITIONS[i]);
+        //obj uvs start at the bottom, so they end up the same as the gltf ones
+        assert_close(&vertex.uv, &QUAD_UVS[i]);
+        assert_close(&vertex.normal, &[0.0, 0.0, 1.0]);
+        assert_close(&vertex.tangent, &[1.0, 0.0, 0.0, 1.0]);
+    }
+    assert_close(&primitive.vertices[1].color, &[0.0, 1.0, 0.0, 1.0]);
+
+    let material = &model.materials[0];
+    assert_eq!(material.name, "Brick");
+    assert_eq!(material.base_color, Vec4::new(0.5, 0.25, 1.0, 0.5));
+    assert_eq!(material.alpha_mode, AlphaMode::Blend);
+    assert!((material.roughness - 0.02f32.sqrt()).abs() < EPSILON);
+    assert_eq!(material.base_color_texture, Some(0));
+    assert_eq!(model.images[0].pixels, CHECKER);
+}
+
+#[test]
+fn obj_objects_become_meshes() {
+    let source = "
+o First
+v 0 0 0
+v 1 0 0
+v 0 1 0
+f 1 2 3
+o Second
+v 0 0 1
+v 1 0 1
+v 1 1 1
+v 0 1 1
+usemtl Missing
+f 4 5 6 7
+";
+    let model = Model::from_obj_str(source, None).unwrap();
+
+    assert_eq!(model.meshes.len(), 2);
+    assert_eq!(model.nodes.len(), 2);
+    assert_eq!(model.meshes[0].primitives[0].triangle_count(), 1);
+    assert_eq!(model.meshes[1].primitives[0].triangle_count(), 2);
+    //no materials without a directory to find the mtl in
+    assert!(model.materials.is_empty());
+    assert_eq!(model.meshes[1].primitives[0].material, None);
+
+    assert!(Model::load("model.fbx").is_err());
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use std::{thread, time::Duration};
+
+use common::render;
+use imgui::Context;
+use rgengine::{
+    imgui::{ImGuiRenderer, NullUiBackend, PerfOverlay, resident_memory},
+    layers::{Layer, LayerStack, LayerTiming},
+    math::{Vec2, Vec3, Vec4},
+    renderer::{camera::OrthographicCamera, renderer2d::Renderer2D, stats},
+};
+
+struct SlowLayer {
+    id: usize,
+    update: Duration,
+}
+
+impl Layer for SlowLayer {
+    fn on_update(&mut self, _dt: f32) {
+        thread::sleep(self.update);
+    }
+
+    fn get_id(&self) -> usize {
+        self.id
+    }
+
+    fn get_name(&self) -> &str {
+        if self.id == 1 { "slow" } else { "fast" }
+    }
+}
+
+fn layer(id: usize, update_ms: u64) -> Box<dyn Layer> {
+    Box::new(SlowLayer {
+        id,
+        update: Duration::from_millis(update_ms),
+    })
+}
+
+#[test]
+fn draws_are_counted_per_frame() {
+    let mut renderer_2d = None;
+    render(64, 64, 2, |_, frame| {
+        let renderer_2d = renderer_2d.get_or_insert_with(Renderer2D::new);
+        renderer_2d.begin_scene(&OrthographicCamera::new(0.0, 64.0, 0.0, 64.0));
+        for i in 0..3 {
+            let position = Vec3::new(8.0 + 16.0 * i as f32, 32.0, 0.0);
+            renderer_2d.draw_quad(position, Vec2::splat(8.0), 0.0, Vec4::ONE);
+        }
+        renderer_2d.end_scene();
+
+        let current = stats::current();
+        //the three quads go out in one batch
+        assert_eq!((current.draw_calls, current.triangles), (1, 6));
+        //binding the target, the shader, the white texture and the quads
+        assert!(current.state_changes >= 4, "{current:?}");
+        if frame == 1 {
+            assert_eq!(stats::last_frame().draw_calls, 1);
+        }
+    });
+}
+
+#[test]
+fn layers_are_timed_in_stack_order() {
+    let mut layer_stack = LayerStack::new();
+    layer_stack.push_overlay(layer(2, 0));
+    layer_stack.push_layer(layer(1, 5));
+    layer_stack.update();
+
+    let timings: Vec<(String, LayerTiming)> = layer_stack
+        .timings()
+        .map(|(layer, timing)| (layer.get_name().to_string(), timing))
+        .collect();
+    let names: Vec<&str> = timings.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["slow", "fast"]);
+    let slow = timings[0].1;
+    assert!(slow.update >= Duration::from_millis(5), "{slow:?}");
+    assert_eq!(slow.total(), slow.update);
+    assert!(timings[1].1.update < slow.update);
+
+    //the timings stay with their layer when the one before it goes
+    layer_stack.pop_layer(layer(1, 0));
+    let (fast, timing) = layer_stack.timings().next().unwrap();
+    assert_eq!(fast.get_name(), "fast");
+    assert!(timing.update < Duration::from_millis(5));
+
+    layer_stack.reset_timings();
+    assert!(
+        layer_stack
+            .timings()
+            .all(|(_, timing)| timing == LayerTiming::default())
+    );
+}
+
+#[test]
+fn perf_overlay_draws_when_open() {
+    let mut imgui = Context::create();
+    imgui.set_ini_filename(None);
+    imgui.io_mut().display_size = [320.0, 240.0];
+    let mut renderer = ImGuiRenderer::new(&mut imgui, NullUiBackend::new());
+    let mut layer_stack = LayerStack::new();
+    layer_stack.push_layer(layer(1, 0));
+
+    let mut overlay = PerfOverlay::new();
+    for _ in 0..4 {
+        overlay.record_frame(Duration::from_millis(10));
+    }
+    assert!((overlay.fps() - 100.0).abs() < 0.01, "{}", overlay.fps());
+
+    let mut drawn = Vec::new();
+    //auto resized windows are measured on their first frame and shown from the second
+    for open in [false, true, true] {
+        overlay.set_open(open);
+        overlay.record_layers(&layer_stack);
+        let ui = imgui.new_frame();
+        overlay.draw(ui);
+        renderer.render(&mut imgui);
+        drawn.push(renderer.backend().commands.len());
+    }
+    assert_eq!(drawn[0], 0);
+    assert!(drawn[2] > 0);
+
+    overlay.toggle();
+    assert!(!overlay.is_open());
+    assert!(resident_memory().is_none_or(|bytes| bytes > 0));
+}
@@ -0,0 +1,180 @@
+mod common;
+
+use std::rc::Rc;
+
+use common::{assert_golden, render};
+use rgengine::{
+    events::{RGEvent, WindowResize},
+    math::{Vec2, Vec3, Vec4},
+    renderer::{
+        camera::OrthographicCamera,
+        capture::CapturedFrame,
+        post_process::{Bloom, ColorGrading, PostProcessStack, Tonemapping, neutral_lut},
+        renderer2d::Renderer2D,
+        texture::Texture2D,
+    },
+    rgevent,
+};
+
+const WIDTH: u32 = 128;
+const HEIGHT: u32 = 96;
+const TOLERANCE: u8 = 3;
+
+fn camera() -> OrthographicCamera {
+    OrthographicCamera::new(
+        -(WIDTH as f32) / 2.0,
+        WIDTH as f32 / 2.0,
+        -(HEIGHT as f32) / 2.0,
+        HEIGHT as f32 / 2.0,
+    )
+}
+
+///a dim backdrop, a rotated quad for aliased edges and a small quad far brighter than 1
+fn draw_scene(renderer_2d: &mut Renderer2D) {
+    renderer_2d.begin_scene(&camera());
+    renderer_2d.draw_quad(
+        Vec3::ZERO,
+        Vec2::new(100.0, 70.0),
+        0.0,
+        Vec4::new(0.1, 0.12, 0.2, 1.0),
+    );
+    renderer_2d.draw_quad(
+        Vec3::new(-22.0, 0.0, 0.0),
+        Vec2::splat(30.0),
+        0.3,
+        Vec4::new(0.8, 0.4, 0.2, 1.0),
+    );
+    renderer_2d.draw_quad(
+        Vec3::new(25.0, 8.0, 0.0),
+        Vec2::splat(10.0),
+        0.0,
+        Vec4::new(8.0, 6.0, 2.0, 1.0),
+    );
+    renderer_2d.end_scene();
+}
+
+///`setup` builds the stack on the first frame, `None` renders without post processing
+fn render_post<F: FnMut() -> Option<PostProcessStack>>(mut setup: F) -> CapturedFrame {
+    let mut scene = None;
+    render(WIDTH, HEIGHT, 2, |renderer, _| {
+        let (renderer_2d, stack) = scene.get_or_insert_with(|| (Renderer2D::new(), setup()));
+        if let Some(stack) = stack {
+            stack.begin(renderer.clear_color());
+        }
+        draw_scene(renderer_2d);
+        if let Some(stack) = stack {
+            stack.end();
+        }
+    })
+}
+
+///a lut that swaps red and blue
+fn swap_lut(size: u32) -> Texture2D {
+    let mut pixels = neutral_lut(size);
+    for pixel in pixels.chunks_mut(4) {
+        pixel.swap(0, 2);
+    }
+    Texture2D::from_rgba(size * size, size, &pixels)
+}
+
+fn assert_close(a: &CapturedFrame, b: &CapturedFrame, tolerance: u8) {
+    let worst = a
+        .pixels
+        .iter()
+        .zip(&b.pixels)
+        .map(|(a, b)| a.abs_diff(*b))
+        .max()
+        .unwrap();
+    assert!(worst <= tolerance, "frames differ by up to {worst}");
+}
+
+#[test]
+fn post_process_default_chain() {
+    let frame = render_post(|| {
+        let mut stack = PostProcessStack::with_default_effects(WIDTH, HEIGHT, 4);
+        let grading = stack.effect_mut::<ColorGrading>().unwrap();
+        grading.set_lut(Rc::new(swap_lut(16))).unwrap();
+        grading.contribution = 0.5;
+        Some(stack)
+    });
+    assert_golden("post_process_default_chain", &frame, TOLERANCE);
+}
+
+#[test]
+fn disabled_effects_copy_the_scene() {
+    let plain = render_post(|| None);
+    let copied = render_post(|| {
+        let mut stack = PostProcessStack::with_default_effects(WIDTH, HEIGHT, 1);
+        for name in ["Bloom", "Tonemapping", "Vignette", "Fxaa"] {
+            assert!(stack.set_enabled(name, false));
+        }
+        assert!(!stack.set_enabled("Missing", false));
+        Some(stack)
+    });
+    assert_close(&plain, &copied, 1);
+}
+
+#[test]
+fn neutral_lut_changes_nothing() {
+    let setup = |lut: bool| {
+        let mut stack = PostProcessStack::new(WIDTH, HEIGHT, 1);
+        let mut tonemapping = Tonemapping::new();
+        tonemapping.gamma = 1.0;
+        stack.push(Box::new(tonemapping));
+        if lut {
+            let mut grading = ColorGrading::new();
+            grading
+                .set_lut(Rc::new(Texture2D::from_rgba(256, 16, &neutral_lut(16))))
+                .unwrap();
+            stack.push(Box::new(grading));
+        }
+        Some(stack)
+    };
+    assert_close(
+        &render_post(|| setup(false)),
+        &render_post(|| setup(true)),
+        2,
+    );
+}
+
+#[test]
+fn effects_can_be_found_removed_and_reordered() {
+    let context = common::HeadlessContext::new().unwrap();
+    let _renderer = context.renderer();
+    let mut stack = PostProcessStack::with_default_effects(WIDTH, HEIGHT, 1);
+    let names = |stack: &PostProcessStack| -> Vec<String> {
+        stack
+            .effects()
+            .iter()
+            .map(|e| e.name().to_string())
+            .collect()
+    };
+    assert_eq!(
+        names(&stack),
+        ["Bloom", "Tonemapping", "ColorGrading", "Vignette", "Fxaa"]
+    );
+    assert!(!stack.effect::<ColorGrading>().unwrap().enabled);
+
+    stack.effect_mut::<Bloom>().unwrap().intensity = 2.0;
+    assert_eq!(stack.effect::<Bloom>().unwrap().intensity, 2.0);
+
+    let bloom = stack.remove("Bloom").unwrap();
+    assert!(stack.effect::<Bloom>().is_none());
+    stack.insert(1, bloom);
+    stack.effects_mut().swap(0, 1);
+    assert_eq!(names(&stack)[..2], ["Bloom", "Tonemapping"]);
+}
+
+#[test]
+fn stack_resizes_on_window_resize() {
+    let context = common::HeadlessContext::new().unwrap();
+    let _renderer = context.renderer();
+    let mut stack = PostProcessStack::with_default_effects(WIDTH, HEIGHT, 4);
+    let (width, height) = (300, 200);
+    stack.on_event(&rgevent!(WindowResize, width, height));
+    assert_eq!((stack.width(), stack.height()), (300, 200));
+    //minimized windows report 0 and keep the old size
+    let (width, height) = (0, 0);
+    stack.on_event(&rgevent!(WindowResize, width, height));
+    assert_eq!((stack.width(), stack.height()), (300, 200));
+}
@@ -0,0 +1,320 @@
+mod common;
+
+use std::{cell::RefCell, rc::Rc};
+
+use rgengine::renderer::{
+    framebuffer::{Framebuffer, FramebufferSpec, FramebufferTextureFormat},
+    render_graph::{
+        Access, BufferDesc, ClearValue, GlBackend, GlTexture, NullBackend, RenderGraph, TextureDesc,
+    },
+};
+
+const COLOR: FramebufferTextureFormat = FramebufferTextureFormat::Rgba16F;
+const DEPTH: FramebufferTextureFormat = FramebufferTextureFormat::Depth32F;
+
+///shadows, geometry, bloom and ui into the backbuffer, plus a debug view nobody reads
+fn frame_graph<'a>() -> RenderGraph<'a, NullBackend> {
+    let mut graph = RenderGraph::new();
+    let backbuffer = graph.import_texture(
+        "Backbuffer",
+        TextureDesc::new(320, 180, FramebufferTextureFormat::Rgba8),
+        "Backbuffer".into(),
+    );
+    let shadow_map = graph.create_texture(
+        "ShadowMap",
+        TextureDesc::new(512, 512, DEPTH).clear(ClearValue::Depth(1.0)),
+    );
+    let scene = graph.create_texture(
+        "Scene",
+        TextureDesc::new(320, 180, COLOR).clear(ClearValue::Color([0.0, 0.0, 0.0, 1.0])),
+    );
+    let depth = graph.create_texture(
+        "Depth",
+        TextureDesc::new(320, 180, DEPTH).clear(ClearValue::Depth(1.0)),
+    );
+    let bloom = graph.create_texture("Bloom", TextureDesc::new(160, 90, COLOR));
+    let debug = graph.create_texture("Debug", TextureDesc::new(320, 180, COLOR));
+
+    let shadow_map = graph.add_pass("Shadows").write(shadow_map);
+
+    let mut pass = graph.add_pass("Geometry");
+    pass.read(shadow_map);
+    let scene = pass.write(scene);
+    pass.write(depth);
+
+    let mut pass = graph.add_pass("Debug");
+    pass.read(shadow_map);
+    pass.write(debug);
+
+    let mut pass = graph.add_pass("Bloom");
+    pass.read(scene);
+    let bloom = pass.write(bloom);
+
+    let mut pass = graph.add_pass("Ui");
+    pass.read(scene);
+    pass.read(bloom);
+    pass.write(backbuffer);
+    graph
+}
+
+#[test]
+fn unused_passes_are_culled() {
+    let compiled = frame_graph().compile().unwrap();
+    assert_eq!(compiled.order(), ["Shadows", "Geometry", "Bloom", "Ui"]);
+    assert_eq!(compiled.culled(), ["Debug"]);
+}
+
+#[test]
+fn a_write_waits_for_the_reads_of_the_previous_version() {
+    let mut graph = RenderGraph::<NullBackend>::new();
+    let history = graph.import_texture("History", TextureDesc::new(4, 4, COLOR), "History".into());
+    let output = graph.import_texture("Output", TextureDesc::new(4, 4, COLOR), "Output".into());
+    let current = graph.create_texture("Current", TextureDesc::new(4, 4, COLOR));
+
+    let mut pass = graph.add_pass("Render");
+    let current = pass.write(current);
+    //declared before the resolve that reads last frame's history, so it has to move after it
+    let mut pass = graph.add_pass("StoreHistory");
+    pass.read(current);
+    pass.write(history);
+    let mut pass = graph.add_pass("Resolve");
+    pass.read(current);
+    pass.read(history);
+    pass.write(output);
+    let compiled = graph.compile().unwrap();
+    assert_eq!(compiled.order(), ["Render", "Resolve", "StoreHistory"]);
+}
+
+#[test]
+fn transient_targets_share_allocations_when_their_lifetimes_do_not_overlap() {
+    let mut graph = RenderGraph::<NullBackend>::new();
+    let backbuffer = graph.import_texture(
+        "Backbuffer",
+        TextureDesc::new(8, 8, COLOR),
+        "Backbuffer".into(),
+    );
+    let a = graph.create_texture("A", TextureDesc::new(8, 8, COLOR));
+    let b = graph.create_texture("B", TextureDesc::new(8, 8, COLOR));
+    let c = graph.create_texture("C", TextureDesc::new(8, 8, COLOR));
+    let half = graph.create_texture("Half", TextureDesc::new(4, 4, COLOR));
+
+    let mut pass = graph.add_pass("WriteA");
+    let a = pass.write(a);
+    let mut pass = graph.add_pass("AToB");
+    pass.read(a);
+    let b = pass.write(b);
+    let mut pass = graph.add_pass("BToC");
+    pass.read(b);
+    let c = pass.write(c);
+    let mut pass = graph.add_pass("CToHalf");
+    pass.read(c);
+    let half = pass.write(half);
+    let mut pass = graph.add_pass("Present");
+    pass.read(half);
+    pass.write(backbuffer);
+
+    let compiled = graph.compile().unwrap();
+    //a is dead once b is written, so c reuses a's allocation, b overlaps both
+    assert_eq!(compiled.slot(a), compiled.slot(c));
+    assert_ne!(compiled.slot(a), compiled.slot(b));
+    assert_ne!(compiled.slot(half), compiled.slot(a));
+    assert_eq!(compiled.allocation_count(), 3);
+
+    let mut backend = NullBackend::new();
+    compiled.execute(&mut backend);
+    let creates: Vec<&String> = backend
+        .commands
+        .iter()
+        .filter(|c| c.starts_with("create"))
+        .collect();
+    assert_eq!(
+        creates,
+        [
+            "create 0 A 8x8 Rgba16F",
+            "create 1 B 8x8 Rgba16F",
+            "create 2 Half 4x4 Rgba16F"
+        ]
+    );
+}
+
+#[test]
+fn clears_and_barriers_are_inserted() {
+    let mut graph = RenderGraph::<NullBackend>::new();
+    let backbuffer = graph.import_texture(
+        "Backbuffer",
+        TextureDesc::new(8, 8, COLOR),
+        "Backbuffer".into(),
+    );
+    let args = graph.create_buffer("DrawArgs", BufferDesc::new(64).zeroed());
+    let color = graph.create_texture(
+        "Color",
+        TextureDesc::new(8, 8, COLOR).clear(ClearValue::Color([0.0, 0.0, 0.0, 1.0])),
+    );
+
+    let mut pass = graph.add_pass("Cull");
+    let args = pass.write(args);
+    let mut pass = graph.add_pass("Draw");
+    pass.read_as(args, Access::Indirect);
+    let color = pass.write(color);
+    let mut pass = graph.add_pass("Blit");
+    pass.read(color);
+    pass.write(backbuffer);
+
+    let compiled = graph.compile().unwrap();
+    assert_eq!(compiled.clears("Cull"), ["DrawArgs"]);
+    assert_eq!(compiled.clears("Draw"), ["Color"]);
+    let barrier = &compiled.barriers("Draw")[0];
+    assert_eq!(
+        (barrier.from, barrier.to),
+        (Access::Storage, Access::Indirect)
+    );
+
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let mut backend = NullBackend::new();
+    let mut graph = RenderGraph::<NullBackend>::new();
+    let target = graph.import_texture(
+        "Backbuffer",
+        TextureDesc::new(8, 8, COLOR),
+        "Backbuffer".into(),
+    );
+    let log = calls.clone();
+    graph
+        .add_pass("Only")
+        .execute(move |context| {
+            log.borrow_mut().push(context.texture(target).clone());
+        })
+        .write(target);
+    graph.compile().unwrap().execute(&mut backend);
+    assert_eq!(calls.borrow().as_slice(), ["Backbuffer"]);
+    assert_eq!(backend.commands, ["begin Only [Backbuffer]", "end Only"]);
+
+    let mut backend = NullBackend::new();
+    compiled.execute(&mut backend);
+    assert_eq!(
+        backend.commands,
+        [
+            "create 0 DrawArgs 64 bytes",
+            "create 1 Color 8x8 Rgba16F",
+            "clear DrawArgs zero",
+            "begin Cull []",
+            "end Cull",
+            "barrier DrawArgs Storage -> Indirect",
+            "clear Color Color([0.0, 0.0, 0.0, 1.0])",
+            "begin Draw [Color]",
+            "end Draw",
+            "barrier Color Attachment -> Sampled",
+            "begin Blit [Backbuffer]",
+            "end Blit",
+        ]
+    );
+}
+
+#[test]
+fn graphviz_dump_shows_order_culling_and_allocations() {
+    let dot = frame_graph().compile().unwrap().to_dot();
+    assert!(dot.starts_with("digraph RenderGraph {\n"));
+    assert!(dot.ends_with("}\n"));
+    for line in [
+        r##"    p0 [shape=box, style=filled, fillcolor="#cfe2f3", label="0: Shadows\nclear ShadowMap"];"##,
+        r##"    p2 [shape=box, style="filled,dashed", fillcolor="#eeeeee", label="Debug (culled)"];"##,
+        r##"    p1 [shape=box, style=filled, fillcolor="#cfe2f3", label="1: Geometry\nclear Scene\nclear Depth\nbarrier ShadowMap Attachment -> Sampled"];"##,
+        r##"    r0v1 [shape=doubleoctagon, label="Backbuffer v1\n320x180 Rgba8\nimported"];"##,
+        r##"    r1v1 [shape=ellipse, label="ShadowMap v1\n512x512 Depth32F\nslot 0"];"##,
+        r##"    r5v1 [shape=ellipse, label="Debug v1\n320x180 Rgba16F\nunused"];"##,
+        r##"    p0 -> r1v1 [label="Attachment"];"##,
+        r##"    r1v1 -> p1 [label="Sampled"];"##,
+    ] {
+        assert!(dot.contains(line), "missing `{line}` in\n{dot}");
+    }
+}
+
+#[test]
+fn invalid_graphs_fail_to_compile() {
+    let mut graph = RenderGraph::<NullBackend>::new();
+    let texture = graph.create_texture("Never", TextureDesc::new(4, 4, COLOR));
+    graph.add_pass("Reader").read(texture);
+    graph.add_pass("Reader").side_effects();
+    let error = graph.compile().err().unwrap();
+    assert!(error.contains("before any pass writes it"), "{error}");
+
+    let mut graph = RenderGraph::<NullBackend>::new();
+    let texture = graph.create_texture("Twice", TextureDesc::new(4, 4, COLOR));
+    graph.add_pass("First").write(texture);
+    graph.add_pass("Second").write(texture);
+    let error = graph.compile().err().unwrap();
+    assert!(error.contains("already exists"), "{error}");
+
+    let mut graph = RenderGraph::<NullBackend>::new();
+    let buffer = graph.create_buffer("Buffer", BufferDesc::new(16));
+    graph.add_pass("Draw").write_as(buffer, Access::Attachment);
+    let error = graph.compile().err().unwrap();
+    assert!(
+        error.contains("can't write `Buffer` as Attachment"),
+        "{error}"
+    );
+
+    let mut graph = RenderGraph::<NullBackend>::new();
+    graph.create_texture(
+        "Depth",
+        TextureDesc::new(4, 4, DEPTH).clear(ClearValue::Color([0.0; 4])),
+    );
+    assert!(graph.compile().is_err());
+}
+
+#[test]
+fn gl_backend_clears_and_draws_into_an_imported_target() {
+    let context = common::HeadlessContext::new().unwrap();
+    let _renderer = context.renderer();
+    let target = Framebuffer::new(FramebufferSpec::new(
+        4,
+        4,
+        vec![FramebufferTextureFormat::Rgba8],
+    ));
+    let mut backend = GlBackend::new();
+    for frame in 0..2 {
+        let mut graph = RenderGraph::<GlBackend>::new();
+        let output = graph.import_texture(
+            "Target",
+            TextureDesc::new(4, 4, FramebufferTextureFormat::Rgba8),
+            GlTexture::from_framebuffer(&target, 0),
+        );
+        let scratch = graph.create_texture(
+            "Scratch",
+            TextureDesc::new(4, 4, FramebufferTextureFormat::Rgba8)
+                .clear(ClearValue::Color([1.0, 0.0, 0.0, 1.0])),
+        );
+        let mut pass = graph.add_pass("Fill");
+        let scratch = pass.write(scratch);
+        let mut pass = graph.add_pass("Copy");
+        pass.read(scratch);
+        let output = pass.write(output);
+        pass.execute(move |context| {
+            let source = context.texture(scratch).id();
+            let destination = context.texture(output).id();
+            unsafe {
+                gl::CopyImageSubData(
+                    source,
+                    gl::TEXTURE_2D,
+                    0,
+                    0,
+                    0,
+                    0,
+                    destination,
+                    gl::TEXTURE_2D,
+                    0,
+                    0,
+                    0,
+                    0,
+                    4,
+                    4,
+                    1,
+                );
+            }
+        });
+        let compiled = graph.compile().unwrap();
+        assert_eq!(compiled.order(), ["Fill", "Copy"], "frame {frame}");
+        compiled.execute(&mut backend);
+    }
+    let pixels = target.read_pixels(0);
+    assert_eq!(&pixels[..4], &[255, 0, 0, 255]);
+}
@@ -0,0 +1,193 @@
+mod common;
+
+use std::rc::Rc;
+
+use common::{assert_golden, render};
+use rgengine::{
+    math::{Rect, Vec3, Vec4},
+    renderer::{
+        camera::OrthographicCamera,
+        renderer2d::Renderer2D,
+        text::{AtlasKind, Font, FontAtlas, TextAlign, TextLayout, TextStyle},
+    },
+};
+
+const WIDTH: u32 = 200;
+const HEIGHT: u32 = 120;
+const TOLERANCE: u8 = 3;
+
+//roboto is apache 2.0, the license is next to it
+fn font() -> Rc<Font> {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/assets/fonts/Roboto-Regular.ttf"
+    );
+    Rc::new(Font::load(path).unwrap())
+}
+
+fn line_text(layout: &TextLayout, line: usize) -> String {
+    layout.glyphs[layout.lines[line].glyphs.clone()]
+        .iter()
+        .map(|g| g.ch)
+        .collect()
+}
+
+#[test]
+fn kerning_pulls_pairs_together() {
+    let font = font();
+    let style = TextStyle::new(32.0);
+    let pair = TextLayout::new(&font, "AV", &style);
+    let apart = font.advance('A', 32.0) + font.advance('V', 32.0);
+    assert!(font.kerning('A', 'V', 32.0) < 0.0);
+    assert!(pair.size.x < apart);
+    assert_eq!(
+        pair.glyphs[1].position.x,
+        pair.glyphs[0].advance + font.kerning('A', 'V', 32.0)
+    );
+}
+
+#[test]
+fn lines_wrap_at_spaces_and_break_long_words() {
+    let font = font();
+    let style = TextStyle::new(16.0).with_max_width(80.0);
+    let layout = TextLayout::new(&font, "the quick brown fox jumps\nover", &style);
+    assert!(layout.lines.len() >= 3);
+    for line in &layout.lines {
+        assert!(line.width <= 80.0, "{} is too wide", line.width);
+    }
+    //words stay whole, spaces stay at the end of the line they follow
+    let lines: Vec<String> = (0..layout.lines.len())
+        .map(|i| line_text(&layout, i))
+        .collect();
+    assert_eq!(
+        lines.concat().replace('\n', ""),
+        "the quick brown fox jumpsover"
+    );
+    for line in &lines {
+        assert!(!line.starts_with(' '), "{line:?} starts with a space");
+    }
+    assert_eq!(lines.last().unwrap(), "over");
+
+    let long = TextLayout::new(&font, "incomprehensibilities", &style);
+    assert!(long.lines.len() > 1);
+    assert!(long.lines.iter().all(|line| line.width <= 80.0));
+
+    let height = font.line_height(16.0);
+    let baselines: Vec<f32> = layout.lines.iter().map(|l| l.baseline).collect();
+    for pair in baselines.windows(2) {
+        assert!((pair[1] - pair[0] - height).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn lines_align_inside_the_box() {
+    let font = font();
+    let text = "wide line here\nshort";
+    let left = TextLayout::new(&font, text, &TextStyle::new(20.0));
+    let center = TextLayout::new(
+        &font,
+        text,
+        &TextStyle::new(20.0).with_align(TextAlign::Center),
+    );
+    let right = TextLayout::new(
+        &font,
+        text,
+        &TextStyle::new(20.0)
+            .with_align(TextAlign::Right)
+            .with_max_width(300.0),
+    );
+    let first = |layout: &TextLayout, line: usize| layout.glyphs[layout.lines[line].glyphs.start];
+
+    assert_eq!(first(&left, 1).position.x, 0.0);
+    let slack = left.lines[0].width - left.lines[1].width;
+    assert!((first(&center, 1).position.x - slack / 2.0).abs() < 1e-3);
+    assert!((first(&right, 1).position.x - (300.0 - right.lines[1].width)).abs() < 1e-3);
+    assert_eq!(left.size, center.size);
+}
+
+#[test]
+fn tabs_move_to_the_next_stop() {
+    let font = font();
+    let style = TextStyle::new(20.0);
+    let stop = font.advance(' ', 20.0) * 4.0;
+    let layout = TextLayout::new(&font, "a\tb\r", &style);
+    assert_eq!(layout.glyphs.len(), 3);
+    assert!((layout.glyphs[2].position.x - stop).abs() < 1e-3);
+
+    let empty = TextLayout::new(&font, "", &style);
+    assert_eq!(empty.lines.len(), 1);
+    assert!((empty.size.y - (font.ascent(20.0) - font.descent(20.0))).abs() < 1e-3);
+}
+
+#[test]
+fn atlas_grows_and_keeps_glyphs_apart() {
+    let context = common::HeadlessContext::new().unwrap();
+    let _renderer = context.renderer();
+    let mut atlas = FontAtlas::sdf(font(), 48.0, 6);
+    assert_eq!(atlas.kind(), AtlasKind::Sdf { spread: 6 });
+    let text: String = ('!'..='~').chain('À'..='ÿ').collect();
+    let layout = TextLayout::new(atlas.font(), &text, &TextStyle::new(48.0));
+    atlas.cache(&layout);
+    atlas.upload();
+    assert!(atlas.size() > 256);
+    assert_eq!(atlas.texture().width(), atlas.size());
+
+    let rects: Vec<Rect> = layout
+        .glyphs
+        .iter()
+        .filter_map(|g| atlas.glyph(g.index))
+        .map(|g| g.rect)
+        .collect();
+    assert_eq!(rects.len(), text.chars().count());
+    for (i, a) in rects.iter().enumerate() {
+        assert!(a.max.x <= atlas.size() as f32 && a.max.y <= atlas.size() as f32);
+        for b in &rects[i + 1..] {
+            assert!(a == b || !a.intersects(b), "{a:?} overlaps {b:?}");
+        }
+    }
+}
+
+#[test]
+fn text_2d() {
+    let font = font();
+    let mut scene = None;
+    let frame = render(WIDTH, HEIGHT, 2, |_, _| {
+        let (renderer_2d, bitmap, sdf) = scene.get_or_insert_with(|| {
+            (
+                Renderer2D::new(),
+                FontAtlas::new(font.clone(), 16.0),
+                FontAtlas::sdf(font.clone(), 32.0, 4),
+            )
+        });
+        //pixels with the origin in the top left
+        let camera = OrthographicCamera::new(0.0, WIDTH as f32, -(HEIGHT as f32), 0.0);
+        renderer_2d.reset_stats();
+        renderer_2d.begin_scene(&camera);
+        let size = renderer_2d.draw_text(
+            bitmap,
+            "Kerning: AVAWAY Tå",
+            Vec3::new(4.0, -4.0, 0.0),
+            &TextStyle::new(16.0),
+            Vec4::ONE,
+        );
+        renderer_2d.draw_text(
+            bitmap,
+            "wrapped and centered text under the first line",
+            Vec3::new(4.0, -4.0 - size.y, 0.0),
+            &TextStyle::new(16.0)
+                .with_align(TextAlign::Center)
+                .with_max_width(190.0),
+            Vec4::new(0.4, 0.9, 1.0, 1.0),
+        );
+        renderer_2d.draw_text(
+            sdf,
+            "SDF x2",
+            Vec3::new(4.0, -56.0, 0.0),
+            &TextStyle::new(56.0),
+            Vec4::new(1.0, 0.8, 0.3, 1.0),
+        );
+        renderer_2d.end_scene();
+        assert_eq!(renderer_2d.stats().draw_calls, 1);
+    });
+    assert_golden("text_2d", &frame, TOLERANCE);
+}
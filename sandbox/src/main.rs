@@ -1,27 +1,149 @@
+use std::{cell::RefCell, rc::Rc};
+
+use imgui::Ui;
 use rgengine::{
-    engine::GameEngine,
-    layers::{Layer, LayerStack},
-    rge_info, rge_warn,
+    console::{self, CVar},
+    engine::{EngineConfig, GameEngine},
+    events::RGEvent,
+    imgui::{Editor, StandardPanel},
+    layers::Layer,
+    log::{LogConfig, LogFileConfig},
+    math::{Vec2, Vec3, Vec4},
+    renderer::{
+        camera_controller::OrthographicCameraController,
+        debug_draw,
+        framebuffer::{Framebuffer, FramebufferSpec, FramebufferTextureFormat},
+        renderer2d::Renderer2D,
+    },
+    window::WindowManager,
 };
 
-#[derive(Debug, Default)]
 struct ExampleLayer {
     pub id: usize,
+    renderer_2d: Renderer2D,
+    camera_controller: OrthographicCameraController,
+    rotation: f32,
+    rotation_speed: f32,
+    quad_color: [f32; 4],
+    //the engine renders the layers into this, the editor shows it in the scene viewport
+    scene: Rc<RefCell<Framebuffer>>,
+    scene_size: [u32; 2],
+}
+
+impl ExampleLayer {
+    fn new(id: usize, scene: Rc<RefCell<Framebuffer>>) -> Self {
+        Self {
+            id,
+            renderer_2d: Renderer2D::new(),
+            camera_controller: OrthographicCameraController::new(1, 1280, 720, true),
+            rotation: 0.0,
+            rotation_speed: 1.0,
+            quad_color: [0.8, 0.2, 0.3, 1.0],
+            scene,
+            scene_size: [1280, 720],
+        }
+    }
 }
 
 impl Layer for ExampleLayer {
     fn get_id(&self) -> usize {
         self.id
     }
+    fn on_attach(&mut self) {
+        console::register_cvar(
+            CVar::new("sb_grid", 10, "quads along each side of the sandbox grid").archived(),
+        );
+    }
     fn on_update(&mut self, dt: f32) {
-        rge_info!("{:?}", self.id);
+        let [width, height] = self.scene_size;
+        let mut scene = self.scene.borrow_mut();
+        if (scene.width(), scene.height()) != (width, height) {
+            scene.resize(width, height);
+        }
+        drop(scene);
+        //the window's resize events would otherwise give the camera the window's aspect
+        self.camera_controller.on_resize(width, height);
+
+        self.rotation += dt * self.rotation_speed;
+        self.camera_controller.on_update(dt);
+
+        debug_draw::set_camera(self.camera_controller.camera().view_projection());
+        debug_draw::circle(
+            Vec3::new(-1.2, 0.0, 0.0),
+            Vec3::Z,
+            0.36,
+            Vec4::new(1.0, 1.0, 0.3, 1.0),
+        );
+        debug_draw::screen_text(Vec2::new(8.0, 8.0), "sandbox", Vec4::ONE);
+    }
+    fn on_event(&mut self, event: &RGEvent) {
+        self.camera_controller.on_event(event);
+    }
+    fn on_render(&mut self, _window: &mut WindowManager) {
+        self.renderer_2d.reset_stats();
+        self.renderer_2d
+            .begin_scene(self.camera_controller.camera());
+        let grid = console::cvar_int("sb_grid").unwrap_or(10).clamp(1, 100) as u32;
+        let step = 1.1 / grid as f32;
+        for y in 0..grid {
+            for x in 0..grid {
+                let color = Vec4::new(x as f32 / grid as f32, 0.4, y as f32 / grid as f32, 0.7);
+                let position = Vec3::new(x as f32 * step - 0.5, y as f32 * step - 0.5, 0.0);
+                self.renderer_2d
+                    .draw_quad(position, Vec2::splat(step * 0.9), 0.0, color);
+            }
+        }
+        self.renderer_2d.draw_quad(
+            Vec3::new(-1.2, 0.0, 0.0),
+            Vec2::splat(0.5),
+            self.rotation,
+            Vec4::from_array(self.quad_color),
+        );
+        self.renderer_2d.end_scene();
+    }
+    fn on_imgui(&mut self, ui: &Ui, editor: &mut Editor) {
+        if let Some(viewport) = editor.scene_viewport(ui, &self.scene.borrow()) {
+            self.scene_size = viewport.size;
+        }
+        editor.standard_panel(ui, StandardPanel::Properties, || {
+            ui.slider("rotation speed", -5.0, 5.0, &mut self.rotation_speed);
+            ui.color_edit4("quad color", &mut self.quad_color);
+        });
+        let stats = self.renderer_2d.stats();
+        editor.standard_panel(ui, StandardPanel::Stats, || {
+            ui.separator();
+            ui.text(format!("draw calls: {}", stats.draw_calls));
+            ui.text(format!("quads: {}", stats.quad_count));
+            ui.text(format!("textures: {}", stats.texture_count));
+        });
     }
 }
 
 fn main() {
-    let _ = rgengine::log::init();
+    //the ui layout and cvars are kept under "Sandbox", and one log file per run in logs/ where
+    //the last 10 are kept
+    let mut engine = GameEngine::with_config(EngineConfig {
+        log: Some(LogConfig {
+            file: Some(LogFileConfig::new("logs", "sandbox")),
+            ..LogConfig::from_env()
+        }),
+        ..EngineConfig::new("Sandbox")
+    });
+    engine.imgui().set_editor(true);
+    engine.imgui().set_viewports(true);
 
-    let mut engine = GameEngine::new();
-    //
+    let scene = Rc::new(RefCell::new(Framebuffer::new(FramebufferSpec::new(
+        1280,
+        720,
+        vec![
+            FramebufferTextureFormat::Rgba8,
+            FramebufferTextureFormat::Depth24Stencil8,
+        ],
+    ))));
+    engine.set_render_target(Some(scene.clone()));
+    //console commands to run at startup, e.g. `sb_grid 20; r_vsync 0`
+    engine.set_startup_script(Some("autoexec.cfg".into()));
+    engine.push_layer(Box::new(ExampleLayer::new(0, scene)));
+    rgengine::rge_info!("sandbox started, logs show in the editor's log panel");
     engine.run();
 }